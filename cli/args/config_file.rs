@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::ConfigFlag;
+use crate::args::CoverageThresholds;
 use crate::args::Flags;
 use crate::util::fs::canonicalize_path;
 use crate::util::path::specifier_parent;
@@ -279,12 +280,36 @@ impl Serialize for TsConfig {
   }
 }
 
+/// How the diagnostics of a lint rule are reported.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LintRuleSeverity {
+  /// Diagnostics are reported and fail the run.
+  Error,
+  /// Diagnostics are reported, but only fail the run when there are more
+  /// than `--max-warnings`.
+  Warn,
+  /// The rule doesn't run.
+  Off,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, PartialEq)]
 #[serde(default, deny_unknown_fields)]
 pub struct LintRulesConfig {
   pub tags: Option<Vec<String>>,
   pub include: Option<Vec<String>>,
   pub exclude: Option<Vec<String>>,
+  /// Severities by rule code. Rules with an `error` or `warn` severity run
+  /// even if they're not in the configured tags.
+  pub severity: Option<BTreeMap<String, LintRuleSeverity>>,
+}
+
+impl LintRulesConfig {
+  /// The severity configured for the rule. Diagnostics of rules without one
+  /// are errors on the command line and warnings in the editor.
+  pub fn severity(&self, code: &str) -> Option<LintRuleSeverity> {
+    self.severity.as_ref()?.get(code).copied()
+  }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq)]
@@ -402,6 +427,7 @@ struct SerializedLintConfig {
   #[serde(rename = "files")]
   pub deprecated_files: SerializedFilesConfig,
   pub report: Option<String>,
+  pub plugins: Vec<String>,
 }
 
 impl SerializedLintConfig {
@@ -412,11 +438,22 @@ impl SerializedLintConfig {
     let (include, exclude) = (self.include, self.exclude);
     let files = SerializedFilesConfig { include, exclude };
 
+    let plugins = self
+      .plugins
+      .iter()
+      .map(|plugin| {
+        config_file_specifier.join(plugin).with_context(|| {
+          format!("Invalid lint plugin specifier \"{plugin}\"")
+        })
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
     Ok(LintConfig {
       rules: self.rules,
       files: choose_files(files, self.deprecated_files)
         .into_resolved(config_file_specifier)?,
       report: self.report,
+      plugins,
     })
   }
 }
@@ -426,6 +463,8 @@ pub struct LintConfig {
   pub rules: LintRulesConfig,
   pub files: FilesConfig,
   pub report: Option<String>,
+  /// Modules that export lint rules, see `tools::lint::LintPluginHost`.
+  pub plugins: Vec<ModuleSpecifier>,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
@@ -612,6 +651,51 @@ pub struct BenchConfig {
   pub files: FilesConfig,
 }
 
+/// `coverage` config representation for serde
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedCoverageConfig {
+  pub thresholds: SerializedCoverageThresholds,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedCoverageThresholds {
+  pub lines: Option<u8>,
+  pub branches: Option<u8>,
+  pub functions: Option<u8>,
+}
+
+impl SerializedCoverageConfig {
+  pub fn into_resolved(self) -> Result<CoverageConfig, AnyError> {
+    let thresholds = self.thresholds;
+    for (name, value) in [
+      ("lines", thresholds.lines),
+      ("branches", thresholds.branches),
+      ("functions", thresholds.functions),
+    ] {
+      if let Some(value) = value {
+        if value > 100 {
+          bail!("Coverage threshold for {name} must be between 0 and 100, but got {value}");
+        }
+      }
+    }
+
+    Ok(CoverageConfig {
+      thresholds: CoverageThresholds {
+        lines: thresholds.lines,
+        branches: thresholds.branches,
+        functions: thresholds.functions,
+      },
+    })
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CoverageConfig {
+  pub thresholds: CoverageThresholds,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub enum LockConfig {
@@ -631,6 +715,7 @@ pub struct ConfigFileJson {
   pub tasks: Option<Value>,
   pub test: Option<Value>,
   pub bench: Option<Value>,
+  pub coverage: Option<Value>,
   pub lock: Option<Value>,
 }
 
@@ -884,6 +969,17 @@ impl ConfigFile {
     }
   }
 
+  pub fn to_coverage_config(&self) -> Result<Option<CoverageConfig>, AnyError> {
+    if let Some(config) = self.json.coverage.clone() {
+      let coverage_config: SerializedCoverageConfig =
+        serde_json::from_value(config)
+          .context("Failed to parse \"coverage\" configuration")?;
+      Ok(Some(coverage_config.into_resolved()?))
+    } else {
+      Ok(None)
+    }
+  }
+
   /// Return any tasks that are defined in the configuration file as a sequence
   /// of JSON objects providing the name of the task and the arguments of the
   /// task in a detail field.
@@ -1203,7 +1299,8 @@ mod tests {
         "exclude": ["src/testdata/"],
         "rules": {
           "tags": ["recommended"],
-          "include": ["ban-untagged-todo"]
+          "include": ["ban-untagged-todo"],
+          "severity": { "no-var": "warn", "no-empty": "off" }
         }
       },
       "fmt": {
@@ -1248,6 +1345,10 @@ mod tests {
           include: Some(vec!["ban-untagged-todo".to_string()]),
           exclude: None,
           tags: Some(vec!["recommended".to_string()]),
+          severity: Some(BTreeMap::from([
+            ("no-empty".to_string(), LintRuleSeverity::Off),
+            ("no-var".to_string(), LintRuleSeverity::Warn),
+          ])),
         },
         ..Default::default()
       }
@@ -1403,6 +1504,35 @@ mod tests {
     assert_eq!(fmt_options_deprecated.semi_colons, Some(true));
   }
 
+  #[test]
+  fn test_parse_config_with_coverage_thresholds() {
+    let config_text = r#"{
+      "coverage": {
+        "thresholds": {
+          "lines": 80,
+          "branches": 60
+        }
+      }
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/tsconfig.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let coverage_config =
+      unpack_object(config_file.to_coverage_config(), "coverage");
+    assert_eq!(
+      coverage_config.thresholds,
+      CoverageThresholds {
+        lines: Some(80),
+        branches: Some(60),
+        functions: None,
+      }
+    );
+
+    let config_text = r#"{ "coverage": { "thresholds": { "lines": 120 } } }"#;
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    assert!(config_file.to_coverage_config().is_err());
+  }
+
   #[test]
   fn test_parse_config_with_empty_file() {
     let config_text = "";
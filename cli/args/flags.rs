@@ -3,6 +3,7 @@
 use clap::value_parser;
 use clap::Arg;
 use clap::ArgAction;
+use clap::ArgGroup;
 use clap::ArgMatches;
 use clap::ColorChoice;
 use clap::Command;
@@ -37,6 +38,13 @@ pub struct BenchFlags {
   pub filter: Option<String>,
   pub json: bool,
   pub no_run: bool,
+  pub save_baseline: Option<String>,
+  pub baseline: Option<String>,
+  pub regression_threshold: Option<u32>,
+  pub warmup: Option<u32>,
+  pub min_iterations: Option<u32>,
+  pub min_time: Option<u64>,
+  pub max_time: Option<u64>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -53,6 +61,7 @@ pub struct CacheFlags {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CheckFlags {
   pub files: Vec<String>,
+  pub sarif: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -69,13 +78,38 @@ pub struct CompletionsFlags {
   pub buf: Box<[u8]>,
 }
 
+/// Minimum coverage percentages, from 0 to 100.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CoverageThresholds {
+  pub lines: Option<u8>,
+  pub branches: Option<u8>,
+  pub functions: Option<u8>,
+}
+
+impl CoverageThresholds {
+  pub fn is_empty(&self) -> bool {
+    self.lines.is_none() && self.branches.is_none() && self.functions.is_none()
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CoverageReporterConfig {
+  #[default]
+  Pretty,
+  Lcov,
+  Html,
+  Cobertura,
+  Json,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CoverageFlags {
   pub files: FileFlags,
   pub output: Option<PathBuf>,
   pub include: Vec<String>,
   pub exclude: Vec<String>,
-  pub lcov: bool,
+  pub reporter: CoverageReporterConfig,
+  pub thresholds: CoverageThresholds,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -142,6 +176,37 @@ pub struct UninstallFlags {
   pub root: Option<PathBuf>,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KvSubcommand {
+  List {
+    prefix: Option<String>,
+    limit: Option<usize>,
+    reverse: bool,
+  },
+  Get {
+    key: String,
+  },
+  Set {
+    key: String,
+    value: String,
+  },
+  Delete {
+    key: String,
+  },
+  Dump {
+    output: Option<PathBuf>,
+  },
+  Restore {
+    input: Option<PathBuf>,
+  },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KvFlags {
+  pub path: PathBuf,
+  pub subcommand: KvSubcommand,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct LintFlags {
   pub files: FileFlags,
@@ -151,6 +216,9 @@ pub struct LintFlags {
   pub maybe_rules_exclude: Option<Vec<String>>,
   pub json: bool,
   pub compact: bool,
+  pub sarif: bool,
+  pub fix: bool,
+  pub max_warnings: Option<usize>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -177,6 +245,46 @@ pub struct TaskFlags {
   pub task: Option<String>,
 }
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TestReporterConfig {
+  #[default]
+  Pretty,
+  Dot,
+  Junit,
+  Tap,
+}
+
+/// Selects one of `total` roughly equally sized parts of the test modules, to
+/// split a test suite across several machines. `index` is 1-based.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TestShard {
+  pub index: usize,
+  pub total: usize,
+}
+
+impl FromStr for TestShard {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (index, total) = s.split_once('/').ok_or_else(|| {
+      "Expected a shard in the form of INDEX/TOTAL".to_string()
+    })?;
+    let index = index
+      .parse::<usize>()
+      .map_err(|_| format!("Invalid shard index: {index}"))?;
+    let total = total
+      .parse::<usize>()
+      .map_err(|_| format!("Invalid shard total: {total}"))?;
+    if total == 0 {
+      return Err("The shard total must be at least 1".to_string());
+    }
+    if index == 0 || index > total {
+      return Err(format!("The shard index must be between 1 and {total}"));
+    }
+    Ok(Self { index, total })
+  }
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct TestFlags {
   pub doc: bool,
@@ -188,6 +296,9 @@ pub struct TestFlags {
   pub shuffle: Option<u64>,
   pub concurrent_jobs: Option<NonZeroUsize>,
   pub trace_ops: bool,
+  pub reporter: TestReporterConfig,
+  pub junit_path: Option<String>,
+  pub shard: Option<TestShard>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -222,6 +333,7 @@ pub enum DenoSubcommand {
   Info(InfoFlags),
   Install(InstallFlags),
   Uninstall(UninstallFlags),
+  Kv(KvFlags),
   Lsp,
   Lint(LintFlags),
   Repl(ReplFlags),
@@ -651,6 +763,7 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::error::Result<Flags> {
       "init" => init_parse(&mut flags, &mut m),
       "info" => info_parse(&mut flags, &mut m),
       "install" => install_parse(&mut flags, &mut m),
+      "kv" => kv_parse(&mut flags, &mut m),
       "lint" => lint_parse(&mut flags, &mut m),
       "lsp" => lsp_parse(&mut flags, &mut m),
       "repl" => repl_parse(&mut flags, &mut m),
@@ -750,6 +863,7 @@ fn clap_root() -> Command {
     .subcommand(info_subcommand())
     .subcommand(install_subcommand())
     .subcommand(uninstall_subcommand())
+    .subcommand(kv_subcommand())
     .subcommand(lsp_subcommand())
     .subcommand(lint_subcommand())
     .subcommand(repl_subcommand())
@@ -800,6 +914,63 @@ fn bench_subcommand() -> Command {
         .help("Cache bench modules, but don't run benchmarks")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("save-baseline")
+        .long("save-baseline")
+        .value_name("NAME")
+        .require_equals(true)
+        .help("Save the results as a baseline with the given name")
+        .value_parser(parse_baseline_name),
+    )
+    .arg(
+      Arg::new("baseline")
+        .long("baseline")
+        .value_name("NAME")
+        .require_equals(true)
+        .help("Compare the results with a baseline saved before")
+        .value_parser(parse_baseline_name),
+    )
+    .arg(
+      Arg::new("regression-threshold")
+        .long("regression-threshold")
+        .value_name("PERCENT")
+        .require_equals(true)
+        .requires("baseline")
+        .help("Fail if a benchmark is slower than the baseline by more than PERCENT, defaults to 10")
+        .value_parser(value_parser!(u32)),
+    )
+    .arg(
+      Arg::new("warmup")
+        .long("warmup")
+        .value_name("N")
+        .require_equals(true)
+        .help("Number of warmup iterations before measuring each benchmark")
+        .value_parser(value_parser!(u32)),
+    )
+    .arg(
+      Arg::new("min-iterations")
+        .long("min-iterations")
+        .value_name("N")
+        .require_equals(true)
+        .help("Minimum number of measured iterations of each benchmark, defaults to 10")
+        .value_parser(value_parser!(u32)),
+    )
+    .arg(
+      Arg::new("min-time")
+        .long("min-time")
+        .value_name("MS")
+        .require_equals(true)
+        .help("Minimum time to spend measuring each benchmark, defaults to 500")
+        .value_parser(value_parser!(u64)),
+    )
+    .arg(
+      Arg::new("max-time")
+        .long("max-time")
+        .value_name("MS")
+        .require_equals(true)
+        .help("Maximum time to spend running each benchmark, takes precedence over the minimums")
+        .value_parser(value_parser!(u64)),
+    )
     .arg(watch_arg(false))
     .arg(no_clear_screen_arg())
     .arg(script_arg().last(true))
@@ -815,7 +986,19 @@ and report results to standard output:
 Directory arguments are expanded to all contained files matching the
 glob {*_,*.,}bench.{js,mjs,ts,mts,jsx,tsx}:
 
-  deno bench src/",
+  deno bench src/
+
+Save the results and compare later runs with them, failing when a benchmark
+got significantly slower:
+
+  deno bench --save-baseline=main
+  deno bench --baseline=main --regression-threshold=5
+
+The number of iterations and the time spent on each benchmark can be tuned
+on the command line, and for a single benchmark with the options of the same
+name passed to 'Deno.bench()':
+
+  deno bench --warmup=100 --min-time=2000 --max-time=10000",
     )
 }
 
@@ -889,6 +1072,12 @@ fn check_subcommand() -> Command {
         .conflicts_with("no-remote")
         .hide(true)
       )
+    .arg(
+      Arg::new("sarif")
+        .long("sarif")
+        .help("Output the type errors in SARIF format")
+        .action(ArgAction::SetTrue)
+    )
     .arg(
       Arg::new("file")
         .num_args(1..)
@@ -901,6 +1090,10 @@ fn check_subcommand() -> Command {
 
   deno check https://deno.land/std/http/file_server.ts
 
+Print the type errors in the SARIF format, to upload them to code scanning tools:
+
+  deno check --sarif main.ts
+
 Unless --reload is specified, this command will not re-download already cached dependencies.",
     )
 }
@@ -994,6 +1187,10 @@ Collect a coverage profile with deno test:
 
   deno test --coverage=cov_profile
 
+Or with deno run, including the profiles of web workers:
+
+  deno run --coverage=cov_profile main.ts
+
 Print a report to stdout:
 
   deno coverage cov_profile
@@ -1016,9 +1213,22 @@ Write a report using the lcov format:
 
   deno coverage --lcov --output=cov.lcov cov_profile/
 
+Write a report as HTML pages to html_cov/:
+
+  deno coverage --html --output=html_cov cov_profile/
+
+Write a Cobertura XML report, or a JSON summary, to a file:
+
+  deno coverage --reporter=cobertura --output=cobertura.xml cov_profile/
+  deno coverage --reporter=json --output=coverage.json cov_profile/
+
 Generate html reports from lcov:
 
   genhtml -o html_cov cov.lcov
+
+Fail if less than 80% of the lines or 60% of the branches are covered:
+
+  deno coverage --threshold-lines=80 --threshold-branches=60 cov_profile/
 ",
     )
     .arg(
@@ -1050,22 +1260,66 @@ Generate html reports from lcov:
         .default_value(r"test\.(js|mjs|ts|jsx|tsx)$")
         .help("Exclude source files from the report"),
     )
+    .arg(
+      Arg::new("reporter")
+        .long("reporter")
+        .value_name("REPORTER")
+        .require_equals(true)
+        .help("Select the format of the coverage report. Defaults to 'pretty'.")
+        .value_parser(["pretty", "lcov", "html", "cobertura", "json"]),
+    )
     .arg(
       Arg::new("lcov")
         .long("lcov")
-        .help("Output coverage report in lcov format")
+        .help("Output coverage report in lcov format, same as --reporter=lcov")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("html")
+        .long("html")
+        .help("Output coverage report as HTML pages, same as --reporter=html")
         .action(ArgAction::SetTrue),
     )
+    .group(ArgGroup::new("report-format").args(["reporter", "lcov", "html"]))
+    .arg(
+      Arg::new("threshold-lines")
+        .long("threshold-lines")
+        .value_name("PERCENT")
+        .require_equals(true)
+        .value_parser(value_parser!(u8).range(0..=100))
+        .help("Fail if less than PERCENT of the lines are covered, in total or in any file"),
+    )
+    .arg(
+      Arg::new("threshold-branches")
+        .long("threshold-branches")
+        .value_name("PERCENT")
+        .require_equals(true)
+        .value_parser(value_parser!(u8).range(0..=100))
+        .help("Fail if less than PERCENT of the branches are covered, in total or in any file"),
+    )
+    .arg(
+      Arg::new("threshold-functions")
+        .long("threshold-functions")
+        .value_name("PERCENT")
+        .require_equals(true)
+        .value_parser(value_parser!(u8).range(0..=100))
+        .help("Fail if less than PERCENT of the functions are covered, in total or in any file"),
+    )
     .arg(
       Arg::new("output")
-        .requires("lcov")
+        .requires("report-format")
         .long("output")
         .value_parser(value_parser!(PathBuf))
-        .help("Output file (defaults to stdout) for lcov")
+        .help("Output file (defaults to stdout) for lcov, cobertura and json, or output directory for html")
         .long_help(
-          "Exports the coverage report in lcov format to the given file.
-    Filename should be passed along with '=' For example '--output=foo.lcov'
-    If no --output arg is specified then the report is written to stdout.",
+          "Exports the coverage report in lcov, cobertura or json format to the
+    given file. Filename should be passed along with '=' For example
+    '--output=foo.lcov'
+    If no --output arg is specified then the report is written to stdout.
+
+    With --html, this is the directory the HTML pages are written to. It
+    defaults to a directory named 'html' in the first coverage profile
+    directory.",
         )
         .require_equals(true)
         .value_hint(ValueHint::FilePath),
@@ -1439,6 +1693,119 @@ https://deno.land/manual@v",
   "/getting_started/setup_your_environment#editors-and-ides",
 );
 
+fn kv_subcommand() -> Command {
+  Command::new("kv")
+    .about("UNSTABLE: Inspect, export and import local KV databases")
+    .long_about(
+      "UNSTABLE: Inspect, export and import local KV databases.
+
+Keys are given as JSON arrays. Strings, numbers and booleans map to the
+corresponding key parts, while bigints, byte arrays and non-finite numbers
+are written as {\"bigint\": \"1\"}, {\"bytes\": [1, 2]} and
+{\"number\": \"NaN\"} respectively.
+
+Values are printed as JSON in the same notation, with Maps, Sets, Dates and
+Deno.KvU64 values written as {\"map\": [[key, value]]}, {\"set\": [value]},
+{\"date\": \"...\"} and {\"u64\": \"1\"}. This notation is lossy, so use
+dump to export values exactly.
+
+List entries, optionally under a key prefix:
+
+  deno kv --unstable list kv.sqlite3 '[\"users\"]'
+
+Get, set or delete a single entry (values are JSON):
+
+  deno kv --unstable get kv.sqlite3 '[\"users\", \"alice\"]'
+  deno kv --unstable set kv.sqlite3 '[\"users\", \"alice\"]' '{\"age\": 30}'
+  deno kv --unstable delete kv.sqlite3 '[\"users\", \"alice\"]'
+
+Export a database as line-delimited JSON and import it into another one,
+preserving versionstamps and expiration times:
+
+  deno kv --unstable dump kv.sqlite3 > dump.jsonl
+  deno kv --unstable restore copy.sqlite3 dump.jsonl",
+    )
+    .subcommand_required(true)
+    .subcommand(
+      Command::new("list")
+        .about("List entries, optionally under a key prefix")
+        .arg(kv_path_arg())
+        .arg(Arg::new("prefix").help("Key prefix, as a JSON array"))
+        .arg(
+          Arg::new("limit")
+            .long("limit")
+            .help("Maximum number of entries to list")
+            .value_parser(value_parser!(usize)),
+        )
+        .arg(
+          Arg::new("reverse")
+            .long("reverse")
+            .help("List entries in descending key order")
+            .action(ArgAction::SetTrue),
+        ),
+    )
+    .subcommand(
+      Command::new("get")
+        .about("Print the entry for a key")
+        .arg(kv_path_arg())
+        .arg(kv_key_arg()),
+    )
+    .subcommand(
+      Command::new("set")
+        .about("Set the value of a key")
+        .arg(kv_path_arg())
+        .arg(kv_key_arg())
+        .arg(
+          Arg::new("value")
+            .help("Value, as JSON")
+            .required(true)
+            .allow_hyphen_values(true),
+        ),
+    )
+    .subcommand(
+      Command::new("delete")
+        .about("Delete a key")
+        .arg(kv_path_arg())
+        .arg(kv_key_arg()),
+    )
+    .subcommand(
+      Command::new("dump")
+        .about("Export all entries as line-delimited JSON")
+        .arg(kv_path_arg())
+        .arg(
+          Arg::new("output")
+            .long("output")
+            .short('o')
+            .help("Write the dump to a file instead of stdout")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath),
+        ),
+    )
+    .subcommand(
+      Command::new("restore")
+        .about("Import entries from a dump")
+        .arg(kv_path_arg())
+        .arg(
+          Arg::new("input")
+            .help("Dump file to read (defaults to stdin)")
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath),
+        ),
+    )
+}
+
+fn kv_path_arg() -> Arg {
+  Arg::new("path")
+    .help("Path to the database file")
+    .required(true)
+    .value_parser(value_parser!(PathBuf))
+    .value_hint(ValueHint::FilePath)
+}
+
+fn kv_key_arg() -> Arg {
+  Arg::new("key").help("Key, as a JSON array").required(true)
+}
+
 fn lsp_subcommand() -> Command {
   Command::new("lsp")
     .about("Start the language server")
@@ -1458,6 +1825,10 @@ Print result as JSON:
 
   deno lint --json
 
+Print result in the SARIF format, to upload it to code scanning tools:
+
+  deno lint --sarif
+
 Read from stdin:
 
   cat file.ts | deno lint -
@@ -1467,6 +1838,16 @@ List available rules:
 
   deno lint --rules
 
+Fix the problems that have a safe automatic fix:
+
+  deno lint --fix
+
+Report the problems of some rules as warnings, which only fail the run when
+there are more than --max-warnings, with \"lint.rules.severity\" in the
+configuration file:
+
+  deno lint --max-warnings=10
+
 Ignore diagnostics on the next line by preceding it with an ignore comment and
 rule name:
 
@@ -1486,6 +1867,22 @@ Ignore linting a file by adding an ignore comment at the top of the file:
         .help("List available rules")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("fix")
+        .long("fix")
+        .help("Fix any linting errors for rules that support it")
+        .action(ArgAction::SetTrue)
+        .conflicts_with("rules"),
+    )
+    .arg(
+      Arg::new("max-warnings")
+        .long("max-warnings")
+        .require_equals(true)
+        .value_name("N")
+        .value_parser(value_parser!(usize))
+        .conflicts_with("rules")
+        .help("Fail when there are more than N warnings"),
+    )
     .arg(
       Arg::new("rules-tags")
         .long("rules-tags")
@@ -1539,6 +1936,13 @@ Ignore linting a file by adding an ignore comment at the top of the file:
         .action(ArgAction::SetTrue)
         .conflicts_with("json"),
     )
+    .arg(
+      Arg::new("sarif")
+        .long("sarif")
+        .help("Output lint result in SARIF format")
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["json", "compact"]),
+    )
     .arg(
       Arg::new("files")
         .value_parser(value_parser!(PathBuf))
@@ -1583,6 +1987,12 @@ fn run_subcommand() -> Command {
     )
     .arg(no_clear_screen_arg())
     .arg(executable_ext_arg())
+    .arg(
+      coverage_arg()
+        .conflicts_with("inspect")
+        .conflicts_with("inspect-wait")
+        .conflicts_with("inspect-brk"),
+    )
     .arg(
       script_arg()
         .required_unless_present("v8-flags")
@@ -1627,6 +2037,10 @@ fn task_subcommand() -> Command {
         .help("Specify the directory to run the task in")
         .value_hint(ValueHint::DirPath),
     )
+    .arg(coverage_arg().long_help(
+      "Collect coverage profile data into DIR from every Deno process
+started by the task, including nested `deno run` and `deno test` calls.",
+    ))
     .about("Run a task defined in the configuration file")
     .long_about(
       "Run a task defined in the configuration file
@@ -1698,14 +2112,18 @@ fn test_subcommand() -> Command {
         .value_parser(value_parser!(u64)),
     )
     .arg(
-      Arg::new("coverage")
-        .long("coverage")
+      Arg::new("shard")
+        .long("shard")
+        .value_name("INDEX/TOTAL")
+        .help("Only run the INDEX-th of TOTAL equally sized groups of test modules, e.g. --shard=1/3. Running every shard once runs every test module exactly once.")
         .require_equals(true)
-        .value_name("DIR")
+        .value_parser(value_parser!(TestShard)),
+    )
+    .arg(
+      coverage_arg()
         .conflicts_with("inspect")
         .conflicts_with("inspect-wait")
-        .conflicts_with("inspect-brk")
-        .help("Collect coverage profile data into DIR"),
+        .conflicts_with("inspect-brk"),
     )
     .arg(
       Arg::new("parallel")
@@ -1736,6 +2154,22 @@ fn test_subcommand() -> Command {
         .conflicts_with("no-run")
         .conflicts_with("coverage"),
     )
+    .arg(
+      Arg::new("reporter")
+        .long("reporter")
+        .value_name("REPORTER")
+        .require_equals(true)
+        .help("Select reporter to use. Defaults to 'pretty'. The 'junit' reporter writes a JUnit XML report to stdout, 'tap' prints TAP version 14 and 'dot' prints a single character per test.")
+        .value_parser(["pretty", "dot", "junit", "tap"]),
+    )
+    .arg(
+      Arg::new("junit-path")
+        .long("junit-path")
+        .value_name("PATH")
+        .require_equals(true)
+        .value_hint(ValueHint::FilePath)
+        .help("Write a JUnit XML test report to PATH, in addition to the output of the selected reporter"),
+    )
     .arg(no_clear_screen_arg())
     .arg(script_arg().last(true))
     .about("Run tests")
@@ -2259,6 +2693,15 @@ fn seed_arg() -> Arg {
     .value_parser(value_parser!(u64))
 }
 
+fn coverage_arg() -> Arg {
+  Arg::new("coverage")
+    .long("coverage")
+    .require_equals(true)
+    .value_name("DIR")
+    .help("Collect coverage profile data into DIR")
+    .value_hint(ValueHint::DirPath)
+}
+
 fn watch_arg(takes_files: bool) -> Arg {
   let arg = Arg::new("watch")
     .long("watch")
@@ -2473,6 +2916,13 @@ fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   };
 
   let no_run = matches.get_flag("no-run");
+  let save_baseline = matches.remove_one::<String>("save-baseline");
+  let baseline = matches.remove_one::<String>("baseline");
+  let regression_threshold = matches.remove_one::<u32>("regression-threshold");
+  let warmup = matches.remove_one::<u32>("warmup");
+  let min_iterations = matches.remove_one::<u32>("min-iterations");
+  let min_time = matches.remove_one::<u64>("min-time");
+  let max_time = matches.remove_one::<u64>("max-time");
 
   watch_arg_parse(flags, matches, false);
   flags.subcommand = DenoSubcommand::Bench(BenchFlags {
@@ -2480,9 +2930,31 @@ fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     filter,
     json,
     no_run,
+    save_baseline,
+    baseline,
+    regression_threshold,
+    warmup,
+    min_iterations,
+    min_time,
+    max_time,
   });
 }
 
+/// Baseline names are used as file names, so they are restricted to
+/// characters that are safe on every platform.
+fn parse_baseline_name(name: &str) -> Result<String, String> {
+  if !name.is_empty()
+    && name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    && !name.starts_with('.')
+  {
+    Ok(name.to_string())
+  } else {
+    Err("Baseline names may only contain letters, digits, '-', '_' and '.', and may not start with '.'".to_string())
+  }
+}
+
 fn bundle_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.type_check_mode = TypeCheckMode::Local;
 
@@ -2520,7 +2992,8 @@ fn check_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if matches.get_flag("all") || matches.get_flag("remote") {
     flags.type_check_mode = TypeCheckMode::All;
   }
-  flags.subcommand = DenoSubcommand::Check(CheckFlags { files });
+  let sarif = matches.get_flag("sarif");
+  flags.subcommand = DenoSubcommand::Check(CheckFlags { files, sarif });
 }
 
 fn compile_parse(flags: &mut Flags, matches: &mut ArgMatches) {
@@ -2593,8 +3066,25 @@ fn coverage_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     Some(f) => f.collect(),
     None => vec![],
   };
-  let lcov = matches.get_flag("lcov");
+  let reporter = if matches.get_flag("lcov") {
+    CoverageReporterConfig::Lcov
+  } else if matches.get_flag("html") {
+    CoverageReporterConfig::Html
+  } else {
+    match matches.remove_one::<String>("reporter").as_deref() {
+      Some("lcov") => CoverageReporterConfig::Lcov,
+      Some("html") => CoverageReporterConfig::Html,
+      Some("cobertura") => CoverageReporterConfig::Cobertura,
+      Some("json") => CoverageReporterConfig::Json,
+      _ => CoverageReporterConfig::Pretty,
+    }
+  };
   let output = matches.remove_one::<PathBuf>("output");
+  let thresholds = CoverageThresholds {
+    lines: matches.remove_one::<u8>("threshold-lines"),
+    branches: matches.remove_one::<u8>("threshold-branches"),
+    functions: matches.remove_one::<u8>("threshold-functions"),
+  };
   flags.subcommand = DenoSubcommand::Coverage(CoverageFlags {
     files: FileFlags {
       include: files,
@@ -2603,7 +3093,8 @@ fn coverage_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     output,
     include,
     exclude,
-    lcov,
+    reporter,
+    thresholds,
   });
 }
 
@@ -2756,6 +3247,36 @@ fn uninstall_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.subcommand = DenoSubcommand::Uninstall(UninstallFlags { name, root });
 }
 
+fn kv_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  let (subcommand, mut m) = matches.remove_subcommand().unwrap();
+  let path = m.remove_one::<PathBuf>("path").unwrap();
+  let subcommand = match subcommand.as_str() {
+    "list" => KvSubcommand::List {
+      prefix: m.remove_one::<String>("prefix"),
+      limit: m.remove_one::<usize>("limit"),
+      reverse: m.get_flag("reverse"),
+    },
+    "get" => KvSubcommand::Get {
+      key: m.remove_one::<String>("key").unwrap(),
+    },
+    "set" => KvSubcommand::Set {
+      key: m.remove_one::<String>("key").unwrap(),
+      value: m.remove_one::<String>("value").unwrap(),
+    },
+    "delete" => KvSubcommand::Delete {
+      key: m.remove_one::<String>("key").unwrap(),
+    },
+    "dump" => KvSubcommand::Dump {
+      output: m.remove_one::<PathBuf>("output"),
+    },
+    "restore" => KvSubcommand::Restore {
+      input: m.remove_one::<PathBuf>("input"),
+    },
+    _ => unreachable!(),
+  };
+  flags.subcommand = DenoSubcommand::Kv(KvFlags { path, subcommand });
+}
+
 fn lsp_parse(flags: &mut Flags, _matches: &mut ArgMatches) {
   flags.subcommand = DenoSubcommand::Lsp;
 }
@@ -2786,6 +3307,9 @@ fn lint_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 
   let json = matches.get_flag("json");
   let compact = matches.get_flag("compact");
+  let sarif = matches.get_flag("sarif");
+  let fix = matches.get_flag("fix");
+  let max_warnings = matches.remove_one::<usize>("max-warnings");
   flags.subcommand = DenoSubcommand::Lint(LintFlags {
     files: FileFlags {
       include: files,
@@ -2798,6 +3322,9 @@ fn lint_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 
     json,
     compact,
+    sarif,
+    fix,
+    max_warnings,
   });
 }
 
@@ -2830,6 +3357,7 @@ fn run_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   ext_arg_parse(flags, matches);
 
   watch_arg_parse(flags, matches, true);
+  flags.coverage_dir = matches.remove_one::<String>("coverage");
   flags.subcommand = DenoSubcommand::Run(RunFlags { script });
 }
 
@@ -2843,6 +3371,7 @@ fn task_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     cwd: matches.remove_one::<String>("cwd"),
     task: None,
   };
+  flags.coverage_dir = matches.remove_one::<String>("coverage");
 
   if let Some((task, mut matches)) = matches.remove_subcommand() {
     task_flags.task = Some(task);
@@ -2931,6 +3460,15 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     Vec::new()
   };
 
+  let reporter = match matches.remove_one::<String>("reporter").as_deref() {
+    Some("dot") => TestReporterConfig::Dot,
+    Some("junit") => TestReporterConfig::Junit,
+    Some("tap") => TestReporterConfig::Tap,
+    _ => TestReporterConfig::Pretty,
+  };
+  let junit_path = matches.remove_one::<String>("junit-path");
+  let shard = matches.remove_one::<TestShard>("shard");
+
   flags.coverage_dir = matches.remove_one::<String>("coverage");
   watch_arg_parse(flags, matches, false);
   flags.subcommand = DenoSubcommand::Test(TestFlags {
@@ -2943,6 +3481,9 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     allow_none,
     concurrent_jobs,
     trace_ops,
+    reporter,
+    junit_path,
+    shard,
   });
 }
 
@@ -3400,6 +3941,30 @@ mod tests {
     );
   }
 
+  #[test]
+  fn run_coverage() {
+    let r = flags_from_vec(svec!["deno", "run", "--coverage=cov", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        coverage_dir: Some("cov".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--coverage=cov",
+      "--inspect",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn run_watch_with_external() {
     let r =
@@ -3878,6 +4443,64 @@ mod tests {
     );
   }
 
+  #[test]
+  fn lint_fix() {
+    let r = flags_from_vec(svec!["deno", "lint", "--fix", "script_1.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("script_1.ts")],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          sarif: false,
+          fix: true,
+          max_warnings: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--fix", "--rules"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn lint_max_warnings() {
+    let r = flags_from_vec(svec!["deno", "lint", "--max-warnings=5"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          sarif: false,
+          fix: false,
+          max_warnings: Some(5),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--max-warnings=-1"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn lint() {
     let r = flags_from_vec(svec!["deno", "lint", "script_1.ts", "script_2.ts"]);
@@ -3898,6 +4521,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          sarif: false,
+          fix: false,
+          max_warnings: None,
         }),
         ..Flags::default()
       }
@@ -3927,6 +4553,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          sarif: false,
+          fix: false,
+          max_warnings: None,
         }),
         watch: Some(vec![]),
         ..Flags::default()
@@ -3958,6 +4587,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          sarif: false,
+          fix: false,
+          max_warnings: None,
         }),
         watch: Some(vec![]),
         no_clear_screen: true,
@@ -3984,6 +4616,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          sarif: false,
+          fix: false,
+          max_warnings: None,
         }),
         ..Flags::default()
       }
@@ -4004,6 +4639,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          sarif: false,
+          fix: false,
+          max_warnings: None,
         }),
         ..Flags::default()
       }
@@ -4030,6 +4668,9 @@ mod tests {
           maybe_rules_exclude: Some(svec!["no-const-assign"]),
           json: false,
           compact: false,
+          sarif: false,
+          fix: false,
+          max_warnings: None,
         }),
         ..Flags::default()
       }
@@ -4050,6 +4691,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: true,
           compact: false,
+          sarif: false,
+          fix: false,
+          max_warnings: None,
         }),
         ..Flags::default()
       }
@@ -4077,6 +4721,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: true,
           compact: false,
+          sarif: false,
+          fix: false,
+          max_warnings: None,
         }),
         config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
         ..Flags::default()
@@ -4105,11 +4752,41 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: true,
+          sarif: false,
+          fix: false,
+          max_warnings: None,
         }),
         config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--sarif", "script_1.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("script_1.ts")],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          sarif: true,
+          fix: false,
+          max_warnings: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "lint", "--sarif", "--json", "script_1.ts"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -4146,19 +4823,34 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Check(CheckFlags {
           files: svec!["script.ts"],
+          sarif: false,
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
 
-    for all_flag in ["--remote", "--all"] {
-      let r = flags_from_vec(svec!["deno", "check", all_flag, "script.ts"]);
-      assert_eq!(
-        r.unwrap(),
-        Flags {
+    let r = flags_from_vec(svec!["deno", "check", "--sarif", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["script.ts"],
+          sarif: true,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    for all_flag in ["--remote", "--all"] {
+      let r = flags_from_vec(svec!["deno", "check", all_flag, "script.ts"]);
+      assert_eq!(
+        r.unwrap(),
+        Flags {
           subcommand: DenoSubcommand::Check(CheckFlags {
             files: svec!["script.ts"],
+            sarif: false,
           }),
           type_check_mode: TypeCheckMode::All,
           ..Flags::default()
@@ -5163,6 +5855,147 @@ mod tests {
     assert_eq!(r.err().unwrap().kind(), clap::error::ErrorKind::DisplayHelp);
   }
 
+  #[test]
+  fn kv_list() {
+    let r = flags_from_vec(svec!["deno", "kv", "list", "kv.sqlite3"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Kv(KvFlags {
+          path: PathBuf::from("kv.sqlite3"),
+          subcommand: KvSubcommand::List {
+            prefix: None,
+            limit: None,
+            reverse: false,
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "kv",
+      "list",
+      "--limit",
+      "10",
+      "--reverse",
+      "kv.sqlite3",
+      r#"["users"]"#
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Kv(KvFlags {
+          path: PathBuf::from("kv.sqlite3"),
+          subcommand: KvSubcommand::List {
+            prefix: Some(r#"["users"]"#.to_string()),
+            limit: Some(10),
+            reverse: true,
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn kv_get_set_delete() {
+    let r =
+      flags_from_vec(svec!["deno", "kv", "get", "kv.sqlite3", r#"["a"]"#]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Kv(KvFlags {
+          path: PathBuf::from("kv.sqlite3"),
+          subcommand: KvSubcommand::Get {
+            key: r#"["a"]"#.to_string(),
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "kv",
+      "set",
+      "kv.sqlite3",
+      r#"["a"]"#,
+      "-1"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Kv(KvFlags {
+          path: PathBuf::from("kv.sqlite3"),
+          subcommand: KvSubcommand::Set {
+            key: r#"["a"]"#.to_string(),
+            value: "-1".to_string(),
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "kv", "delete", "kv.sqlite3", r#"["a"]"#]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Kv(KvFlags {
+          path: PathBuf::from("kv.sqlite3"),
+          subcommand: KvSubcommand::Delete {
+            key: r#"["a"]"#.to_string(),
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "kv", "get", "kv.sqlite3"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn kv_dump_restore() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "kv",
+      "dump",
+      "kv.sqlite3",
+      "--output",
+      "dump.jsonl"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Kv(KvFlags {
+          path: PathBuf::from("kv.sqlite3"),
+          subcommand: KvSubcommand::Dump {
+            output: Some(PathBuf::from("dump.jsonl")),
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "kv", "restore", "kv.sqlite3"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Kv(KvFlags {
+          path: PathBuf::from("kv.sqlite3"),
+          subcommand: KvSubcommand::Restore { input: None },
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "kv"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn log_level() {
     let r =
@@ -5690,6 +6523,9 @@ mod tests {
           shuffle: None,
           concurrent_jobs: None,
           trace_ops: true,
+          reporter: Default::default(),
+          junit_path: None,
+          shard: None,
         }),
         unstable: true,
         no_prompt: true,
@@ -5765,6 +6601,9 @@ mod tests {
           },
           concurrent_jobs: Some(NonZeroUsize::new(4).unwrap()),
           trace_ops: false,
+          reporter: Default::default(),
+          junit_path: None,
+          shard: None,
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5795,6 +6634,9 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporter: Default::default(),
+          junit_path: None,
+          shard: None,
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5829,6 +6671,9 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporter: Default::default(),
+          junit_path: None,
+          shard: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5857,6 +6702,9 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporter: Default::default(),
+          junit_path: None,
+          shard: None,
         }),
         no_prompt: true,
         watch: None,
@@ -5885,6 +6733,9 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporter: Default::default(),
+          junit_path: None,
+          shard: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5912,6 +6763,9 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporter: Default::default(),
+          junit_path: None,
+          shard: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5941,6 +6795,9 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          reporter: Default::default(),
+          junit_path: None,
+          shard: None,
         }),
         watch: Some(vec![]),
         type_check_mode: TypeCheckMode::Local,
@@ -5951,6 +6808,91 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_reporter() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--reporter=junit",
+      "--junit-path=report.xml"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Junit,
+          junit_path: Some("report.xml".to_string()),
+          ..TestFlags::default()
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        no_prompt: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=tap"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Tap,
+          ..TestFlags::default()
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        no_prompt: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=dot"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Dot,
+          ..TestFlags::default()
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        no_prompt: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=xml"]);
+    assert!(r.is_err());
+
+    // Like the other value flags of `deno test`, the value must be given
+    // with an equals sign, so that it's not mistaken for a test file.
+    let r =
+      flags_from_vec(svec!["deno", "test", "--reporter", "dot", "foo_test.ts"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "test", "--junit-path", "report.xml"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_shard() {
+    let r = flags_from_vec(svec!["deno", "test", "--shard=2/3"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          shard: Some(TestShard { index: 2, total: 3 }),
+          ..TestFlags::default()
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        no_prompt: true,
+        ..Flags::default()
+      }
+    );
+
+    for invalid in ["0/3", "4/3", "1/0", "1", "a/b"] {
+      let r =
+        flags_from_vec(svec!["deno", "test", format!("--shard={invalid}")]);
+      assert!(r.is_err(), "{invalid}");
+    }
+  }
+
   #[test]
   fn bundle_with_cafile() {
     let r = flags_from_vec(svec![
@@ -6242,7 +7184,8 @@ mod tests {
           output: None,
           include: vec![r"^file:".to_string()],
           exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
-          lcov: false,
+          reporter: CoverageReporterConfig::Pretty,
+          thresholds: Default::default(),
         }),
         ..Flags::default()
       }
@@ -6268,13 +7211,135 @@ mod tests {
           },
           include: vec![r"^file:".to_string()],
           exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
-          lcov: true,
+          reporter: CoverageReporterConfig::Lcov,
           output: Some(PathBuf::from("foo.lcov")),
+          thresholds: Default::default(),
         }),
         ..Flags::default()
       }
     );
   }
+  #[test]
+  fn coverage_with_html() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--html",
+      "--output=cov_html",
+      "foo.json"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("foo.json")],
+            ignore: vec![],
+          },
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          reporter: CoverageReporterConfig::Html,
+          output: Some(PathBuf::from("cov_html")),
+          thresholds: Default::default(),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "coverage", "--html", "--lcov", "foo.json"]);
+    assert!(r.is_err());
+
+    let r =
+      flags_from_vec(svec!["deno", "coverage", "--output=foo", "foo.json"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn coverage_with_reporter() {
+    for (reporter, expected) in [
+      ("pretty", CoverageReporterConfig::Pretty),
+      ("lcov", CoverageReporterConfig::Lcov),
+      ("html", CoverageReporterConfig::Html),
+      ("cobertura", CoverageReporterConfig::Cobertura),
+      ("json", CoverageReporterConfig::Json),
+    ] {
+      let r = flags_from_vec(svec![
+        "deno",
+        "coverage",
+        format!("--reporter={reporter}"),
+        "foo.json"
+      ]);
+      assert_eq!(
+        r.unwrap(),
+        Flags {
+          subcommand: DenoSubcommand::Coverage(CoverageFlags {
+            files: FileFlags {
+              include: vec![PathBuf::from("foo.json")],
+              ignore: vec![],
+            },
+            output: None,
+            include: vec![r"^file:".to_string()],
+            exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+            reporter: expected,
+            thresholds: Default::default(),
+          }),
+          ..Flags::default()
+        }
+      );
+    }
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--reporter=cobertura",
+      "--lcov",
+      "foo.json"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn coverage_with_thresholds() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--threshold-lines=80",
+      "--threshold-branches=60",
+      "--threshold-functions=100",
+      "foo.json"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("foo.json")],
+            ignore: vec![],
+          },
+          output: None,
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          reporter: CoverageReporterConfig::Pretty,
+          thresholds: CoverageThresholds {
+            lines: Some(80),
+            branches: Some(60),
+            functions: Some(100),
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--threshold-lines=101",
+      "foo.json"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn location_with_bad_scheme() {
     #[rustfmt::skip]
@@ -6556,6 +7621,22 @@ mod tests {
     );
   }
 
+  #[test]
+  fn task_subcommand_coverage() {
+    let r = flags_from_vec(svec!["deno", "task", "--coverage=cov", "build"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+        }),
+        coverage_dir: Some("cov".to_string()),
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn task_subcommand_config_short() {
     let r = flags_from_vec(svec!["deno", "task", "-c", "deno.jsonc"]);
@@ -6613,6 +7694,13 @@ mod tests {
             include: vec![PathBuf::from("dir1/"), PathBuf::from("dir2/")],
             ignore: vec![],
           },
+          save_baseline: None,
+          baseline: None,
+          regression_threshold: None,
+          warmup: None,
+          min_iterations: None,
+          min_time: None,
+          max_time: None,
         }),
         unstable: true,
         no_npm: true,
@@ -6641,6 +7729,13 @@ mod tests {
             include: vec![],
             ignore: vec![],
           },
+          save_baseline: None,
+          baseline: None,
+          regression_threshold: None,
+          warmup: None,
+          min_iterations: None,
+          min_time: None,
+          max_time: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -6650,6 +7745,66 @@ mod tests {
     );
   }
 
+  #[test]
+  fn bench_baseline() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bench",
+      "--save-baseline=next",
+      "--baseline=main",
+      "--regression-threshold=5"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bench(BenchFlags {
+          save_baseline: Some("next".to_string()),
+          baseline: Some("main".to_string()),
+          regression_threshold: Some(5),
+          ..BenchFlags::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "bench", "--baseline=../main"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "bench", "--regression-threshold=5"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn bench_measure_options() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bench",
+      "--warmup=100",
+      "--min-iterations=50",
+      "--min-time=2000",
+      "--max-time=10000"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bench(BenchFlags {
+          warmup: Some(100),
+          min_iterations: Some(50),
+          min_time: Some(2000),
+          max_time: Some(10000),
+          ..BenchFlags::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "bench", "--warmup=-1"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn run_with_check() {
     let r = flags_from_vec(svec!["deno", "run", "--check", "script.ts",]);
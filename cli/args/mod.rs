@@ -24,6 +24,7 @@ pub use config_file::EmitConfigOptions;
 pub use config_file::FilesConfig;
 pub use config_file::FmtOptionsConfig;
 pub use config_file::JsxImportSourceConfig;
+pub use config_file::LintRuleSeverity;
 pub use config_file::LintRulesConfig;
 pub use config_file::ProseWrap;
 pub use config_file::TsConfig;
@@ -124,6 +125,14 @@ pub struct BenchOptions {
   pub filter: Option<String>,
   pub json: bool,
   pub no_run: bool,
+  pub save_baseline: Option<String>,
+  pub baseline: Option<String>,
+  /// Percentage by which a benchmark may be slower than the baseline.
+  pub regression_threshold: u32,
+  pub warmup: Option<u32>,
+  pub min_iterations: Option<u32>,
+  pub min_time: Option<u64>,
+  pub max_time: Option<u64>,
 }
 
 impl BenchOptions {
@@ -140,6 +149,13 @@ impl BenchOptions {
       filter: bench_flags.filter,
       json: bench_flags.json,
       no_run: bench_flags.no_run,
+      save_baseline: bench_flags.save_baseline,
+      baseline: bench_flags.baseline,
+      regression_threshold: bench_flags.regression_threshold.unwrap_or(10),
+      warmup: bench_flags.warmup,
+      min_iterations: bench_flags.min_iterations,
+      min_time: bench_flags.min_time,
+      max_time: bench_flags.max_time,
     })
   }
 }
@@ -238,6 +254,9 @@ pub struct TestOptions {
   pub shuffle: Option<u64>,
   pub concurrent_jobs: NonZeroUsize,
   pub trace_ops: bool,
+  pub reporter: TestReporterConfig,
+  pub junit_path: Option<String>,
+  pub shard: Option<TestShard>,
 }
 
 impl TestOptions {
@@ -262,6 +281,9 @@ impl TestOptions {
       no_run: test_flags.no_run,
       shuffle: test_flags.shuffle,
       trace_ops: test_flags.trace_ops,
+      reporter: test_flags.reporter,
+      junit_path: test_flags.junit_path,
+      shard: test_flags.shard,
     })
   }
 }
@@ -272,6 +294,7 @@ pub enum LintReporterKind {
   Pretty,
   Json,
   Compact,
+  Sarif,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -280,6 +303,9 @@ pub struct LintOptions {
   pub files: FilesConfig,
   pub is_stdin: bool,
   pub reporter_kind: LintReporterKind,
+  pub fix: bool,
+  pub max_warnings: Option<usize>,
+  pub plugins: Vec<ModuleSpecifier>,
 }
 
 impl LintOptions {
@@ -305,6 +331,8 @@ impl LintOptions {
           Some(LintReporterKind::Json)
         } else if lint_flags.compact {
           Some(LintReporterKind::Compact)
+        } else if lint_flags.sarif {
+          Some(LintReporterKind::Sarif)
         } else {
           None
         }
@@ -316,6 +344,7 @@ impl LintOptions {
         maybe_reporter_kind = match lint_config.report.as_deref() {
           Some("json") => Some(LintReporterKind::Json),
           Some("compact") => Some(LintReporterKind::Compact),
+          Some("sarif") => Some(LintReporterKind::Sarif),
           Some("pretty") => Some(LintReporterKind::Pretty),
           Some(_) => {
             bail!("Invalid lint report type in config file")
@@ -330,6 +359,8 @@ impl LintOptions {
       maybe_rules_tags,
       maybe_rules_include,
       maybe_rules_exclude,
+      fix,
+      max_warnings,
     ) = maybe_lint_flags
       .map(|f| {
         (
@@ -337,15 +368,24 @@ impl LintOptions {
           f.maybe_rules_tags,
           f.maybe_rules_include,
           f.maybe_rules_exclude,
+          f.fix,
+          f.max_warnings,
         )
       })
       .unwrap_or_default();
 
+    let plugins = maybe_lint_config
+      .as_ref()
+      .map(|c| c.plugins.clone())
+      .unwrap_or_default();
     let (maybe_config_files, maybe_config_rules) =
       maybe_lint_config.map(|c| (c.files, c.rules)).unzip();
     Ok(Self {
       reporter_kind: maybe_reporter_kind.unwrap_or_default(),
       is_stdin,
+      fix,
+      max_warnings,
+      plugins,
       files: resolve_files(maybe_config_files, Some(maybe_file_flags)),
       rules: resolve_lint_rules_options(
         maybe_config_rules,
@@ -363,6 +403,7 @@ fn resolve_lint_rules_options(
   mut maybe_rules_include: Option<Vec<String>>,
   mut maybe_rules_exclude: Option<Vec<String>>,
 ) -> LintRulesConfig {
+  let mut maybe_severity = None;
   if let Some(config_rules) = maybe_lint_rules_config {
     // Try to get configured rules. CLI flags take precedence
     // over config file, i.e. if there's `rules.include` in config file
@@ -376,11 +417,40 @@ fn resolve_lint_rules_options(
     if maybe_rules_tags.is_none() {
       maybe_rules_tags = config_rules.tags;
     }
+    maybe_severity = config_rules.severity;
+  }
+  // Rules that have a severity are turned on or off regardless of the tags,
+  // but excluding a rule still wins.
+  for (code, severity) in maybe_severity.iter().flatten() {
+    let is_excluded = maybe_rules_exclude
+      .as_ref()
+      .map(|exclude| exclude.contains(code))
+      .unwrap_or(false);
+    match severity {
+      LintRuleSeverity::Off => {
+        if let Some(include) = &mut maybe_rules_include {
+          include.retain(|c| c != code);
+        }
+        if !is_excluded {
+          maybe_rules_exclude
+            .get_or_insert_with(Vec::new)
+            .push(code.clone());
+        }
+      }
+      LintRuleSeverity::Error | LintRuleSeverity::Warn => {
+        if !is_excluded {
+          maybe_rules_include
+            .get_or_insert_with(Vec::new)
+            .push(code.clone());
+        }
+      }
+    }
   }
   LintRulesConfig {
     exclude: maybe_rules_exclude,
     include: maybe_rules_include,
     tags: maybe_rules_tags,
+    severity: maybe_severity,
   }
 }
 
@@ -975,6 +1045,27 @@ impl CliOptions {
     TestOptions::resolve(maybe_test_config, Some(test_flags))
   }
 
+  /// Combines the coverage thresholds given on the command line with the
+  /// ones from the config file, where the former take precedence.
+  pub fn resolve_coverage_thresholds(
+    &self,
+    flag_thresholds: CoverageThresholds,
+  ) -> Result<CoverageThresholds, AnyError> {
+    let config_thresholds = if let Some(config_file) = &self.maybe_config_file {
+      config_file
+        .to_coverage_config()?
+        .map(|config| config.thresholds)
+        .unwrap_or_default()
+    } else {
+      CoverageThresholds::default()
+    };
+    Ok(CoverageThresholds {
+      lines: flag_thresholds.lines.or(config_thresholds.lines),
+      branches: flag_thresholds.branches.or(config_thresholds.branches),
+      functions: flag_thresholds.functions.or(config_thresholds.functions),
+    })
+  }
+
   pub fn resolve_bench_options(
     &self,
     bench_flags: BenchFlags,
@@ -1306,6 +1397,7 @@ fn has_flag_env_var(name: &str) -> bool {
 #[cfg(test)]
 mod test {
   use super::*;
+  use std::collections::BTreeMap;
 
   #[cfg(not(windows))]
   #[test]
@@ -1460,4 +1552,30 @@ mod test {
     let resolver = StorageKeyResolver::empty();
     assert_eq!(resolver.resolve_storage_key(&specifier), None);
   }
+
+  #[test]
+  fn resolve_lint_rules_severity() {
+    let config_rules = LintRulesConfig {
+      tags: None,
+      include: Some(vec!["no-empty".to_string()]),
+      exclude: Some(vec!["no-debugger".to_string()]),
+      severity: Some(BTreeMap::from([
+        ("ban-untagged-todo".to_string(), LintRuleSeverity::Warn),
+        ("no-debugger".to_string(), LintRuleSeverity::Error),
+        ("no-empty".to_string(), LintRuleSeverity::Off),
+      ])),
+    };
+    let rules =
+      resolve_lint_rules_options(Some(config_rules), None, None, None);
+    assert_eq!(rules.include, Some(vec!["ban-untagged-todo".to_string()]));
+    assert_eq!(
+      rules.exclude,
+      Some(vec!["no-debugger".to_string(), "no-empty".to_string()])
+    );
+    assert_eq!(
+      rules.severity("ban-untagged-todo"),
+      Some(LintRuleSeverity::Warn)
+    );
+    assert_eq!(rules.severity("no-var"), None);
+  }
 }
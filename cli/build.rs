@@ -10,7 +10,7 @@ use deno_core::ExtensionFileSource;
 use deno_core::ExtensionFileSourceCode;
 use deno_runtime::deno_cache::SqliteBackedCache;
 use deno_runtime::deno_http::DefaultHttpPropertyExtractor;
-use deno_runtime::deno_kv::sqlite::SqliteDbHandler;
+use deno_runtime::deno_kv::dynamic::MultiBackendDbHandler;
 use deno_runtime::permissions::PermissionsContainer;
 use deno_runtime::*;
 
@@ -358,7 +358,7 @@ fn create_cli_snapshot(snapshot_path: PathBuf) {
     ),
     deno_tls::deno_tls::init_ops(),
     deno_kv::deno_kv::init_ops(
-      SqliteDbHandler::<PermissionsContainer>::new(None),
+      MultiBackendDbHandler::<PermissionsContainer>::remote_or_sqlite(None),
       false, // No --unstable.
     ),
     deno_napi::deno_napi::init_ops::<PermissionsContainer>(),
@@ -83,6 +83,11 @@ impl DenoDir {
     self.root.join("check_cache_v1")
   }
 
+  /// Folder for the benchmark results saved with `deno bench --save-baseline`.
+  pub fn bench_baselines_folder_path(&self) -> PathBuf {
+    self.root.join("bench_baselines")
+  }
+
   /// Path to the registries cache, used for the lps.
   pub fn registries_folder_path(&self) -> PathBuf {
     self.root.join("registries")
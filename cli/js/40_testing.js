@@ -20,6 +20,8 @@ const {
   MapPrototypeHas,
   MapPrototypeSet,
   MathCeil,
+  MathMax,
+  NumberIsInteger,
   ObjectKeys,
   ObjectHasOwn,
   ObjectPrototypeIsPrototypeOf,
@@ -569,6 +571,10 @@ function withPermissions(fn, permissions) {
  *   only: boolean.
  *   sanitizeExit: boolean,
  *   permissions: PermissionOptions,
+ *   warmup?: number,
+ *   minIterations?: number,
+ *   minTime?: number,
+ *   maxTime?: number,
  * }} BenchDescription
  */
 
@@ -774,13 +780,28 @@ function bench(
     benchDesc = { ...defaults, ...nameOrFnOrOptions, fn, name };
   }
 
+  validateBenchMeasureOption(benchDesc, "warmup");
+  validateBenchMeasureOption(benchDesc, "minIterations");
+  validateBenchMeasureOption(benchDesc, "minTime");
+  validateBenchMeasureOption(benchDesc, "maxTime");
+
   const AsyncFunction = (async () => {}).constructor;
   benchDesc.async = AsyncFunction === benchDesc.fn.constructor;
   benchDesc.fn = wrapBenchmark(benchDesc);
 
-  const { id, origin } = ops.op_register_bench(benchDesc);
+  const { id, origin, measure } = ops.op_register_bench(benchDesc);
   benchDesc.id = id;
   benchDesc.origin = origin;
+  benchDesc.measure = measure;
+}
+
+function validateBenchMeasureOption(benchDesc, name) {
+  const value = benchDesc[name];
+  if (value !== undefined && (!NumberIsInteger(value) || value < 0)) {
+    throw new TypeError(
+      `Invalid '${name}' option, expected a non-negative integer: ${value}`,
+    );
+  }
 }
 
 function compareMeasurements(a, b) {
@@ -790,7 +811,16 @@ function compareMeasurements(a, b) {
   return 0;
 }
 
-function benchStats(n, highPrecision, avg, min, max, all) {
+function benchStats(n, highPrecision, avg, min, max, all, alloc) {
+  // Sample variance of the measurements, used to tell whether the difference
+  // to a saved baseline is significant.
+  const mean = avg / n;
+  let variance = 0;
+  for (let i = 0; i < n; i++) {
+    variance += (all[i] - mean) ** 2;
+  }
+  variance = n > 1 ? variance / (n - 1) : 0;
+
   return {
     n,
     min,
@@ -800,10 +830,12 @@ function benchStats(n, highPrecision, avg, min, max, all) {
     p995: all[MathCeil(n * (99.5 / 100)) - 1],
     p999: all[MathCeil(n * (99.9 / 100)) - 1],
     avg: !highPrecision ? (avg / n) : MathCeil(avg / n),
+    variance,
+    alloc,
   };
 }
 
-async function benchMeasure(timeBudget, fn, async) {
+async function benchMeasure(measure, fn, async) {
   let n = 0;
   let avg = 0;
   let wavg = 0;
@@ -811,14 +843,23 @@ async function benchMeasure(timeBudget, fn, async) {
   let min = Infinity;
   let max = -Infinity;
   const lowPrecisionThresholdInNs = 1e4;
+  // The minimum number of samples taken in low precision mode, so that the
+  // percentiles and the variance are meaningful.
+  const lowPrecisionMinSamples = 10;
+
+  // The time spent on warmup counts towards `maxTime`.
+  const maxTime = (measure.maxTime ?? Infinity) * 1e6;
+  let elapsed = 0;
 
   // warmup step
+  // Without an explicit number of iterations, warm up for at least 10ms. At
+  // least one iteration is needed to choose the precision of the measurement.
   let c = 0;
-  let iterations = 20;
-  let budget = 10 * 1e6;
+  let iterations = measure.warmup ?? 20;
+  let budget = measure.warmup == null ? 10 * 1e6 : 0;
 
   if (!async) {
-    while (budget > 0 || iterations-- > 0) {
+    while (c === 0 || ((budget > 0 || c < iterations) && elapsed < maxTime)) {
       const t1 = benchNow();
 
       fn();
@@ -827,9 +868,10 @@ async function benchMeasure(timeBudget, fn, async) {
       c++;
       wavg += iterationTime;
       budget -= iterationTime;
+      elapsed += iterationTime;
     }
   } else {
-    while (budget > 0 || iterations-- > 0) {
+    while (c === 0 || ((budget > 0 || c < iterations) && elapsed < maxTime)) {
       const t1 = benchNow();
 
       await fn();
@@ -838,80 +880,113 @@ async function benchMeasure(timeBudget, fn, async) {
       c++;
       wavg += iterationTime;
       budget -= iterationTime;
+      elapsed += iterationTime;
     }
   }
 
   wavg /= c;
 
   // measure step
-  if (wavg > lowPrecisionThresholdInNs) {
-    let iterations = 10;
-    let budget = timeBudget * 1e6;
+  // Heap usage is sampled around every measurement, outside of the timed
+  // section. Samples during which the heap shrank because of a garbage
+  // collection count as no allocation, as the actual amount is unknown.
+  let alloc = 0;
+  iterations = measure.minIterations ?? 10;
+  budget = (measure.minTime ?? 500) * 1e6;
 
+  if (wavg > lowPrecisionThresholdInNs) {
     if (!async) {
-      while (budget > 0 || iterations-- > 0) {
+      while (n === 0 || ((budget > 0 || n < iterations) && elapsed < maxTime)) {
+        const h1 = benchHeapUsed();
         const t1 = benchNow();
 
         fn();
         const iterationTime = benchNow() - t1;
+        const allocated = benchHeapUsed() - h1;
 
         n++;
         avg += iterationTime;
         budget -= iterationTime;
+        elapsed += iterationTime;
         ArrayPrototypePush(all, iterationTime);
         if (iterationTime < min) min = iterationTime;
         if (iterationTime > max) max = iterationTime;
+        alloc += MathMax(allocated, 0);
       }
     } else {
-      while (budget > 0 || iterations-- > 0) {
+      while (n === 0 || ((budget > 0 || n < iterations) && elapsed < maxTime)) {
+        const h1 = benchHeapUsed();
         const t1 = benchNow();
 
         await fn();
         const iterationTime = benchNow() - t1;
+        const allocated = benchHeapUsed() - h1;
 
         n++;
         avg += iterationTime;
         budget -= iterationTime;
+        elapsed += iterationTime;
         ArrayPrototypePush(all, iterationTime);
         if (iterationTime < min) min = iterationTime;
         if (iterationTime > max) max = iterationTime;
+        alloc += MathMax(allocated, 0);
       }
     }
   } else {
-    let iterations = 10;
-    let budget = timeBudget * 1e6;
+    // Every measurement runs the function `lowPrecisionThresholdInNs` times,
+    // so the minimum number of iterations is reached much earlier.
+    iterations = MathMax(
+      MathCeil(iterations / lowPrecisionThresholdInNs),
+      lowPrecisionMinSamples,
+    );
 
     if (!async) {
-      while (budget > 0 || iterations-- > 0) {
+      while (n === 0 || ((budget > 0 || n < iterations) && elapsed < maxTime)) {
+        const h1 = benchHeapUsed();
         const t1 = benchNow();
         for (let c = 0; c < lowPrecisionThresholdInNs; c++) fn();
         const iterationTime = (benchNow() - t1) / lowPrecisionThresholdInNs;
+        const allocated = (benchHeapUsed() - h1) / lowPrecisionThresholdInNs;
 
         n++;
         avg += iterationTime;
         ArrayPrototypePush(all, iterationTime);
         if (iterationTime < min) min = iterationTime;
         if (iterationTime > max) max = iterationTime;
+        alloc += MathMax(allocated, 0);
         budget -= iterationTime * lowPrecisionThresholdInNs;
+        elapsed += iterationTime * lowPrecisionThresholdInNs;
       }
     } else {
-      while (budget > 0 || iterations-- > 0) {
+      while (n === 0 || ((budget > 0 || n < iterations) && elapsed < maxTime)) {
+        const h1 = benchHeapUsed();
         const t1 = benchNow();
         for (let c = 0; c < lowPrecisionThresholdInNs; c++) await fn();
         const iterationTime = (benchNow() - t1) / lowPrecisionThresholdInNs;
+        const allocated = (benchHeapUsed() - h1) / lowPrecisionThresholdInNs;
 
         n++;
         avg += iterationTime;
         ArrayPrototypePush(all, iterationTime);
         if (iterationTime < min) min = iterationTime;
         if (iterationTime > max) max = iterationTime;
+        alloc += MathMax(allocated, 0);
         budget -= iterationTime * lowPrecisionThresholdInNs;
+        elapsed += iterationTime * lowPrecisionThresholdInNs;
       }
     }
   }
 
   all.sort(compareMeasurements);
-  return benchStats(n, wavg > lowPrecisionThresholdInNs, avg, min, max, all);
+  return benchStats(
+    n,
+    wavg > lowPrecisionThresholdInNs,
+    avg,
+    min,
+    max,
+    all,
+    alloc / n,
+  );
 }
 
 /** Wrap a user benchmark function in one which returns a structured result. */
@@ -939,8 +1014,7 @@ function wrapBenchmark(desc) {
         });
       }
 
-      const benchTimeInMs = 500;
-      const stats = await benchMeasure(benchTimeInMs, fn, desc.async);
+      const stats = await benchMeasure(desc.measure, fn, desc.async);
 
       return { ok: stats };
     } catch (error) {
@@ -957,6 +1031,10 @@ function benchNow() {
   return ops.op_bench_now();
 }
 
+function benchHeapUsed() {
+  return ops.op_bench_heap_used();
+}
+
 function getFullName(desc) {
   if ("parent" in desc) {
     return `${getFullName(desc.parent)} ... ${desc.name}`;
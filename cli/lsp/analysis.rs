@@ -5,8 +5,14 @@ use super::documents::Documents;
 use super::language_server;
 use super::tsc;
 
+use crate::args::LintRuleSeverity;
+use crate::args::LintRulesConfig;
 use crate::tools::lint::create_linter;
+use crate::tools::lint::get_lint_fix;
+use crate::tools::lint::lint_with_plugins;
+use crate::tools::lint::LintPluginHost;
 
+use deno_ast::LineAndColumnIndex;
 use deno_ast::SourceRange;
 use deno_ast::SourceRangedForSpanned;
 use deno_ast::SourceTextInfo;
@@ -66,6 +72,9 @@ pub enum Category {
     message: String,
     code: String,
     hint: Option<String>,
+    /// The severity configured for the rule, lint diagnostics are warnings
+    /// by default.
+    severity: Option<LintRuleSeverity>,
   },
 }
 
@@ -83,9 +92,13 @@ impl Reference {
         message,
         code,
         hint,
+        severity,
       } => lsp::Diagnostic {
         range: self.range,
-        severity: Some(lsp::DiagnosticSeverity::WARNING),
+        severity: Some(match severity {
+          Some(LintRuleSeverity::Error) => lsp::DiagnosticSeverity::ERROR,
+          _ => lsp::DiagnosticSeverity::WARNING,
+        }),
         code: Some(lsp::NumberOrString::String(code.to_string())),
         code_description: None,
         source: Some("deno-lint".to_string()),
@@ -121,20 +134,32 @@ fn as_lsp_range(range: &deno_lint::diagnostic::Range) -> Range {
 pub fn get_lint_references(
   parsed_source: &deno_ast::ParsedSource,
   lint_rules: Vec<&'static dyn LintRule>,
+  rules_config: &LintRulesConfig,
+  plugin_host: Option<&LintPluginHost>,
 ) -> Result<Vec<Reference>, AnyError> {
-  let linter = create_linter(parsed_source.media_type(), lint_rules);
-  let lint_diagnostics = linter.lint_with_ast(parsed_source);
+  let lint_diagnostics =
+    lint_with_plugins(lint_rules, plugin_host, |lint_rules| {
+      let linter = create_linter(parsed_source.media_type(), lint_rules);
+      Ok(linter.lint_with_ast(parsed_source))
+    })?;
 
   Ok(
     lint_diagnostics
       .into_iter()
-      .map(|d| Reference {
-        category: Category::Lint {
-          message: d.message,
-          code: d.code,
-          hint: d.hint,
-        },
-        range: as_lsp_range(&d.range),
+      .filter_map(|d| {
+        let severity = rules_config.severity(&d.code);
+        if severity == Some(LintRuleSeverity::Off) {
+          return None;
+        }
+        Some(Reference {
+          category: Category::Lint {
+            message: d.message,
+            code: d.code,
+            hint: d.hint,
+            severity,
+          },
+          range: as_lsp_range(&d.range),
+        })
       })
       .collect(),
   )
@@ -371,6 +396,65 @@ impl CodeActionCollection {
     Ok(())
   }
 
+  /// Adds a quick fix for a lint diagnostic, if its rule has a safe fix.
+  pub fn add_deno_lint_fix_action(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    diagnostic: &lsp::Diagnostic,
+    text_info: &SourceTextInfo,
+  ) {
+    let code = match &diagnostic.code {
+      Some(lsp::NumberOrString::String(code)) => code,
+      _ => return,
+    };
+    let as_loc = |position: &lsp::Position| LineAndColumnIndex {
+      line_index: position.line as usize,
+      column_index: position.character as usize,
+    };
+    let fix = match get_lint_fix(
+      code,
+      text_info,
+      as_loc(&diagnostic.range.start)..as_loc(&diagnostic.range.end),
+    ) {
+      Some(fix) => fix,
+      None => return,
+    };
+    let as_position = |byte_index: usize| {
+      let loc =
+        text_info.line_and_column_index(text_info.range().start + byte_index);
+      lsp::Position {
+        line: loc.line_index as u32,
+        character: loc.column_index as u32,
+      }
+    };
+    let edits = fix
+      .changes
+      .into_iter()
+      .map(|change| lsp::TextEdit {
+        range: lsp::Range {
+          start: as_position(change.range.start),
+          end: as_position(change.range.end),
+        },
+        new_text: change.new_text,
+      })
+      .collect();
+    let fix_action = lsp::CodeAction {
+      title: fix.description,
+      kind: Some(lsp::CodeActionKind::QUICKFIX),
+      diagnostics: Some(vec![diagnostic.clone()]),
+      command: None,
+      is_preferred: Some(true),
+      disabled: None,
+      data: None,
+      edit: Some(lsp::WorkspaceEdit {
+        changes: Some(HashMap::from([(specifier.clone(), edits)])),
+        change_annotations: None,
+        document_changes: None,
+      }),
+    };
+    self.actions.push(CodeActionKind::DenoLint(fix_action));
+  }
+
   pub fn add_deno_lint_ignore_action(
     &mut self,
     specifier: &ModuleSpecifier,
@@ -757,6 +841,7 @@ mod tests {
             message: "message1".to_string(),
             code: "code1".to_string(),
             hint: None,
+            severity: None,
           },
           range,
         },
@@ -775,6 +860,7 @@ mod tests {
             message: "message2".to_string(),
             code: "code2".to_string(),
             hint: Some("hint2".to_string()),
+            severity: Some(LintRuleSeverity::Warn),
           },
           range,
         },
@@ -787,6 +873,25 @@ mod tests {
           ..Default::default()
         },
       ),
+      (
+        Reference {
+          category: Category::Lint {
+            message: "message3".to_string(),
+            code: "code3".to_string(),
+            hint: None,
+            severity: Some(LintRuleSeverity::Error),
+          },
+          range,
+        },
+        lsp::Diagnostic {
+          range,
+          severity: Some(lsp::DiagnosticSeverity::ERROR),
+          code: Some(lsp::NumberOrString::String("code3".to_string())),
+          source: Some("deno-lint".to_string()),
+          message: "message3".to_string(),
+          ..Default::default()
+        },
+      ),
     ];
 
     for (input, expected) in test_cases.iter() {
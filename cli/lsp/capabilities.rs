@@ -108,14 +108,22 @@ pub fn server_capabilities(
       resolve_provider: Some(true),
     }),
     document_formatting_provider: Some(OneOf::Left(true)),
-    document_range_formatting_provider: None,
-    document_on_type_formatting_provider: None,
+    document_range_formatting_provider: Some(OneOf::Left(true)),
+    document_on_type_formatting_provider: Some(
+      DocumentOnTypeFormattingOptions {
+        first_trigger_character: "}".to_string(),
+        more_trigger_character: Some(vec![";".to_string()]),
+      },
+    ),
     selection_range_provider: Some(SelectionRangeProviderCapability::Simple(
       true,
     )),
     folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
     rename_provider: Some(OneOf::Left(true)),
-    document_link_provider: None,
+    document_link_provider: Some(DocumentLinkOptions {
+      resolve_provider: Some(false),
+      work_done_progress_options: Default::default(),
+    }),
     color_provider: None,
     execute_command_provider: None,
     call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
@@ -134,9 +142,23 @@ pub fn server_capabilities(
         supported: Some(true),
         change_notifications: Some(OneOf::Left(true)),
       }),
-      file_operations: None,
+      file_operations: Some(WorkspaceFileOperationsServerCapabilities {
+        will_rename: Some(FileOperationRegistrationOptions {
+          filters: vec![FileOperationFilter {
+            scheme: Some("file".to_string()),
+            pattern: FileOperationPattern {
+              glob: "**/*".to_string(),
+              matches: None,
+              options: None,
+            },
+          }],
+        }),
+        ..Default::default()
+      }),
     }),
-    linked_editing_range_provider: None,
+    linked_editing_range_provider: Some(
+      LinkedEditingRangeServerCapabilities::Simple(true),
+    ),
     moniker_provider: None,
     experimental: Some(json!({
       "denoConfigTasks": true,
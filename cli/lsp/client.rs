@@ -15,6 +15,7 @@ use crate::lsp::repl::get_repl_workspace_settings;
 
 use super::config::SpecifierSettings;
 use super::config::SETTINGS_SECTION;
+use super::logging::lsp_warn;
 use super::lsp_custom;
 use super::testing::lsp_custom as testing_lsp_custom;
 use super::urls::LspClientUrl;
@@ -80,6 +81,15 @@ impl Client {
     });
   }
 
+  /// Asks a client which pulls diagnostics to pull them again.
+  pub fn send_diagnostic_refresh_request(&self) {
+    // do on a task in case the caller currently is in the lsp lock
+    let client = self.0.clone();
+    tokio::task::spawn(async move {
+      client.send_diagnostic_refresh_request().await;
+    });
+  }
+
   pub fn show_message(
     &self,
     message_type: lsp::MessageType,
@@ -172,6 +182,7 @@ trait ClientTrait: Send + Sync {
     params: lsp_custom::RegistryStateNotificationParams,
   );
   async fn send_test_notification(&self, params: TestingNotification);
+  async fn send_diagnostic_refresh_request(&self);
   async fn specifier_configurations(
     &self,
     uris: Vec<lsp::Url>,
@@ -239,6 +250,16 @@ impl ClientTrait for TowerClient {
     }
   }
 
+  async fn send_diagnostic_refresh_request(&self) {
+    if let Err(err) = self
+      .0
+      .send_request::<lsp_custom::DiagnosticRefreshRequest>(())
+      .await
+    {
+      lsp_warn!("Client errored on diagnostic refresh request.\n{:#}", err);
+    }
+  }
+
   async fn specifier_configurations(
     &self,
     uris: Vec<lsp::Url>,
@@ -332,6 +353,8 @@ impl ClientTrait for ReplClient {
 
   async fn send_test_notification(&self, _params: TestingNotification) {}
 
+  async fn send_diagnostic_refresh_request(&self) {}
+
   async fn specifier_configurations(
     &self,
     uris: Vec<lsp::Url>,
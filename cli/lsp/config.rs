@@ -19,7 +19,14 @@ pub const SETTINGS_SECTION: &str = "deno";
 #[derive(Debug, Clone, Default)]
 pub struct ClientCapabilities {
   pub code_action_disabled_support: bool,
+  /// The client provides the `workspace.diagnostics.refreshSupport`
+  /// capability, see `pull_diagnostics`.
+  pub diagnostic_refresh_support: bool,
   pub line_folding_only: bool,
+  /// The client provides the `textDocument.diagnostic` capability. It
+  /// indicates that the client requests the diagnostics of documents instead
+  /// of the server publishing them.
+  pub pull_diagnostics: bool,
   pub snippet_support: bool,
   pub status_notification: bool,
   /// The client provides the `experimental.testingApi` capability, which is
@@ -318,6 +325,11 @@ pub struct WorkspaceSettings {
   #[serde(default = "default_to_true")]
   pub lint: bool,
 
+  /// A flag that indicates if the lint plugins of the config file should be
+  /// run. Plugins execute code of the workspace, so they are opt-in.
+  #[serde(default)]
+  pub lint_plugins: bool,
+
   /// A flag that indicates if Dene should validate code against the unstable
   /// APIs for the workspace.
   #[serde(default)]
@@ -354,6 +366,7 @@ impl Default for WorkspaceSettings {
       inlay_hints: Default::default(),
       internal_debug: false,
       lint: true,
+      lint_plugins: false,
       suggest: Default::default(),
       testing: Default::default(),
       tls_certificate: None,
@@ -750,6 +763,7 @@ mod tests {
         },
         internal_debug: false,
         lint: true,
+        lint_plugins: false,
         suggest: CompletionSettings {
           complete_function_calls: false,
           names: true,
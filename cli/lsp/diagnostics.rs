@@ -17,10 +17,14 @@ use crate::args::LintOptions;
 use crate::graph_util;
 use crate::graph_util::enhanced_resolution_error_message;
 use crate::tools::lint::get_configured_rules;
+use crate::tools::lint::LintPluginHost;
 
 use deno_ast::MediaType;
 use deno_core::anyhow::anyhow;
 use deno_core::error::AnyError;
+use deno_core::futures::future::BoxFuture;
+use deno_core::futures::future::Shared;
+use deno_core::futures::FutureExt;
 use deno_core::resolve_url;
 use deno_core::serde::Deserialize;
 use deno_core::serde_json;
@@ -142,12 +146,147 @@ impl TsDiagnosticsStore {
       })
       .collect();
   }
+
+  fn insert(&self, diagnostics: &DiagnosticVec) {
+    let mut stored_ts_diagnostics = self.0.lock();
+    for (specifier, version, diagnostics) in diagnostics {
+      stored_ts_diagnostics
+        .insert(specifier.clone(), (*version, diagnostics.clone()));
+    }
+  }
+}
+
+/// The diagnostics last reported to a client which pulls them. Their result
+/// IDs are derived from the version of the document and the version of the
+/// project, which changes with every change of the documents or the
+/// configuration, as the diagnostics of a document depend on both.
+#[derive(Debug, Default)]
+pub struct DiagnosticReportCache(
+  deno_core::parking_lot::Mutex<DiagnosticReportCacheInner>,
+);
+
+#[derive(Debug, Default)]
+struct DiagnosticReportCacheInner {
+  project_version: usize,
+  reports: HashMap<ModuleSpecifier, (String, Vec<lsp::Diagnostic>)>,
+}
+
+impl DiagnosticReportCache {
+  /// Outdates the reports of all documents.
+  pub fn increment_project_version(&self) {
+    let mut inner = self.0.lock();
+    inner.project_version += 1;
+    inner.reports.clear();
+  }
+
+  pub fn result_id(&self, document_version: Option<i32>) -> String {
+    let project_version = self.0.lock().project_version;
+    match document_version {
+      Some(document_version) => format!("{project_version}:{document_version}"),
+      None => project_version.to_string(),
+    }
+  }
+
+  /// Returns the diagnostics last reported for the document, if they were
+  /// reported with the result ID.
+  pub fn get(
+    &self,
+    specifier: &ModuleSpecifier,
+    result_id: &str,
+  ) -> Option<Vec<lsp::Diagnostic>> {
+    let inner = self.0.lock();
+    let (cached_result_id, diagnostics) = inner.reports.get(specifier)?;
+    (cached_result_id == result_id).then(|| diagnostics.clone())
+  }
+
+  pub fn insert(
+    &self,
+    specifier: ModuleSpecifier,
+    result_id: String,
+    diagnostics: Vec<lsp::Diagnostic>,
+  ) {
+    self
+      .0
+      .lock()
+      .reports
+      .insert(specifier, (result_id, diagnostics));
+  }
+}
+
+type LintPluginHostFuture =
+  Shared<BoxFuture<'static, Option<Arc<LintPluginHost>>>>;
+
+/// The host of the configured lint plugins. It's loaded on a blocking thread
+/// and only loaded again when the configured modules or excluded rules
+/// change, or when a loaded module is modified.
+#[derive(Clone, Default)]
+struct LintPluginHostCache(
+  Arc<
+    deno_core::parking_lot::Mutex<
+      Option<((Vec<ModuleSpecifier>, Vec<String>), LintPluginHostFuture)>,
+    >,
+  >,
+);
+
+impl std::fmt::Debug for LintPluginHostCache {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("LintPluginHostCache")
+      .finish_non_exhaustive()
+  }
+}
+
+impl LintPluginHostCache {
+  /// Gets the host of the plugins, if running them is enabled with the
+  /// `lintPlugins` setting.
+  pub async fn get(
+    &self,
+    config: &ConfigSnapshot,
+    lint_options: &LintOptions,
+  ) -> Option<Arc<LintPluginHost>> {
+    if !config.settings.workspace.lint_plugins
+      || lint_options.plugins.is_empty()
+    {
+      return None;
+    }
+    let key = (
+      lint_options.plugins.clone(),
+      lint_options.rules.exclude.clone().unwrap_or_default(),
+    );
+    let future = {
+      let mut cache = self.0.lock();
+      let is_current = cache
+        .as_ref()
+        .map(|(cached_key, future)| {
+          cached_key == &key
+            && !matches!(future.peek(), Some(Some(host)) if host.is_outdated())
+        })
+        .unwrap_or(false);
+      if !is_current {
+        let (plugins, exclude) = key.clone();
+        let future = tokio::task::spawn_blocking(move || {
+          LintPluginHost::new(plugins, exclude)
+            .map(Arc::new)
+            .map_err(|err| {
+              error!("Error loading lint plugins: {}", err);
+            })
+            .ok()
+        })
+        .map(|result| result.ok().flatten())
+        .boxed()
+        .shared();
+        *cache = Some((key, future));
+      }
+      cache.as_ref().unwrap().1.clone()
+    };
+    future.await
+  }
 }
 
 #[derive(Debug)]
 pub struct DiagnosticsServer {
   channel: Option<mpsc::UnboundedSender<SnapshotForDiagnostics>>,
   ts_diagnostics: TsDiagnosticsStore,
+  lint_plugin_host: LintPluginHostCache,
   client: Client,
   performance: Arc<Performance>,
   ts_server: Arc<TsServer>,
@@ -162,6 +301,7 @@ impl DiagnosticsServer {
     DiagnosticsServer {
       channel: Default::default(),
       ts_diagnostics: Default::default(),
+      lint_plugin_host: Default::default(),
       client,
       performance,
       ts_server,
@@ -191,6 +331,7 @@ impl DiagnosticsServer {
     let client = self.client.clone();
     let performance = self.performance.clone();
     let ts_diagnostics_store = self.ts_diagnostics.clone();
+    let lint_plugin_host = self.lint_plugin_host.clone();
     let ts_server = self.ts_server.clone();
 
     let _join_handle = thread::spawn(move || {
@@ -300,16 +441,23 @@ impl DiagnosticsServer {
                 let token = token.clone();
                 let snapshot = snapshot.clone();
                 let config = config.clone();
+                let lint_plugin_host = lint_plugin_host.clone();
                 async move {
                   if let Some(previous_handle) = previous_lint_handle {
                     previous_handle.await;
                   }
+                  let plugin_host = tokio::select! {
+                    _ = token.cancelled() => { return; }
+                    plugin_host =
+                      lint_plugin_host.get(&config, &lint_options) => plugin_host,
+                  };
                   let mark =
                     performance.mark("update_diagnostics_lint", None::<()>);
                   let diagnostics = generate_lint_diagnostics(
                     &snapshot,
                     &config,
                     &lint_options,
+                    plugin_host.as_deref(),
                     token.clone(),
                   )
                   .await;
@@ -341,6 +489,74 @@ impl DiagnosticsServer {
       Err(anyhow!("diagnostics server not started"))
     }
   }
+
+  /// Generate the TypeScript, lint and dependency diagnostics of the supplied
+  /// documents. This is used for clients which request the diagnostics of the
+  /// documents they show instead of having them published.
+  pub async fn pull_diagnostics(
+    &self,
+    snapshot: Arc<StateSnapshot>,
+    config: &ConfigSnapshot,
+    lint_options: &LintOptions,
+    specifiers: &[ModuleSpecifier],
+    token: CancellationToken,
+  ) -> Result<DiagnosticVec, AnyError> {
+    let mark = self.performance.mark("pull_diagnostics", None::<()>);
+    let documents = specifiers
+      .iter()
+      .filter_map(|specifier| snapshot.documents.get(specifier))
+      .filter(|document| document.is_diagnosable())
+      .collect::<Vec<_>>();
+    let ts_diagnostics = generate_specifiers_ts_diagnostics(
+      snapshot.clone(),
+      config,
+      &self.ts_server,
+      documents.iter().map(|d| d.specifier().clone()).collect(),
+      token.clone(),
+    )
+    .await?;
+    self.ts_diagnostics.insert(&ts_diagnostics);
+    let mut ts_diagnostics = ts_diagnostics
+      .into_iter()
+      .map(|(specifier, _, diagnostics)| (specifier, diagnostics))
+      .collect::<HashMap<_, _>>();
+
+    let lint_rules = get_configured_rules(lint_options.rules.clone());
+    let plugin_host = self.lint_plugin_host.get(config, lint_options).await;
+    let mut diagnostics_vec = Vec::new();
+    for document in documents {
+      if token.is_cancelled() {
+        return Err(anyhow!("Pulling the diagnostics was cancelled."));
+      }
+      let specifier = document.specifier();
+      let mut diagnostics =
+        ts_diagnostics.remove(specifier).unwrap_or_default();
+      diagnostics.extend(generate_document_deno_diagnostics(
+        &snapshot, config, &document,
+      ));
+      let in_npm_package = snapshot
+        .maybe_node_resolver
+        .as_ref()
+        .map(|node_resolver| node_resolver.in_npm_package(specifier))
+        .unwrap_or(false);
+      if config.settings.workspace.lint && !in_npm_package {
+        diagnostics.extend(generate_document_lint_diagnostics(
+          config,
+          lint_options,
+          lint_rules.clone(),
+          plugin_host.as_deref(),
+          &document,
+        ));
+      }
+      diagnostics_vec.push((
+        specifier.clone(),
+        document.maybe_lsp_version(),
+        diagnostics,
+      ));
+    }
+    self.performance.measure(mark);
+    Ok(diagnostics_vec)
+  }
 }
 
 impl<'a> From<&'a crate::tsc::DiagnosticCategory> for lsp::DiagnosticSeverity {
@@ -453,6 +669,7 @@ async fn generate_lint_diagnostics(
   snapshot: &language_server::StateSnapshot,
   config: &ConfigSnapshot,
   lint_options: &LintOptions,
+  plugin_host: Option<&LintPluginHost>,
   token: CancellationToken,
 ) -> DiagnosticVec {
   let documents = snapshot
@@ -483,6 +700,7 @@ async fn generate_lint_diagnostics(
           config,
           lint_options,
           lint_rules.clone(),
+          plugin_host,
           &document,
         ),
       ));
@@ -495,6 +713,7 @@ fn generate_document_lint_diagnostics(
   config: &ConfigSnapshot,
   lint_options: &LintOptions,
   lint_rules: Vec<&'static dyn LintRule>,
+  plugin_host: Option<&LintPluginHost>,
   document: &Document,
 ) -> Vec<lsp::Diagnostic> {
   if !config.specifier_enabled(document.specifier()) {
@@ -505,15 +724,20 @@ fn generate_document_lint_diagnostics(
   }
   match document.maybe_parsed_source() {
     Some(Ok(parsed_source)) => {
-      if let Ok(references) =
-        analysis::get_lint_references(&parsed_source, lint_rules)
-      {
-        references
+      match analysis::get_lint_references(
+        &parsed_source,
+        lint_rules,
+        &lint_options.rules,
+        plugin_host,
+      ) {
+        Ok(references) => references
           .into_iter()
           .map(|r| r.to_diagnostic())
-          .collect::<Vec<_>>()
-      } else {
-        Vec::new()
+          .collect::<Vec<_>>(),
+        Err(err) => {
+          error!("Error linting {}: {}", document.specifier(), err);
+          Vec::new()
+        }
       }
     }
     Some(Err(_)) => Vec::new(),
@@ -530,12 +754,26 @@ async fn generate_ts_diagnostics(
   ts_server: &tsc::TsServer,
   token: CancellationToken,
 ) -> Result<DiagnosticVec, AnyError> {
-  let mut diagnostics_vec = Vec::new();
   let specifiers = snapshot
     .documents
     .documents(DocumentsFilter::OpenDiagnosable)
     .into_iter()
-    .map(|d| d.specifier().clone());
+    .map(|d| d.specifier().clone())
+    .collect();
+  generate_specifiers_ts_diagnostics(
+    snapshot, config, ts_server, specifiers, token,
+  )
+  .await
+}
+
+async fn generate_specifiers_ts_diagnostics(
+  snapshot: Arc<language_server::StateSnapshot>,
+  config: &ConfigSnapshot,
+  ts_server: &tsc::TsServer,
+  specifiers: Vec<ModuleSpecifier>,
+  token: CancellationToken,
+) -> Result<DiagnosticVec, AnyError> {
+  let mut diagnostics_vec = Vec::new();
   let (enabled_specifiers, disabled_specifiers) = specifiers
     .into_iter()
     .partition::<Vec<_>, _>(|s| config.specifier_enabled(s));
@@ -1052,29 +1290,37 @@ async fn generate_deno_diagnostics(
     if token.is_cancelled() {
       break;
     }
-    let mut diagnostics = Vec::new();
-    let specifier = document.specifier();
-    if config.specifier_enabled(specifier) {
-      for (dependency_key, dependency) in document.dependencies() {
-        diagnose_dependency(
-          &mut diagnostics,
-          snapshot,
-          specifier,
-          dependency_key,
-          dependency,
-        );
-      }
-    }
     diagnostics_vec.push((
-      specifier.clone(),
+      document.specifier().clone(),
       document.maybe_lsp_version(),
-      diagnostics,
+      generate_document_deno_diagnostics(snapshot, config, &document),
     ));
   }
 
   diagnostics_vec
 }
 
+fn generate_document_deno_diagnostics(
+  snapshot: &language_server::StateSnapshot,
+  config: &ConfigSnapshot,
+  document: &Document,
+) -> Vec<lsp::Diagnostic> {
+  let mut diagnostics = Vec::new();
+  let specifier = document.specifier();
+  if config.specifier_enabled(specifier) {
+    for (dependency_key, dependency) in document.dependencies() {
+      diagnose_dependency(
+        &mut diagnostics,
+        snapshot,
+        specifier,
+        dependency_key,
+        dependency,
+      );
+    }
+  }
+  diagnostics
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -1174,6 +1420,7 @@ let c: number = "a";
         &snapshot,
         &enabled_config,
         &Default::default(),
+        None,
         Default::default(),
       )
       .await;
@@ -1212,6 +1459,7 @@ let c: number = "a";
         &snapshot,
         &disabled_config,
         &Default::default(),
+        None,
         Default::default(),
       )
       .await;
@@ -0,0 +1,390 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::documents::to_lsp_range;
+use super::documents::Document;
+use super::text::LineIndex;
+use crate::util::fs::canonicalize_path;
+use crate::util::fs::FileCollector;
+use crate::util::path::is_supported_ext;
+use crate::util::path::relative_specifier;
+use crate::util::path::specifier_to_file_path;
+
+use deno_core::ModuleSpecifier;
+use jsonc_parser::ast::ObjectProp;
+use jsonc_parser::ast::ObjectPropName;
+use jsonc_parser::ast::StringLit;
+use jsonc_parser::ast::Value;
+use text_size::TextSize;
+use tower_lsp::lsp_types as lsp;
+
+/// A set of files and directories which are about to be renamed or moved by
+/// the client.
+#[derive(Debug, Default)]
+pub struct FileRenames(Vec<(ModuleSpecifier, ModuleSpecifier)>);
+
+impl FileRenames {
+  pub fn new(renames: Vec<(ModuleSpecifier, ModuleSpecifier)>) -> Self {
+    Self(renames)
+  }
+
+  /// Return the files which are about to be renamed, which are the renamed
+  /// files themselves and the supported files within renamed directories.
+  pub fn old_files(&self) -> Vec<ModuleSpecifier> {
+    let mut files = Vec::new();
+    for (old, _) in &self.0 {
+      let Ok(path) = specifier_to_file_path(old) else {
+        continue;
+      };
+      if !path.is_dir() {
+        files.push(old.clone());
+        continue;
+      }
+      let Ok(canonicalized_path) = canonicalize_path(&path) else {
+        continue;
+      };
+      let collected = FileCollector::new(is_supported_ext)
+        .ignore_git_folder()
+        .ignore_node_modules()
+        .collect_files(&[path.clone()])
+        .unwrap_or_default();
+      // the collected paths are canonicalized, so they are made relative to
+      // the renamed directory in order to match the specifiers of the renames
+      files.extend(collected.into_iter().filter_map(|file| {
+        let relative = file.strip_prefix(&canonicalized_path).ok()?;
+        ModuleSpecifier::from_file_path(path.join(relative)).ok()
+      }));
+    }
+    files
+  }
+
+  /// Return the location the specifier will have after the renames, if it is
+  /// one of the renamed files or is within one of the renamed directories.
+  pub fn get(&self, specifier: &ModuleSpecifier) -> Option<ModuleSpecifier> {
+    for (old, new) in &self.0 {
+      if specifier == old {
+        return Some(new.clone());
+      }
+      let old_dir = format!("{}/", old.as_str().trim_end_matches('/'));
+      if let Some(rest) = specifier.as_str().strip_prefix(&old_dir) {
+        let new_dir = format!("{}/", new.as_str().trim_end_matches('/'));
+        return ModuleSpecifier::parse(&format!("{new_dir}{rest}")).ok();
+      }
+    }
+    None
+  }
+}
+
+fn is_relative_specifier(specifier: &str) -> bool {
+  specifier.starts_with("./") || specifier.starts_with("../")
+}
+
+/// Return the edits which keep the relative specifiers of the document
+/// pointing at the same modules once the renames have been applied. This
+/// covers both a moved document and documents depending on moved modules.
+pub fn get_dependency_edits(
+  document: &Document,
+  renames: &FileRenames,
+) -> Vec<lsp::TextEdit> {
+  let referrer = document.specifier();
+  let new_referrer = renames.get(referrer);
+  let text = document.content();
+  let mut edits = Vec::new();
+  for (specifier, dependency) in document.dependencies() {
+    if !is_relative_specifier(specifier) {
+      continue;
+    }
+    let resolved = match dependency
+      .maybe_code
+      .maybe_specifier()
+      .or_else(|| dependency.maybe_type.maybe_specifier())
+    {
+      Some(resolved) => resolved,
+      None => continue,
+    };
+    let new_resolved = renames.get(resolved);
+    if new_referrer.is_none() && new_resolved.is_none() {
+      continue;
+    }
+    let new_specifier = match relative_specifier(
+      new_referrer.as_ref().unwrap_or(referrer),
+      new_resolved.as_ref().unwrap_or(resolved),
+    ) {
+      Some(new_specifier) if &new_specifier != specifier => new_specifier,
+      _ => continue,
+    };
+    for import in &dependency.imports {
+      let range =
+        get_specifier_range(&text, to_lsp_range(&import.range), specifier);
+      if let Some(range) = range {
+        edits.push(lsp::TextEdit {
+          range,
+          new_text: new_specifier.clone(),
+        });
+      }
+    }
+  }
+  edits
+}
+
+/// Return the range of the specifier itself within the range of an import,
+/// which may include the quotes of a string literal or a whole comment.
+fn get_specifier_range(
+  text: &str,
+  range: lsp::Range,
+  specifier: &str,
+) -> Option<lsp::Range> {
+  if range.start.line != range.end.line {
+    return None;
+  }
+  let line = text.lines().nth(range.start.line as usize)?;
+  let line_utf16 = line.encode_utf16().collect::<Vec<_>>();
+  let import_text = String::from_utf16(
+    line_utf16
+      .get(range.start.character as usize..range.end.character as usize)?,
+  )
+  .ok()?;
+  let index = import_text.find(specifier)?;
+  let start =
+    range.start.character + import_text[..index].encode_utf16().count() as u32;
+  let end = start + specifier.encode_utf16().count() as u32;
+  Some(lsp::Range {
+    start: lsp::Position {
+      line: range.start.line,
+      character: start,
+    },
+    end: lsp::Position {
+      line: range.start.line,
+      character: end,
+    },
+  })
+}
+
+/// Return the edits which keep the relative addresses in the `"imports"` and
+/// `"scopes"` of an import map pointing at renamed files and directories.
+pub fn get_import_map_edits(
+  import_map_specifier: &ModuleSpecifier,
+  text: &str,
+  renames: &FileRenames,
+) -> Vec<lsp::TextEdit> {
+  let ast = match jsonc_parser::parse_to_ast(
+    text,
+    &Default::default(),
+    &Default::default(),
+  ) {
+    Ok(ast) => ast,
+    Err(_) => return Vec::new(),
+  };
+  let obj = match ast.value {
+    Some(Value::Object(obj)) => obj,
+    _ => return Vec::new(),
+  };
+  let mut maps = Vec::new();
+  if let Some(ObjectProp {
+    value: Value::Object(imports),
+    ..
+  }) = obj.get("imports")
+  {
+    maps.push(imports);
+  }
+  let mut scope_keys = Vec::new();
+  if let Some(ObjectProp {
+    value: Value::Object(scopes),
+    ..
+  }) = obj.get("scopes")
+  {
+    for prop in &scopes.properties {
+      if let ObjectPropName::String(key) = &prop.name {
+        scope_keys.push(key);
+      }
+      if let Value::Object(scope) = &prop.value {
+        maps.push(scope);
+      }
+    }
+  }
+
+  let line_index = LineIndex::new(text);
+  let mut edits = Vec::new();
+  let mut push_edit = |lit: &StringLit| {
+    if let Some(edit) =
+      get_address_edit(lit, import_map_specifier, renames, text, &line_index)
+    {
+      edits.push(edit);
+    }
+  };
+  for map in maps {
+    for prop in &map.properties {
+      if let Value::StringLit(lit) = &prop.value {
+        push_edit(lit);
+      }
+    }
+  }
+  // the keys of scopes are addresses as well
+  for key in scope_keys {
+    push_edit(key);
+  }
+  edits
+}
+
+/// Return the edit which keeps a relative address in an import map pointing
+/// at a renamed file or directory.
+fn get_address_edit(
+  lit: &StringLit,
+  import_map_specifier: &ModuleSpecifier,
+  renames: &FileRenames,
+  text: &str,
+  line_index: &LineIndex,
+) -> Option<lsp::TextEdit> {
+  if !is_relative_specifier(&lit.value) {
+    return None;
+  }
+  let address = import_map_specifier.join(&lit.value).ok()?;
+  let new_address =
+    relative_specifier(import_map_specifier, &renames.get(&address)?)?;
+  let position = |byte_index: usize| {
+    let utf16_index = text[..byte_index].encode_utf16().count() as u32;
+    line_index.position_tsc(TextSize::from(utf16_index))
+  };
+  // the range of the literal includes its quotes
+  Some(lsp::TextEdit {
+    range: lsp::Range {
+      start: position(lit.range.start + 1),
+      end: position(lit.range.end - 1),
+    },
+    new_text: new_address.replace('\"', "\\\""),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn renames(renames: &[(&str, &str)]) -> FileRenames {
+    FileRenames::new(
+      renames
+        .iter()
+        .map(|(old, new)| {
+          (
+            ModuleSpecifier::parse(old).unwrap(),
+            ModuleSpecifier::parse(new).unwrap(),
+          )
+        })
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn test_file_renames_get() {
+    let renames = renames(&[
+      ("file:///a/b.ts", "file:///a/c.ts"),
+      ("file:///a/lib", "file:///a/src"),
+    ]);
+    let get = |s: &str| {
+      renames
+        .get(&ModuleSpecifier::parse(s).unwrap())
+        .map(|s| s.to_string())
+    };
+    assert_eq!(get("file:///a/b.ts"), Some("file:///a/c.ts".to_string()));
+    assert_eq!(
+      get("file:///a/lib/mod.ts"),
+      Some("file:///a/src/mod.ts".to_string())
+    );
+    assert_eq!(get("file:///a/lib/"), Some("file:///a/src/".to_string()));
+    assert_eq!(get("file:///a/library.ts"), None);
+    assert_eq!(get("file:///a/d.ts"), None);
+  }
+
+  #[test]
+  fn test_get_specifier_range() {
+    let range = |start: u32, end: u32| lsp::Range {
+      start: lsp::Position {
+        line: 1,
+        character: start,
+      },
+      end: lsp::Position {
+        line: 1,
+        character: end,
+      },
+    };
+    let text = "const s = \"\u{1F600}\";\nimport { a } from \"./a.ts\";\n";
+    assert_eq!(
+      get_specifier_range(text, range(18, 26), "./a.ts"),
+      Some(range(19, 25))
+    );
+    let text = "// \u{1F600}\n/* \u{1F600} */ import \"./\u{1F600}.ts\";\n";
+    assert_eq!(
+      get_specifier_range(text, range(16, 25), "./\u{1F600}.ts"),
+      Some(range(17, 24))
+    );
+    let text = "\n/// <reference path=\"./a.d.ts\" />\n";
+    assert_eq!(
+      get_specifier_range(text, range(0, 33), "./a.d.ts"),
+      Some(range(21, 29))
+    );
+    assert_eq!(get_specifier_range(text, range(0, 33), "./b.d.ts"), None);
+  }
+
+  #[test]
+  fn test_get_import_map_edits() {
+    let text = r#"{
+  // comment
+  "imports": {
+    "@/": "./lib/",
+    "b": "./b.ts",
+    "std/": "https://deno.land/std/"
+  },
+  "scopes": {
+    "./vendor/": { "c": "./lib/c.ts" },
+    "./lib/": { "d": "./d.ts" }
+  }
+}"#;
+    let edits = get_import_map_edits(
+      &ModuleSpecifier::parse("file:///a/deno.json").unwrap(),
+      text,
+      &renames(&[("file:///a/lib", "file:///a/src/lib")]),
+    );
+    assert_eq!(
+      edits,
+      vec![
+        lsp::TextEdit {
+          range: lsp::Range {
+            start: lsp::Position {
+              line: 3,
+              character: 11,
+            },
+            end: lsp::Position {
+              line: 3,
+              character: 17,
+            },
+          },
+          new_text: "./src/lib/".to_string(),
+        },
+        lsp::TextEdit {
+          range: lsp::Range {
+            start: lsp::Position {
+              line: 8,
+              character: 25,
+            },
+            end: lsp::Position {
+              line: 8,
+              character: 35,
+            },
+          },
+          new_text: "./src/lib/c.ts".to_string(),
+        },
+        lsp::TextEdit {
+          range: lsp::Range {
+            start: lsp::Position {
+              line: 9,
+              character: 5,
+            },
+            end: lsp::Position {
+              line: 9,
+              character: 11,
+            },
+          },
+          new_text: "./src/lib/".to_string(),
+        },
+      ]
+    );
+  }
+}
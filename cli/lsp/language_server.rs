@@ -1,6 +1,10 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_ast::view::Node;
+use deno_ast::view::NodeTrait;
 use deno_ast::MediaType;
+use deno_ast::ParsedSource;
+use deno_ast::SourceRanged;
 use deno_core::anyhow::anyhow;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
@@ -10,11 +14,14 @@ use deno_core::serde_json::json;
 use deno_core::serde_json::Value;
 use deno_core::ModuleSpecifier;
 use deno_runtime::deno_fs;
+use deno_runtime::deno_node::NodeResolutionMode;
 use deno_runtime::deno_node::NodeResolver;
 use deno_runtime::deno_node::PackageJson;
 use deno_runtime::deno_tls::rustls::RootCertStore;
 use deno_runtime::deno_tls::RootCertStoreProvider;
 use deno_runtime::deno_web::BlobStore;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_semver::npm::NpmPackageReqReference;
 use import_map::ImportMap;
 use log::error;
 use serde_json::from_value;
@@ -24,6 +31,7 @@ use std::env;
 use std::fmt::Write as _;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 use tower_lsp::jsonrpc::Error as LspError;
 use tower_lsp::jsonrpc::Result as LspResult;
 use tower_lsp::lsp_types::request::*;
@@ -41,6 +49,7 @@ use super::completions;
 use super::config::Config;
 use super::config::SETTINGS_SECTION;
 use super::diagnostics;
+use super::diagnostics::DiagnosticReportCache;
 use super::diagnostics::DiagnosticsServer;
 use super::documents::to_hover_text;
 use super::documents::to_lsp_range;
@@ -49,12 +58,14 @@ use super::documents::Document;
 use super::documents::Documents;
 use super::documents::DocumentsFilter;
 use super::documents::LanguageId;
+use super::file_operations;
 use super::logging::lsp_log;
 use super::logging::lsp_warn;
 use super::lsp_custom;
 use super::parent_process_checker;
 use super::performance::Performance;
 use super::performance::PerformanceMark;
+use super::pull_diagnostics::SharedPullDiagnosticsCapabilities;
 use super::refactor;
 use super::registries::ModuleRegistry;
 use super::testing;
@@ -130,6 +141,8 @@ pub struct Inner {
   /// Configuration information.
   pub config: Config,
   deps_http_cache: HttpCache,
+  /// The diagnostics last reported to a client which pulls them.
+  diagnostic_reports: DiagnosticReportCache,
   diagnostics_server: diagnostics::DiagnosticsServer,
   /// The collection of documents that the server is currently handling, either
   /// on disk or "open" within the client.
@@ -167,6 +180,9 @@ pub struct Inner {
   npm_resolver: Arc<CliNpmResolver>,
   /// A collection of measurements which instrument that performance of the LSP.
   performance: Arc<Performance>,
+  /// The pull diagnostics capabilities of the client, which are read from
+  /// the raw `initialize` request.
+  pull_diagnostics_capabilities: SharedPullDiagnosticsCapabilities,
   /// A memoized version of fixable diagnostic codes retrieved from TypeScript.
   ts_fixable_diagnostics: Vec<String>,
   /// An abstraction that handles interactions with TypeScript.
@@ -176,8 +192,14 @@ pub struct Inner {
 }
 
 impl LanguageServer {
-  pub fn new(client: Client) -> Self {
-    Self(Arc::new(tokio::sync::RwLock::new(Inner::new(client))))
+  pub fn new(
+    client: Client,
+    pull_diagnostics_capabilities: SharedPullDiagnosticsCapabilities,
+  ) -> Self {
+    Self(Arc::new(tokio::sync::RwLock::new(Inner::new(
+      client,
+      pull_diagnostics_capabilities,
+    ))))
   }
 
   /// Similar to `deno cache` on the command line, where modules will be cached
@@ -316,6 +338,20 @@ impl LanguageServer {
     self.0.read().await.inlay_hint(params).await
   }
 
+  pub async fn document_diagnostic(
+    &self,
+    params: lsp_custom::DocumentDiagnosticParams,
+  ) -> LspResult<lsp_custom::DocumentDiagnosticReport> {
+    self.0.read().await.document_diagnostic(params).await
+  }
+
+  pub async fn workspace_diagnostic(
+    &self,
+    params: lsp_custom::WorkspaceDiagnosticParams,
+  ) -> LspResult<lsp_custom::WorkspaceDiagnosticReport> {
+    self.0.read().await.workspace_diagnostic(params).await
+  }
+
   pub async fn virtual_text_document(
     &self,
     params: Option<Value>,
@@ -473,8 +509,40 @@ fn create_lsp_structs(
   )
 }
 
+/// Returns the line on which the outermost node that ends at the byte offset
+/// starts, like a statement that was just terminated with `;` or a block that
+/// was just closed with `}`.
+fn get_closed_node_start_line(
+  parsed_source: &ParsedSource,
+  offset: usize,
+) -> Option<u32> {
+  let text_info = parsed_source.text_info();
+  let start_pos = text_info.range().start;
+  parsed_source.with_view(|program| {
+    let mut maybe_start = None;
+    let mut nodes: Vec<Node> = vec![program.into()];
+    while let Some(node) = nodes.pop() {
+      for child in node.children() {
+        let start = child.start().as_byte_index(start_pos);
+        let end = child.end().as_byte_index(start_pos);
+        if end == offset {
+          if maybe_start.map(|s| start < s).unwrap_or(true) {
+            maybe_start = Some(start);
+          }
+        } else if start < offset && end > offset {
+          nodes.push(child);
+        }
+      }
+    }
+    maybe_start.map(|start| text_info.line_index(start_pos + start) as u32)
+  })
+}
+
 impl Inner {
-  fn new(client: Client) -> Self {
+  fn new(
+    client: Client,
+    pull_diagnostics_capabilities: SharedPullDiagnosticsCapabilities,
+  ) -> Self {
     let maybe_custom_root = env::var("DENO_DIR").map(String::into).ok();
     let dir =
       DenoDir::new(maybe_custom_root).expect("could not access DENO_DIR");
@@ -504,6 +572,7 @@ impl Inner {
       client,
       config,
       deps_http_cache,
+      diagnostic_reports: Default::default(),
       diagnostics_server,
       documents,
       http_client,
@@ -522,6 +591,7 @@ impl Inner {
       npm_resolution,
       npm_resolver,
       performance,
+      pull_diagnostics_capabilities,
       ts_fixable_diagnostics: Default::default(),
       ts_server,
       url_map: Default::default(),
@@ -1113,6 +1183,11 @@ impl Inner {
           .collect()
       });
       self.config.update_capabilities(&params.capabilities);
+      let pull_diagnostics = *self.pull_diagnostics_capabilities.lock();
+      self.config.client_capabilities.pull_diagnostics =
+        pull_diagnostics.enabled;
+      self.config.client_capabilities.diagnostic_refresh_support =
+        pull_diagnostics.refresh_support;
     }
 
     self.update_debug_flag();
@@ -1225,7 +1300,12 @@ impl Inner {
           self
             .diagnostics_server
             .invalidate(&self.documents.dependents(&specifier));
-          self.send_diagnostics_update();
+          // clients which pull diagnostics request them again after edits
+          if self.config.client_capabilities.pull_diagnostics {
+            self.diagnostic_reports.increment_project_version();
+          } else {
+            self.send_diagnostics_update();
+          }
           self.send_testing_update();
         }
       }
@@ -1448,14 +1528,62 @@ impl Inner {
     &self,
     params: DocumentFormattingParams,
   ) -> LspResult<Option<Vec<TextEdit>>> {
-    let specifier = self
-      .url_map
-      .normalize_url(&params.text_document.uri, LspUrlKind::File);
+    let mark = self.performance.mark("formatting", Some(&params));
+    let result = self.format_document(&params.text_document.uri, |_| None);
+    self.performance.measure(mark);
+    result
+  }
+
+  async fn range_formatting(
+    &self,
+    params: DocumentRangeFormattingParams,
+  ) -> LspResult<Option<Vec<TextEdit>>> {
+    let mark = self.performance.mark("range_formatting", Some(&params));
+    let result = self.format_document(&params.text_document.uri, |_| {
+      Some((params.range.start.line, params.range.end.line))
+    });
+    self.performance.measure(mark);
+    result
+  }
+
+  async fn on_type_formatting(
+    &self,
+    params: DocumentOnTypeFormattingParams,
+  ) -> LspResult<Option<Vec<TextEdit>>> {
+    let mark = self.performance.mark("on_type_formatting", Some(&params));
+    let position = params.text_document_position.position;
+    let result = self.format_document(
+      &params.text_document_position.text_document.uri,
+      |document| {
+        // format the statement or block that was just closed, which can
+        // start on an earlier line
+        let start_line = document
+          .maybe_parsed_source()
+          .and_then(|r| r.ok())
+          .and_then(|parsed_source| {
+            let offset = document.line_index().offset(position).ok()?;
+            get_closed_node_start_line(&parsed_source, offset.into())
+          })
+          .unwrap_or(position.line);
+        Some((start_line.min(position.line), position.line))
+      },
+    );
+    self.performance.measure(mark);
+    result
+  }
+
+  /// Formats the document. When `get_line_range` returns the first and last
+  /// line of a range, only the edits within those lines are returned.
+  fn format_document(
+    &self,
+    uri: &Url,
+    get_line_range: impl FnOnce(&Document) -> Option<(u32, u32)>,
+  ) -> LspResult<Option<Vec<TextEdit>>> {
+    let specifier = self.url_map.normalize_url(uri, LspUrlKind::File);
     let document = match self.documents.get(&specifier) {
       Some(doc) if doc.is_open() => doc,
       _ => return Ok(None),
     };
-    let mark = self.performance.mark("formatting", Some(&params));
     let file_path = specifier_to_file_path(&specifier).map_err(|err| {
       error!("{}", err);
       LspError::invalid_request()
@@ -1486,11 +1614,19 @@ impl Inner {
     };
 
     let text_edits = match format_result {
-      Ok(Some(new_text)) => Some(text::get_edits(
-        &document.content(),
-        &new_text,
-        document.line_index().as_ref(),
-      )),
+      Ok(Some(new_text)) => {
+        let text_edits = text::get_edits(
+          &document.content(),
+          &new_text,
+          document.line_index().as_ref(),
+        );
+        Some(match get_line_range(&document) {
+          Some((start_line, end_line)) => {
+            text::restrict_edits_to_lines(text_edits, start_line, end_line)
+          }
+          None => text_edits,
+        })
+      }
       Ok(None) => Some(Vec::new()),
       Err(err) => {
         // TODO(lucacasonato): handle error properly
@@ -1499,7 +1635,6 @@ impl Inner {
       }
     };
 
-    self.performance.measure(mark);
     if let Some(text_edits) = text_edits {
       if text_edits.is_empty() {
         Ok(None)
@@ -1692,17 +1827,26 @@ impl Inner {
               error!("{}", err);
               LspError::internal_error()
             })?,
-          Some("deno-lint") => code_actions
-            .add_deno_lint_ignore_action(
-              &specifier,
-              diagnostic,
-              asset_or_doc.document().map(|d| d.text_info()),
-              asset_or_doc.maybe_parsed_source().and_then(|r| r.ok()),
-            )
-            .map_err(|err| {
-              error!("Unable to fix lint error: {}", err);
-              LspError::internal_error()
-            })?,
+          Some("deno-lint") => {
+            if let Some(document) = asset_or_doc.document() {
+              code_actions.add_deno_lint_fix_action(
+                &specifier,
+                diagnostic,
+                &document.text_info(),
+              );
+            }
+            code_actions
+              .add_deno_lint_ignore_action(
+                &specifier,
+                diagnostic,
+                asset_or_doc.document().map(|d| d.text_info()),
+                asset_or_doc.maybe_parsed_source().and_then(|r| r.ok()),
+              )
+              .map_err(|err| {
+                error!("Unable to fix lint error: {}", err);
+                LspError::internal_error()
+              })?
+          }
           _ => (),
         }
       }
@@ -1962,6 +2106,95 @@ impl Inner {
     }
   }
 
+  async fn document_link(
+    &self,
+    params: DocumentLinkParams,
+  ) -> LspResult<Option<Vec<DocumentLink>>> {
+    let specifier = self
+      .url_map
+      .normalize_url(&params.text_document.uri, LspUrlKind::File);
+    if !self.is_diagnosable(&specifier)
+      || !self.config.specifier_enabled(&specifier)
+    {
+      return Ok(None);
+    }
+
+    let mark = self.performance.mark("document_link", Some(&params));
+    let document = match self.documents.get(&specifier) {
+      Some(document) => document,
+      None => {
+        self.performance.measure(mark);
+        return Ok(None);
+      }
+    };
+    let node_resolver =
+      NodeResolver::new(Arc::new(deno_fs::RealFs), self.npm_resolver.clone());
+    let mut document_links = Vec::new();
+    for dependency in document.dependencies().values() {
+      let resolved = match dependency
+        .maybe_code
+        .maybe_specifier()
+        .or_else(|| dependency.maybe_type.maybe_specifier())
+      {
+        Some(resolved) => resolved,
+        None => continue,
+      };
+      let target = match self.resolve_link_target(resolved, &node_resolver) {
+        Some(target) => target,
+        None => continue,
+      };
+      for import in &dependency.imports {
+        document_links.push(DocumentLink {
+          range: to_lsp_range(&import.range),
+          target: Some(target.clone()),
+          tooltip: Some(resolved.to_string()),
+          data: None,
+        });
+      }
+    }
+    self.performance.measure(mark);
+    Ok(Some(document_links))
+  }
+
+  /// Resolve the document a dependency should link to. Remote modules link to
+  /// their `deno:/` virtual document, like go to definition does, and npm
+  /// packages to the entry point of the package in the npm cache.
+  fn resolve_link_target(
+    &self,
+    specifier: &ModuleSpecifier,
+    node_resolver: &NodeResolver,
+  ) -> Option<Url> {
+    match specifier.scheme() {
+      "file" => Some(specifier.clone()),
+      "http" | "https" => {
+        // follow any redirects to the module that was actually cached
+        let document = self.documents.get(specifier)?;
+        self
+          .url_map
+          .normalize_specifier(document.specifier())
+          .ok()
+          .map(|url| url.into_url())
+      }
+      "npm" => {
+        let req_ref = NpmPackageReqReference::from_specifier(specifier).ok()?;
+        let url = node_resolver
+          .resolve_npm_req_reference(
+            &req_ref,
+            NodeResolutionMode::Execution,
+            &PermissionsContainer::allow_all(),
+          )
+          .ok()??
+          .into_url();
+        if url.scheme() == "file" {
+          Some(url)
+        } else {
+          None
+        }
+      }
+      _ => None,
+    }
+  }
+
   async fn references(
     &self,
     params: ReferenceParams,
@@ -2618,6 +2851,42 @@ impl Inner {
     Ok(Some(selection_ranges))
   }
 
+  async fn linked_editing_range(
+    &self,
+    params: LinkedEditingRangeParams,
+  ) -> LspResult<Option<LinkedEditingRanges>> {
+    let specifier = self.url_map.normalize_url(
+      &params.text_document_position_params.text_document.uri,
+      LspUrlKind::File,
+    );
+    if !self.is_diagnosable(&specifier)
+      || !self.config.specifier_enabled(&specifier)
+    {
+      return Ok(None);
+    }
+
+    let mark = self.performance.mark("linked_editing_range", Some(&params));
+    let asset_or_doc = self.get_asset_or_document(&specifier)?;
+    let line_index = asset_or_doc.line_index();
+    let req = tsc::RequestMethod::GetLinkedEditingRange((
+      specifier,
+      line_index.offset_tsc(params.text_document_position_params.position)?,
+    ));
+    let maybe_linked_editing_info: Option<tsc::LinkedEditingInfo> = self
+      .ts_server
+      .request(self.snapshot(), req)
+      .await
+      .map_err(|err| {
+        error!("Failed to request to tsserver {}", err);
+        LspError::invalid_request()
+      })?;
+
+    let result = maybe_linked_editing_info
+      .map(|info| info.to_linked_editing_ranges(line_index));
+    self.performance.measure(mark);
+    Ok(result)
+  }
+
   async fn semantic_tokens_full(
     &self,
     params: SemanticTokensParams,
@@ -2798,7 +3067,99 @@ impl Inner {
     Ok(maybe_symbol_information)
   }
 
+  async fn will_rename_files(
+    &self,
+    params: RenameFilesParams,
+  ) -> LspResult<Option<WorkspaceEdit>> {
+    let mark = self.performance.mark("will_rename_files", Some(&params));
+    let mut renames = Vec::new();
+    for file in &params.files {
+      match (Url::parse(&file.old_uri), Url::parse(&file.new_uri)) {
+        (Ok(old_uri), Ok(new_uri)) => renames.push((
+          self.url_map.normalize_url(&old_uri, LspUrlKind::File),
+          self.url_map.normalize_url(&new_uri, LspUrlKind::File),
+        )),
+        _ => {
+          self.performance.measure(mark);
+          return Err(LspError::invalid_params("Invalid file URI."));
+        }
+      }
+    }
+    let renames = file_operations::FileRenames::new(renames);
+
+    let mut documents =
+      self.documents.documents(DocumentsFilter::AllDiagnosable);
+    // the renamed modules, including the ones within renamed directories,
+    // might not have been loaded yet, but their own relative imports need to
+    // be updated as well
+    for specifier in renames.old_files() {
+      if documents.iter().any(|d| d.specifier() == &specifier) {
+        continue;
+      }
+      if let Some(document) = self.documents.get(&specifier) {
+        if document.is_diagnosable() {
+          documents.push(document);
+        }
+      }
+    }
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for document in documents {
+      let specifier = document.specifier();
+      if specifier.scheme() != "file"
+        || !self.config.specifier_enabled(specifier)
+      {
+        continue;
+      }
+      let edits = file_operations::get_dependency_edits(&document, &renames);
+      if !edits.is_empty() {
+        changes.entry(specifier.clone()).or_default().extend(edits);
+      }
+    }
+
+    if let Some(import_map_uri) = &self.maybe_import_map_uri {
+      let maybe_text = match self.documents.get(import_map_uri) {
+        Some(document) if document.is_open() => {
+          Some(document.content().to_string())
+        }
+        _ => specifier_to_file_path(import_map_uri)
+          .ok()
+          .and_then(|path| std::fs::read_to_string(path).ok()),
+      };
+      if let Some(text) = maybe_text {
+        let edits = file_operations::get_import_map_edits(
+          import_map_uri,
+          &text,
+          &renames,
+        );
+        if !edits.is_empty() {
+          changes
+            .entry(import_map_uri.clone())
+            .or_default()
+            .extend(edits);
+        }
+      }
+    }
+
+    self.performance.measure(mark);
+    if changes.is_empty() {
+      Ok(None)
+    } else {
+      Ok(Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+      }))
+    }
+  }
+
   fn send_diagnostics_update(&self) {
+    if self.config.client_capabilities.pull_diagnostics {
+      self.diagnostic_reports.increment_project_version();
+      if self.config.client_capabilities.diagnostic_refresh_support {
+        self.client.send_diagnostic_refresh_request();
+      }
+      return;
+    }
     let snapshot = (
       self.snapshot(),
       self.config.snapshot(),
@@ -3054,6 +3415,20 @@ impl tower_lsp::LanguageServer for LanguageServer {
     self.0.read().await.formatting(params).await
   }
 
+  async fn range_formatting(
+    &self,
+    params: DocumentRangeFormattingParams,
+  ) -> LspResult<Option<Vec<TextEdit>>> {
+    self.0.read().await.range_formatting(params).await
+  }
+
+  async fn on_type_formatting(
+    &self,
+    params: DocumentOnTypeFormattingParams,
+  ) -> LspResult<Option<Vec<TextEdit>>> {
+    self.0.read().await.on_type_formatting(params).await
+  }
+
   async fn hover(&self, params: HoverParams) -> LspResult<Option<Hover>> {
     self.0.read().await.hover(params).await
   }
@@ -3090,6 +3465,13 @@ impl tower_lsp::LanguageServer for LanguageServer {
     self.0.read().await.document_highlight(params).await
   }
 
+  async fn document_link(
+    &self,
+    params: DocumentLinkParams,
+  ) -> LspResult<Option<Vec<DocumentLink>>> {
+    self.0.read().await.document_link(params).await
+  }
+
   async fn references(
     &self,
     params: ReferenceParams,
@@ -3174,6 +3556,13 @@ impl tower_lsp::LanguageServer for LanguageServer {
     self.0.read().await.selection_range(params).await
   }
 
+  async fn linked_editing_range(
+    &self,
+    params: LinkedEditingRangeParams,
+  ) -> LspResult<Option<LinkedEditingRanges>> {
+    self.0.read().await.linked_editing_range(params).await
+  }
+
   async fn semantic_tokens_full(
     &self,
     params: SemanticTokensParams,
@@ -3201,6 +3590,13 @@ impl tower_lsp::LanguageServer for LanguageServer {
   ) -> LspResult<Option<Vec<SymbolInformation>>> {
     self.0.read().await.symbol(params).await
   }
+
+  async fn will_rename_files(
+    &self,
+    params: RenameFilesParams,
+  ) -> LspResult<Option<WorkspaceEdit>> {
+    self.0.read().await.will_rename_files(params).await
+  }
 }
 
 struct PrepareCacheResult {
@@ -3344,6 +3740,171 @@ impl Inner {
     Ok(maybe_inlay_hints)
   }
 
+  /// Returns the reports of the diagnostics of the documents for a client
+  /// which pulls them. A report is "unchanged" when the client still has the
+  /// current result ID of the document, and the last report of a document is
+  /// reused until the documents or the configuration change. Dropping the
+  /// future, like when the client cancels its request, cancels generating the
+  /// diagnostics.
+  async fn pull_diagnostics(
+    &self,
+    specifiers: Vec<ModuleSpecifier>,
+    previous_result_ids: &HashMap<ModuleSpecifier, String>,
+  ) -> LspResult<
+    Vec<(
+      ModuleSpecifier,
+      Option<i32>,
+      lsp_custom::DocumentDiagnosticReport,
+    )>,
+  > {
+    let mut reports = Vec::new();
+    let mut outdated = Vec::new();
+    for specifier in specifiers {
+      let version = self
+        .documents
+        .get(&specifier)
+        .and_then(|d| d.maybe_lsp_version());
+      let result_id = self.diagnostic_reports.result_id(version);
+      if previous_result_ids.get(&specifier) == Some(&result_id) {
+        let report =
+          lsp_custom::DocumentDiagnosticReport::Unchanged { result_id };
+        reports.push((specifier, version, report));
+      } else if let Some(items) =
+        self.diagnostic_reports.get(&specifier, &result_id)
+      {
+        let report =
+          lsp_custom::DocumentDiagnosticReport::Full { result_id, items };
+        reports.push((specifier, version, report));
+      } else {
+        outdated.push((specifier, version, result_id));
+      }
+    }
+    if outdated.is_empty() {
+      return Ok(reports);
+    }
+
+    let token = CancellationToken::new();
+    let _drop_guard = token.clone().drop_guard();
+    let specifiers = outdated
+      .iter()
+      .map(|(specifier, _, _)| specifier.clone())
+      .collect::<Vec<_>>();
+    let mut diagnostics = self
+      .diagnostics_server
+      .pull_diagnostics(
+        self.snapshot(),
+        &self.config.snapshot(),
+        &self.lint_options,
+        &specifiers,
+        token,
+      )
+      .await
+      .map_err(|err| {
+        error!("Unable to get diagnostics: {}", err);
+        LspError::internal_error()
+      })?
+      .into_iter()
+      .map(|(specifier, _, diagnostics)| (specifier, diagnostics))
+      .collect::<HashMap<_, _>>();
+    for (specifier, version, result_id) in outdated {
+      let items = diagnostics.remove(&specifier).unwrap_or_default();
+      self.diagnostic_reports.insert(
+        specifier.clone(),
+        result_id.clone(),
+        items.clone(),
+      );
+      let report =
+        lsp_custom::DocumentDiagnosticReport::Full { result_id, items };
+      reports.push((specifier, version, report));
+    }
+    Ok(reports)
+  }
+
+  async fn document_diagnostic(
+    &self,
+    params: lsp_custom::DocumentDiagnosticParams,
+  ) -> LspResult<lsp_custom::DocumentDiagnosticReport> {
+    let specifier = self
+      .url_map
+      .normalize_url(&params.text_document.uri, LspUrlKind::File);
+    let mark = self.performance.mark("document_diagnostic", Some(&params));
+    if !self.is_diagnosable(&specifier)
+      || !self.config.specifier_enabled(&specifier)
+    {
+      let version = self
+        .documents
+        .get(&specifier)
+        .and_then(|d| d.maybe_lsp_version());
+      self.performance.measure(mark);
+      return Ok(lsp_custom::DocumentDiagnosticReport::Full {
+        result_id: self.diagnostic_reports.result_id(version),
+        items: vec![],
+      });
+    }
+    let previous_result_ids = params
+      .previous_result_id
+      .map(|result_id| (specifier.clone(), result_id))
+      .into_iter()
+      .collect();
+    let result = self
+      .pull_diagnostics(vec![specifier], &previous_result_ids)
+      .await;
+    self.performance.measure(mark);
+    let (_, _, report) = result?.remove(0);
+    Ok(report)
+  }
+
+  async fn workspace_diagnostic(
+    &self,
+    params: lsp_custom::WorkspaceDiagnosticParams,
+  ) -> LspResult<lsp_custom::WorkspaceDiagnosticReport> {
+    let mark = self.performance.mark("workspace_diagnostic", Some(&params));
+    let previous_result_ids = params
+      .previous_result_ids
+      .into_iter()
+      .map(|id| {
+        (
+          self.url_map.normalize_url(&id.uri, LspUrlKind::File),
+          id.value,
+        )
+      })
+      .collect::<HashMap<_, _>>();
+    let specifiers = self
+      .documents
+      .documents(DocumentsFilter::AllDiagnosable)
+      .into_iter()
+      .map(|d| d.specifier().clone())
+      .filter(|s| self.config.specifier_enabled(s))
+      .collect::<Vec<_>>();
+    let result = self
+      .pull_diagnostics(specifiers, &previous_result_ids)
+      .await;
+    let reports = match result {
+      Ok(reports) => reports,
+      Err(err) => {
+        self.performance.measure(mark);
+        return Err(err);
+      }
+    };
+    let mut items = Vec::new();
+    for (specifier, version, report) in reports {
+      let uri = match self.url_map.normalize_specifier(&specifier) {
+        Ok(uri) => uri.into_url(),
+        Err(err) => {
+          error!("{}", err);
+          continue;
+        }
+      };
+      items.push(lsp_custom::WorkspaceDocumentDiagnosticReport {
+        uri,
+        version,
+        report,
+      });
+    }
+    self.performance.measure(mark);
+    Ok(lsp_custom::WorkspaceDiagnosticReport { items })
+  }
+
   async fn reload_import_registries(&mut self) -> LspResult<Option<Value>> {
     remove_dir_all_if_exists(&self.module_registries_location)
       .await
@@ -14,6 +14,10 @@ pub const VIRTUAL_TEXT_DOCUMENT: &str = "deno/virtualTextDocument";
 // While lsp_types supports inlay hints currently, tower_lsp does not.
 pub const INLAY_HINT: &str = "textDocument/inlayHint";
 
+// Neither lsp_types nor tower_lsp support pull diagnostics currently.
+pub const DOCUMENT_DIAGNOSTIC: &str = "textDocument/diagnostic";
+pub const WORKSPACE_DIAGNOSTIC: &str = "workspace/diagnostic";
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CacheParams {
@@ -44,3 +48,62 @@ impl lsp::notification::Notification for RegistryStateNotification {
 pub struct VirtualTextDocumentParams {
   pub text_document: lsp::TextDocumentIdentifier,
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiagnosticParams {
+  pub text_document: lsp::TextDocumentIdentifier,
+  pub identifier: Option<String>,
+  pub previous_result_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DocumentDiagnosticReport {
+  #[serde(rename_all = "camelCase")]
+  Full {
+    result_id: String,
+    items: Vec<lsp::Diagnostic>,
+  },
+  #[serde(rename_all = "camelCase")]
+  Unchanged { result_id: String },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousResultId {
+  pub uri: lsp::Url,
+  pub value: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiagnosticParams {
+  pub identifier: Option<String>,
+  pub previous_result_ids: Vec<PreviousResultId>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDocumentDiagnosticReport {
+  pub uri: lsp::Url,
+  pub version: Option<i32>,
+  #[serde(flatten)]
+  pub report: DocumentDiagnosticReport,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WorkspaceDiagnosticReport {
+  pub items: Vec<WorkspaceDocumentDiagnosticReport>,
+}
+
+/// Sent to clients which pull diagnostics when the diagnostics of the
+/// documents might have changed for reasons other than edits to them.
+pub enum DiagnosticRefreshRequest {}
+
+impl lsp::request::Request for DiagnosticRefreshRequest {
+  type Params = ();
+  type Result = ();
+
+  const METHOD: &'static str = "workspace/diagnostic/refresh";
+}
@@ -5,6 +5,8 @@ use tower_lsp::LspService;
 use tower_lsp::Server;
 
 use crate::lsp::language_server::LanguageServer;
+use crate::lsp::pull_diagnostics::PullDiagnosticsService;
+use crate::lsp::pull_diagnostics::SharedPullDiagnosticsCapabilities;
 pub use repl::ReplCompletionItem;
 pub use repl::ReplLanguageServer;
 
@@ -17,12 +19,14 @@ mod completions;
 mod config;
 mod diagnostics;
 mod documents;
+mod file_operations;
 pub mod language_server;
 mod logging;
 mod lsp_custom;
 mod parent_process_checker;
 mod path_to_regex;
 mod performance;
+mod pull_diagnostics;
 mod refactor;
 mod registries;
 mod repl;
@@ -36,8 +40,13 @@ pub async fn start() -> Result<(), AnyError> {
   let stdin = tokio::io::stdin();
   let stdout = tokio::io::stdout();
 
+  let pull_diagnostics_capabilities =
+    SharedPullDiagnosticsCapabilities::default();
   let (service, socket) = LspService::build(|client| {
-    language_server::LanguageServer::new(client::Client::from_tower(client))
+    language_server::LanguageServer::new(
+      client::Client::from_tower(client),
+      pull_diagnostics_capabilities.clone(),
+    )
   })
   .custom_method(lsp_custom::CACHE_REQUEST, LanguageServer::cache_request)
   .custom_method(
@@ -59,8 +68,18 @@ pub async fn start() -> Result<(), AnyError> {
     LanguageServer::virtual_text_document,
   )
   .custom_method(lsp_custom::INLAY_HINT, LanguageServer::inlay_hint)
+  .custom_method(
+    lsp_custom::DOCUMENT_DIAGNOSTIC,
+    LanguageServer::document_diagnostic,
+  )
+  .custom_method(
+    lsp_custom::WORKSPACE_DIAGNOSTIC,
+    LanguageServer::workspace_diagnostic,
+  )
   .finish();
 
+  let service =
+    PullDiagnosticsService::new(service, pull_diagnostics_capabilities);
   Server::new(stdin, stdout, socket).serve(service).await;
 
   Ok(())
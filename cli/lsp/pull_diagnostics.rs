@@ -0,0 +1,153 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Clients which support the pull model of diagnostics request the
+//! diagnostics of documents with `textDocument/diagnostic` and
+//! `workspace/diagnostic` instead of having them published. Neither
+//! `lsp_types` nor `tower_lsp` know the capabilities of the pull model yet,
+//! so they are read from and added to the raw `initialize` messages.
+
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use deno_core::futures::future::BoxFuture;
+use deno_core::futures::FutureExt;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use tower_lsp::jsonrpc::Request;
+use tower_lsp::jsonrpc::Response;
+use tower_service::Service;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PullDiagnosticsCapabilities {
+  /// The client provides the `textDocument.diagnostic` capability.
+  pub enabled: bool,
+  /// The client provides the `workspace.diagnostics.refreshSupport`
+  /// capability, so it can be asked to pull the diagnostics again.
+  pub refresh_support: bool,
+}
+
+impl PullDiagnosticsCapabilities {
+  fn from_client_capabilities(capabilities: &Value) -> Self {
+    Self {
+      enabled: capabilities
+        .pointer("/textDocument/diagnostic")
+        .map(|it| it.is_object())
+        .unwrap_or(false),
+      refresh_support: capabilities
+        .pointer("/workspace/diagnostics/refreshSupport")
+        .and_then(|it| it.as_bool())
+        .unwrap_or(false),
+    }
+  }
+}
+
+/// The capabilities of the client, which are set when the `initialize`
+/// request is received.
+pub type SharedPullDiagnosticsCapabilities =
+  Arc<Mutex<PullDiagnosticsCapabilities>>;
+
+/// Wraps the service of the language server, reading the pull diagnostics
+/// capabilities of the client from the `initialize` request and advertising
+/// the `diagnosticProvider` capability in the response to it.
+pub struct PullDiagnosticsService<S> {
+  inner: S,
+  capabilities: SharedPullDiagnosticsCapabilities,
+}
+
+impl<S> PullDiagnosticsService<S> {
+  pub fn new(
+    inner: S,
+    capabilities: SharedPullDiagnosticsCapabilities,
+  ) -> Self {
+    Self {
+      inner,
+      capabilities,
+    }
+  }
+}
+
+impl<S> Service<Request> for PullDiagnosticsService<S>
+where
+  S: Service<Request, Response = Option<Response>>,
+  S::Error: Send + 'static,
+  S::Future: Send + 'static,
+{
+  type Response = Option<Response>;
+  type Error = S::Error;
+  type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  fn poll_ready(
+    &mut self,
+    cx: &mut Context<'_>,
+  ) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready(cx)
+  }
+
+  fn call(&mut self, request: Request) -> Self::Future {
+    if request.method() != "initialize" {
+      return self.inner.call(request).boxed();
+    }
+    let capabilities = request
+      .params()
+      .and_then(|params| params.get("capabilities"))
+      .map(PullDiagnosticsCapabilities::from_client_capabilities)
+      .unwrap_or_default();
+    // the language server reads the capabilities when handling the request
+    *self.capabilities.lock() = capabilities;
+    let response = self.inner.call(request);
+    async move {
+      let response = response.await?;
+      if !capabilities.enabled {
+        return Ok(response);
+      }
+      Ok(response.map(|response| {
+        let (id, result) = response.into_parts();
+        Response::from_parts(id, result.map(add_diagnostic_provider))
+      }))
+    }
+    .boxed()
+  }
+}
+
+fn add_diagnostic_provider(mut result: Value) -> Value {
+  if let Some(capabilities) = result
+    .get_mut("capabilities")
+    .and_then(|it| it.as_object_mut())
+  {
+    capabilities.insert(
+      "diagnosticProvider".to_string(),
+      json!({
+        "identifier": "deno",
+        "interFileDependencies": true,
+        "workspaceDiagnostics": true,
+      }),
+    );
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_from_client_capabilities() {
+    let capabilities =
+      PullDiagnosticsCapabilities::from_client_capabilities(&json!({
+        "textDocument": { "diagnostic": { "dynamicRegistration": false } },
+        "workspace": { "diagnostics": { "refreshSupport": true } },
+      }));
+    assert!(capabilities.enabled);
+    assert!(capabilities.refresh_support);
+
+    let capabilities =
+      PullDiagnosticsCapabilities::from_client_capabilities(&json!({
+        "textDocument": {},
+        "experimental": { "pullDiagnostics": true },
+      }));
+    assert!(!capabilities.enabled);
+    assert!(!capabilities.refresh_support);
+  }
+}
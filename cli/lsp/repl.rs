@@ -57,8 +57,10 @@ impl ReplLanguageServer {
     super::logging::set_lsp_log_level(log::Level::Debug);
     super::logging::set_lsp_warn_level(log::Level::Debug);
 
-    let language_server =
-      super::language_server::LanguageServer::new(Client::new_for_repl());
+    let language_server = super::language_server::LanguageServer::new(
+      Client::new_for_repl(),
+      Default::default(),
+    );
 
     let cwd_uri = get_cwd_uri()?;
 
@@ -294,6 +296,7 @@ pub fn get_repl_workspace_settings() -> WorkspaceSettings {
     inlay_hints: Default::default(),
     internal_debug: false,
     lint: false,
+    lint_plugins: false,
     tls_certificate: None,
     unsafely_ignore_certificate_errors: None,
     unstable: false,
@@ -257,6 +257,28 @@ pub fn get_edits(a: &str, b: &str, line_index: &LineIndex) -> Vec<TextEdit> {
   text_edits
 }
 
+/// Only keeps the edits that are within the lines, including the line break
+/// at the end of the last line. The edits of a diff don't overlap, so they
+/// can be dropped independently of each other.
+pub fn restrict_edits_to_lines(
+  text_edits: Vec<TextEdit>,
+  start_line: u32,
+  end_line: u32,
+) -> Vec<TextEdit> {
+  let start = lsp::Position {
+    line: start_line,
+    character: 0,
+  };
+  let end = lsp::Position {
+    line: end_line + 1,
+    character: 0,
+  };
+  text_edits
+    .into_iter()
+    .filter(|edit| edit.range.start >= start && edit.range.end <= end)
+    .collect()
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -527,4 +549,44 @@ const C: char = \"メ メ\";
       ]
     )
   }
+
+  #[test]
+  fn test_restrict_edits_to_lines() {
+    let a = "const a = 1\nif (a) {\nconsole.log(a)\n}\nconst b=2\n";
+    let b = "const a = 1;\nif (a) {\n  console.log(a);\n}\nconst b = 2;\n";
+    let line_index = LineIndex::new(a);
+    let edits = get_edits(a, b, &line_index);
+    let actual = restrict_edits_to_lines(edits, 1, 3);
+    assert_eq!(
+      actual,
+      vec![
+        TextEdit {
+          range: lsp::Range {
+            start: lsp::Position {
+              line: 2,
+              character: 0
+            },
+            end: lsp::Position {
+              line: 2,
+              character: 0
+            }
+          },
+          new_text: "  ".to_string()
+        },
+        TextEdit {
+          range: lsp::Range {
+            start: lsp::Position {
+              line: 2,
+              character: 14
+            },
+            end: lsp::Position {
+              line: 2,
+              character: 14
+            }
+          },
+          new_text: ";".to_string()
+        },
+      ]
+    );
+  }
 }
@@ -2606,6 +2606,29 @@ impl SelectionRange {
   }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedEditingInfo {
+  ranges: Vec<TextSpan>,
+  word_pattern: Option<String>,
+}
+
+impl LinkedEditingInfo {
+  pub fn to_linked_editing_ranges(
+    &self,
+    line_index: Arc<LineIndex>,
+  ) -> lsp::LinkedEditingRanges {
+    lsp::LinkedEditingRanges {
+      ranges: self
+        .ranges
+        .iter()
+        .map(|span| span.to_range(line_index.clone()))
+        .collect(),
+      word_pattern: self.word_pattern.clone(),
+    }
+  }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct Response {
   // id: usize,
@@ -3197,6 +3220,9 @@ pub enum RequestMethod {
   GetEncodedSemanticClassifications((ModuleSpecifier, TextSpan)),
   /// Get implementation information for a specific position.
   GetImplementation((ModuleSpecifier, u32)),
+  /// Get the ranges of the JSX tags which should be edited together with the
+  /// tag at the position.
+  GetLinkedEditingRange((ModuleSpecifier, u32)),
   /// Get "navigate to" items, which are converted to workspace symbols
   GetNavigateToItems {
     search: String,
@@ -3356,6 +3382,12 @@ impl RequestMethod {
         "specifier": state.denormalize_specifier(specifier),
         "position": position,
       }),
+      RequestMethod::GetLinkedEditingRange((specifier, position)) => json!({
+        "id": id,
+        "method": "getLinkedEditingRange",
+        "specifier": state.denormalize_specifier(specifier),
+        "position": position,
+      }),
       RequestMethod::GetNavigateToItems {
         search,
         max_result_count,
@@ -45,6 +45,7 @@ use args::CliOptions;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::error::JsError;
+use deno_core::serde_json;
 use deno_runtime::colors;
 use deno_runtime::fmt_errors::format_js_error;
 use deno_runtime::tokio_util::run_local;
@@ -91,9 +92,19 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
     DenoSubcommand::Check(check_flags) => {
       let factory = CliFactory::from_flags(flags).await?;
       let module_load_preparer = factory.module_load_preparer().await?;
-      module_load_preparer
+      let result = module_load_preparer
         .load_and_type_check_files(&check_flags.files)
-        .await?;
+        .await;
+      if check_flags.sarif {
+        let diagnostics = match result {
+          Ok(()) => tsc::Diagnostics::default(),
+          Err(err) => err.downcast::<tsc::Diagnostics>()?,
+        };
+        let json = serde_json::to_string_pretty(&diagnostics.to_sarif())?;
+        println!("{json}");
+        return Ok(if diagnostics.is_empty() { 0 } else { 1 });
+      }
+      result?;
       Ok(0)
     }
     DenoSubcommand::Compile(compile_flags) => {
@@ -126,6 +137,10 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
       tools::installer::uninstall(uninstall_flags.name, uninstall_flags.root)?;
       Ok(0)
     }
+    DenoSubcommand::Kv(kv_flags) => {
+      tools::kv::kv(flags, kv_flags).await?;
+      Ok(0)
+    }
     DenoSubcommand::Lsp => {
       lsp::start().await?;
       Ok(0)
@@ -147,22 +162,21 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
       if run_flags.is_stdin() {
         tools::run::run_from_stdin(flags).await
       } else {
+        if let Some(ref coverage_dir) = flags.coverage_dir {
+          init_coverage_dir(coverage_dir)?;
+        }
         tools::run::run_script(flags).await
       }
     }
     DenoSubcommand::Task(task_flags) => {
+      if let Some(ref coverage_dir) = flags.coverage_dir {
+        init_coverage_dir(coverage_dir)?;
+      }
       tools::task::execute_script(flags, task_flags).await
     }
     DenoSubcommand::Test(test_flags) => {
       if let Some(ref coverage_dir) = flags.coverage_dir {
-        std::fs::create_dir_all(coverage_dir)
-          .with_context(|| format!("Failed creating: {coverage_dir}"))?;
-        // this is set in order to ensure spawned processes use the same
-        // coverage directory
-        env::set_var(
-          "DENO_UNSTABLE_COVERAGE_DIR",
-          PathBuf::from(coverage_dir).canonicalize()?,
-        );
+        init_coverage_dir(coverage_dir)?;
       }
       let cli_options = CliOptions::from_flags(flags)?;
       let test_options = cli_options.resolve_test_options(test_flags)?;
@@ -195,6 +209,18 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
   }
 }
 
+fn init_coverage_dir(coverage_dir: &str) -> Result<(), AnyError> {
+  std::fs::create_dir_all(coverage_dir)
+    .with_context(|| format!("Failed creating: {coverage_dir}"))?;
+  // this is set in order to ensure spawned processes use the same
+  // coverage directory
+  env::set_var(
+    "DENO_UNSTABLE_COVERAGE_DIR",
+    PathBuf::from(coverage_dir).canonicalize()?,
+  );
+  Ok(())
+}
+
 fn setup_panic_hook() {
   // This function does two things inside of the panic hook:
   // - Tokio does not exit the process when a task panics, so we define a custom
@@ -21,6 +21,7 @@ use uuid::Uuid;
 
 use crate::tools::bench::BenchDescription;
 use crate::tools::bench::BenchEvent;
+use crate::tools::bench::BenchMeasureOptions;
 
 #[derive(Default)]
 pub(crate) struct BenchContainer(
@@ -34,12 +35,15 @@ deno_core::extension!(deno_bench,
     op_register_bench,
     op_dispatch_bench_event,
     op_bench_now,
+    op_bench_heap_used,
   ],
   options = {
     sender: UnboundedSender<BenchEvent>,
+    measure: BenchMeasureOptions,
   },
   state = |state, options| {
     state.put(options.sender);
+    state.put(options.measure);
     state.put(BenchContainer::default());
   },
   customizer = |ext: &mut deno_core::ExtensionBuilder| {
@@ -104,6 +108,10 @@ struct BenchInfo<'s> {
   group: Option<String>,
   ignore: bool,
   only: bool,
+  warmup: Option<u32>,
+  min_iterations: Option<u32>,
+  min_time: Option<u64>,
+  max_time: Option<u64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -111,6 +119,7 @@ struct BenchInfo<'s> {
 struct BenchRegisterResult {
   id: usize,
   origin: String,
+  measure: BenchMeasureOptions,
 }
 
 static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
@@ -123,6 +132,13 @@ fn op_register_bench<'a>(
 ) -> Result<BenchRegisterResult, AnyError> {
   let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
   let origin = state.borrow::<ModuleSpecifier>().to_string();
+  let measure = BenchMeasureOptions {
+    warmup: info.warmup,
+    min_iterations: info.min_iterations,
+    min_time: info.min_time,
+    max_time: info.max_time,
+  }
+  .or(state.borrow::<BenchMeasureOptions>());
   let description = BenchDescription {
     id,
     name: info.name,
@@ -140,7 +156,11 @@ fn op_register_bench<'a>(
     .push((description.clone(), function));
   let sender = state.borrow::<UnboundedSender<BenchEvent>>().clone();
   sender.send(BenchEvent::Register(description)).ok();
-  Ok(BenchRegisterResult { id, origin })
+  Ok(BenchRegisterResult {
+    id,
+    origin,
+    measure,
+  })
 }
 
 #[op]
@@ -159,3 +179,10 @@ fn op_bench_now(state: &mut OpState) -> Result<u64, AnyError> {
   let ns_u64 = u64::try_from(ns)?;
   Ok(ns_u64)
 }
+
+#[op(v8)]
+fn op_bench_heap_used(scope: &mut v8::HandleScope) -> f64 {
+  let mut stats = v8::HeapStatistics::default();
+  scope.get_heap_statistics(&mut stats);
+  stats.used_heap_size() as f64
+}
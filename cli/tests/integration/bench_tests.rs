@@ -1,10 +1,12 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use deno_core::url::Url;
 use test_util as util;
 use util::assert_contains;
 use util::env_vars_for_npm_tests;
 use util::TestContext;
+use util::TestContextBuilder;
 
 itest!(overloads {
   args: "bench bench/overloads.ts",
@@ -174,6 +176,12 @@ itest!(check_local_by_default2 {
   exit_code: 1,
 });
 
+itest!(invalid_measure_options {
+  args: "bench bench/invalid_measure_options.ts",
+  exit_code: 1,
+  output: "bench/invalid_measure_options.out",
+});
+
 itest!(bench_with_config {
   args: "bench --config bench/collect/deno.jsonc bench/collect",
   exit_code: 0,
@@ -250,3 +258,57 @@ itest!(bench_no_lock {
   cwd: Some("lockfile/basic"),
   output: "lockfile/basic/bench.nolock.out",
 });
+
+#[test]
+fn baseline() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("main.bench.ts", "Deno.bench(\"noop\", () => {});\n");
+
+  let output = context
+    .new_command()
+    .args("bench --baseline=main main.bench.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Baseline \"main\" not found. Save it first with --save-baseline=main"
+  );
+
+  let output = context
+    .new_command()
+    .args("bench --save-baseline=main main.bench.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Saved baseline \"main\"");
+
+  // a threshold this high can't be exceeded by a noop
+  let output = context
+    .new_command()
+    .args("bench --baseline=main --regression-threshold=100000 main.bench.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "vs baseline \"main\"");
+}
+
+#[test]
+fn measure_options() {
+  let context = TestContext::default();
+  let output = context
+    .new_command()
+    .args("bench --json bench/measure_options.ts")
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  let json: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  let benches = json["benches"].as_array().unwrap();
+  let stats = |name: &str| {
+    let bench = benches.iter().find(|b| b["name"] == name).unwrap();
+    bench["results"][0]["ok"].clone()
+  };
+
+  assert_eq!(stats("min iterations")["n"], 5);
+  // without `maxTime` this would run for 10 seconds
+  assert!(stats("max time")["n"].as_u64().unwrap() < 100);
+  assert!(stats("alloc")["alloc"].as_f64().unwrap() > 0.0);
+}
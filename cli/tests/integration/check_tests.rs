@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use test_util as util;
 use util::env_vars_for_npm_tests;
 use util::env_vars_for_npm_tests_no_sync_download;
@@ -303,3 +304,41 @@ fn check_error_in_dep_then_fix() {
   output.assert_matches_text("Check [WILDCARD]main.ts\nerror: TS234[WILDCARD]");
   output.assert_exit_code(1);
 }
+
+#[test]
+fn check_sarif() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("main.ts", "const a: string = 1;\nconsole.log(a);\n");
+
+  let output = context
+    .new_command()
+    .args("check --sarif main.ts")
+    .split_output()
+    .run();
+  output.assert_exit_code(1);
+  let sarif: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  let run = &sarif["runs"][0];
+  assert_eq!(run["tool"]["driver"]["name"], "deno check");
+  assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "TS2322");
+  let result = &run["results"][0];
+  assert_eq!(result["ruleId"], "TS2322");
+  assert_eq!(result["level"], "error");
+  let location = &result["locations"][0]["physicalLocation"];
+  assert!(location["artifactLocation"]["uri"]
+    .as_str()
+    .unwrap()
+    .ends_with("/main.ts"));
+  assert_eq!(location["region"]["startLine"], 1);
+  assert_eq!(location["region"]["startColumn"], 7);
+
+  temp_dir.write("main.ts", "const a: string = \"1\";\nconsole.log(a);\n");
+  let output = context
+    .new_command()
+    .args("check --sarif main.ts")
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  let sarif: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  assert_eq!(sarif["runs"][0]["results"], serde_json::json!([]));
+}
@@ -1,8 +1,11 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use std::fs;
+use std::path::Path;
 use test_util as util;
 use test_util::TempDir;
+use util::assert_contains;
 use util::TestContext;
 use util::TestContextBuilder;
 
@@ -93,6 +96,302 @@ fn error_if_invalid_cache() {
   assert!(error.contains("Before generating coverage report, run `deno test --coverage` to ensure consistent state."));
 }
 
+#[test]
+fn thresholds() {
+  let context = TestContext::default();
+  let tempdir = context.deno_dir();
+  let tempdir = tempdir.path().join("cov");
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "test".to_string(),
+      "--quiet".to_string(),
+      format!("--coverage={}", tempdir.to_str().unwrap()),
+      "coverage/branch_test.ts".to_string(),
+    ])
+    .run();
+
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--threshold-lines=20".to_string(),
+      format!("{}/", tempdir.to_str().unwrap()),
+    ])
+    .run();
+
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--threshold-lines=80".to_string(),
+      format!("{}/", tempdir.to_str().unwrap()),
+    ])
+    .run();
+
+  output.assert_exit_code(1);
+  let out = util::strip_ansi_codes(output.combined_output()).to_string();
+  assert!(out.contains("error: Coverage is below the configured thresholds:"));
+  assert!(out.contains("lines: 28.571% (4/14) is below 80% in total"));
+  assert!(out.contains("lines: 28.571% (4/14) is below 80% in file:///"));
+  assert!(out.contains("coverage/branch.ts"));
+}
+
+#[test]
+fn html_report() {
+  let context = TestContext::default();
+  let tempdir = context.deno_dir();
+  let cov_dir = tempdir.path().join("cov");
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "test".to_string(),
+      "--quiet".to_string(),
+      format!("--coverage={}", cov_dir.to_str().unwrap()),
+      "coverage/branch_test.ts".to_string(),
+    ])
+    .run();
+
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--html".to_string(),
+      format!("{}/", cov_dir.to_str().unwrap()),
+    ])
+    .run();
+
+  output.assert_exit_code(0);
+  let out = output.combined_output();
+  assert!(out.contains("HTML coverage report has been generated at file://"));
+  assert!(out.contains("/cov/html/index.html"));
+
+  let html_dir = cov_dir.join("html");
+  let index = fs::read_to_string(html_dir.join("index.html")).unwrap();
+  assert!(index.contains(r##"<a href="#coverage">coverage</a>"##));
+  assert!(
+    index.contains(r#"<a href="files/coverage/branch.ts.html">branch.ts</a>"#)
+  );
+  assert!(index.contains(r#"<td class="low">28.57% (4/14)</td>"#));
+
+  let page =
+    fs::read_to_string(html_dir.join("files/coverage/branch.ts.html")).unwrap();
+  // Uses the original TypeScript source, not the transpiled code.
+  assert!(
+    page.contains("export function branch(condition: boolean): boolean {")
+  );
+  assert!(page.contains(r#"<a href="../../index.html">All files</a>"#));
+  assert!(page.contains(r#"<tr class="miss">"#));
+  assert!(page.contains(r#"<tr class="hit">"#));
+  assert!(page.contains(r#"class="branches branch-miss">0/1</td>"#));
+}
+
+#[test]
+fn cobertura_and_json_reports() {
+  let context = TestContext::default();
+  let tempdir = context.deno_dir();
+  let cov_dir = tempdir.path().join("cov");
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "test".to_string(),
+      "--quiet".to_string(),
+      format!("--coverage={}", cov_dir.to_str().unwrap()),
+      "coverage/branch_test.ts".to_string(),
+    ])
+    .run();
+
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--reporter=cobertura".to_string(),
+      format!("{}/", cov_dir.to_str().unwrap()),
+    ])
+    .split_output()
+    .run();
+
+  output.assert_exit_code(0);
+  let xml = output.stdout();
+  assert!(xml.starts_with("<?xml version=\"1.0\" ?>\n"));
+  assert!(xml.contains(r#"<coverage line-rate="0.2857" branch-rate="0.0000" lines-covered="4" lines-valid="14" branches-covered="0" branches-valid="1""#));
+  assert!(xml.contains(r#"<package name="coverage" line-rate="0.2857""#));
+  assert!(xml.contains(r#"<class name="branch.ts" filename="coverage/branch.ts" line-rate="0.2857""#));
+  assert!(xml.contains(r#"<method name="unused" signature="" line-rate="0""#));
+  assert!(xml.contains(r#"branch="true" condition-coverage="0% (0/1)"/>"#));
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--reporter=json".to_string(),
+      format!("{}/", cov_dir.to_str().unwrap()),
+    ])
+    .split_output()
+    .run();
+
+  output.assert_exit_code(0);
+  let json: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  assert_eq!(
+    json["total"]["lines"],
+    serde_json::json!({ "covered": 4, "total": 14, "percent": 28.571 })
+  );
+  let file = &json["files"][0];
+  assert_eq!(file["path"], "coverage/branch.ts");
+  assert_eq!(
+    file["functions"],
+    serde_json::json!({ "covered": 1, "total": 2, "percent": 50.0 })
+  );
+  assert_eq!(
+    file["uncoveredLines"],
+    serde_json::json!([4, 5, 6, 9, 10, 11, 12, 13, 14, 15])
+  );
+  assert_eq!(
+    file["uncoveredFunctions"],
+    serde_json::json!([{ "name": "unused", "line": 9 }])
+  );
+}
+
+#[test]
+fn pretty_report_rejects_output() {
+  let context = TestContext::default();
+  let tempdir = context.deno_dir();
+  let output_path = tempdir.path().join("report.txt");
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--reporter=pretty".to_string(),
+      format!("--output={}", output_path.to_str().unwrap()),
+      format!("{}/", tempdir.path().join("cov").to_str().unwrap()),
+    ])
+    .run();
+
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "--output is not supported by the pretty coverage reporter"
+  );
+  assert!(!output_path.exists());
+}
+
+#[test]
+fn run_coverage() {
+  let context = TestContext::default();
+  let tempdir = context.deno_dir();
+  let cov_dir = tempdir.path().join("cov");
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "run".to_string(),
+      "--quiet".to_string(),
+      format!("--coverage={}", cov_dir.to_str().unwrap()),
+      "coverage/run/main.ts".to_string(),
+    ])
+    .run();
+
+  // the exit code passed to `Deno.exit()` is kept
+  output.assert_exit_code(3);
+  output.assert_matches_text("worker: 6\n3\n");
+
+  assert_run_coverage(&context, &cov_dir);
+}
+
+#[test]
+fn run_coverage_stdin() {
+  let context = TestContext::default();
+  let tempdir = context.deno_dir();
+  let cov_dir = tempdir.path().join("cov");
+
+  // scripts read from stdin are not covered, so no directory is created
+  context
+    .new_command()
+    .args_vec(vec![
+      "run".to_string(),
+      format!("--coverage={}", cov_dir.to_str().unwrap()),
+      "-".to_string(),
+    ])
+    .stdin("console.log(1);")
+    .run()
+    .assert_matches_text("1\n")
+    .assert_exit_code(0);
+  assert!(!cov_dir.exists());
+}
+
+#[test]
+fn task_coverage() {
+  let context = TestContext::default();
+  let tempdir = context.deno_dir();
+  let cov_dir = tempdir.path().join("cov");
+
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "task".to_string(),
+      "--quiet".to_string(),
+      "--config".to_string(),
+      "coverage/task/deno.json".to_string(),
+      format!("--coverage={}", cov_dir.to_str().unwrap()),
+      "run".to_string(),
+    ])
+    .run();
+
+  output.assert_exit_code(3);
+  output.assert_matches_text("worker: 6\n3\n");
+
+  assert_run_coverage(&context, &cov_dir);
+}
+
+/// Checks the coverage collected from `coverage/run/main.ts`.
+fn assert_run_coverage(context: &TestContext, cov_dir: &Path) {
+  let output = context
+    .new_command()
+    .args_vec(vec![
+      "coverage".to_string(),
+      "--reporter=json".to_string(),
+      format!("{}/", cov_dir.to_str().unwrap()),
+    ])
+    .split_output()
+    .run();
+
+  output.assert_exit_code(0);
+  let json: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  let file = |path: &str| {
+    json["files"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .find(|file| file["path"] == path)
+      .unwrap_or_else(|| panic!("no coverage for {path}"))
+      .clone()
+  };
+  assert!(file("coverage/run/main.ts")["lines"]["covered"].as_u64() > Some(0));
+  // the worker's profile is merged with the one of the main worker
+  let worker = file("coverage/run/worker.ts");
+  assert_eq!(worker["lines"]["covered"], worker["lines"]["total"]);
+  assert_eq!(
+    file("coverage/run/math.ts")["uncoveredFunctions"],
+    serde_json::json!([{ "name": "unused", "line": 9 }])
+  );
+}
+
 fn run_coverage_text(test_name: &str, extension: &str) {
   let context = TestContext::default();
   let tempdir = context.deno_dir();
@@ -0,0 +1,253 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use test_util as util;
+use util::assert_contains;
+use util::TestContext;
+use util::TestContextBuilder;
+
+fn seed_db(context: &TestContext, path: &str) {
+  context.temp_dir().write(
+    "seed.ts",
+    r#"
+const kv = await Deno.openKv(Deno.args[0]);
+await kv.set(["users", "alice"], { name: "Alice", tags: new Set(["a"]) });
+await kv.set(["users", "bob"], new Uint8Array([1, 2]));
+await kv.set(["counter", 1n], new Deno.KvU64(5n));
+await kv.set(["expiring"], true, { expireIn: 60 * 60 * 1000 });
+kv.close();
+"#,
+  );
+  context
+    .new_command()
+    .args_vec(["run", "--unstable", "-A", "seed.ts", path])
+    .run()
+    .assert_matches_text("")
+    .assert_exit_code(0);
+}
+
+#[test]
+fn kv_list() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  seed_db(&context, "kv.sqlite3");
+
+  let output = context
+    .new_command()
+    .args("kv --unstable list kv.sqlite3")
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  output.assert_stdout_matches_text(
+    r#"["counter",{"bigint":"1"}]
+  value: {"u64":"5"}
+  versionstamp: [WILDCARD]
+["expiring"]
+  value: true
+  versionstamp: [WILDCARD]
+["users","alice"]
+  value: {"name":"Alice","tags":{"set":["a"]}}
+  versionstamp: [WILDCARD]
+["users","bob"]
+  value: {"bytes":[1,2]}
+  versionstamp: [WILDCARD]
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args_vec(["kv", "--unstable", "list", "--reverse", "kv.sqlite3", r#"["users"]"#])
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  output.assert_stdout_matches_text(
+    r#"["users","bob"]
+[WILDCARD]
+["users","alice"]
+[WILDCARD]
+"#,
+  );
+  assert!(!output.stdout().contains("counter"));
+}
+
+#[test]
+fn kv_get_set_delete() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+
+  context
+    .new_command()
+    .args_vec([
+      "kv",
+      "--unstable",
+      "set",
+      "kv.sqlite3",
+      r#"["a", 1]"#,
+      r#"{"b": [1, "c"]}"#,
+    ])
+    .run()
+    .assert_matches_text("")
+    .assert_exit_code(0);
+
+  let output = context
+    .new_command()
+    .args_vec(["kv", "--unstable", "get", "kv.sqlite3", r#"["a", 1]"#])
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  output.assert_stdout_matches_text(
+    r#"["a",1.0]
+  value: {"b":[1,"c"]}
+  versionstamp: 00000000000000010000
+"#,
+  );
+
+  // Values written by the CLI read back as the equivalent JavaScript values.
+  context.temp_dir().write(
+    "read.ts",
+    r#"
+const kv = await Deno.openKv("kv.sqlite3");
+console.log((await kv.get(["a", 1])).value);
+kv.close();
+"#,
+  );
+  context
+    .new_command()
+    .args("run --unstable -A read.ts")
+    .run()
+    .assert_matches_text("{ b: [ 1, \"c\" ] }\n")
+    .assert_exit_code(0);
+
+  context
+    .new_command()
+    .args_vec(["kv", "--unstable", "delete", "kv.sqlite3", r#"["a", 1]"#])
+    .run()
+    .assert_matches_text("")
+    .assert_exit_code(0);
+
+  let output = context
+    .new_command()
+    .args_vec(["kv", "--unstable", "get", "kv.sqlite3", r#"["a", 1]"#])
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Key not found: [\"a\",1.0]");
+}
+
+#[test]
+fn kv_requires_unstable() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+
+  let output = context.new_command().args("kv list kv.sqlite3").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Unstable subcommand 'deno kv'. The --unstable flag must be provided."
+  );
+}
+
+#[test]
+fn kv_read_only_commands_do_not_modify_database() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  seed_db(&context, "kv.sqlite3");
+  let path = context.temp_dir().path().join("kv.sqlite3");
+  let before = std::fs::read(&path).unwrap();
+
+  for args in [
+    "kv --unstable list kv.sqlite3",
+    "kv --unstable dump kv.sqlite3",
+  ] {
+    context
+      .new_command()
+      .args(args)
+      .split_output()
+      .run()
+      .assert_exit_code(0);
+  }
+  context
+    .new_command()
+    .args_vec(["kv", "--unstable", "get", "kv.sqlite3", r#"["expiring"]"#])
+    .split_output()
+    .run()
+    .assert_exit_code(0);
+
+  assert_eq!(std::fs::read(&path).unwrap(), before);
+}
+
+#[test]
+fn kv_missing_database() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+
+  let output = context.new_command().args("kv --unstable list missing.sqlite3").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Failed to open database: missing.sqlite3"
+  );
+  assert!(!context.temp_dir().path().join("missing.sqlite3").exists());
+}
+
+#[test]
+fn kv_dump_restore() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  seed_db(&context, "kv.sqlite3");
+
+  context
+    .new_command()
+    .args("kv --unstable dump kv.sqlite3 --output dump.jsonl")
+    .run()
+    .assert_matches_text("")
+    .assert_exit_code(0);
+  let dump = context.temp_dir().read_to_string("dump.jsonl");
+  assert_eq!(dump.lines().count(), 4);
+  assert_contains!(
+    dump,
+    r#"{"key":["counter",{"bigint":"1"}],"value":{"type":"u64","value":"5"},"versionstamp":"00000000000000030000"}"#
+  );
+  assert_contains!(dump, r#""value":{"type":"bytes","value":"AQI="}"#);
+  assert_contains!(dump, r#""expireAt":"#);
+
+  let output = context
+    .new_command()
+    .args("kv --unstable restore copy.sqlite3 dump.jsonl")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Restored 4 entries.");
+
+  // The restored database dumps to exactly the same entries, including
+  // versionstamps and expiration times.
+  let output = context
+    .new_command()
+    .args("kv --unstable dump copy.sqlite3")
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  assert_eq!(output.stdout(), dump);
+
+  // Writes to the restored database get versionstamps greater than all of
+  // the restored ones.
+  context
+    .new_command()
+    .args_vec(["kv", "--unstable", "set", "copy.sqlite3", r#"["new"]"#, "1"])
+    .run()
+    .assert_matches_text("")
+    .assert_exit_code(0);
+  let output = context
+    .new_command()
+    .args_vec(["kv", "--unstable", "get", "copy.sqlite3", r#"["new"]"#])
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.stdout(), "versionstamp: 00000000000000050000");
+
+  // Restoring from stdin, with a malformed line, writes nothing.
+  let output = context
+    .new_command()
+    .args("kv --unstable restore other.sqlite3")
+    .stdin(format!("{dump}not json\n"))
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Invalid entry on line 5");
+  context
+    .new_command()
+    .args("kv --unstable list other.sqlite3")
+    .run()
+    .assert_matches_text("")
+    .assert_exit_code(0);
+}
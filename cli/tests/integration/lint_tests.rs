@@ -1,5 +1,10 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
+use test_util::assert_contains;
+use test_util::assert_not_contains;
+use test_util::TestContextBuilder;
+
 itest!(ignore_unexplicit_files {
   args: "lint --unstable --ignore=./",
   output_str: Some("error: No target files found.\n"),
@@ -114,3 +119,164 @@ itest!(lint_with_malformed_config2 {
   output: "lint/with_malformed_config2.out",
   exit_code: 1,
 });
+
+#[test]
+fn lint_fix() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("main.ts", "let a = 1;;\nconsole.log(a);;\n");
+
+  let output = context.new_command().args("lint --fix main.ts").run();
+  // prefer-const has no fix, so it's still reported
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "(prefer-const)");
+  assert_contains!(output.combined_output(), "Found 1 problem");
+  assert_eq!(
+    temp_dir.read_to_string("main.ts"),
+    "let a = 1;\nconsole.log(a);\n"
+  );
+}
+
+#[test]
+fn lint_plugins() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("deno.json", r#"{ "lint": { "plugins": ["./plugin.ts"] } }"#);
+  temp_dir.write(
+    "plugin.ts",
+    r#"export default {
+  name: "test-plugin",
+  rules: {
+    "no-foo": {
+      create(context: any) {
+        return {
+          Ident(node: any) {
+            if (node.sym === "foo") {
+              context.report({ node, message: "Don't use foo." });
+            }
+          },
+        };
+      },
+    },
+    "no-alert": {
+      create(context: any) {
+        return {
+          CallExpr(node: any) {
+            if (node.callee.sym === "alert" && node.args.length === 1) {
+              context.report({ node, message: "Don't call alert." });
+            }
+          },
+        };
+      },
+    },
+  },
+};
+"#,
+  );
+  temp_dir.write(
+    "main.ts",
+    r#"export const foo = 1;
+// deno-lint-ignore test-plugin/no-foo
+export const bar = foo;
+alert("bar");
+"#,
+  );
+
+  let output = context.new_command().args("lint main.ts").run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "(test-plugin/no-foo)");
+  assert_contains!(output.combined_output(), "Don't use foo.");
+  assert_contains!(output.combined_output(), "main.ts:1:14");
+  assert_contains!(output.combined_output(), "(test-plugin/no-alert)");
+  assert_contains!(output.combined_output(), "main.ts:4:1");
+  assert_contains!(output.combined_output(), "Found 2 problems");
+
+  let output = context
+    .new_command()
+    .args(
+      "lint --rules-exclude=test-plugin/no-foo,test-plugin/no-alert main.ts",
+    )
+    .run();
+  output.assert_exit_code(0);
+}
+
+#[test]
+fn lint_sarif() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("main.ts", "let a = 1;\nconsole.log(a);\n");
+
+  let output = context
+    .new_command()
+    .args("lint --sarif main.ts")
+    .split_output()
+    .run();
+  output.assert_exit_code(1);
+  let sarif: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  assert_eq!(sarif["version"], "2.1.0");
+  let run = &sarif["runs"][0];
+  let rule = &run["tool"]["driver"]["rules"][0];
+  assert_eq!(rule["id"], "prefer-const");
+  assert_eq!(rule["helpUri"], "https://lint.deno.land/#prefer-const");
+  assert_eq!(
+    rule["properties"]["tags"],
+    serde_json::json!(["recommended"])
+  );
+  let result = &run["results"][0];
+  assert_eq!(result["ruleId"], "prefer-const");
+  assert_eq!(result["ruleIndex"], 0);
+  assert_eq!(
+    result["locations"][0]["physicalLocation"]["region"],
+    serde_json::json!({
+      "startLine": 1,
+      "startColumn": 5,
+      "endLine": 1,
+      "endColumn": 6,
+      "byteOffset": 4,
+      "byteLength": 1,
+    })
+  );
+}
+
+#[test]
+fn lint_rule_severity() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "lint": {
+    "rules": {
+      "severity": {
+        "prefer-const": "warn",
+        "no-var": "off",
+        "eqeqeq": "error"
+      }
+    }
+  }
+}"#,
+  );
+  temp_dir.write("main.ts", "let a = 1;\nvar b = 2;\nconsole.log(a, b);\n");
+
+  let output = context.new_command().args("lint main.ts").run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "(prefer-const) warning:");
+  assert_not_contains!(output.combined_output(), "(no-var)");
+  assert_contains!(output.combined_output(), "Found 1 problem (1 warning)");
+
+  let output = context
+    .new_command()
+    .args("lint --max-warnings=0 main.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Found 1 warning, which is more than the maximum of 0."
+  );
+
+  // `eqeqeq` isn't a recommended rule, but has a severity
+  temp_dir.write("main.ts", "if (1 == 2) {}\n");
+  let output = context.new_command().args("lint main.ts").run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "(eqeqeq)");
+}
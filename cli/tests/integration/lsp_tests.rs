@@ -204,6 +204,228 @@ fn lsp_import_map() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_document_links() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("import-map.json", r#"{ "imports": { "/~/": "./lib/" } }"#);
+  temp_dir.create_dir_all("lib");
+  temp_dir.write("lib/b.ts", r#"export const b = "b";"#);
+  temp_dir.write("c.ts", r#"export const c = "c";"#);
+
+  let mut client = context.new_lsp_command().build();
+  client.initialize(|builder| {
+    builder.set_import_map("import-map.json");
+  });
+
+  let uri = Url::from_file_path(temp_dir.path().join("a.ts")).unwrap();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": uri,
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import { b } from \"/~/b.ts\";\nexport { c } from \"./c.ts\";\nconst d = await import(\"/~/b.ts\");\n"
+    }
+  }));
+
+  let res = client.write_request(
+    "textDocument/documentLink",
+    json!({
+      "textDocument": { "uri": uri }
+    }),
+  );
+  let b_url = Url::from_file_path(temp_dir.path().join("lib/b.ts")).unwrap();
+  let c_url = Url::from_file_path(temp_dir.path().join("c.ts")).unwrap();
+  assert_eq!(
+    res,
+    json!([
+      {
+        "range": {
+          "start": { "line": 0, "character": 18 },
+          "end": { "line": 0, "character": 27 }
+        },
+        "target": b_url,
+        "tooltip": b_url
+      }, {
+        "range": {
+          "start": { "line": 2, "character": 23 },
+          "end": { "line": 2, "character": 32 }
+        },
+        "target": b_url,
+        "tooltip": b_url
+      }, {
+        "range": {
+          "start": { "line": 1, "character": 18 },
+          "end": { "line": 1, "character": 26 }
+        },
+        "target": c_url,
+        "tooltip": c_url
+      }
+    ])
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_document_links_remote() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import \"http://localhost:4545/subdir/mod1.ts\";\n"
+    }
+  }));
+  client.write_request(
+    "deno/cache",
+    json!({
+      "referrer": { "uri": "file:///a/file.ts" },
+      "uris": [],
+    }),
+  );
+
+  let res = client.write_request(
+    "textDocument/documentLink",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" }
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([
+      {
+        "range": {
+          "start": { "line": 0, "character": 7 },
+          "end": { "line": 0, "character": 45 }
+        },
+        "target": "deno:/http/localhost:4545/subdir/mod1.ts",
+        "tooltip": "http://localhost:4545/subdir/mod1.ts"
+      }
+    ])
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_will_rename_files() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "imports": {
+    "b": "./lib/b.ts"
+  }
+}"#,
+  );
+  temp_dir.create_dir_all("lib");
+  temp_dir.write("lib/b.ts", "export { c } from \"../c.ts\";\n");
+  temp_dir.write("c.ts", r#"export const c = "c";"#);
+
+  let mut client = context.new_lsp_command().build();
+  client.initialize(|builder| {
+    builder.set_config("./deno.json");
+  });
+
+  let a_uri = temp_dir.uri().join("a.ts").unwrap();
+  let b_uri = temp_dir.uri().join("lib/b.ts").unwrap();
+  let config_uri = temp_dir.uri().join("deno.json").unwrap();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": a_uri,
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import { c } from \"./lib/b.ts\";\n\nconsole.log(c);\n"
+    }
+  }));
+
+  let res = client.write_request(
+    "workspace/willRenameFiles",
+    json!({
+      "files": [{
+        "oldUri": b_uri,
+        "newUri": temp_dir.uri().join("lib/sub/b.ts").unwrap()
+      }]
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "changes": {
+        a_uri.as_str(): [{
+          "range": {
+            "start": { "line": 0, "character": 19 },
+            "end": { "line": 0, "character": 29 }
+          },
+          "newText": "./lib/sub/b.ts"
+        }],
+        b_uri.as_str(): [{
+          "range": {
+            "start": { "line": 0, "character": 19 },
+            "end": { "line": 0, "character": 26 }
+          },
+          "newText": "../../c.ts"
+        }],
+        config_uri.as_str(): [{
+          "range": {
+            "start": { "line": 2, "character": 10 },
+            "end": { "line": 2, "character": 20 }
+          },
+          "newText": "./lib/sub/b.ts"
+        }]
+      }
+    })
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_will_rename_directory() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.create_dir_all("lib/sub");
+  temp_dir.write(
+    "lib/sub/d.ts",
+    "import { c } from \"../../c.ts\";\n\nconsole.log(c);\n",
+  );
+  temp_dir.write("c.ts", r#"export const c = "c";"#);
+
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+
+  // the module within the renamed directory was never loaded
+  let res = client.write_request(
+    "workspace/willRenameFiles",
+    json!({
+      "files": [{
+        "oldUri": temp_dir.uri().join("lib").unwrap(),
+        "newUri": temp_dir.uri().join("src/lib").unwrap()
+      }]
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "changes": {
+        temp_dir.uri().join("lib/sub/d.ts").unwrap().as_str(): [{
+          "range": {
+            "start": { "line": 0, "character": 19 },
+            "end": { "line": 0, "character": 29 }
+          },
+          "newText": "../../../c.ts"
+        }]
+      }
+    })
+  );
+  client.shutdown();
+}
+
 #[test]
 fn lsp_import_map_data_url() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -2380,6 +2602,57 @@ fn lsp_selection_range() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_linked_editing_range() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.tsx",
+      "languageId": "typescriptreact",
+      "version": 1,
+      "text": "const a = <div>hello</div>;\n"
+    }
+  }));
+  let res = client.write_request(
+    "textDocument/linkedEditingRange",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.tsx"
+      },
+      "position": { "line": 0, "character": 12 }
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "ranges": [
+        {
+          "start": { "line": 0, "character": 11 },
+          "end": { "line": 0, "character": 14 }
+        },
+        {
+          "start": { "line": 0, "character": 22 },
+          "end": { "line": 0, "character": 25 }
+        }
+      ],
+      "wordPattern": "[a-zA-Z0-9:\\-\\._$]*"
+    })
+  );
+  let res = client.write_request(
+    "textDocument/linkedEditingRange",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.tsx"
+      },
+      "position": { "line": 0, "character": 17 }
+    }),
+  );
+  assert_eq!(res, json!(null));
+  client.shutdown();
+}
+
 #[test]
 fn lsp_semantic_tokens() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -3488,6 +3761,122 @@ fn lsp_signature_help() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_pull_diagnostics() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize(|builder| {
+    builder.enable_pull_diagnostics();
+  });
+  client.did_open_raw(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "export function a(): void {\n  await Promise.resolve(\"a\");\n}\n"
+    }
+  }));
+  client.handle_configuration_request(json!([{ "enable": true }]));
+  // the diagnostics are not published, the client is asked to pull them
+  let (id, method, _) = client.read_request::<Value>();
+  assert_eq!(method, "workspace/diagnostic/refresh");
+  client.write_response(id, json!(null));
+
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" }
+    }),
+  );
+  assert_eq!(res["kind"], json!("full"));
+  assert_eq!(
+    res["items"],
+    json!([{
+      "range": {
+        "start": { "line": 1, "character": 2 },
+        "end": { "line": 1, "character": 7 }
+      },
+      "severity": 1,
+      "code": 1308,
+      "source": "deno-ts",
+      "message": "'await' expressions are only allowed within async functions and at the top levels of modules.",
+      "relatedInformation": []
+    }])
+  );
+  let result_id = res["resultId"].clone();
+
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "previousResultId": result_id
+    }),
+  );
+  assert_eq!(res, json!({ "kind": "unchanged", "resultId": result_id }));
+
+  let res = client.write_request(
+    "workspace/diagnostic",
+    json!({
+      "previousResultIds": [{
+        "uri": "file:///a/file.ts",
+        "value": result_id
+      }]
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "items": [{
+        "uri": "file:///a/file.ts",
+        "version": 1,
+        "kind": "unchanged",
+        "resultId": result_id
+      }]
+    })
+  );
+
+  // an edit outdates the previous result
+  client.write_notification(
+    "textDocument/didChange",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts",
+        "version": 2
+      },
+      "contentChanges": [
+        {
+          "range": {
+            "start": { "line": 1, "character": 2 },
+            "end": { "line": 1, "character": 8 }
+          },
+          "text": ""
+        }
+      ]
+    }),
+  );
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "previousResultId": result_id
+    }),
+  );
+  assert_eq!(res["kind"], json!("full"));
+  assert_ne!(res["resultId"], result_id);
+  assert_eq!(res["items"], json!([]));
+
+  // documents which aren't diagnosable get an empty report
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": "file:///a/README.md" }
+    }),
+  );
+  assert_eq!(res["kind"], json!("full"));
+  assert_eq!(res["items"], json!([]));
+  client.shutdown();
+}
+
 #[test]
 fn lsp_code_actions() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -6350,6 +6739,93 @@ fn lsp_format_json() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_format_range() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "const a = 1\nif (a) {\nconsole.log(a)\n}\nconst b=2\n"
+    }
+  }));
+  let expected = json!([
+    {
+      "range": {
+        "start": { "line": 2, "character": 0 },
+        "end": { "line": 2, "character": 0 }
+      },
+      "newText": "  "
+    }, {
+      "range": {
+        "start": { "line": 2, "character": 14 },
+        "end": { "line": 2, "character": 14 }
+      },
+      "newText": ";"
+    }
+  ]);
+
+  let res = client.write_request(
+    "textDocument/rangeFormatting",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "range": {
+        "start": { "line": 1, "character": 0 },
+        "end": { "line": 3, "character": 1 }
+      },
+      "options": {
+        "tabSize": 2,
+        "insertSpaces": true
+      }
+    }),
+  );
+  assert_eq!(res, expected);
+
+  // typing the closing brace formats the whole `if` statement
+  let res = client.write_request(
+    "textDocument/onTypeFormatting",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "position": { "line": 3, "character": 1 },
+      "ch": "}",
+      "options": {
+        "tabSize": 2,
+        "insertSpaces": true
+      }
+    }),
+  );
+  assert_eq!(res, expected);
+
+  let res = client.write_request(
+    "textDocument/onTypeFormatting",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "position": { "line": 0, "character": 11 },
+      "ch": ";",
+      "options": {
+        "tabSize": 2,
+        "insertSpaces": true
+      }
+    }),
+  );
+  assert_eq!(
+    res,
+    json!([
+      {
+        "range": {
+          "start": { "line": 0, "character": 11 },
+          "end": { "line": 0, "character": 11 }
+        },
+        "newText": ";"
+      }
+    ])
+  );
+  client.shutdown();
+}
+
 #[test]
 fn lsp_json_no_diagnostics() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
@@ -6772,6 +7248,71 @@ fn lsp_workspace_symbol() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_code_actions_lint_fix() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "console.log('Hello, Deno!');;\n"
+    }
+  }));
+  let diagnostic = json!({
+    "range": {
+      "start": { "line": 0, "character": 28 },
+      "end": { "line": 0, "character": 29 }
+    },
+    "severity": 2,
+    "code": "no-extra-semi",
+    "source": "deno-lint",
+    "message": "Unnecessary semicolon.\nRemove the extra (and unnecessary) semi-colon",
+    "relatedInformation": []
+  });
+  let res = client.write_request(
+    "textDocument/codeAction",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts"
+      },
+      "range": {
+        "start": { "line": 0, "character": 28 },
+        "end": { "line": 0, "character": 29 }
+      },
+      "context": {
+        "diagnostics": [diagnostic],
+        "only": ["quickfix"]
+      }
+    }),
+  );
+  // the fix comes first, followed by the actions to ignore the diagnostic
+  assert_eq!(
+    res[0],
+    json!({
+      "title": "Remove extra semicolon",
+      "kind": "quickfix",
+      "diagnostics": [diagnostic],
+      "isPreferred": true,
+      "edit": {
+        "changes": {
+          "file:///a/file.ts": [{
+            "range": {
+              "start": { "line": 0, "character": 28 },
+              "end": { "line": 0, "character": 29 }
+            },
+            "newText": ""
+          }]
+        }
+      }
+    })
+  );
+  assert_eq!(res[1]["title"], "Disable no-extra-semi for this line");
+  client.shutdown();
+}
+
 #[test]
 fn lsp_code_actions_ignore_lint() {
   let context = TestContextBuilder::new().use_temp_cwd().build();
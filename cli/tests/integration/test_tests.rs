@@ -59,6 +59,57 @@ itest!(fail {
   output: "test/fail.out",
 });
 
+itest!(junit {
+  args: "test --reporter=junit test/reporters/reporters.ts",
+  exit_code: 1,
+  output: "test/reporters/junit.out",
+});
+
+itest!(tap {
+  args: "test --reporter=tap test/reporters/reporters.ts",
+  exit_code: 1,
+  output: "test/reporters/tap.out",
+});
+
+itest!(dot {
+  args: "test --reporter=dot test/reporters/reporters.ts",
+  exit_code: 1,
+  output: "test/reporters/dot.out",
+});
+
+#[test]
+fn junit_path() {
+  let context = TestContext::default();
+  let temp_dir = context.temp_dir();
+  let report_path = temp_dir.path().join("report.xml");
+  let output = context
+    .new_command()
+    .args_vec([
+      "test",
+      "test/reporters/reporters.ts",
+      &format!("--junit-path={}", report_path.display()),
+    ])
+    .run();
+  output.assert_exit_code(1);
+  // The pretty reporter still prints to stdout.
+  assert_contains!(output.combined_output(), "pass ... ok");
+  let report = temp_dir.read_to_string("report.xml");
+  assert_contains!(report, r#"<testsuites name="deno test" tests="7""#);
+  assert_contains!(report, "<system-out>some output\n</system-out>");
+}
+
+itest!(shard_1 {
+  args: "test --shard=1/2 test/shard",
+  exit_code: 0,
+  output: "test/shard/shard_1.out",
+});
+
+itest!(shard_2 {
+  args: "test --shard=2/2 test/shard",
+  exit_code: 0,
+  output: "test/shard/shard_2.out",
+});
+
 itest!(collect {
   args: "test --ignore=test/collect/ignore test/collect",
   exit_code: 0,
@@ -310,6 +310,48 @@ async fn lint_watch_without_args_test() {
   drop(t);
 }
 
+#[tokio::test]
+async fn lint_watch_max_warnings_test() {
+  let t = TempDir::new();
+  t.write(
+    "deno.json",
+    r#"{ "lint": { "rules": { "severity": { "prefer-const": "warn" } } } }"#,
+  );
+  t.write("main.ts", "let a = 1;\nconsole.log(a);\n");
+
+  let mut child = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("lint")
+    .arg("--watch")
+    .arg("--max-warnings=0")
+    .arg("--unstable")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let (_stdout_lines, mut stderr_lines) = child_lines(&mut child);
+
+  wait_contains(
+    "Found 1 warning, which is more than the maximum of 0.",
+    &mut stderr_lines,
+  )
+  .await;
+
+  // every run reports its own warnings
+  t.write("main.ts", "let a = 1;\nlet b = 2;\nconsole.log(a, b);\n");
+  wait_contains(
+    "Found 2 warnings, which is more than the maximum of 0.",
+    &mut stderr_lines,
+  )
+  .await;
+
+  // the watcher process is still alive
+  assert!(child.try_wait().unwrap().is_none());
+
+  child.kill().unwrap();
+  drop(t);
+}
+
 #[tokio::test]
 async fn lint_all_files_on_each_change_test() {
   let t = TempDir::new();
@@ -0,0 +1 @@
+Deno.bench("negative warmup", { warmup: -1 }, () => {});
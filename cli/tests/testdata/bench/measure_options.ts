@@ -0,0 +1,20 @@
+function busyWait(ms: number) {
+  const start = performance.now();
+  while (performance.now() - start < ms);
+}
+
+Deno.bench("min iterations", {
+  warmup: 1,
+  minIterations: 5,
+  minTime: 0,
+}, () => busyWait(0.1));
+
+Deno.bench("max time", { minTime: 10000, maxTime: 50 }, () => busyWait(1));
+
+Deno.bench("alloc", { minTime: 50 }, () => {
+  const arrays = [];
+  for (let i = 0; i < 100; i++) {
+    arrays.push(new Array(100).fill(i));
+  }
+  return arrays;
+});
@@ -0,0 +1,10 @@
+import { add } from "./math.ts";
+
+const worker = new Worker(new URL("./worker.ts", import.meta.url), {
+  type: "module",
+});
+worker.onmessage = (e) => {
+  console.log(`worker: ${e.data}`);
+  console.log(add(1, 2));
+  Deno.exit(3);
+};
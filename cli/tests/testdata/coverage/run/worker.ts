@@ -0,0 +1,4 @@
+import { multiply } from "./math.ts";
+
+self.postMessage(multiply(2, 3));
+self.close();
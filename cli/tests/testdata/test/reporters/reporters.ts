@@ -0,0 +1,18 @@
+Deno.test("pass", () => {
+  console.log("some output");
+});
+
+Deno.test("fail", () => {
+  throw new Error("boom");
+});
+
+Deno.test({ name: "ignored", ignore: true, fn() {} });
+
+Deno.test("steps", async (t) => {
+  await t.step("step 1", async (t) => {
+    await t.step("nested", () => {});
+  });
+  await t.step("step 2", () => {
+    throw new Error("step failed");
+  });
+});
@@ -3,8 +3,11 @@ import {
   assert,
   assertEquals,
   AssertionError,
+  assertNotEquals,
   assertRejects,
   assertThrows,
+  deferred,
+  delay,
 } from "./test_util.ts";
 import { assertType, IsExact } from "../../../test_util/std/testing/types.ts";
 
@@ -1017,6 +1020,140 @@ dbTest("list invalid selector", async (db) => {
   }, TypeError);
 });
 
+dbTest("atomic deleteRange prefix", async (db) => {
+  await setupData(db);
+  const res = await db.atomic().deleteRange({ prefix: ["a"] }).commit();
+  assert(res.ok);
+  const entries = await collect(db.list({ prefix: [] }));
+  assertEquals(entries.map((entry) => entry.key), [["a"], ["b"], ["b", "a"]]);
+});
+
+dbTest("atomic deleteRange range", async (db) => {
+  await setupData(db);
+  await db.atomic().deleteRange({ start: ["a", "b"], end: ["a", "d"] })
+    .commit();
+  const entries = await collect(db.list({ prefix: ["a"] }));
+  assertEquals(entries.map((entry) => entry.key), [
+    ["a", "a"],
+    ["a", "d"],
+    ["a", "e"],
+  ]);
+});
+
+dbTest("atomic deleteRange prefix with start", async (db) => {
+  await setupData(db);
+  await db.atomic().deleteRange({ prefix: ["a"], start: ["a", "c"] })
+    .commit();
+  const entries = await collect(db.list({ prefix: ["a"] }));
+  assertEquals(entries.map((entry) => entry.key), [["a", "a"], ["a", "b"]]);
+});
+
+dbTest("atomic deleteRange with failed check", async (db) => {
+  await setupData(db);
+  const res = await db.atomic()
+    .check({ key: ["b"], versionstamp: null })
+    .deleteRange({ prefix: ["a"] })
+    .commit();
+  assert(!res.ok);
+  const entries = await collect(db.list({ prefix: ["a"] }));
+  assertEquals(entries.length, 5);
+});
+
+dbTest("atomic deleteRange and set", async (db) => {
+  await setupData(db);
+  await db.atomic()
+    .deleteRange({ prefix: ["a"] })
+    .set(["a", "z"], 26)
+    .commit();
+  const entries = await collect(db.list({ prefix: ["a"] }));
+  assertEquals(entries.map((entry) => entry.key), [["a", "z"]]);
+});
+
+dbTest("atomic deleteRange invalid selector", async (db) => {
+  await assertRejects(async () => {
+    await db.atomic()
+      .deleteRange({ prefix: ["a"], start: ["a", "b"], end: ["a", "c"] })
+      .commit();
+  }, TypeError);
+
+  await assertRejects(async () => {
+    await db.atomic()
+      // @ts-expect-error missing end
+      .deleteRange({ start: ["a", "b"] })
+      .commit();
+  }, TypeError);
+});
+
+dbTest("read at versionstamp requires history mode", async (db) => {
+  const res = await db.set(["a"], 1);
+  await assertRejects(
+    () => db.get(["a"], { atVersionstamp: res.versionstamp }),
+    TypeError,
+    "requires history mode",
+  );
+});
+
+Deno.test({
+  name: "read at versionstamp with history mode",
+  // https://github.com/denoland/deno/issues/18363
+  ignore: Deno.build.os === "darwin" && isCI,
+  async fn() {
+    const db = await Deno.openKv(":memory:", {
+      historyRetention: 3600 * 1000,
+    });
+    try {
+      const res1 = await db.set(["a"], 1);
+      const res2 = await db.atomic().set(["a"], 2).set(["b"], 2).commit();
+      assert(res2.ok);
+      await db.delete(["a"]);
+
+      assertEquals((await db.get(["a"])).value, null);
+      assertEquals(
+        await db.get(["a"], { atVersionstamp: res1.versionstamp }),
+        { key: ["a"], value: 1, versionstamp: res1.versionstamp },
+      );
+      const entries = await db.getMany([["a"], ["b"]], {
+        atVersionstamp: res1.versionstamp,
+      });
+      assertEquals(entries.map((entry) => entry.value), [1, null]);
+
+      const listed = await collect(
+        db.list({ prefix: [] }, {
+          atVersionstamp: res2.versionstamp,
+          batchSize: 1,
+        }),
+      );
+      assertEquals(listed.map((entry) => [entry.key, entry.value]), [
+        [["a"], 2],
+        [["b"], 2],
+      ]);
+
+      await assertRejects(
+        () => db.get(["a"], { atVersionstamp: "00000000000000ff0000" }),
+        TypeError,
+        "has not been committed yet",
+      );
+      await assertRejects(
+        () => db.get(["a"], { atVersionstamp: "xx" }),
+        TypeError,
+        "invalid versionstamp",
+      );
+    } finally {
+      db.close();
+    }
+  },
+});
+
+Deno.test("invalid historyRetention rejects", async () => {
+  for (const historyRetention of [-1, 1.5, NaN]) {
+    await assertRejects(
+      () => Deno.openKv(":memory:", { historyRetention }),
+      TypeError,
+      "historyRetention must be a non-negative integer",
+    );
+  }
+});
+
 dbTest("invalid versionstamp in atomic check rejects", async (db) => {
   await assertRejects(async () => {
     await db.atomic().check({ key: ["a"], versionstamp: "" }).commit();
@@ -1256,6 +1393,304 @@ dbTest("keys must be arrays", async (db) => {
   );
 });
 
+dbTest("set with expireIn", async (db) => {
+  const res = await db.set(["a"], "b", { expireIn: 200 });
+  assert(res.ok);
+  assertEquals((await db.get(["a"])).value, "b");
+  await delay(400);
+  const entry = await db.get(["a"]);
+  assertEquals(entry.value, null);
+  assertEquals(entry.versionstamp, null);
+  assertEquals(await collect(db.list({ prefix: [] })), []);
+});
+
+dbTest("atomic set with expireIn and check", async (db) => {
+  const res = await db.atomic()
+    .set(["a"], 1, { expireIn: 200 })
+    .mutate({ key: ["b"], type: "set", value: 2, expireIn: 200 })
+    .commit();
+  assert(res.ok);
+  assertEquals((await db.get(["b"])).value, 2);
+  await delay(400);
+  // An expired key behaves as if it does not exist.
+  const res2 = await db.atomic()
+    .check({ key: ["a"], versionstamp: null })
+    .check({ key: ["b"], versionstamp: null })
+    .set(["a"], 3)
+    .commit();
+  assert(res2.ok);
+  assertEquals((await db.get(["a"])).value, 3);
+});
+
+dbTest("set without expireIn clears expiration", async (db) => {
+  await db.set(["a"], "b", { expireIn: 200 });
+  await db.set(["a"], "c");
+  await delay(400);
+  assertEquals((await db.get(["a"])).value, "c");
+});
+
+dbTest("sum on expired key starts from zero", async (db) => {
+  await db.set(["a"], new Deno.KvU64(10n), { expireIn: 200 });
+  await delay(400);
+  await db.atomic().sum(["a"], 1n).commit();
+  assertEquals((await db.get(["a"])).value, new Deno.KvU64(1n));
+});
+
+dbTest("negative expireIn throws", async (db) => {
+  await assertRejects(
+    async () => await db.set(["a"], "b", { expireIn: -1 }),
+    TypeError,
+    "expireIn cannot be negative",
+  );
+  assertThrows(
+    () => db.atomic().set(["a"], "b", { expireIn: -1 }),
+    TypeError,
+    "expireIn cannot be negative",
+  );
+});
+
+dbTest("watch reports initial state and changes", async (db) => {
+  await db.set(["a"], 1);
+  const reader = db.watch([["a"], ["b"]]).getReader();
+  try {
+    const first = await reader.read();
+    assert(!first.done);
+    assertEquals(first.value[0].value, 1);
+    assertEquals(first.value[1].value, null);
+    assertEquals(first.value[1].versionstamp, null);
+
+    const { versionstamp } = await db.set(["b"], 2);
+    const second = await reader.read();
+    assert(!second.done);
+    assertEquals(second.value[0].value, 1);
+    assertEquals(second.value[1].value, 2);
+    assertEquals(second.value[1].versionstamp, versionstamp);
+
+    await db.delete(["a"]);
+    const third = await reader.read();
+    assert(!third.done);
+    assertEquals(third.value[0].value, null);
+    assertEquals(third.value[1].value, 2);
+  } finally {
+    await reader.cancel();
+  }
+});
+
+dbTest("watch ignores writes to other keys", async (db) => {
+  const reader = db.watch([["a"]]).getReader();
+  try {
+    await reader.read();
+    await db.set(["b"], 1);
+    await db.set(["a"], 2);
+    const next = await reader.read();
+    assert(!next.done);
+    assertEquals(next.value[0].value, 2);
+  } finally {
+    await reader.cancel();
+  }
+});
+
+dbTest("watch too many keys throws", (db) => {
+  const keys = Array.from({ length: 11 }, (_, i) => ["a", i]);
+  assertThrows(() => db.watch(keys), TypeError, "too many keys (max 10)");
+  return Promise.resolve();
+});
+
+Deno.test({
+  name: "watch stream ends when database is closed",
+  // https://github.com/denoland/deno/issues/18363
+  ignore: Deno.build.os === "darwin" && isCI,
+  async fn() {
+    const db = await Deno.openKv(":memory:");
+    const reader = db.watch([["a"]]).getReader();
+    await reader.read();
+    db.close();
+    const next = await reader.read();
+    assert(next.done);
+  },
+});
+
+function queueTest(name: string, fn: (db: Deno.Kv) => Promise<void>) {
+  Deno.test({
+    name,
+    // https://github.com/denoland/deno/issues/18363
+    ignore: Deno.build.os === "darwin" && isCI,
+    async fn() {
+      const db: Deno.Kv = await Deno.openKv(
+        ":memory:",
+      );
+      await fn(db);
+    },
+  });
+}
+
+queueTest("basic listenQueue and enqueue", async (db) => {
+  const promise = deferred();
+  let dequeuedMessage: unknown = null;
+  const listener = db.listenQueue((msg) => {
+    dequeuedMessage = msg;
+    promise.resolve();
+  });
+  try {
+    const res = await db.enqueue("test");
+    assert(res.ok);
+    assertNotEquals(res.versionstamp, null);
+    await promise;
+    assertEquals(dequeuedMessage, "test");
+  } finally {
+    db.close();
+    await listener;
+  }
+});
+
+queueTest("queue with delay", async (db) => {
+  const promise = deferred();
+  let dequeueTime = 0;
+  const listener = db.listenQueue(() => {
+    dequeueTime = Date.now();
+    promise.resolve();
+  });
+  try {
+    const enqueueTime = Date.now();
+    await db.enqueue("test", { delay: 500 });
+    await promise;
+    assert(dequeueTime - enqueueTime >= 500);
+  } finally {
+    db.close();
+    await listener;
+  }
+});
+
+queueTest("queue retries failed handler", async (db) => {
+  const promise = deferred();
+  let count = 0;
+  const listener = db.listenQueue((_msg) => {
+    count += 1;
+    if (count == 1) {
+      throw new TypeError("dequeue error");
+    }
+    promise.resolve();
+  });
+  try {
+    await db.enqueue("test");
+    await promise;
+    assertEquals(count, 2);
+  } finally {
+    db.close();
+    await listener;
+  }
+});
+
+queueTest("queue with empty backoff schedule is not retried", async (db) => {
+  let count = 0;
+  const listener = db.listenQueue((_msg) => {
+    count += 1;
+    throw new TypeError("dequeue error");
+  });
+  try {
+    await db.enqueue("test", {
+      backoffSchedule: [],
+      keysIfUndelivered: [["undelivered"]],
+    });
+    while ((await db.get(["undelivered"])).value === null) {
+      await delay(20);
+    }
+    assertEquals((await db.get(["undelivered"])).value, "test");
+    assertEquals(count, 1);
+  } finally {
+    db.close();
+    await listener;
+  }
+});
+
+queueTest("atomic enqueue commits with mutations", async (db) => {
+  const promise = deferred();
+  let dequeuedMessage: unknown = null;
+  const listener = db.listenQueue((msg) => {
+    dequeuedMessage = msg;
+    promise.resolve();
+  });
+  try {
+    const res = await db.atomic()
+      .check({ key: ["a"], versionstamp: null })
+      .set(["a"], 1)
+      .enqueue({ a: 1 })
+      .commit();
+    assert(res.ok);
+    await promise;
+    assertEquals(dequeuedMessage, { a: 1 });
+    assertEquals((await db.get(["a"])).value, 1);
+  } finally {
+    db.close();
+    await listener;
+  }
+});
+
+queueTest("failed atomic check does not enqueue", async (db) => {
+  let dequeued = false;
+  const listener = db.listenQueue(() => {
+    dequeued = true;
+  });
+  try {
+    await db.set(["a"], 1);
+    const res = await db.atomic()
+      .check({ key: ["a"], versionstamp: null })
+      .enqueue("test")
+      .commit();
+    assert(!res.ok);
+    await delay(200);
+    assert(!dequeued);
+  } finally {
+    db.close();
+    await listener;
+  }
+});
+
+queueTest("listenQueue can only be called once", async (db) => {
+  const listener = db.listenQueue(() => {});
+  try {
+    await assertRejects(
+      () => db.listenQueue(() => {}),
+      TypeError,
+      "Already listening to the queue",
+    );
+  } finally {
+    db.close();
+    await listener;
+  }
+});
+
+dbTest("invalid enqueue delay rejects", async (db) => {
+  await assertRejects(
+    () => db.enqueue("test", { delay: -100 }),
+    TypeError,
+    "delay cannot be negative",
+  );
+  await assertRejects(
+    () => db.enqueue("test", { delay: 31 * 24 * 60 * 60 * 1000 }),
+    TypeError,
+    "delay cannot be greater than",
+  );
+});
+
+dbTest("invalid enqueue backoffSchedule rejects", async (db) => {
+  await assertRejects(
+    () => db.enqueue("test", { backoffSchedule: [-1] }),
+    TypeError,
+    "backoffSchedule intervals must be non-negative integers",
+  );
+  await assertRejects(
+    () => db.enqueue("test", { backoffSchedule: [1, 1, 1, 1, 1, 1] }),
+    TypeError,
+    "backoff schedule cannot have more than 5 intervals",
+  );
+  assertThrows(
+    () => db.atomic().enqueue("test", { backoffSchedule: [0.5] }),
+    TypeError,
+    "backoffSchedule intervals must be non-negative integers",
+  );
+});
+
 Deno.test("Deno.Kv constructor throws", () => {
   assertThrows(() => {
     new Deno.Kv();
@@ -0,0 +1,232 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::Context;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::BenchDescription;
+use super::BenchReport;
+use super::BenchStats;
+use crate::util::checksum;
+
+/// Results of an earlier run, saved with `--save-baseline` and compared with
+/// the current results when passing `--baseline`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchBaseline {
+  #[serde(skip)]
+  pub name: String,
+  benches: Vec<BaselineBench>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineBench {
+  origin: String,
+  group: Option<String>,
+  name: String,
+  stats: BenchStats,
+}
+
+/// Baselines are kept in `baselines_dir`, separately for every project so
+/// that equally named baselines of different projects don't collide.
+pub fn baseline_path(
+  baselines_dir: &Path,
+  project_root: &Path,
+  name: &str,
+) -> PathBuf {
+  baselines_dir
+    .join(checksum::gen(&[project_root.to_string_lossy().as_bytes()]))
+    .join(format!("{name}.json"))
+}
+
+impl BenchBaseline {
+  pub fn from_report(name: &str, report: &BenchReport) -> Self {
+    Self {
+      name: name.to_string(),
+      benches: report
+        .measurements
+        .iter()
+        .map(|(desc, stats)| BaselineBench {
+          origin: desc.origin.clone(),
+          group: desc.group.clone(),
+          name: desc.name.clone(),
+          stats: stats.clone(),
+        })
+        .collect(),
+    }
+  }
+
+  pub fn load(name: &str, path: &Path) -> Result<Self, AnyError> {
+    let text = match fs::read_to_string(path) {
+      Ok(text) => text,
+      Err(err) if err.kind() == ErrorKind::NotFound => {
+        return Err(generic_error(format!(
+          "Baseline \"{name}\" not found. Save it first with --save-baseline={name}"
+        )));
+      }
+      Err(err) => return Err(err.into()),
+    };
+    let mut baseline: Self = serde_json::from_str(&text)
+      .with_context(|| format!("Failed to parse baseline \"{name}\""))?;
+    baseline.name = name.to_string();
+    Ok(baseline)
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), AnyError> {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(self)?)
+      .with_context(|| format!("Failed to save baseline \"{}\"", self.name))
+  }
+
+  /// Compares `stats` with the saved results of the same benchmark, if there
+  /// are any.
+  pub fn compare(
+    &self,
+    desc: &BenchDescription,
+    stats: &BenchStats,
+  ) -> Option<BenchComparison> {
+    self
+      .benches
+      .iter()
+      .find(|bench| {
+        bench.origin == desc.origin
+          && bench.group == desc.group
+          && bench.name == desc.name
+      })
+      .map(|bench| BenchComparison::new(&bench.stats, stats))
+  }
+}
+
+/// How the average time of a benchmark changed compared to the baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchComparison {
+  /// Change of the average time in percent, positive if it got slower.
+  pub change: f64,
+  /// Whether the change is statistically significant according to Welch's
+  /// t-test at a 95% confidence level.
+  pub significant: bool,
+}
+
+impl BenchComparison {
+  pub fn new(baseline: &BenchStats, current: &BenchStats) -> Self {
+    let change = if baseline.avg > 0.0 {
+      (current.avg - baseline.avg) / baseline.avg * 100.0
+    } else {
+      0.0
+    };
+    Self {
+      change,
+      significant: is_significant(baseline, current),
+    }
+  }
+
+  /// A regression is a significant slowdown of more than `threshold` percent.
+  pub fn is_regression(&self, threshold: u32) -> bool {
+    self.significant && self.change > threshold as f64
+  }
+}
+
+fn is_significant(a: &BenchStats, b: &BenchStats) -> bool {
+  if a.n < 2 || b.n < 2 {
+    return false;
+  }
+  let (n_a, n_b) = (a.n as f64, b.n as f64);
+  let (var_a, var_b) = (a.variance / n_a, b.variance / n_b);
+  let var = var_a + var_b;
+  if var == 0.0 {
+    return a.avg != b.avg;
+  }
+  let t = (b.avg - a.avg).abs() / var.sqrt();
+  // Welch–Satterthwaite approximation of the degrees of freedom.
+  let df =
+    var * var / (var_a * var_a / (n_a - 1.0) + var_b * var_b / (n_b - 1.0));
+  t > t_critical_975(df)
+}
+
+/// Approximates the 97.5% quantile of Student's t-distribution with `df`
+/// degrees of freedom, using the Cornish-Fisher expansion around the one of
+/// the normal distribution. It is accurate to a few thousandths from about
+/// five degrees of freedom on, while benchmarks have at least ten samples.
+fn t_critical_975(df: f64) -> f64 {
+  const Z: f64 = 1.959963984540054;
+  let z3 = Z.powi(3);
+  let z5 = Z.powi(5);
+  let z7 = Z.powi(7);
+  let z9 = Z.powi(9);
+  Z + (z3 + Z) / (4.0 * df)
+    + (5.0 * z5 + 16.0 * z3 + 3.0 * Z) / (96.0 * df.powi(2))
+    + (3.0 * z7 + 19.0 * z5 + 17.0 * z3 - 15.0 * Z) / (384.0 * df.powi(3))
+    + (79.0 * z9 + 776.0 * z7 + 1482.0 * z5 - 1920.0 * z3 - 945.0 * Z)
+      / (92160.0 * df.powi(4))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stats(n: u64, avg: f64, variance: f64) -> BenchStats {
+    BenchStats {
+      n,
+      min: avg,
+      max: avg,
+      avg,
+      p75: avg,
+      p99: avg,
+      p995: avg,
+      p999: avg,
+      variance,
+      alloc: 0.0,
+    }
+  }
+
+  #[test]
+  fn test_t_critical_975() {
+    assert!((t_critical_975(5.0) - 2.571).abs() < 0.005);
+    assert!((t_critical_975(10.0) - 2.228).abs() < 0.001);
+    assert!((t_critical_975(30.0) - 2.042).abs() < 0.001);
+    assert!((t_critical_975(1e9) - 1.960).abs() < 0.001);
+  }
+
+  #[test]
+  fn test_comparison() {
+    // 20% slower with little noise
+    let comparison =
+      BenchComparison::new(&stats(100, 100.0, 25.0), &stats(100, 120.0, 25.0));
+    assert!((comparison.change - 20.0).abs() < 1e-9);
+    assert!(comparison.significant);
+    assert!(comparison.is_regression(10));
+    assert!(!comparison.is_regression(20));
+
+    // 20% slower, but within the noise
+    let comparison = BenchComparison::new(
+      &stats(10, 100.0, 10000.0),
+      &stats(10, 120.0, 10000.0),
+    );
+    assert!(!comparison.significant);
+    assert!(!comparison.is_regression(10));
+
+    // faster is never a regression
+    let comparison =
+      BenchComparison::new(&stats(100, 100.0, 1.0), &stats(100, 50.0, 1.0));
+    assert!((comparison.change + 50.0).abs() < 1e-9);
+    assert!(comparison.significant);
+    assert!(!comparison.is_regression(0));
+
+    // without any noise every difference is significant
+    let comparison =
+      BenchComparison::new(&stats(10, 100.0, 0.0), &stats(10, 101.0, 0.0));
+    assert!(comparison.significant);
+    let comparison =
+      BenchComparison::new(&stats(10, 100.0, 0.0), &stats(10, 100.0, 0.0));
+    assert!(!comparison.significant);
+  }
+}
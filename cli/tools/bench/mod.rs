@@ -3,6 +3,7 @@
 use crate::args::BenchOptions;
 use crate::args::CliOptions;
 use crate::args::TypeCheckMode;
+use crate::cache::DenoDir;
 use crate::colors;
 use crate::display::write_json_to_stdout;
 use crate::factory::CliFactory;
@@ -44,11 +45,63 @@ use std::sync::Arc;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedSender;
 
+mod baseline;
+
+use baseline::baseline_path;
+use baseline::BenchBaseline;
+use baseline::BenchComparison;
+
 #[derive(Debug, Clone)]
 struct BenchSpecifierOptions {
   filter: TestFilter,
   json: bool,
   log_level: Option<log::Level>,
+  baseline: Option<Arc<BenchBaseline>>,
+  regression_threshold: u32,
+  /// Name and path of the baseline to save the results as.
+  save_baseline: Option<(String, PathBuf)>,
+  measure: BenchMeasureOptions,
+}
+
+impl BenchSpecifierOptions {
+  fn resolve(
+    cli_options: &CliOptions,
+    deno_dir: &DenoDir,
+    bench_options: &BenchOptions,
+  ) -> Result<Self, AnyError> {
+    let project_root = cli_options
+      .maybe_config_file()
+      .as_ref()
+      .and_then(|config_file| config_file.specifier.to_file_path().ok())
+      .and_then(|path| path.parent().map(ToOwned::to_owned))
+      .unwrap_or_else(|| cli_options.initial_cwd().to_path_buf());
+    let baselines_dir = deno_dir.bench_baselines_folder_path();
+    let baseline = match &bench_options.baseline {
+      Some(name) => {
+        let path = baseline_path(&baselines_dir, &project_root, name);
+        Some(Arc::new(BenchBaseline::load(name, &path)?))
+      }
+      None => None,
+    };
+    let save_baseline = bench_options.save_baseline.as_ref().map(|name| {
+      let path = baseline_path(&baselines_dir, &project_root, name);
+      (name.clone(), path)
+    });
+    Ok(Self {
+      filter: TestFilter::from_flag(&bench_options.filter),
+      json: bench_options.json,
+      log_level: cli_options.log_level(),
+      baseline,
+      regression_threshold: bench_options.regression_threshold,
+      save_baseline,
+      measure: BenchMeasureOptions {
+        warmup: bench_options.warmup,
+        min_iterations: bench_options.min_iterations,
+        min_time: bench_options.min_time,
+        max_time: bench_options.max_time,
+      },
+    })
+  }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
@@ -70,6 +123,31 @@ pub enum BenchEvent {
   Result(usize, BenchResult),
 }
 
+/// Controls how long a benchmark is run. Unset options fall back to the ones
+/// passed on the command line, and then to the defaults of the bench runner.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchMeasureOptions {
+  pub warmup: Option<u32>,
+  pub min_iterations: Option<u32>,
+  /// In milliseconds.
+  pub min_time: Option<u64>,
+  /// In milliseconds.
+  pub max_time: Option<u64>,
+}
+
+impl BenchMeasureOptions {
+  /// Fills the options that are not set with the ones from `defaults`.
+  pub fn or(self, defaults: &Self) -> Self {
+    Self {
+      warmup: self.warmup.or(defaults.warmup),
+      min_iterations: self.min_iterations.or(defaults.min_iterations),
+      min_time: self.min_time.or(defaults.min_time),
+      max_time: self.max_time.or(defaults.max_time),
+    }
+  }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum BenchResult {
@@ -106,6 +184,9 @@ pub struct BenchStats {
   pub p99: f64,
   pub p995: f64,
   pub p999: f64,
+  pub variance: f64,
+  /// Average number of bytes allocated on the V8 heap per iteration.
+  pub alloc: f64,
 }
 
 impl BenchReport {
@@ -122,11 +203,12 @@ impl BenchReport {
 fn create_reporter(
   show_output: bool,
   json: bool,
+  baseline: Option<Arc<BenchBaseline>>,
 ) -> Box<dyn BenchReporter + Send> {
   if json {
     return Box::new(JsonReporter::new());
   }
-  Box::new(ConsoleReporter::new(show_output))
+  Box::new(ConsoleReporter::new(show_output, baseline))
 }
 
 pub trait BenchReporter {
@@ -222,10 +304,14 @@ struct ConsoleReporter {
   baseline: bool,
   group_measurements: Vec<(BenchDescription, BenchStats)>,
   options: Option<mitata::reporter::Options>,
+  saved_baseline: Option<Arc<BenchBaseline>>,
 }
 
 impl ConsoleReporter {
-  fn new(show_output: bool) -> Self {
+  fn new(
+    show_output: bool,
+    saved_baseline: Option<Arc<BenchBaseline>>,
+  ) -> Self {
     Self {
       show_output,
       group: None,
@@ -234,10 +320,32 @@ impl ConsoleReporter {
       name: String::new(),
       has_ungrouped: false,
       group_measurements: Vec::new(),
+      saved_baseline,
     }
   }
 }
 
+fn fmt_comparison(baseline_name: &str, comparison: &BenchComparison) -> String {
+  let change = format!("{:+.2}%", comparison.change);
+  let change = if !comparison.significant {
+    colors::gray(change).to_string()
+  } else if comparison.change > 0.0 {
+    colors::red(change).to_string()
+  } else {
+    colors::green(change).to_string()
+  };
+  format!(
+    "  {} {}{}",
+    change,
+    colors::gray(format!("vs baseline \"{baseline_name}\"")),
+    if comparison.significant {
+      String::new()
+    } else {
+      colors::gray(" (not significant)").to_string()
+    }
+  )
+}
+
 impl BenchReporter for ConsoleReporter {
   #[cold]
   fn report_plan(&mut self, plan: &BenchPlan) {
@@ -348,11 +456,18 @@ impl BenchReporter for ConsoleReporter {
               p75: stats.p75,
               p99: stats.p99,
               p995: stats.p995,
+              alloc: stats.alloc,
             },
             options
           )
         );
 
+        if let Some(saved_baseline) = &self.saved_baseline {
+          if let Some(comparison) = saved_baseline.compare(&desc, stats) {
+            println!("{}", fmt_comparison(&saved_baseline.name, &comparison));
+          }
+        }
+
         self.group_measurements.push((desc, stats.clone()));
       }
 
@@ -399,6 +514,7 @@ impl BenchReporter for ConsoleReporter {
                 p75: s.p75,
                 p99: s.p99,
                 p995: s.p995,
+                alloc: s.alloc,
               },
             })
             .collect::<Vec<mitata::reporter::GroupBenchmark>>(),
@@ -441,12 +557,13 @@ async fn bench_specifier(
   specifier: ModuleSpecifier,
   sender: UnboundedSender<BenchEvent>,
   filter: TestFilter,
+  measure: BenchMeasureOptions,
 ) -> Result<(), AnyError> {
   let mut worker = worker_factory
     .create_custom_worker(
       specifier.clone(),
       PermissionsContainer::new(permissions),
-      vec![ops::bench::deno_bench::init_ops(sender.clone())],
+      vec![ops::bench::deno_bench::init_ops(sender.clone(), measure)],
       Default::default(),
     )
     .await?;
@@ -529,6 +646,7 @@ async fn bench_specifiers(
         specifier,
         sender,
         options.filter,
+        options.measure,
       );
       run_local(future)
     })
@@ -542,8 +660,11 @@ async fn bench_specifiers(
     tokio::task::spawn(async move {
       let mut used_only = false;
       let mut report = BenchReport::new();
-      let mut reporter =
-        create_reporter(log_level != Some(Level::Error), options.json);
+      let mut reporter = create_reporter(
+        log_level != Some(Level::Error),
+        options.json,
+        options.baseline.clone(),
+      );
       let mut benches = IndexMap::new();
 
       while let Some(event) = receiver.recv().await {
@@ -589,6 +710,19 @@ async fn bench_specifiers(
 
       reporter.report_end(&report);
 
+      if let Some((name, path)) = &options.save_baseline {
+        // Partial results would make for a misleading baseline.
+        if !used_only && report.failed == 0 {
+          BenchBaseline::from_report(name, &report).save(path)?;
+          if !options.json {
+            println!(
+              "\n{}",
+              colors::gray(format!("Saved baseline \"{name}\""))
+            );
+          }
+        }
+      }
+
       if used_only {
         return Err(generic_error(
           "Bench failed because the \"only\" option was used",
@@ -599,6 +733,32 @@ async fn bench_specifiers(
         return Err(generic_error("Bench failed"));
       }
 
+      if let Some(baseline) = &options.baseline {
+        let threshold = options.regression_threshold;
+        let regressions = report
+          .measurements
+          .iter()
+          .filter_map(|(desc, stats)| {
+            let comparison = baseline.compare(desc, stats)?;
+            comparison.is_regression(threshold).then(|| {
+              format!(
+                "  {} ({}): {:+.2}%",
+                desc.name, desc.origin, comparison.change
+              )
+            })
+          })
+          .collect::<Vec<_>>();
+        if !regressions.is_empty() {
+          return Err(generic_error(format!(
+            "Bench failed because {} benchmark(s) got slower than baseline \"{}\" by more than {}%:\n{}",
+            regressions.len(),
+            baseline.name,
+            threshold,
+            regressions.join("\n")
+          )));
+        }
+      }
+
       Ok(())
     })
   };
@@ -658,20 +818,14 @@ pub async fn run_benchmarks(
     return Ok(());
   }
 
-  let log_level = cli_options.log_level();
+  let options = BenchSpecifierOptions::resolve(
+    cli_options,
+    factory.deno_dir()?,
+    &bench_options,
+  )?;
   let worker_factory =
     Arc::new(factory.create_cli_main_worker_factory().await?);
-  bench_specifiers(
-    worker_factory,
-    &permissions,
-    specifiers,
-    BenchSpecifierOptions {
-      filter: TestFilter::from_flag(&bench_options.filter),
-      json: bench_options.json,
-      log_level,
-    },
-  )
-  .await?;
+  bench_specifiers(worker_factory, &permissions, specifiers, options).await?;
 
   Ok(())
 }
@@ -692,6 +846,11 @@ pub async fn run_benchmarks_with_watch(
   let permissions =
     Permissions::from_options(&cli_options.permissions_options())?;
   let no_check = cli_options.type_check_mode() == TypeCheckMode::None;
+  let options = BenchSpecifierOptions::resolve(
+    cli_options,
+    factory.deno_dir()?,
+    &bench_options,
+  )?;
 
   let resolver = |changed: Option<Vec<PathBuf>>| {
     let paths_to_watch = bench_options.files.include.clone();
@@ -813,6 +972,7 @@ pub async fn run_benchmarks_with_watch(
     let module_load_preparer = module_load_preparer.clone();
     let cli_options = cli_options.clone();
     let create_cli_main_worker_factory = create_cli_main_worker_factory.clone();
+    let options = options.clone();
 
     async move {
       let worker_factory = Arc::new(create_cli_main_worker_factory());
@@ -829,18 +989,8 @@ pub async fn run_benchmarks_with_watch(
         return Ok(());
       }
 
-      let log_level = cli_options.log_level();
-      bench_specifiers(
-        worker_factory,
-        permissions,
-        specifiers,
-        BenchSpecifierOptions {
-          filter: TestFilter::from_flag(&bench_options.filter),
-          json: bench_options.json,
-          log_level,
-        },
-      )
-      .await?;
+      bench_specifiers(worker_factory, permissions, specifiers, options)
+        .await?;
 
       Ok(())
     }
@@ -920,6 +1070,20 @@ mod mitata {
     }
   }
 
+  fn fmt_bytes(bytes: f64) -> String {
+    if bytes < 1024.0 {
+      return format!("{} B", bytes.round());
+    }
+    if bytes < 1024.0 * 1024.0 {
+      return format!("{:.2} KiB", bytes / 1024.0);
+    }
+    if bytes < 1024.0 * 1024.0 * 1024.0 {
+      return format!("{:.2} MiB", bytes / (1024.0 * 1024.0));
+    }
+
+    format!("{:.2} GiB", bytes / (1024.0 * 1024.0 * 1024.0))
+  }
+
   pub mod cpu {
     #![allow(dead_code)]
 
@@ -1010,6 +1174,7 @@ mod mitata {
       pub p75: f64,
       pub p99: f64,
       pub p995: f64,
+      pub alloc: f64,
     }
 
     #[derive(Clone, PartialEq)]
@@ -1027,6 +1192,7 @@ mod mitata {
       pub colors: bool,
       pub min_max: bool,
       pub percentiles: bool,
+      pub alloc: bool,
     }
 
     impl Options {
@@ -1037,6 +1203,7 @@ mod mitata {
           min_max: true,
           size: size(names),
           percentiles: true,
+          alloc: true,
         }
       }
     }
@@ -1067,6 +1234,11 @@ mod mitata {
         s.push_str(&"-".repeat(9 + 10 + 10));
       }
 
+      if options.alloc {
+        s.push(' ');
+        s.push_str(&"-".repeat(11));
+      }
+
       s
     }
 
@@ -1111,6 +1283,9 @@ mod mitata {
       if options.percentiles {
         s.push_str(&format!(" {:>9} {:>9} {:>9}", "p75", "p99", "p995"));
       }
+      if options.alloc {
+        s.push_str(&format!(" {:>11}", "alloc/iter"));
+      }
 
       s
     }
@@ -1150,6 +1325,9 @@ mod mitata {
             fmt_duration(stats.p995)
           ));
         }
+        if options.alloc {
+          s.push_str(&format!(" {:>11}", fmt_bytes(stats.alloc)));
+        }
       } else {
         if options.avg {
           s.push_str(&format!(
@@ -1175,6 +1353,14 @@ mod mitata {
             colors::magenta(fmt_duration(stats.p995))
           ));
         }
+        if options.alloc {
+          // pad before coloring, as the escape codes would count towards
+          // the width of the column otherwise
+          s.push_str(&format!(
+            " {}",
+            colors::magenta(format!("{:>11}", fmt_bytes(stats.alloc)))
+          ));
+        }
       }
 
       s
@@ -0,0 +1,278 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+
+use super::report_path;
+use super::CoverageCount;
+use super::CoverageReport;
+use super::CoverageReporter;
+use super::CoverageStats;
+use crate::util::xml::escape_xml;
+
+/// The subdirectory of the report which holds the pages of the source files.
+const FILES_DIR: &str = "files";
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; color: #222; }
+a { color: #0645ad; text-decoration: none; }
+a:hover { text-decoration: underline; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { padding: 0.25em 0.75em; text-align: left; }
+table.summary th, table.summary td { border-bottom: 1px solid #ddd; }
+td.high { background: #d8f5d0; }
+td.medium { background: #fff4c2; }
+td.low { background: #fcd9d9; }
+table.source { font-family: monospace; width: 100%; }
+table.source td { padding: 0 0.5em; white-space: pre; vertical-align: top; }
+table.source td.line-number, table.source td.hits, table.source td.branches {
+  color: #888; text-align: right; user-select: none;
+}
+tr.hit td.code { background: #e6f5e0; }
+tr.miss td.code { background: #fbe0e0; }
+td.branch-hit { background: #d8f5d0; }
+td.branch-partial { background: #fff4c2; }
+td.branch-miss { background: #fcd9d9; }
+"#;
+
+/// Writes a self-contained HTML report: an `index.html` with a summary per
+/// directory, and one page per source file in the [FILES_DIR] subdirectory of
+/// the output directory, so that the pages never overwrite the index.
+pub struct HtmlCoverageReporter {
+  output_dir: PathBuf,
+  cwd: PathBuf,
+  files: Vec<HtmlFileEntry>,
+}
+
+struct HtmlFileEntry {
+  /// Path of the source file in the report, with `/` as the separator.
+  path: String,
+  stats: CoverageStats,
+}
+
+impl HtmlCoverageReporter {
+  pub fn new(output_dir: PathBuf, cwd: &Path) -> HtmlCoverageReporter {
+    HtmlCoverageReporter {
+      output_dir,
+      cwd: cwd.to_path_buf(),
+      files: vec![],
+    }
+  }
+
+  fn write_page(&self, path: &str, html: String) -> Result<(), AnyError> {
+    let page_path = self.output_dir.join(path);
+    if let Some(parent) = page_path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(&page_path, html).with_context(|| {
+      format!("Failed to write coverage report to {}", page_path.display())
+    })
+  }
+}
+
+impl CoverageReporter for HtmlCoverageReporter {
+  fn report(
+    &mut self,
+    coverage_report: &CoverageReport,
+    file_text: &str,
+  ) -> Result<(), AnyError> {
+    let path = report_path(&coverage_report.url, &self.cwd);
+    let stats = coverage_report.stats();
+    let line_counts = coverage_report
+      .found_lines
+      .iter()
+      .copied()
+      .collect::<HashMap<_, _>>();
+    let mut branch_counts: HashMap<usize, CoverageCount> = HashMap::new();
+    for branch in &coverage_report.branches {
+      let count = branch_counts.entry(branch.line_index).or_default();
+      count.found += 1;
+      if branch.is_hit {
+        count.hit += 1;
+      }
+    }
+
+    let root = "../".repeat(path.matches('/').count() + 1);
+    let mut html = page_start(&path);
+    writeln!(
+      html,
+      r#"<nav><a href="{root}index.html">All files</a> / {}</nav>"#,
+      escape_xml(&path)
+    )
+    .unwrap();
+    writeln!(html, "<h1>{}</h1>", escape_xml(&path)).unwrap();
+    html.push_str(&summary_table(&[(None, &stats)]));
+
+    html.push_str("<table class=\"source\">\n");
+    for (line_index, line) in file_text.split('\n').enumerate() {
+      let line_number = line_index + 1;
+      let (class, hits) = match line_counts.get(&line_index) {
+        Some(count) if *count > 0 => ("hit", format!("{count}x")),
+        Some(_) => ("miss", "0x".to_string()),
+        None => ("", String::new()),
+      };
+      let (branch_class, branches) = match branch_counts.get(&line_index) {
+        Some(count) => {
+          let class = if count.hit == count.found {
+            "branch-hit"
+          } else if count.hit > 0 {
+            "branch-partial"
+          } else {
+            "branch-miss"
+          };
+          (class, format!("{}/{}", count.hit, count.found))
+        }
+        None => ("", String::new()),
+      };
+      writeln!(
+        html,
+        r##"<tr class="{class}"><td class="line-number" id="L{line_number}"><a href="#L{line_number}">{line_number}</a></td><td class="hits">{hits}</td><td class="branches {branch_class}">{branches}</td><td class="code">{}</td></tr>"##,
+        escape_xml(line.trim_end_matches('\r')),
+      )
+      .unwrap();
+    }
+    html.push_str("</table>\n");
+    html.push_str(PAGE_END);
+
+    self.write_page(&format!("{FILES_DIR}/{path}.html"), html)?;
+    self.files.push(HtmlFileEntry { path, stats });
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    let mut directories: BTreeMap<&str, Vec<&HtmlFileEntry>> = BTreeMap::new();
+    for file in &self.files {
+      let directory = file.path.rsplit_once('/').map_or(".", |(dir, _)| dir);
+      directories.entry(directory).or_default().push(file);
+    }
+    let directory_stats = directories
+      .iter()
+      .map(|(directory, files)| {
+        let mut stats = CoverageStats::default();
+        for file in files {
+          stats += file.stats;
+        }
+        (*directory, stats)
+      })
+      .collect::<Vec<_>>();
+    let mut total = CoverageStats::default();
+    for (_, stats) in &directory_stats {
+      total += *stats;
+    }
+
+    let mut html = page_start("All files");
+    html.push_str("<h1>All files</h1>\n");
+    html.push_str(&summary_table(&[(None, &total)]));
+    html.push_str("<h2>Directories</h2>\n");
+    let rows = directory_stats
+      .iter()
+      .map(|(directory, stats)| {
+        let link =
+          format!(r##"<a href="#{0}">{0}</a>"##, escape_xml(directory));
+        (Some(link), stats)
+      })
+      .collect::<Vec<_>>();
+    html.push_str(&summary_table(&rows));
+
+    for (directory, files) in &directories {
+      writeln!(html, r#"<h2 id="{0}">{0}</h2>"#, escape_xml(directory))
+        .unwrap();
+      let rows = files
+        .iter()
+        .map(|file| {
+          let name = file.path.rsplit('/').next().unwrap();
+          let link = format!(
+            r#"<a href="{FILES_DIR}/{}.html">{}</a>"#,
+            escape_xml(&file.path),
+            escape_xml(name)
+          );
+          (Some(link), &file.stats)
+        })
+        .collect::<Vec<_>>();
+      html.push_str(&summary_table(&rows));
+    }
+    html.push_str(PAGE_END);
+
+    self.write_page("index.html", html)?;
+    let index_path = self.output_dir.join("index.html");
+    let index_path = index_path.canonicalize().unwrap_or(index_path);
+    match Url::from_file_path(&index_path) {
+      Ok(url) => println!("HTML coverage report has been generated at {url}"),
+      Err(_) => println!(
+        "HTML coverage report has been generated at {}",
+        index_path.display()
+      ),
+    }
+    Ok(())
+  }
+}
+
+const PAGE_END: &str = "</body>\n</html>\n";
+
+fn page_start(title: &str) -> String {
+  format!(
+    r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Coverage report - {}</title>
+<style>{}</style>
+</head>
+<body>
+"#,
+    escape_xml(title),
+    STYLE
+  )
+}
+
+/// Renders one row per entry with the line, branch and function coverage.
+/// Entries without a name are rendered as a single row without the name
+/// column.
+fn summary_table(rows: &[(Option<String>, &CoverageStats)]) -> String {
+  let has_names = rows.iter().any(|(name, _)| name.is_some());
+  let mut html = String::from("<table class=\"summary\">\n<tr>");
+  if has_names {
+    html.push_str("<th></th>");
+  }
+  html.push_str("<th>Lines</th><th>Branches</th><th>Functions</th></tr>\n");
+  for (name, stats) in rows {
+    html.push_str("<tr>");
+    if let Some(name) = name {
+      write!(html, "<td>{name}</td>").unwrap();
+    }
+    for count in [stats.lines, stats.branches, stats.functions] {
+      write!(
+        html,
+        r#"<td class="{}">{:.2}% ({}/{})</td>"#,
+        coverage_level(&count),
+        count.percent(),
+        count.hit,
+        count.found
+      )
+      .unwrap();
+    }
+    html.push_str("</tr>\n");
+  }
+  html.push_str("</table>\n");
+  html
+}
+
+/// The same levels as used for coloring the pretty reporter's output.
+fn coverage_level(count: &CoverageCount) -> &'static str {
+  let percent = count.percent();
+  if percent >= 90.0 {
+    "high"
+  } else if percent >= 75.0 {
+    "medium"
+  } else {
+    "low"
+  }
+}
@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::CoverageFlags;
+use crate::args::CoverageReporterConfig;
 use crate::args::FileFlags;
 use crate::args::Flags;
 use crate::colors;
@@ -9,6 +10,7 @@ use crate::tools::fmt::format_json;
 use crate::tools::test::is_supported_test_path;
 use crate::util::fs::FileCollector;
 use crate::util::text_encoding::source_map_from_code;
+use crate::util::xml::escape_xml;
 
 use deno_ast::MediaType;
 use deno_ast::ModuleSpecifier;
@@ -22,21 +24,31 @@ use deno_core::url::Url;
 use deno_core::LocalInspectorSession;
 use deno_core::ModuleCode;
 use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::fs;
 use std::fs::File;
 use std::io::BufWriter;
 use std::io::Error;
 use std::io::Write;
 use std::io::{self};
+use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 use text_lines::TextLines;
 use uuid::Uuid;
 
+mod html;
 mod json_types;
 mod merge;
 mod range_tree;
+mod thresholds;
 
+use html::HtmlCoverageReporter;
 use json_types::*;
 
 pub struct CoverageCollector {
@@ -170,6 +182,74 @@ struct CoverageReport {
   output: Option<PathBuf>,
 }
 
+/// Number of covered items out of all items of one kind.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct CoverageCount {
+  hit: usize,
+  found: usize,
+}
+
+impl CoverageCount {
+  /// Percentage of covered items, where nothing to cover counts as fully
+  /// covered.
+  fn percent(&self) -> f64 {
+    if self.found == 0 {
+      100.0
+    } else {
+      self.hit as f64 * 100.0 / self.found as f64
+    }
+  }
+}
+
+impl std::ops::AddAssign for CoverageCount {
+  fn add_assign(&mut self, other: Self) {
+    self.hit += other.hit;
+    self.found += other.found;
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct CoverageStats {
+  lines: CoverageCount,
+  branches: CoverageCount,
+  functions: CoverageCount,
+}
+
+impl std::ops::AddAssign for CoverageStats {
+  fn add_assign(&mut self, other: Self) {
+    self.lines += other.lines;
+    self.branches += other.branches;
+    self.functions += other.functions;
+  }
+}
+
+impl CoverageReport {
+  fn stats(&self) -> CoverageStats {
+    CoverageStats {
+      lines: CoverageCount {
+        hit: self
+          .found_lines
+          .iter()
+          .filter(|(_, count)| *count > 0)
+          .count(),
+        found: self.found_lines.len(),
+      },
+      branches: CoverageCount {
+        hit: self.branches.iter().filter(|b| b.is_hit).count(),
+        found: self.branches.len(),
+      },
+      functions: CoverageCount {
+        hit: self
+          .named_functions
+          .iter()
+          .filter(|f| f.execution_count > 0)
+          .count(),
+        found: self.named_functions.len(),
+      },
+    }
+  }
+}
+
 fn generate_coverage_report(
   script_coverage: &ScriptCoverage,
   script_source: String,
@@ -360,17 +440,76 @@ fn generate_coverage_report(
   coverage_report
 }
 
+/// Path of a covered module for use in reports: local files relative to
+/// `cwd`, remote modules under their host name. Always uses `/` as the
+/// separator.
+fn report_path(url: &Url, cwd: &Path) -> String {
+  let path = match url.to_file_path() {
+    Ok(path) => path
+      .strip_prefix(cwd)
+      .map(Path::to_path_buf)
+      .unwrap_or(path),
+    Err(_) => {
+      let mut path = PathBuf::from(url.host_str().unwrap_or(url.scheme()));
+      path.push(url.path().trim_start_matches('/'));
+      path
+    }
+  };
+  path
+    .components()
+    .filter_map(|component| match component {
+      Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+      _ => None,
+    })
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
 enum CoverageReporterKind {
   Pretty,
   Lcov,
+  /// Writes the pages into the given directory.
+  Html(PathBuf),
+  /// Writes the report to the given file, or stdout.
+  Cobertura(Option<PathBuf>),
+  /// Writes the report to the given file, or stdout.
+  Json(Option<PathBuf>),
 }
 
 fn create_reporter(
   kind: CoverageReporterKind,
+  cwd: &Path,
 ) -> Box<dyn CoverageReporter + Send> {
   match kind {
     CoverageReporterKind::Lcov => Box::new(LcovCoverageReporter::new()),
     CoverageReporterKind::Pretty => Box::new(PrettyCoverageReporter::new()),
+    CoverageReporterKind::Html(output_dir) => {
+      Box::new(HtmlCoverageReporter::new(output_dir, cwd))
+    }
+    CoverageReporterKind::Cobertura(output) => {
+      Box::new(CoberturaCoverageReporter::new(output, cwd))
+    }
+    CoverageReporterKind::Json(output) => {
+      Box::new(JsonCoverageReporter::new(output, cwd))
+    }
+  }
+}
+
+/// Writes `contents` to `output`, or to stdout if there is no output file.
+fn write_report(
+  output: &Option<PathBuf>,
+  contents: &str,
+) -> Result<(), AnyError> {
+  match output {
+    Some(path) => fs::write(path, contents).with_context(|| {
+      format!("Failed to write coverage report to {}", path.display())
+    }),
+    None => {
+      let mut stdout = io::stdout();
+      stdout.write_all(contents.as_bytes())?;
+      stdout.flush()?;
+      Ok(())
+    }
   }
 }
 
@@ -381,7 +520,7 @@ trait CoverageReporter {
     file_text: &str,
   ) -> Result<(), AnyError>;
 
-  fn done(&mut self);
+  fn done(&mut self) -> Result<(), AnyError>;
 }
 
 struct LcovCoverageReporter {}
@@ -483,7 +622,9 @@ impl CoverageReporter for LcovCoverageReporter {
     Ok(())
   }
 
-  fn done(&mut self) {}
+  fn done(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
 }
 
 struct PrettyCoverageReporter {}
@@ -556,7 +697,317 @@ impl CoverageReporter for PrettyCoverageReporter {
     Ok(())
   }
 
-  fn done(&mut self) {}
+  fn done(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
+}
+
+/// Writes a Cobertura XML report, as understood by e.g. GitLab and Azure
+/// DevOps. Every directory is reported as a package and every file as a
+/// class in it.
+struct CoberturaCoverageReporter {
+  output: Option<PathBuf>,
+  cwd: PathBuf,
+  /// The `<class>` elements by the name of their package.
+  packages: BTreeMap<String, Vec<(CoverageStats, String)>>,
+}
+
+impl CoberturaCoverageReporter {
+  pub fn new(output: Option<PathBuf>, cwd: &Path) -> CoberturaCoverageReporter {
+    CoberturaCoverageReporter {
+      output,
+      cwd: cwd.to_path_buf(),
+      packages: Default::default(),
+    }
+  }
+}
+
+impl CoverageReporter for CoberturaCoverageReporter {
+  fn report(
+    &mut self,
+    coverage_report: &CoverageReport,
+    _file_text: &str,
+  ) -> Result<(), AnyError> {
+    let path = report_path(&coverage_report.url, &self.cwd);
+    let (package, name) = path.rsplit_once('/').unwrap_or((".", path.as_str()));
+    let stats = coverage_report.stats();
+
+    let mut branches_by_line: HashMap<usize, CoverageCount> = HashMap::new();
+    for branch in &coverage_report.branches {
+      let count = branches_by_line.entry(branch.line_index).or_default();
+      count.found += 1;
+      if branch.is_hit {
+        count.hit += 1;
+      }
+    }
+
+    let mut xml = String::new();
+    writeln!(
+      xml,
+      r#"        <class name="{}" filename="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+      escape_xml(name),
+      escape_xml(&path),
+      format_rate(&stats.lines),
+      format_rate(&stats.branches),
+    )?;
+    writeln!(xml, "          <methods>")?;
+    for function in &coverage_report.named_functions {
+      let is_hit = function.execution_count > 0;
+      writeln!(
+        xml,
+        r#"            <method name="{}" signature="" line-rate="{}" branch-rate="{}" complexity="0">"#,
+        escape_xml(&function.name),
+        if is_hit { "1" } else { "0" },
+        if is_hit { "1" } else { "0" },
+      )?;
+      writeln!(xml, "              <lines>")?;
+      writeln!(
+        xml,
+        r#"                <line number="{}" hits="{}"/>"#,
+        function.line_index + 1,
+        function.execution_count
+      )?;
+      writeln!(xml, "              </lines>")?;
+      writeln!(xml, "            </method>")?;
+    }
+    writeln!(xml, "          </methods>")?;
+    writeln!(xml, "          <lines>")?;
+    for (line_index, count) in &coverage_report.found_lines {
+      write!(
+        xml,
+        r#"            <line number="{}" hits="{}""#,
+        line_index + 1,
+        count
+      )?;
+      match branches_by_line.get(line_index) {
+        Some(branches) => writeln!(
+          xml,
+          r#" branch="true" condition-coverage="{}% ({}/{})"/>"#,
+          branches.percent().round(),
+          branches.hit,
+          branches.found
+        )?,
+        None => writeln!(xml, r#" branch="false"/>"#)?,
+      }
+    }
+    writeln!(xml, "          </lines>")?;
+    writeln!(xml, "        </class>")?;
+
+    self
+      .packages
+      .entry(package.to_string())
+      .or_default()
+      .push((stats, xml));
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    let mut total = CoverageStats::default();
+    for (stats, _) in self.packages.values().flatten() {
+      total += *stats;
+    }
+    let timestamp = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_millis())
+      .unwrap_or(0);
+
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" ?>"#)?;
+    writeln!(
+      xml,
+      r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )?;
+    writeln!(
+      xml,
+      r#"<coverage line-rate="{}" branch-rate="{}" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}" complexity="0" version="{}" timestamp="{}">"#,
+      format_rate(&total.lines),
+      format_rate(&total.branches),
+      total.lines.hit,
+      total.lines.found,
+      total.branches.hit,
+      total.branches.found,
+      escape_xml(&format!("deno {}", crate::version::deno())),
+      timestamp,
+    )?;
+    writeln!(xml, "  <sources>")?;
+    writeln!(
+      xml,
+      "    <source>{}</source>",
+      escape_xml(&self.cwd.to_string_lossy())
+    )?;
+    writeln!(xml, "  </sources>")?;
+    writeln!(xml, "  <packages>")?;
+    for (package, classes) in &self.packages {
+      let mut stats = CoverageStats::default();
+      for (class_stats, _) in classes {
+        stats += *class_stats;
+      }
+      writeln!(
+        xml,
+        r#"    <package name="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+        escape_xml(package),
+        format_rate(&stats.lines),
+        format_rate(&stats.branches),
+      )?;
+      writeln!(xml, "      <classes>")?;
+      for (_, class) in classes {
+        xml.push_str(class);
+      }
+      writeln!(xml, "      </classes>")?;
+      writeln!(xml, "    </package>")?;
+    }
+    writeln!(xml, "  </packages>")?;
+    writeln!(xml, "</coverage>")?;
+
+    write_report(&self.output, &xml)
+  }
+}
+
+/// Formats the covered fraction as a number from 0 to 1, as used by
+/// Cobertura.
+fn format_rate(count: &CoverageCount) -> String {
+  format!("{:.4}", count.percent() / 100.0)
+}
+
+/// Writes a JSON document with the coverage of every file and in total, and
+/// the lines, functions and branches that weren't covered.
+struct JsonCoverageReporter {
+  output: Option<PathBuf>,
+  cwd: PathBuf,
+  files: Vec<JsonFileCoverage>,
+}
+
+#[derive(Serialize)]
+struct JsonCoverageSummary<'a> {
+  total: JsonCoverageStats,
+  files: &'a [JsonFileCoverage],
+}
+
+#[derive(Serialize)]
+struct JsonCoverageStats {
+  lines: JsonCoverageCount,
+  branches: JsonCoverageCount,
+  functions: JsonCoverageCount,
+}
+
+#[derive(Serialize)]
+struct JsonCoverageCount {
+  covered: usize,
+  total: usize,
+  percent: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonFileCoverage {
+  url: String,
+  path: String,
+  #[serde(flatten)]
+  stats: JsonCoverageStats,
+  #[serde(skip)]
+  raw_stats: CoverageStats,
+  uncovered_lines: Vec<usize>,
+  uncovered_functions: Vec<JsonUncoveredFunction>,
+  uncovered_branches: Vec<JsonUncoveredBranch>,
+}
+
+#[derive(Serialize)]
+struct JsonUncoveredFunction {
+  name: String,
+  line: usize,
+}
+
+#[derive(Serialize)]
+struct JsonUncoveredBranch {
+  line: usize,
+  block: usize,
+  branch: usize,
+}
+
+impl From<CoverageStats> for JsonCoverageStats {
+  fn from(stats: CoverageStats) -> Self {
+    JsonCoverageStats {
+      lines: stats.lines.into(),
+      branches: stats.branches.into(),
+      functions: stats.functions.into(),
+    }
+  }
+}
+
+impl From<CoverageCount> for JsonCoverageCount {
+  fn from(count: CoverageCount) -> Self {
+    JsonCoverageCount {
+      covered: count.hit,
+      total: count.found,
+      percent: (count.percent() * 1000.0).round() / 1000.0,
+    }
+  }
+}
+
+impl JsonCoverageReporter {
+  pub fn new(output: Option<PathBuf>, cwd: &Path) -> JsonCoverageReporter {
+    JsonCoverageReporter {
+      output,
+      cwd: cwd.to_path_buf(),
+      files: vec![],
+    }
+  }
+}
+
+impl CoverageReporter for JsonCoverageReporter {
+  fn report(
+    &mut self,
+    coverage_report: &CoverageReport,
+    _file_text: &str,
+  ) -> Result<(), AnyError> {
+    let stats = coverage_report.stats();
+    self.files.push(JsonFileCoverage {
+      url: coverage_report.url.to_string(),
+      path: report_path(&coverage_report.url, &self.cwd),
+      stats: stats.into(),
+      raw_stats: stats,
+      uncovered_lines: coverage_report
+        .found_lines
+        .iter()
+        .filter(|(_, count)| *count == 0)
+        .map(|(index, _)| index + 1)
+        .collect(),
+      uncovered_functions: coverage_report
+        .named_functions
+        .iter()
+        .filter(|function| function.execution_count == 0)
+        .map(|function| JsonUncoveredFunction {
+          name: function.name.clone(),
+          line: function.line_index + 1,
+        })
+        .collect(),
+      uncovered_branches: coverage_report
+        .branches
+        .iter()
+        .filter(|branch| !branch.is_hit)
+        .map(|branch| JsonUncoveredBranch {
+          line: branch.line_index + 1,
+          block: branch.block_number,
+          branch: branch.branch_number,
+        })
+        .collect(),
+    });
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    let mut total = CoverageStats::default();
+    for file in &self.files {
+      total += file.raw_stats;
+    }
+    let summary = JsonCoverageSummary {
+      total: total.into(),
+      files: &self.files,
+    };
+    let mut json = serde_json::to_string_pretty(&summary)?;
+    json.push('\n');
+    write_report(&self.output, &json)
+  }
 }
 
 fn collect_coverages(
@@ -619,6 +1070,13 @@ pub async fn cover_files(
   flags: Flags,
   coverage_flags: CoverageFlags,
 ) -> Result<(), AnyError> {
+  if coverage_flags.reporter == CoverageReporterConfig::Pretty
+    && coverage_flags.output.is_some()
+  {
+    return Err(generic_error(
+      "--output is not supported by the pretty coverage reporter. Use --reporter=lcov, html, cobertura or json.",
+    ));
+  }
   if coverage_flags.files.include.is_empty() {
     return Err(generic_error("No matching coverage profiles found"));
   }
@@ -629,6 +1087,15 @@ pub async fn cover_files(
   let cli_options = factory.cli_options();
   let emitter = factory.emitter()?;
 
+  // The HTML report is written next to the coverage profiles by default.
+  let first_profile = cli_options
+    .initial_cwd()
+    .join(&coverage_flags.files.include[0]);
+  let profile_dir = if first_profile.is_file() {
+    first_profile.parent().unwrap().to_path_buf()
+  } else {
+    first_profile
+  };
   let script_coverages = collect_coverages(coverage_flags.files)?;
   let script_coverages = filter_coverages(
     script_coverages,
@@ -649,22 +1116,41 @@ pub async fn cover_files(
     vec![]
   };
 
-  let reporter_kind = if coverage_flags.lcov {
-    CoverageReporterKind::Lcov
-  } else {
-    CoverageReporterKind::Pretty
+  let reporter_kind = match coverage_flags.reporter {
+    CoverageReporterConfig::Pretty => CoverageReporterKind::Pretty,
+    CoverageReporterConfig::Lcov => CoverageReporterKind::Lcov,
+    CoverageReporterConfig::Html => CoverageReporterKind::Html(
+      coverage_flags
+        .output
+        .clone()
+        .unwrap_or_else(|| profile_dir.join("html")),
+    ),
+    CoverageReporterConfig::Cobertura => {
+      CoverageReporterKind::Cobertura(coverage_flags.output.clone())
+    }
+    CoverageReporterConfig::Json => {
+      CoverageReporterKind::Json(coverage_flags.output.clone())
+    }
   };
 
-  let mut reporter = create_reporter(reporter_kind);
+  let thresholds =
+    cli_options.resolve_coverage_thresholds(coverage_flags.thresholds)?;
+  let mut reporter = create_reporter(reporter_kind, cli_options.initial_cwd());
+  let mut file_stats = vec![];
 
   let out_mode = match coverage_flags.output {
-    Some(ref path) => match File::create(path) {
-      Ok(_) => Some(PathBuf::from(path)),
-      Err(e) => {
-        return Err(anyhow!("Failed to create output file: {}", e));
+    // Create the output file up front, to fail early if that isn't possible.
+    Some(ref path)
+      if coverage_flags.reporter != CoverageReporterConfig::Html =>
+    {
+      match File::create(path) {
+        Ok(_) => Some(PathBuf::from(path)),
+        Err(e) => {
+          return Err(anyhow!("Failed to create output file: {}", e));
+        }
       }
-    },
-    None => None,
+    }
+    _ => None,
   };
 
   for script_coverage in script_coverages {
@@ -729,10 +1215,11 @@ pub async fn cover_files(
 
     if !coverage_report.found_lines.is_empty() {
       reporter.report(&coverage_report, &original_source)?;
+      file_stats.push((coverage_report.url.clone(), coverage_report.stats()));
     }
   }
 
-  reporter.done();
+  reporter.done()?;
 
-  Ok(())
+  thresholds::check_thresholds(&thresholds, &file_stats)
 }
@@ -0,0 +1,168 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_ast::ModuleSpecifier;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+
+use super::CoverageCount;
+use super::CoverageStats;
+use crate::args::CoverageThresholds;
+
+/// Fails if the total coverage, or the coverage of any single file, is below
+/// one of the `thresholds`. The error lists every number that is too low.
+pub fn check_thresholds(
+  thresholds: &CoverageThresholds,
+  file_stats: &[(ModuleSpecifier, CoverageStats)],
+) -> Result<(), AnyError> {
+  if thresholds.is_empty() {
+    return Ok(());
+  }
+
+  let mut total = CoverageStats::default();
+  for (_, stats) in file_stats {
+    total += *stats;
+  }
+
+  let mut violations = find_violations(thresholds, &total)
+    .into_iter()
+    .map(|violation| format!("  {violation} in total"))
+    .collect::<Vec<_>>();
+  for (url, stats) in file_stats {
+    violations.extend(
+      find_violations(thresholds, stats)
+        .into_iter()
+        .map(|violation| format!("  {violation} in {url}")),
+    );
+  }
+
+  if violations.is_empty() {
+    Ok(())
+  } else {
+    Err(generic_error(format!(
+      "Coverage is below the configured thresholds:\n{}",
+      violations.join("\n")
+    )))
+  }
+}
+
+fn find_violations(
+  thresholds: &CoverageThresholds,
+  stats: &CoverageStats,
+) -> Vec<String> {
+  [
+    ("lines", thresholds.lines, stats.lines),
+    ("branches", thresholds.branches, stats.branches),
+    ("functions", thresholds.functions, stats.functions),
+  ]
+  .into_iter()
+  .filter_map(|(name, threshold, count)| {
+    let threshold = threshold?;
+    is_below(&count, threshold).then(|| {
+      format!(
+        "{}: {:.3}% ({}/{}) is below {}%",
+        name,
+        count.percent(),
+        count.hit,
+        count.found,
+        threshold
+      )
+    })
+  })
+  .collect()
+}
+
+fn is_below(count: &CoverageCount, threshold: u8) -> bool {
+  // Compare the counts directly, so that e.g. 2 out of 3 lines don't fail a
+  // threshold of 66% because of rounding.
+  count.hit * 100 < count.found * threshold as usize
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn count(hit: usize, found: usize) -> CoverageCount {
+    CoverageCount { hit, found }
+  }
+
+  fn stats(lines: (usize, usize), branches: (usize, usize)) -> CoverageStats {
+    CoverageStats {
+      lines: count(lines.0, lines.1),
+      branches: count(branches.0, branches.1),
+      functions: count(0, 0),
+    }
+  }
+
+  #[test]
+  fn no_thresholds() {
+    let file_stats = vec![(
+      ModuleSpecifier::parse("file:///a.ts").unwrap(),
+      stats((0, 10), (0, 2)),
+    )];
+    assert!(
+      check_thresholds(&CoverageThresholds::default(), &file_stats).is_ok()
+    );
+  }
+
+  #[test]
+  fn thresholds_met() {
+    let thresholds = CoverageThresholds {
+      lines: Some(66),
+      branches: Some(50),
+      functions: Some(100),
+    };
+    let file_stats = vec![
+      (
+        ModuleSpecifier::parse("file:///a.ts").unwrap(),
+        stats((2, 3), (1, 2)),
+      ),
+      (
+        ModuleSpecifier::parse("file:///b.ts").unwrap(),
+        stats((10, 10), (0, 0)),
+      ),
+    ];
+    assert!(check_thresholds(&thresholds, &file_stats).is_ok());
+  }
+
+  #[test]
+  fn thresholds_not_met() {
+    let thresholds = CoverageThresholds {
+      lines: Some(80),
+      branches: None,
+      functions: None,
+    };
+    let file_stats = vec![
+      (
+        ModuleSpecifier::parse("file:///a.ts").unwrap(),
+        stats((1, 4), (0, 2)),
+      ),
+      (
+        ModuleSpecifier::parse("file:///b.ts").unwrap(),
+        stats((16, 16), (0, 0)),
+      ),
+    ];
+    let err = check_thresholds(&thresholds, &file_stats).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      concat!(
+        "Coverage is below the configured thresholds:\n",
+        "  lines: 25.000% (1/4) is below 80% in file:///a.ts"
+      )
+    );
+
+    let thresholds = CoverageThresholds {
+      lines: Some(90),
+      branches: None,
+      functions: None,
+    };
+    let err = check_thresholds(&thresholds, &file_stats).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      concat!(
+        "Coverage is below the configured thresholds:\n",
+        "  lines: 85.000% (17/20) is below 90% in total\n",
+        "  lines: 25.000% (1/4) is below 90% in file:///a.ts"
+      )
+    );
+  }
+}
@@ -0,0 +1,651 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::Path;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::located_script_name;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::v8;
+use deno_core::JsRuntime;
+use deno_runtime::deno_kv::codec::decode_key;
+use deno_runtime::deno_kv::codec::encode_key;
+use deno_runtime::deno_kv::sqlite::DumpedKvEntry;
+use deno_runtime::deno_kv::sqlite::SqliteDb;
+use deno_runtime::deno_kv::AtomicWrite;
+use deno_runtime::deno_kv::Consistency;
+use deno_runtime::deno_kv::Database;
+use deno_runtime::deno_kv::Key;
+use deno_runtime::deno_kv::KeyPart;
+use deno_runtime::deno_kv::KvEntry;
+use deno_runtime::deno_kv::KvMutation;
+use deno_runtime::deno_kv::MutationKind;
+use deno_runtime::deno_kv::ReadRange;
+use deno_runtime::deno_kv::SnapshotReadOptions;
+use deno_runtime::deno_kv::Value;
+use rusqlite::OpenFlags;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::args::Flags;
+use crate::args::KvFlags;
+use crate::args::KvSubcommand;
+use crate::util::display;
+
+/// The number of entries read from the database at a time when listing.
+const LIST_BATCH_SIZE: usize = 500;
+
+pub async fn kv(flags: Flags, kv_flags: KvFlags) -> Result<(), AnyError> {
+  if !flags.unstable {
+    bail!(
+      "Unstable subcommand 'deno kv'. The --unstable flag must be provided."
+    );
+  }
+
+  let KvFlags { path, subcommand } = kv_flags;
+  let read_only = matches!(
+    subcommand,
+    KvSubcommand::List { .. }
+      | KvSubcommand::Get { .. }
+      | KvSubcommand::Dump { .. }
+  );
+  let create = matches!(
+    subcommand,
+    KvSubcommand::Set { .. } | KvSubcommand::Restore { .. }
+  );
+  let db = open_db(&path, read_only, create)?;
+
+  match subcommand {
+    KvSubcommand::List {
+      prefix,
+      limit,
+      reverse,
+    } => {
+      let prefix = match prefix {
+        Some(prefix) => parse_key(&prefix)?,
+        None => Key(vec![]),
+      };
+      list(&db, &prefix, limit, reverse).await
+    }
+    KvSubcommand::Get { key } => get(&db, &parse_key(&key)?).await,
+    KvSubcommand::Set { key, value } => {
+      set(&db, &parse_key(&key)?, &value).await
+    }
+    KvSubcommand::Delete { key } => delete(&db, &parse_key(&key)?).await,
+    KvSubcommand::Dump { output } => dump(&db, output.as_deref()),
+    KvSubcommand::Restore { input } => restore(&db, input.as_deref()),
+  }
+}
+
+/// Opens the database at `path`. Databases opened read-only are neither
+/// migrated nor garbage-collected, so that inspecting a database never
+/// modifies it.
+fn open_db(
+  path: &Path,
+  read_only: bool,
+  create: bool,
+) -> Result<SqliteDb, AnyError> {
+  let flags = if read_only {
+    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX
+  } else if create {
+    OpenFlags::default().difference(OpenFlags::SQLITE_OPEN_URI)
+  } else {
+    OpenFlags::default()
+      .difference(OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_CREATE)
+  };
+  let conn = rusqlite::Connection::open_with_flags(path, flags)
+    .with_context(|| format!("Failed to open database: {}", path.display()))?;
+  if read_only {
+    SqliteDb::new_read_only(conn)
+  } else {
+    SqliteDb::new(conn)
+  }
+}
+
+async fn list(
+  db: &SqliteDb,
+  prefix: &Key,
+  limit: Option<usize>,
+  reverse: bool,
+) -> Result<(), AnyError> {
+  let prefix = encode_key(prefix)?;
+  let mut start = prefix.iter().copied().chain(Some(0)).collect::<Vec<_>>();
+  let mut end = prefix.iter().copied().chain(Some(0xff)).collect::<Vec<_>>();
+  let mut remaining = limit.unwrap_or(usize::MAX);
+  let mut formatter = ValueFormatter::new()?;
+
+  while remaining > 0 {
+    let batch_size = remaining.min(LIST_BATCH_SIZE);
+    let entries = read_range(
+      db,
+      ReadRange {
+        start: start.clone(),
+        end: end.clone(),
+        limit: NonZeroU32::new(batch_size as u32).unwrap(),
+        reverse,
+      },
+    )
+    .await?;
+    let Some(last) = entries.last() else {
+      break;
+    };
+    if reverse {
+      end = last.key.clone();
+    } else {
+      start = last.key.iter().copied().chain(Some(0)).collect();
+    }
+    remaining -= entries.len();
+    let is_last_batch = entries.len() < batch_size;
+
+    for entry in &entries {
+      print_entry(&mut formatter, entry)?;
+    }
+    if is_last_batch {
+      break;
+    }
+  }
+
+  Ok(())
+}
+
+async fn get(db: &SqliteDb, key: &Key) -> Result<(), AnyError> {
+  let encoded_key = encode_key(key)?;
+  let entries = read_range(
+    db,
+    ReadRange {
+      start: encoded_key.clone(),
+      end: encoded_key.into_iter().chain(Some(0)).collect(),
+      limit: NonZeroU32::new(1).unwrap(),
+      reverse: false,
+    },
+  )
+  .await?;
+  let Some(entry) = entries.first() else {
+    bail!("Key not found: {}", key_to_json(key));
+  };
+  print_entry(&mut ValueFormatter::new()?, entry)
+}
+
+async fn set(db: &SqliteDb, key: &Key, value: &str) -> Result<(), AnyError> {
+  serde_json::from_str::<serde_json::Value>(value)
+    .with_context(|| format!("Invalid value: {value}"))?;
+  let value =
+    serialize_json_value(&mut JsRuntime::new(Default::default()), value)?;
+  write_mutation(db, key, MutationKind::Set(Value::V8(value))).await
+}
+
+async fn delete(db: &SqliteDb, key: &Key) -> Result<(), AnyError> {
+  write_mutation(db, key, MutationKind::Delete).await
+}
+
+async fn read_range(
+  db: &SqliteDb,
+  range: ReadRange,
+) -> Result<Vec<KvEntry>, AnyError> {
+  let options = SnapshotReadOptions {
+    consistency: Consistency::Strong,
+    at_versionstamp: None,
+  };
+  let mut outputs = db.snapshot_read(vec![range], options).await?;
+  Ok(outputs.remove(0).entries)
+}
+
+async fn write_mutation(
+  db: &SqliteDb,
+  key: &Key,
+  kind: MutationKind,
+) -> Result<(), AnyError> {
+  let write = AtomicWrite {
+    checks: vec![],
+    mutations: vec![KvMutation {
+      key: encode_key(key)?,
+      kind,
+      expire_at: None,
+    }],
+    enqueues: vec![],
+  };
+  db.atomic_write(write).await?;
+  Ok(())
+}
+
+fn print_entry(
+  formatter: &mut ValueFormatter,
+  entry: &KvEntry,
+) -> Result<(), AnyError> {
+  let key = key_to_json(&decode_key(&entry.key)?);
+  let value = formatter.format(&entry.value)?;
+  let versionstamp = hex::encode(entry.versionstamp);
+  let text =
+    format!("{key}\n  value: {value}\n  versionstamp: {versionstamp}\n");
+  display::write_to_stdout_ignore_sigpipe(text.as_bytes())?;
+  Ok(())
+}
+
+fn dump(db: &SqliteDb, output: Option<&Path>) -> Result<(), AnyError> {
+  let mut writer: Box<dyn Write> = match output {
+    Some(path) => {
+      Box::new(BufWriter::new(File::create(path).with_context(|| {
+        format!("Failed to create: {}", path.display())
+      })?))
+    }
+    None => Box::new(BufWriter::new(std::io::stdout().lock())),
+  };
+  db.dump(|entry| {
+    let line = serde_json::to_string(&DumpLine::from_entry(entry)?)?;
+    writeln!(writer, "{line}")?;
+    Ok(())
+  })?;
+  writer.flush()?;
+  Ok(())
+}
+
+fn restore(db: &SqliteDb, input: Option<&Path>) -> Result<(), AnyError> {
+  let reader: Box<dyn BufRead> = match input {
+    Some(path) => Box::new(BufReader::new(
+      File::open(path)
+        .with_context(|| format!("Failed to open: {}", path.display()))?,
+    )),
+    None => Box::new(std::io::stdin().lock()),
+  };
+  let mut count = 0;
+  let entries = reader.lines().enumerate().filter_map(|(i, line)| {
+    let line = match line {
+      Ok(line) => line,
+      Err(err) => return Some(Err(err.into())),
+    };
+    if line.trim().is_empty() {
+      return None;
+    }
+    count += 1;
+    Some(
+      serde_json::from_str::<DumpLine>(&line)
+        .map_err(AnyError::from)
+        .and_then(DumpLine::into_entry)
+        .with_context(|| format!("Invalid entry on line {}", i + 1)),
+    )
+  });
+  db.restore(entries)?;
+  log::info!("Restored {} entries.", count);
+  Ok(())
+}
+
+/// A single entry of a dump, which is written as one line of JSON.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DumpLine {
+  key: serde_json::Value,
+  value: DumpValue,
+  versionstamp: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  expire_at: Option<u64>,
+}
+
+/// A value in a dump, in its raw stored form so that it round-trips exactly.
+/// Binary data is base64 encoded, and u64 values are decimal strings.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+enum DumpValue {
+  V8(String),
+  Bytes(String),
+  U64(String),
+}
+
+impl DumpLine {
+  fn from_entry(entry: DumpedKvEntry) -> Result<Self, AnyError> {
+    Ok(Self {
+      key: key_to_json(&decode_key(&entry.key)?),
+      value: match entry.value {
+        Value::V8(buf) => DumpValue::V8(base64::encode(buf)),
+        Value::Bytes(buf) => DumpValue::Bytes(base64::encode(buf)),
+        Value::U64(n) => DumpValue::U64(n.to_string()),
+      },
+      versionstamp: hex::encode(entry.versionstamp),
+      expire_at: entry.expire_at,
+    })
+  }
+
+  fn into_entry(self) -> Result<DumpedKvEntry, AnyError> {
+    let mut versionstamp = [0; 10];
+    hex::decode_to_slice(&self.versionstamp, &mut versionstamp).with_context(
+      || format!("Invalid versionstamp: {}", self.versionstamp),
+    )?;
+    Ok(DumpedKvEntry {
+      key: encode_key(&key_from_json(self.key)?)?,
+      value: match self.value {
+        DumpValue::V8(data) => Value::V8(base64::decode(data)?),
+        DumpValue::Bytes(data) => Value::Bytes(base64::decode(data)?),
+        DumpValue::U64(n) => Value::U64(n.parse()?),
+      },
+      versionstamp,
+      expire_at: self.expire_at,
+    })
+  }
+}
+
+/// Parses a key given on the command line. Keys are JSON arrays, using the
+/// representation described in [key_to_json].
+fn parse_key(text: &str) -> Result<Key, AnyError> {
+  let value = serde_json::from_str(text)
+    .with_context(|| format!("Invalid key: {text}"))?;
+  key_from_json(value)
+}
+
+fn key_from_json(value: serde_json::Value) -> Result<Key, AnyError> {
+  let serde_json::Value::Array(parts) = value else {
+    bail!("Invalid key: {value} (expected an array)");
+  };
+  let parts = parts
+    .into_iter()
+    .map(key_part_from_json)
+    .collect::<Result<_, _>>()?;
+  Ok(Key(parts))
+}
+
+fn key_part_from_json(value: serde_json::Value) -> Result<KeyPart, AnyError> {
+  let part = match &value {
+    serde_json::Value::String(s) => KeyPart::String(s.clone()),
+    serde_json::Value::Number(n) => KeyPart::Float(n.as_f64().unwrap()),
+    serde_json::Value::Bool(true) => KeyPart::True,
+    serde_json::Value::Bool(false) => KeyPart::False,
+    serde_json::Value::Object(map) if map.len() == 1 => {
+      let (tag, inner) = map.iter().next().unwrap();
+      match (tag.as_str(), inner) {
+        ("bigint", serde_json::Value::String(n)) => match n.parse() {
+          Ok(n) => KeyPart::Int(n),
+          Err(_) => bail!("Invalid key part: {value}"),
+        },
+        ("bytes", serde_json::Value::Array(bytes)) => {
+          match bytes
+            .iter()
+            .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+            .collect::<Option<Vec<_>>>()
+          {
+            Some(bytes) => KeyPart::Bytes(bytes),
+            None => bail!("Invalid key part: {value}"),
+          }
+        }
+        ("number", serde_json::Value::String(n)) => match n.as_str() {
+          "NaN" => KeyPart::Float(f64::NAN),
+          "Infinity" => KeyPart::Float(f64::INFINITY),
+          "-Infinity" => KeyPart::Float(f64::NEG_INFINITY),
+          _ => bail!("Invalid key part: {value}"),
+        },
+        _ => bail!("Invalid key part: {value}"),
+      }
+    }
+    _ => bail!("Invalid key part: {value}"),
+  };
+  Ok(part)
+}
+
+/// Converts a key to JSON. Strings, finite numbers and booleans are
+/// represented as themselves, while the key parts that have no JSON
+/// equivalent are represented as single-property objects:
+/// `{"bigint": "1"}`, `{"bytes": [1, 2]}` and `{"number": "NaN"}`.
+fn key_to_json(key: &Key) -> serde_json::Value {
+  serde_json::Value::Array(key.0.iter().map(key_part_to_json).collect())
+}
+
+fn key_part_to_json(part: &KeyPart) -> serde_json::Value {
+  match part {
+    KeyPart::String(s) => json!(s),
+    KeyPart::Float(n) => match serde_json::Number::from_f64(*n) {
+      Some(n) => serde_json::Value::Number(n),
+      None if n.is_nan() => json!({ "number": "NaN" }),
+      None if *n > 0.0 => json!({ "number": "Infinity" }),
+      None => json!({ "number": "-Infinity" }),
+    },
+    KeyPart::Int(n) => json!({ "bigint": n.to_string() }),
+    KeyPart::Bytes(bytes) => json!({ "bytes": bytes }),
+    KeyPart::True => json!(true),
+    KeyPart::False => json!(false),
+  }
+}
+
+/// Converts a deserialized value to JSON, using the notation of [key_to_json]
+/// for the values that have no JSON equivalent. Maps, sets, dates and byte
+/// arrays are written as `{"map": [[key, value]]}`, `{"set": [value]}`,
+/// `{"date": "..."}` and `{"bytes": [1, 2]}`, `undefined` as `null` and
+/// circular references as `{"circular": true}`. Other objects are converted
+/// the way `JSON.stringify` converts them, so the conversion is lossy.
+const VALUE_TO_JSON_SRC: &str = r#"(value) => {
+  const ancestors = [];
+  return JSON.stringify(value, function (key) {
+    const value = this[key];
+    if (typeof value === "bigint") {
+      return { bigint: value.toString() };
+    }
+    if (typeof value === "number" && !Number.isFinite(value)) {
+      return { number: String(value) };
+    }
+    if (value === undefined) {
+      return null;
+    }
+    if (typeof value !== "object" || value === null) {
+      return value;
+    }
+    while (
+      ancestors.length > 0 &&
+      ancestors[ancestors.length - 1].replacement !== this
+    ) {
+      ancestors.pop();
+    }
+    if (ancestors.some((ancestor) => ancestor.value === value)) {
+      return { circular: true };
+    }
+    let replacement = value;
+    if (value instanceof Map) {
+      replacement = { map: [...value] };
+    } else if (value instanceof Set) {
+      replacement = { set: [...value] };
+    } else if (value instanceof Date) {
+      replacement = { date: isNaN(value) ? null : value.toISOString() };
+    } else if (ArrayBuffer.isView(value)) {
+      const bytes =
+        new Uint8Array(value.buffer, value.byteOffset, value.byteLength);
+      replacement = { bytes: [...bytes] };
+    } else if (value instanceof ArrayBuffer) {
+      replacement = { bytes: [...new Uint8Array(value)] };
+    }
+    ancestors.push({ value, replacement });
+    return replacement;
+  });
+}"#;
+
+/// Formats the values of entries for display, as JSON.
+struct ValueFormatter {
+  runtime: JsRuntime,
+  to_json: v8::Global<v8::Function>,
+}
+
+impl ValueFormatter {
+  fn new() -> Result<Self, AnyError> {
+    let mut runtime = JsRuntime::new(Default::default());
+    let to_json = runtime
+      .execute_script_static(located_script_name!(), VALUE_TO_JSON_SRC)?;
+    let to_json = {
+      let scope = &mut runtime.handle_scope();
+      let to_json = v8::Local::new(scope, to_json);
+      let to_json = v8::Local::<v8::Function>::try_from(to_json)?;
+      v8::Global::new(scope, to_json)
+    };
+    Ok(Self { runtime, to_json })
+  }
+
+  fn format(&mut self, value: &Value) -> Result<String, AnyError> {
+    Ok(match value {
+      Value::V8(buf) => {
+        let scope = &mut self.runtime.handle_scope();
+        let value = deserialize_v8_value(scope, buf)?;
+        let to_json = v8::Local::new(scope, &self.to_json);
+        let undefined = v8::undefined(scope).into();
+        let tc_scope = &mut v8::TryCatch::new(scope);
+        let Some(json) = to_json.call(tc_scope, undefined, &[value]) else {
+          bail!("Failed to format value");
+        };
+        json.to_rust_string_lossy(tc_scope)
+      }
+      Value::Bytes(bytes) => json!({ "bytes": bytes }).to_string(),
+      Value::U64(n) => json!({ "u64": n.to_string() }).to_string(),
+    })
+  }
+}
+
+fn deserialize_v8_value<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  buf: &[u8],
+) -> Result<v8::Local<'s, v8::Value>, AnyError> {
+  let mut deserializer =
+    v8::ValueDeserializer::new(scope, Box::new(SerializerDelegate), buf);
+  let context = scope.get_current_context();
+  if !deserializer.read_header(context).unwrap_or_default() {
+    bail!("Failed to deserialize value");
+  }
+  match deserializer.read_value(context) {
+    Some(value) => Ok(value),
+    None => bail!("Failed to deserialize value"),
+  }
+}
+
+/// Serializes a JSON text the same way `Deno.Kv` serializes JavaScript
+/// values, so that it reads back as the result of `JSON.parse`.
+fn serialize_json_value(
+  runtime: &mut JsRuntime,
+  json: &str,
+) -> Result<Vec<u8>, AnyError> {
+  let scope = &mut runtime.handle_scope();
+  let json = v8::String::new(scope, json).unwrap();
+  let Some(value) = v8::json::parse(scope, json) else {
+    bail!("Invalid value: {}", json.to_rust_string_lossy(scope));
+  };
+  let mut serializer =
+    v8::ValueSerializer::new(scope, Box::new(SerializerDelegate));
+  serializer.write_header();
+  let context = scope.get_current_context();
+  if serializer.write_value(context, value) != Some(true) {
+    bail!("Failed to serialize value");
+  }
+  Ok(serializer.release())
+}
+
+/// Host objects, shared array buffers and wasm modules can not be stored in
+/// the database, so there is nothing for the delegate to do beyond reporting
+/// errors.
+struct SerializerDelegate;
+
+impl v8::ValueSerializerImpl for SerializerDelegate {
+  fn throw_data_clone_error<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    message: v8::Local<'s, v8::String>,
+  ) {
+    let error = v8::Exception::type_error(scope, message);
+    scope.throw_exception(error);
+  }
+}
+
+impl v8::ValueDeserializerImpl for SerializerDelegate {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn key_json_round_trip() {
+    let key = parse_key(
+      r#"["users", 1.5, -2, true, false, {"bigint": "-12345678901234567890"}, {"bytes": [0, 255]}, {"number": "-Infinity"}]"#,
+    )
+    .unwrap();
+    assert_eq!(
+      key,
+      Key(vec![
+        KeyPart::String("users".to_string()),
+        KeyPart::Float(1.5),
+        KeyPart::Float(-2.0),
+        KeyPart::True,
+        KeyPart::False,
+        KeyPart::Int("-12345678901234567890".parse().unwrap()),
+        KeyPart::Bytes(vec![0, 255]),
+        KeyPart::Float(f64::NEG_INFINITY),
+      ])
+    );
+    assert_eq!(parse_key(&key_to_json(&key).to_string()).unwrap(), key);
+
+    let key = Key(vec![KeyPart::Float(f64::NAN)]);
+    assert_eq!(key_to_json(&key), json!([{ "number": "NaN" }]));
+    assert_eq!(parse_key(&key_to_json(&key).to_string()).unwrap(), key);
+  }
+
+  #[test]
+  fn parse_invalid_key() {
+    assert!(parse_key("users").is_err());
+    assert!(parse_key(r#""users""#).is_err());
+    assert!(parse_key(r#"[null]"#).is_err());
+    assert!(parse_key(r#"[{"bytes": [256]}]"#).is_err());
+    assert!(parse_key(r#"[{"bigint": "1.5"}]"#).is_err());
+    assert!(parse_key(r#"[{"bigint": "1", "bytes": []}]"#).is_err());
+  }
+
+  #[test]
+  fn format_values() {
+    let mut formatter = ValueFormatter::new().unwrap();
+    let buf = serialize_json_value(
+      &mut JsRuntime::new(Default::default()),
+      r#"{"name": "Alice", "tags": ["a", "b"], "empty": {}, "nested": {"n": 1.5}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+      formatter.format(&Value::V8(buf)).unwrap(),
+      r#"{"name":"Alice","tags":["a","b"],"empty":{},"nested":{"n":1.5}}"#
+    );
+    assert_eq!(
+      formatter.format(&Value::Bytes(vec![1, 2, 3])).unwrap(),
+      r#"{"bytes":[1,2,3]}"#
+    );
+    assert_eq!(
+      formatter.format(&Value::U64(42)).unwrap(),
+      r#"{"u64":"42"}"#
+    );
+  }
+
+  #[test]
+  fn format_non_json_values() {
+    let mut formatter = ValueFormatter::new().unwrap();
+    let mut format = |script: &'static str| {
+      let mut runtime = JsRuntime::new(Default::default());
+      let value = runtime
+        .execute_script_static(located_script_name!(), script)
+        .unwrap();
+      let scope = &mut runtime.handle_scope();
+      let value = v8::Local::new(scope, value);
+      let mut serializer =
+        v8::ValueSerializer::new(scope, Box::new(SerializerDelegate));
+      serializer.write_header();
+      let context = scope.get_current_context();
+      serializer.write_value(context, value).unwrap();
+      formatter.format(&Value::V8(serializer.release())).unwrap()
+    };
+
+    assert_eq!(
+      format("({ a: new Map([[1n, new Set([NaN, undefined])]]) })"),
+      r#"{"a":{"map":[[{"bigint":"1"},{"set":[{"number":"NaN"},null]}]]}}"#
+    );
+    assert_eq!(
+      format("[new Date(0), new Uint8Array([1, 2]), 'a']"),
+      r#"[{"date":"1970-01-01T00:00:00.000Z"},{"bytes":[1,2]},"a"]"#
+    );
+    assert_eq!(
+      format("const a = { b: {} }; a.b.a = a; a.c = a.b; a"),
+      r#"{"b":{"a":{"circular":true}},"c":{"a":{"circular":true}}}"#
+    );
+    assert_eq!(format("undefined"), "null");
+  }
+}
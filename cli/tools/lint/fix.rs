@@ -0,0 +1,193 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Automatic fixes for lint diagnostics.
+//!
+//! Diagnostics from `deno_lint` don't carry text edits, so the fixes are
+//! derived from the code of the rule and the source text the diagnostic
+//! points at. A fix is only offered when the text matches exactly what the
+//! rule reports, which keeps them safe to apply without a review.
+
+use std::ops::Range;
+
+use deno_ast::LineAndColumnIndex;
+use deno_ast::SourceTextInfo;
+use deno_lint::diagnostic::LintDiagnostic;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFix {
+  pub description: String,
+  pub changes: Vec<LintFixChange>,
+}
+
+/// Replaces the bytes of `range` in the source text with `new_text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFixChange {
+  pub range: Range<usize>,
+  pub new_text: String,
+}
+
+impl LintFixChange {
+  fn new(range: Range<usize>, new_text: impl Into<String>) -> Self {
+    Self {
+      range,
+      new_text: new_text.into(),
+    }
+  }
+}
+
+/// Gets the fix for a diagnostic of the rule `code` that spans `range`, if
+/// the rule has one.
+pub fn get_lint_fix(
+  code: &str,
+  text_info: &SourceTextInfo,
+  range: Range<LineAndColumnIndex>,
+) -> Option<LintFix> {
+  let start = text_info
+    .loc_to_source_pos(range.start)
+    .as_byte_index(text_info.range().start);
+  let end = text_info
+    .loc_to_source_pos(range.end)
+    .as_byte_index(text_info.range().start);
+  let text = text_info.text_str().get(start..end)?;
+  match code {
+    "no-extra-semi" if text == ";" => Some(LintFix {
+      description: "Remove extra semicolon".to_string(),
+      changes: vec![LintFixChange::new(start..end, "")],
+    }),
+    "no-window-prefix" => {
+      let rest = text.strip_prefix("window")?;
+      if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '$')
+      {
+        return None;
+      }
+      Some(LintFix {
+        description: "Replace `window` with `globalThis`".to_string(),
+        changes: vec![LintFixChange::new(start..start + 6, "globalThis")],
+      })
+    }
+    "prefer-namespace-keyword" => {
+      let offset = match text.strip_prefix("declare") {
+        Some(rest) if rest.starts_with(char::is_whitespace) => {
+          text.len() - rest.trim_start().len()
+        }
+        _ => 0,
+      };
+      let rest = text[offset..].strip_prefix("module")?;
+      if !rest.starts_with(char::is_whitespace) {
+        return None;
+      }
+      let start = start + offset;
+      Some(LintFix {
+        description: "Replace `module` with `namespace`".to_string(),
+        changes: vec![LintFixChange::new(start..start + 6, "namespace")],
+      })
+    }
+    _ => None,
+  }
+}
+
+/// Gets the fixes for all the diagnostics of a file that have one.
+pub fn get_lint_fixes(
+  diagnostics: &[LintDiagnostic],
+  text_info: &SourceTextInfo,
+) -> Vec<LintFix> {
+  diagnostics
+    .iter()
+    .filter_map(|d| {
+      get_lint_fix(
+        &d.code,
+        text_info,
+        LineAndColumnIndex {
+          line_index: d.range.start.line_index,
+          column_index: d.range.start.column_index,
+        }..LineAndColumnIndex {
+          line_index: d.range.end.line_index,
+          column_index: d.range.end.column_index,
+        },
+      )
+    })
+    .collect()
+}
+
+/// Applies the fixes to `text`. A fix that overlaps with one applied before
+/// is skipped, as it may no longer make sense. It can be applied by linting
+/// the result again.
+pub fn apply_lint_fixes(text: &str, fixes: Vec<LintFix>) -> String {
+  let mut changes: Vec<LintFixChange> = Vec::new();
+  for fix in fixes {
+    let overlaps = fix.changes.iter().any(|change| {
+      changes.iter().any(|applied| {
+        change.range.start < applied.range.end
+          && applied.range.start < change.range.end
+      })
+    });
+    if !overlaps {
+      changes.extend(fix.changes);
+    }
+  }
+  changes.sort_by_key(|change| change.range.start);
+
+  let mut result = String::with_capacity(text.len());
+  let mut last_end = 0;
+  for change in changes {
+    result.push_str(&text[last_end..change.range.start]);
+    result.push_str(&change.new_text);
+    last_end = change.range.end;
+  }
+  result.push_str(&text[last_end..]);
+  result
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::tools::lint::create_linter;
+  use deno_ast::MediaType;
+  use deno_lint::rules::get_recommended_rules;
+
+  fn fix(source: &str) -> String {
+    let linter = create_linter(MediaType::TypeScript, get_recommended_rules());
+    let (_, diagnostics) = linter
+      .lint("file:///a.ts".to_string(), source.to_string())
+      .unwrap();
+    let text_info = SourceTextInfo::from_string(source.to_string());
+    apply_lint_fixes(source, get_lint_fixes(&diagnostics, &text_info))
+  }
+
+  #[test]
+  fn fixes_recommended_rules() {
+    assert_eq!(fix("console.log(1);;\n"), "console.log(1);\n");
+    assert_eq!(
+      fix("await window.fetch(\"https://deno.land\");\n"),
+      "await globalThis.fetch(\"https://deno.land\");\n"
+    );
+    assert_eq!(
+      fix("declare module Foo {}\nmodule Bar {}\n"),
+      "declare namespace Foo {}\nnamespace Bar {}\n"
+    );
+    // strings are module names, not namespaces
+    assert_eq!(
+      fix("declare module \"foo\" {}\n"),
+      "declare module \"foo\" {}\n"
+    );
+  }
+
+  #[test]
+  fn skips_overlapping_fixes() {
+    let fixes = vec![
+      LintFix {
+        description: "a".to_string(),
+        changes: vec![LintFixChange::new(0..3, "a")],
+      },
+      LintFix {
+        description: "b".to_string(),
+        changes: vec![LintFixChange::new(2..4, "b")],
+      },
+      LintFix {
+        description: "c".to_string(),
+        changes: vec![LintFixChange::new(4..5, "c")],
+      },
+    ];
+    assert_eq!(apply_lint_fixes("012345", fixes), "a3c5");
+  }
+}
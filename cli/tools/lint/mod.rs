@@ -10,20 +10,33 @@ use crate::args::CliOptions;
 use crate::args::FilesConfig;
 use crate::args::LintOptions;
 use crate::args::LintReporterKind;
+use crate::args::LintRuleSeverity;
 use crate::args::LintRulesConfig;
 use crate::colors;
 use crate::factory::CliFactory;
 use crate::tools::fmt::run_parallelized;
 use crate::util::file_watcher;
 use crate::util::file_watcher::ResolutionResult;
+use crate::util::fs::atomic_write_file;
 use crate::util::fs::FileCollector;
 use crate::util::path::is_supported_ext;
+use crate::util::sarif::SarifColumnKind;
+use crate::util::sarif::SarifLevel;
+use crate::util::sarif::SarifLocation;
+use crate::util::sarif::SarifLog;
+use crate::util::sarif::SarifRegion;
+use crate::util::sarif::SarifResult;
+use crate::util::sarif::SarifRule;
+use crate::util::sarif::SarifRuleProperties;
+use crate::util::sarif::SarifRun;
 use deno_ast::MediaType;
+use deno_ast::SourceTextInfo;
 use deno_core::anyhow::bail;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::error::JsStackFrame;
 use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
 use deno_lint::diagnostic::LintDiagnostic;
 use deno_lint::linter::Linter;
 use deno_lint::linter::LinterBuilder;
@@ -33,25 +46,44 @@ use deno_runtime::fmt_errors::format_location;
 use log::debug;
 use log::info;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::fs;
 use std::io::stdin;
 use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 use crate::cache::IncrementalCache;
 
+mod fix;
+mod plugins;
+
+use fix::apply_lint_fixes;
+pub use fix::get_lint_fix;
+use fix::get_lint_fixes;
+pub use plugins::lint_with_plugins;
+pub use plugins::LintPluginHost;
+
 static STDIN_FILE_NAME: &str = "_stdin.ts";
 
-fn create_reporter(kind: LintReporterKind) -> Box<dyn LintReporter + Send> {
+/// Fixes can make new problems surface, for example when removing code, so
+/// files are linted again after fixing them. This limits how many times.
+const MAX_FIX_ITERATIONS: usize = 10;
+
+fn create_reporter(
+  kind: LintReporterKind,
+  lint_rules: &[&'static dyn LintRule],
+) -> Box<dyn LintReporter + Send> {
   match kind {
     LintReporterKind::Pretty => Box::new(PrettyLintReporter::new()),
     LintReporterKind::Json => Box::new(JsonLintReporter::new()),
     LintReporterKind::Compact => Box::new(CompactLintReporter::new()),
+    LintReporterKind::Sarif => Box::new(SarifLintReporter::new(lint_rules)),
   }
 }
 
@@ -60,14 +92,30 @@ pub async fn lint(
   lint_options: LintOptions,
 ) -> Result<(), AnyError> {
   // Try to get lint rules. If none were set use recommended rules.
-  let lint_rules = get_configured_rules(lint_options.rules);
+  let lint_rules = get_configured_rules(lint_options.rules.clone());
+  let plugin_host = if lint_options.plugins.is_empty() {
+    None
+  } else {
+    Some(Arc::new(LintPluginHost::new(
+      lint_options.plugins,
+      lint_options.rules.exclude.clone().unwrap_or_default(),
+    )?))
+  };
 
-  if lint_rules.is_empty() {
+  if lint_rules.is_empty()
+    && plugin_host
+      .as_ref()
+      .map(|host| host.rule_codes().is_empty())
+      .unwrap_or(true)
+  {
     bail!("No rules have been configured")
   }
 
   let files = lint_options.files;
   let reporter_kind = lint_options.reporter_kind;
+  let fix = lint_options.fix;
+  let max_warnings = lint_options.max_warnings;
+  let rules_config = Arc::new(lint_options.rules);
 
   let resolver = |changed: Option<Vec<PathBuf>>| {
     let files_changed = changed.is_some();
@@ -98,28 +146,41 @@ pub async fn lint(
   };
 
   let has_error = Arc::new(AtomicBool::new(false));
+  let warning_count = Arc::new(AtomicUsize::new(0));
   let factory = CliFactory::from_cli_options(Arc::new(cli_options));
   let cli_options = factory.cli_options();
   let caches = factory.caches()?;
+  let is_watch = cli_options.watch_paths().is_some();
   let operation = |paths: Vec<PathBuf>| async {
+    // the maximum of warnings applies to every run when watching
+    warning_count.store(0, Ordering::Relaxed);
     let incremental_cache = Arc::new(IncrementalCache::new(
       caches.lint_incremental_cache_db(),
       // use a hash of the rule names in order to bust the cache
       &{
         // ensure this is stable by sorting it
         let mut names = lint_rules.iter().map(|r| r.code()).collect::<Vec<_>>();
+        if let Some(plugin_host) = &plugin_host {
+          names.extend(plugin_host.rule_codes().iter().map(|c| c.as_str()));
+          names.push(plugin_host.sources_hash());
+        }
         names.sort_unstable();
         names
       },
       &paths,
     ));
     let target_files_len = paths.len();
-    let reporter_lock =
-      Arc::new(Mutex::new(create_reporter(reporter_kind.clone())));
+    let reporter_lock = Arc::new(Mutex::new(create_reporter(
+      reporter_kind.clone(),
+      &lint_rules,
+    )));
 
     run_parallelized(paths, {
       let has_error = has_error.clone();
+      let warning_count = warning_count.clone();
+      let rules_config = rules_config.clone();
       let lint_rules = lint_rules.clone();
+      let plugin_host = plugin_host.clone();
       let reporter_lock = reporter_lock.clone();
       let incremental_cache = incremental_cache.clone();
       move |file_path| {
@@ -130,7 +191,12 @@ pub async fn lint(
           return Ok(());
         }
 
-        let r = lint_file(&file_path, file_text, lint_rules);
+        let plugin_host = plugin_host.as_deref();
+        let r = if fix {
+          lint_and_fix_file(&file_path, file_text, lint_rules, plugin_host)
+        } else {
+          lint_file(&file_path, file_text, lint_rules, plugin_host)
+        };
         if let Ok((file_diagnostics, file_text)) = &r {
           if file_diagnostics.is_empty() {
             // update the incremental cache if there were no diagnostics
@@ -141,8 +207,10 @@ pub async fn lint(
         handle_lint_result(
           &file_path.to_string_lossy(),
           r,
+          &rules_config,
           reporter_lock.clone(),
           has_error,
+          warning_count,
         );
 
         Ok(())
@@ -151,10 +219,16 @@ pub async fn lint(
     .await?;
     incremental_cache.wait_completion().await;
     reporter_lock.lock().unwrap().close(target_files_len);
+    if is_watch {
+      report_too_many_warnings(
+        warning_count.load(Ordering::Relaxed),
+        max_warnings,
+      );
+    }
 
     Ok(())
   };
-  if cli_options.watch_paths().is_some() {
+  if is_watch {
     if lint_options.is_stdin {
       return Err(generic_error(
         "Lint watch on standard input is not supported.",
@@ -171,13 +245,21 @@ pub async fn lint(
     .await?;
   } else {
     if lint_options.is_stdin {
-      let reporter_lock = Arc::new(Mutex::new(create_reporter(reporter_kind)));
-      let r = lint_stdin(lint_rules);
+      if fix {
+        return Err(generic_error(
+          "Lint fix on standard input is not supported.",
+        ));
+      }
+      let reporter_lock =
+        Arc::new(Mutex::new(create_reporter(reporter_kind, &lint_rules)));
+      let r = lint_stdin(lint_rules, plugin_host.as_deref());
       handle_lint_result(
         STDIN_FILE_NAME,
         r,
+        &rules_config,
         reporter_lock.clone(),
         has_error.clone(),
+        warning_count.clone(),
       );
       reporter_lock.lock().unwrap().close(1);
     } else {
@@ -192,7 +274,11 @@ pub async fn lint(
       operation(target_files).await?;
     };
     let has_error = has_error.load(Ordering::Relaxed);
-    if has_error {
+    let too_many_warnings = report_too_many_warnings(
+      warning_count.load(Ordering::Relaxed),
+      max_warnings,
+    );
+    if has_error || too_many_warnings {
       std::process::exit(1);
     }
   }
@@ -200,6 +286,31 @@ pub async fn lint(
   Ok(())
 }
 
+/// Prints an error and returns `true` when there are more warnings than
+/// allowed with `--max-warnings`.
+fn report_too_many_warnings(
+  warning_count: usize,
+  max_warnings: Option<usize>,
+) -> bool {
+  match max_warnings {
+    Some(max_warnings) if warning_count > max_warnings => {
+      eprintln!(
+        "{} Found {} {}, which is more than the maximum of {}.",
+        colors::red_bold("error:"),
+        warning_count,
+        if warning_count == 1 {
+          "warning"
+        } else {
+          "warnings"
+        },
+        max_warnings
+      );
+      true
+    }
+    _ => false,
+  }
+}
+
 fn collect_lint_files(files: &FilesConfig) -> Result<Vec<PathBuf>, AnyError> {
   FileCollector::new(is_supported_ext)
     .ignore_git_folder()
@@ -252,32 +363,85 @@ fn lint_file(
   file_path: &Path,
   source_code: String,
   lint_rules: Vec<&'static dyn LintRule>,
+  plugin_host: Option<&LintPluginHost>,
 ) -> Result<(Vec<LintDiagnostic>, String), AnyError> {
   let file_name = file_path.to_string_lossy().to_string();
   let media_type = MediaType::from_path(file_path);
 
-  let linter = create_linter(media_type, lint_rules);
-
-  let (_, file_diagnostics) = linter.lint(file_name, source_code.clone())?;
+  let file_diagnostics =
+    lint_with_plugins(lint_rules, plugin_host, |lint_rules| {
+      let linter = create_linter(media_type, lint_rules);
+      let (_, file_diagnostics) =
+        linter.lint(file_name, source_code.clone())?;
+      Ok(file_diagnostics)
+    })?;
 
   Ok((file_diagnostics, source_code))
 }
 
+/// Lints the file and applies the fixes of the diagnostics, repeating that
+/// until nothing is left to fix. The file is only written if it changed.
+/// Returns the diagnostics that remain.
+fn lint_and_fix_file(
+  file_path: &Path,
+  source_code: String,
+  lint_rules: Vec<&'static dyn LintRule>,
+  plugin_host: Option<&LintPluginHost>,
+) -> Result<(Vec<LintDiagnostic>, String), AnyError> {
+  let mut text = source_code.clone();
+  let mut iterations = 0;
+  let diagnostics = loop {
+    let (diagnostics, _) =
+      lint_file(file_path, text.clone(), lint_rules.clone(), plugin_host)?;
+    if iterations == MAX_FIX_ITERATIONS {
+      break diagnostics;
+    }
+    let text_info = SourceTextInfo::from_string(text.clone());
+    let fixes = get_lint_fixes(&diagnostics, &text_info);
+    if fixes.is_empty() {
+      break diagnostics;
+    }
+    let fixed_text = apply_lint_fixes(&text, fixes);
+    if fixed_text == text {
+      break diagnostics;
+    }
+    text = fixed_text;
+    iterations += 1;
+  };
+
+  if text != source_code {
+    #[cfg(unix)]
+    let mode = {
+      use std::os::unix::fs::PermissionsExt;
+      fs::metadata(file_path)?.permissions().mode()
+    };
+    #[cfg(not(unix))]
+    let mode = 0o644;
+    atomic_write_file(file_path, &text, mode)?;
+  }
+
+  Ok((diagnostics, text))
+}
+
 /// Lint stdin and write result to stdout.
 /// Treats input as TypeScript.
 /// Compatible with `--json` flag.
 fn lint_stdin(
   lint_rules: Vec<&'static dyn LintRule>,
+  plugin_host: Option<&LintPluginHost>,
 ) -> Result<(Vec<LintDiagnostic>, String), AnyError> {
   let mut source_code = String::new();
   if stdin().read_to_string(&mut source_code).is_err() {
     return Err(generic_error("Failed to read from stdin"));
   }
 
-  let linter = create_linter(MediaType::TypeScript, lint_rules);
-
-  let (_, file_diagnostics) =
-    linter.lint(STDIN_FILE_NAME.to_string(), source_code.clone())?;
+  let file_diagnostics =
+    lint_with_plugins(lint_rules, plugin_host, |lint_rules| {
+      let linter = create_linter(MediaType::TypeScript, lint_rules);
+      let (_, file_diagnostics) =
+        linter.lint(STDIN_FILE_NAME.to_string(), source_code.clone())?;
+      Ok(file_diagnostics)
+    })?;
 
   Ok((file_diagnostics, source_code))
 }
@@ -285,8 +449,10 @@ fn lint_stdin(
 fn handle_lint_result(
   file_path: &str,
   result: Result<(Vec<LintDiagnostic>, String), AnyError>,
+  rules_config: &LintRulesConfig,
   reporter_lock: Arc<Mutex<Box<dyn LintReporter + Send>>>,
   has_error: Arc<AtomicBool>,
+  warning_count: Arc<AtomicUsize>,
 ) {
   let mut reporter = reporter_lock.lock().unwrap();
 
@@ -294,8 +460,17 @@ fn handle_lint_result(
     Ok((mut file_diagnostics, source)) => {
       sort_diagnostics(&mut file_diagnostics);
       for d in file_diagnostics.iter() {
-        has_error.store(true, Ordering::Relaxed);
-        reporter.visit_diagnostic(d, source.split('\n').collect());
+        let severity = rules_config
+          .severity(&d.code)
+          .unwrap_or(LintRuleSeverity::Error);
+        match severity {
+          LintRuleSeverity::Error => has_error.store(true, Ordering::Relaxed),
+          LintRuleSeverity::Warn => {
+            warning_count.fetch_add(1, Ordering::Relaxed);
+          }
+          LintRuleSeverity::Off => continue,
+        }
+        reporter.visit_diagnostic(d, severity, source.split('\n').collect());
       }
     }
     Err(err) => {
@@ -306,7 +481,12 @@ fn handle_lint_result(
 }
 
 trait LintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, source_lines: Vec<&str>);
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+    source_lines: Vec<&str>,
+  );
   fn visit_error(&mut self, file_path: &str, err: &AnyError);
   fn close(&mut self, check_count: usize);
 }
@@ -319,19 +499,38 @@ struct LintError {
 
 struct PrettyLintReporter {
   lint_count: u32,
+  warning_count: u32,
 }
 
 impl PrettyLintReporter {
   fn new() -> PrettyLintReporter {
-    PrettyLintReporter { lint_count: 0 }
+    PrettyLintReporter {
+      lint_count: 0,
+      warning_count: 0,
+    }
   }
 }
 
 impl LintReporter for PrettyLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, source_lines: Vec<&str>) {
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+    source_lines: Vec<&str>,
+  ) {
     self.lint_count += 1;
 
-    let pretty_message = format!("({}) {}", colors::red(&d.code), &d.message);
+    let pretty_message = if severity == LintRuleSeverity::Warn {
+      self.warning_count += 1;
+      format!(
+        "({}) {} {}",
+        colors::yellow(&d.code),
+        colors::yellow("warning:"),
+        &d.message
+      )
+    } else {
+      format!("({}) {}", colors::red(&d.code), &d.message)
+    };
 
     let message = format_diagnostic(
       &d.code,
@@ -358,11 +557,7 @@ impl LintReporter for PrettyLintReporter {
   }
 
   fn close(&mut self, check_count: usize) {
-    match self.lint_count {
-      1 => info!("Found 1 problem"),
-      n if n > 1 => info!("Found {} problems", self.lint_count),
-      _ => (),
-    }
+    print_problem_count(self.lint_count, self.warning_count);
 
     match check_count {
       n if n <= 1 => info!("Checked {} file", n),
@@ -374,23 +569,39 @@ impl LintReporter for PrettyLintReporter {
 
 struct CompactLintReporter {
   lint_count: u32,
+  warning_count: u32,
 }
 
 impl CompactLintReporter {
   fn new() -> CompactLintReporter {
-    CompactLintReporter { lint_count: 0 }
+    CompactLintReporter {
+      lint_count: 0,
+      warning_count: 0,
+    }
   }
 }
 
 impl LintReporter for CompactLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, _source_lines: Vec<&str>) {
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+    _source_lines: Vec<&str>,
+  ) {
     self.lint_count += 1;
+    let warning = if severity == LintRuleSeverity::Warn {
+      self.warning_count += 1;
+      "warning: "
+    } else {
+      ""
+    };
 
     eprintln!(
-      "{}: line {}, col {} - {} ({})",
+      "{}: line {}, col {} - {}{} ({})",
       d.filename,
       d.range.start.line_index + 1,
       d.range.start.column_index + 1,
+      warning,
       d.message,
       d.code
     )
@@ -402,11 +613,7 @@ impl LintReporter for CompactLintReporter {
   }
 
   fn close(&mut self, check_count: usize) {
-    match self.lint_count {
-      1 => info!("Found 1 problem"),
-      n if n > 1 => info!("Found {} problems", self.lint_count),
-      _ => (),
-    }
+    print_problem_count(self.lint_count, self.warning_count);
 
     match check_count {
       n if n <= 1 => info!("Checked {} file", n),
@@ -416,6 +623,19 @@ impl LintReporter for CompactLintReporter {
   }
 }
 
+fn print_problem_count(lint_count: u32, warning_count: u32) {
+  let warnings = match warning_count {
+    0 => "".to_string(),
+    1 => " (1 warning)".to_string(),
+    n => format!(" ({n} warnings)"),
+  };
+  match lint_count {
+    1 => info!("Found 1 problem{}", warnings),
+    n if n > 1 => info!("Found {} problems{}", lint_count, warnings),
+    _ => (),
+  }
+}
+
 pub fn format_diagnostic(
   diagnostic_code: &str,
   message_line: &str,
@@ -479,9 +699,16 @@ pub fn format_diagnostic(
   )
 }
 
+#[derive(Serialize)]
+struct JsonLintDiagnostic {
+  #[serde(flatten)]
+  diagnostic: LintDiagnostic,
+  severity: LintRuleSeverity,
+}
+
 #[derive(Serialize)]
 struct JsonLintReporter {
-  diagnostics: Vec<LintDiagnostic>,
+  diagnostics: Vec<JsonLintDiagnostic>,
   errors: Vec<LintError>,
 }
 
@@ -495,8 +722,16 @@ impl JsonLintReporter {
 }
 
 impl LintReporter for JsonLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, _source_lines: Vec<&str>) {
-    self.diagnostics.push(d.clone());
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+    _source_lines: Vec<&str>,
+  ) {
+    self.diagnostics.push(JsonLintDiagnostic {
+      diagnostic: d.clone(),
+      severity,
+    });
   }
 
   fn visit_error(&mut self, file_path: &str, err: &AnyError) {
@@ -507,31 +742,141 @@ impl LintReporter for JsonLintReporter {
   }
 
   fn close(&mut self, _check_count: usize) {
-    sort_diagnostics(&mut self.diagnostics);
+    self
+      .diagnostics
+      .sort_by(|a, b| compare_diagnostics(&a.diagnostic, &b.diagnostic));
     let json = serde_json::to_string_pretty(&self);
     println!("{}", json.unwrap());
   }
 }
 
+struct SarifLintReporter {
+  diagnostics: Vec<(LintDiagnostic, LintRuleSeverity)>,
+  errors: Vec<LintError>,
+  /// Tags of the built-in rules, the rules of plugins have none.
+  rule_tags: HashMap<&'static str, &'static [&'static str]>,
+}
+
+impl SarifLintReporter {
+  fn new(lint_rules: &[&'static dyn LintRule]) -> SarifLintReporter {
+    SarifLintReporter {
+      diagnostics: Vec::new(),
+      errors: Vec::new(),
+      rule_tags: lint_rules
+        .iter()
+        .map(|rule| (rule.code(), rule.tags()))
+        .collect(),
+    }
+  }
+
+  fn to_sarif_rule(&self, code: &str) -> SarifRule {
+    match self.rule_tags.get(code) {
+      Some(tags) => SarifRule {
+        id: code.to_string(),
+        help_uri: Some(format!("https://lint.deno.land/#{code}")),
+        properties: SarifRuleProperties {
+          tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        },
+      },
+      None => SarifRule {
+        id: code.to_string(),
+        ..Default::default()
+      },
+    }
+  }
+}
+
+fn file_path_to_uri(file_path: &str) -> String {
+  ModuleSpecifier::from_file_path(file_path)
+    .map(|url| url.to_string())
+    .unwrap_or_else(|_| file_path.to_string())
+}
+
+impl LintReporter for SarifLintReporter {
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintRuleSeverity,
+    _source_lines: Vec<&str>,
+  ) {
+    self.diagnostics.push((d.clone(), severity));
+  }
+
+  fn visit_error(&mut self, file_path: &str, err: &AnyError) {
+    self.errors.push(LintError {
+      file_path: file_path.to_string(),
+      message: err.to_string(),
+    });
+  }
+
+  fn close(&mut self, _check_count: usize) {
+    self
+      .diagnostics
+      .sort_by(|(a, _), (b, _)| compare_diagnostics(a, b));
+    let mut run =
+      SarifRun::new("deno lint", SarifColumnKind::UnicodeCodePoints);
+    for (d, severity) in &self.diagnostics {
+      let message = match &d.hint {
+        Some(hint) => format!("{}\n\nhint: {}", d.message, hint),
+        None => d.message.clone(),
+      };
+      let region = SarifRegion {
+        start_line: d.range.start.line_index + 1,
+        start_column: d.range.start.column_index + 1,
+        end_line: d.range.end.line_index + 1,
+        end_column: d.range.end.column_index + 1,
+        byte_offset: Some(d.range.start.byte_index),
+        byte_length: Some(d.range.end.byte_index - d.range.start.byte_index),
+      };
+      run.add_result(
+        self.to_sarif_rule(&d.code),
+        SarifResult::new(
+          match severity {
+            LintRuleSeverity::Warn => SarifLevel::Warning,
+            _ => SarifLevel::Error,
+          },
+          message,
+          vec![SarifLocation::new(
+            file_path_to_uri(&d.filename),
+            Some(region),
+          )],
+        ),
+      );
+    }
+    for error in &self.errors {
+      run.add_notification(
+        error.message.clone(),
+        Some(file_path_to_uri(&error.file_path)),
+      );
+    }
+    let json = serde_json::to_string_pretty(&SarifLog::new(run));
+    println!("{}", json.unwrap());
+  }
+}
+
 fn sort_diagnostics(diagnostics: &mut [LintDiagnostic]) {
   // Sort so that we guarantee a deterministic output which is useful for tests
-  diagnostics.sort_by(|a, b| {
-    use std::cmp::Ordering;
-    let file_order = a.filename.cmp(&b.filename);
-    match file_order {
-      Ordering::Equal => {
-        let line_order =
-          a.range.start.line_index.cmp(&b.range.start.line_index);
-        match line_order {
-          Ordering::Equal => {
-            a.range.start.column_index.cmp(&b.range.start.column_index)
-          }
-          _ => line_order,
+  diagnostics.sort_by(compare_diagnostics);
+}
+
+fn compare_diagnostics(
+  a: &LintDiagnostic,
+  b: &LintDiagnostic,
+) -> std::cmp::Ordering {
+  use std::cmp::Ordering;
+  let file_order = a.filename.cmp(&b.filename);
+  match file_order {
+    Ordering::Equal => {
+      let line_order = a.range.start.line_index.cmp(&b.range.start.line_index);
+      match line_order {
+        Ordering::Equal => {
+          a.range.start.column_index.cmp(&b.range.start.column_index)
         }
+        _ => line_order,
       }
-      _ => file_order,
     }
-  });
+    _ => file_order,
+  }
 }
 
 pub fn get_configured_rules(
@@ -562,6 +907,7 @@ mod test {
       exclude: Some(vec!["no-debugger".to_string()]),
       include: None,
       tags: None,
+      severity: None,
     };
     let rules = get_configured_rules(rules_config);
     let mut rule_names = rules
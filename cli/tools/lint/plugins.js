@@ -0,0 +1,130 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+// Host for the lint plugins, see `cli/tools/lint/plugins.rs`.
+
+((window) => {
+  const core = window.Deno.core;
+
+  function stringify(value) {
+    if (typeof value === "string") {
+      return value;
+    }
+    try {
+      return JSON.stringify(value);
+    } catch {
+      return String(value);
+    }
+  }
+
+  function print(stderr) {
+    return (...args) =>
+      core.print(args.map(stringify).join(" ") + "\n", stderr);
+  }
+
+  window.console = {
+    log: print(false),
+    info: print(false),
+    debug: print(false),
+    warn: print(true),
+    error: print(true),
+  };
+
+  /** @type {{ code: string, create: Function }[]} */
+  const rules = [];
+
+  function registerLintPlugins(plugins, exclude) {
+    for (const plugin of plugins) {
+      if (
+        plugin == null || typeof plugin.name !== "string" ||
+        typeof plugin.rules !== "object" || plugin.rules === null
+      ) {
+        throw new TypeError(
+          "A lint plugin must default export an object with a name and rules.",
+        );
+      }
+      for (const [name, rule] of Object.entries(plugin.rules)) {
+        const code = `${plugin.name}/${name}`;
+        if (typeof rule?.create !== "function") {
+          throw new TypeError(
+            `The lint rule "${code}" has no create function.`,
+          );
+        }
+        if (rules.some((r) => r.code === code)) {
+          throw new TypeError(`The lint rule "${code}" is defined twice.`);
+        }
+        if (!exclude.includes(code)) {
+          rules.push({ code, create: rule.create });
+        }
+      }
+    }
+  }
+
+  function lintRuleCodes() {
+    return rules.map((rule) => rule.code);
+  }
+
+  function prepareNode(node, parent, sourceText) {
+    node.parent = parent;
+    for (const [name, value] of Object.entries(node.fields)) {
+      if (value?.child !== undefined) {
+        node[name] = node.children[value.child];
+      } else if (value?.children !== undefined) {
+        node[name] = value.children.map((index) => node.children[index]);
+      } else {
+        node[name] = value;
+      }
+    }
+    delete node.fields;
+    Object.defineProperty(node, "text", {
+      get() {
+        return sourceText.slice(this.range[0], this.range[1]);
+      },
+    });
+    for (const child of node.children) {
+      prepareNode(child, node, sourceText);
+    }
+  }
+
+  function runLintPlugins({ sourceText, ast }) {
+    prepareNode(ast, null, sourceText);
+    const diagnostics = [];
+    const visitors = rules.map(({ code, create }) => {
+      const context = {
+        sourceText,
+        report({ node, range, message, hint }) {
+          range ??= node?.range;
+          if (!Array.isArray(range) || typeof message !== "string") {
+            throw new TypeError(
+              `The lint rule "${code}" reported a problem without a node or range and a message.`,
+            );
+          }
+          diagnostics.push({
+            code,
+            message,
+            hint: hint ?? null,
+            range: [range[0], range[1]],
+          });
+        },
+      };
+      return create(context) ?? {};
+    });
+
+    function visit(node) {
+      for (const visitor of visitors) {
+        visitor[node.type]?.(node);
+      }
+      for (const child of node.children) {
+        visit(child);
+      }
+      for (const visitor of visitors) {
+        visitor[`${node.type}:exit`]?.(node);
+      }
+    }
+    visit(ast);
+    return diagnostics;
+  }
+
+  window.registerLintPlugins = registerLintPlugins;
+  window.lintRuleCodes = lintRuleCodes;
+  window.runLintPlugins = runLintPlugins;
+})(globalThis);
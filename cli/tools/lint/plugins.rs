@@ -0,0 +1,862 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Lint rules provided by JavaScript or TypeScript modules, configured with
+//! `lint.plugins` in the config file.
+//!
+//! A plugin module default exports an object with a `name` and its `rules`.
+//! Every rule has a `create(context)` function returning a visitor, whose
+//! methods are called with the nodes of the matching type, or with
+//! `"<type>:exit"` after the children of the node were visited. Problems are
+//! reported with `context.report({ node, message, hint })`. Besides its
+//! `type`, `range`, `text`, `parent` and `children`, a node has the fields
+//! of the `swc` node, like `sym` of an `Ident` or `callee` and `args` of a
+//! `CallExpr`, see `node_fields`:
+//!
+//! ```ts
+//! export default {
+//!   name: "my-plugin",
+//!   rules: {
+//!     "no-foo": {
+//!       create(context) {
+//!         return {
+//!           Ident(node) {
+//!             if (node.sym === "foo") {
+//!               context.report({ node, message: "Don't use foo" });
+//!             }
+//!           },
+//!         };
+//!       },
+//!     },
+//!   },
+//! };
+//! ```
+//!
+//! The codes of the rules are prefixed with the name of the plugin, like
+//! `my-plugin/no-foo`. The plugins run in a separate isolate without access
+//! to the `Deno` namespace, and can only import local modules. A run of the
+//! plugins on a file is terminated if it takes longer than
+//! [PLUGIN_RUN_TIMEOUT].
+//!
+//! The plugins are run by a rule of the `deno_lint` linter, so that their
+//! diagnostics are ignored with `deno-lint-ignore` comments like the ones of
+//! the built-in rules.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::mpsc as std_mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use deno_ast::swc::ast::MethodKind;
+use deno_ast::view::Node;
+use deno_ast::view::NodeTrait;
+use deno_ast::view::Program;
+use deno_ast::MediaType;
+use deno_ast::ParseParams;
+use deno_ast::SourceRange;
+use deno_ast::SourceRanged;
+use deno_ast::SourceRangedForSpanned;
+use deno_ast::SourceTextInfo;
+use deno_core::anyhow::anyhow;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::futures::FutureExt;
+use deno_core::located_script_name;
+use deno_core::parking_lot::Condvar;
+use deno_core::parking_lot::Mutex;
+use deno_core::resolve_import;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::JsRuntime;
+use deno_core::ModuleLoader;
+use deno_core::ModuleSource;
+use deno_core::ModuleSourceFuture;
+use deno_core::ModuleSpecifier;
+use deno_core::ModuleType;
+use deno_core::ResolutionKind;
+use deno_core::RuntimeOptions;
+use deno_lint::context::Context;
+use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::rules::LintRule;
+use deno_runtime::tokio_util::create_basic_runtime;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::util::checksum;
+
+static PLUGINS_HOST_SRC: &str = include_str!("plugins.js");
+
+type Request = (
+  LintRequest,
+  std_mpsc::Sender<Result<Vec<PluginDiagnostic>, AnyError>>,
+);
+
+/// The maximum time the plugins may take to lint a single file.
+const PLUGIN_RUN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs the rules of the lint plugins in an isolate on its own thread.
+#[derive(Clone, Debug)]
+pub struct LintPluginHost {
+  sender: mpsc::UnboundedSender<Request>,
+  rule_codes: Vec<String>,
+  sources_hash: String,
+  /// The paths of the loaded modules with their modification times.
+  modules: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl LintPluginHost {
+  /// Loads the plugins, skipping the rules whose codes are in `exclude`.
+  pub fn new(
+    plugins: Vec<ModuleSpecifier>,
+    exclude: Vec<String>,
+  ) -> Result<Self, AnyError> {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<Request>();
+    let (init_sender, init_receiver) = std_mpsc::channel();
+    thread::spawn(move || {
+      let runtime = create_basic_runtime();
+      runtime.block_on(async {
+        let loader = Rc::new(PluginModuleLoader::default());
+        let mut js_runtime = JsRuntime::new(RuntimeOptions {
+          module_loader: Some(loader.clone()),
+          ..Default::default()
+        });
+        let (rule_codes, run_fn) =
+          match load_plugins(&mut js_runtime, &plugins, &exclude).await {
+            Ok(loaded) => loaded,
+            Err(err) => {
+              let _ = init_sender.send(Err(err));
+              return;
+            }
+          };
+        if init_sender
+          .send(Ok((
+            rule_codes,
+            loader.sources_hash(),
+            loader.modules.take(),
+          )))
+          .is_err()
+        {
+          return;
+        }
+        let watchdog =
+          Watchdog::new(js_runtime.v8_isolate().thread_safe_handle());
+        while let Some((request, response_sender)) = receiver.recv().await {
+          let result =
+            run_plugins(&mut js_runtime, &run_fn, &watchdog, &request);
+          if response_sender.send(result).is_err() {
+            break;
+          }
+        }
+      })
+    });
+    let (rule_codes, sources_hash, modules) = init_receiver
+      .recv()
+      .map_err(|_| generic_error("The lint plugins could not be loaded."))??;
+    Ok(Self {
+      sender,
+      rule_codes,
+      sources_hash,
+      modules,
+    })
+  }
+
+  /// Codes of the rules of all plugins.
+  pub fn rule_codes(&self) -> &[String] {
+    &self.rule_codes
+  }
+
+  /// Changes whenever the source of any of the plugin modules changes.
+  pub fn sources_hash(&self) -> &str {
+    &self.sources_hash
+  }
+
+  /// Whether any of the loaded modules was modified or removed since the
+  /// plugins were loaded.
+  pub fn is_outdated(&self) -> bool {
+    self
+      .modules
+      .iter()
+      .any(|(path, modified)| &modified_time(path) != modified)
+  }
+
+  /// Runs the plugins on a program, returning their diagnostics with ranges
+  /// in bytes.
+  fn run(
+    &self,
+    program: Program,
+    text_info: &SourceTextInfo,
+  ) -> Result<Vec<PluginDiagnostic>, AnyError> {
+    let utf16_map = Utf16Map::new(text_info.text_str());
+    let request = LintRequest {
+      source_text: text_info.text_str().to_string(),
+      ast: serialize_node(program.into(), text_info, &utf16_map),
+    };
+    let (response_sender, response_receiver) = std_mpsc::channel();
+    self
+      .sender
+      .send((request, response_sender))
+      .map_err(|_| generic_error("The lint plugin host has stopped."))?;
+    let diagnostics = response_receiver
+      .recv()
+      .map_err(|_| generic_error("The lint plugin host has stopped."))??;
+    Ok(
+      diagnostics
+        .into_iter()
+        .map(|d| {
+          let start = utf16_map.to_byte_index(d.range.0);
+          let end = utf16_map.to_byte_index(d.range.1).max(start);
+          PluginDiagnostic {
+            range: (start, end),
+            ..d
+          }
+        })
+        .collect(),
+    )
+  }
+
+  /// Whether `diagnostic` is the complaint of `ban-unknown-rule-code` about
+  /// an ignore comment naming the code of a plugin rule, which the built-in
+  /// rules don't know.
+  fn is_unknown_plugin_code(&self, diagnostic: &LintDiagnostic) -> bool {
+    // `deno_lint` only names the unknown code in the message.
+    diagnostic.code == "ban-unknown-rule-code"
+      && self.rule_codes.iter().any(|code| {
+        diagnostic.message == format!("Unknown rule for code \"{code}\"")
+      })
+  }
+}
+
+/// Lints with the built-in `lint_rules` and the rules of the plugins of
+/// `plugin_host`, if any, by calling `lint` with the rules to create the
+/// linter with.
+pub fn lint_with_plugins(
+  mut lint_rules: Vec<&'static dyn LintRule>,
+  plugin_host: Option<&LintPluginHost>,
+  lint: impl FnOnce(
+    Vec<&'static dyn LintRule>,
+  ) -> Result<Vec<LintDiagnostic>, AnyError>,
+) -> Result<Vec<LintDiagnostic>, AnyError> {
+  let Some(plugin_host) = plugin_host else {
+    return lint(lint_rules);
+  };
+
+  lint_rules.push(&PluginLintRule);
+  ACTIVE_PLUGIN_HOST.with(|active| {
+    *active.borrow_mut() = Some(ActivePluginHost {
+      plugin_host: plugin_host.clone(),
+      error: None,
+    });
+  });
+  let result = lint(lint_rules);
+  let active = ACTIVE_PLUGIN_HOST.with(|active| active.borrow_mut().take());
+  if let Some(err) = active.and_then(|active| active.error) {
+    return Err(err);
+  }
+  let mut diagnostics = result?;
+  diagnostics.retain(|d| !plugin_host.is_unknown_plugin_code(d));
+  Ok(diagnostics)
+}
+
+struct ActivePluginHost {
+  plugin_host: LintPluginHost,
+  /// The first error of running the plugins, as rules can't fail.
+  error: Option<AnyError>,
+}
+
+thread_local! {
+  /// The plugins run by [PluginLintRule] on this thread, as the linter only
+  /// takes rules with a `'static` lifetime.
+  static ACTIVE_PLUGIN_HOST: RefCell<Option<ActivePluginHost>> =
+    RefCell::new(None);
+}
+
+/// The rule that runs the plugins set up by [lint_with_plugins] and reports
+/// their diagnostics with the codes of the plugin rules.
+#[derive(Debug)]
+struct PluginLintRule;
+
+impl LintRule for PluginLintRule {
+  fn lint_program_with_ast_view<'view>(
+    &self,
+    context: &mut Context<'view>,
+    program: Program<'view>,
+  ) {
+    ACTIVE_PLUGIN_HOST.with(|active| {
+      let mut active = active.borrow_mut();
+      let Some(active) = active.as_mut() else {
+        return;
+      };
+      if active.error.is_some() {
+        return;
+      }
+      let text_info = context.text_info().clone();
+      match active.plugin_host.run(program, &text_info) {
+        Ok(diagnostics) => {
+          let start = text_info.range().start;
+          for d in diagnostics {
+            let range = SourceRange::new(start + d.range.0, start + d.range.1);
+            match d.hint {
+              Some(hint) => {
+                context.add_diagnostic_with_hint(range, d.code, d.message, hint)
+              }
+              None => context.add_diagnostic(range, d.code, d.message),
+            }
+          }
+        }
+        Err(err) => active.error = Some(err),
+      }
+    });
+  }
+
+  fn code(&self) -> &'static str {
+    "lint-plugins"
+  }
+
+  fn docs(&self) -> &'static str {
+    "Runs the rules of the lint plugins configured with `lint.plugins`."
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LintRequest {
+  source_text: String,
+  ast: AstNode,
+}
+
+/// A node of the syntax tree as it's passed to the plugins. Ranges are in
+/// UTF-16 code units, so that they can be used to slice the source text.
+#[derive(Serialize)]
+struct AstNode {
+  #[serde(rename = "type")]
+  kind: String,
+  range: (usize, usize),
+  /// The properties of the node, named like the fields of the `swc` node.
+  fields: BTreeMap<&'static str, AstField>,
+  children: Vec<AstNode>,
+}
+
+/// A property of a node. Child nodes are referenced by their index in the
+/// children of the node.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum AstField {
+  Bool(bool),
+  Number(f64),
+  String(String),
+  Child { child: usize },
+  Children { children: Vec<usize> },
+  Null,
+}
+
+#[derive(Deserialize)]
+struct PluginDiagnostic {
+  code: String,
+  message: String,
+  hint: Option<String>,
+  range: (usize, usize),
+}
+
+fn serialize_node(
+  node: Node,
+  text_info: &SourceTextInfo,
+  utf16_map: &Utf16Map,
+) -> AstNode {
+  let range = node.range();
+  let children = node.children();
+  AstNode {
+    kind: format!("{:?}", node.kind()),
+    range: (
+      utf16_map
+        .to_utf16_index(range.start.as_byte_index(text_info.range().start)),
+      utf16_map
+        .to_utf16_index(range.end.as_byte_index(text_info.range().start)),
+    ),
+    fields: node_fields(node, &children),
+    children: children
+      .into_iter()
+      .map(|child| serialize_node(child, text_info, utf16_map))
+      .collect(),
+  }
+}
+
+/// Collects the scalar fields of a node and the fields that refer to its
+/// children, following the structure of the `swc` node.
+fn node_fields(
+  node: Node,
+  children: &[Node],
+) -> BTreeMap<&'static str, AstField> {
+  // The nodes of the fields are taken from the view of the node, and are
+  // found among its children by their kind and range, which no two siblings
+  // share, even when a wrapper node has the same range as the node it wraps.
+  let find = |field: Node| {
+    children.iter().position(|child| {
+      child.kind() == field.kind() && child.range() == field.range()
+    })
+  };
+  let child = |field: Node| match find(field) {
+    Some(child) => AstField::Child { child },
+    None => AstField::Null,
+  };
+  let opt_child = |field: Option<Node>| match field {
+    Some(field) => child(field),
+    None => AstField::Null,
+  };
+  let child_list = |fields: Vec<Node>| AstField::Children {
+    children: fields.into_iter().filter_map(find).collect(),
+  };
+  let string = |value: &str| AstField::String(value.to_string());
+
+  let fields = match node {
+    Node::Ident(n) => vec![
+      ("sym", string(&n.inner.sym)),
+      ("optional", AstField::Bool(n.inner.optional)),
+    ],
+    Node::PrivateName(n) => vec![("id", string(&n.inner.id.sym))],
+    Node::Str(n) => vec![("value", string(&n.inner.value))],
+    Node::Number(n) => vec![("value", AstField::Number(n.inner.value))],
+    Node::Bool(n) => vec![("value", AstField::Bool(n.inner.value))],
+    Node::BigInt(n) => vec![("value", string(&n.inner.value.to_string()))],
+    Node::Regex(n) => vec![
+      ("exp", string(&n.inner.exp)),
+      ("flags", string(&n.inner.flags)),
+    ],
+    Node::TplElement(n) => vec![("raw", string(&n.inner.raw))],
+    Node::JSXText(n) => vec![("value", string(&n.inner.value))],
+    Node::BinExpr(n) => vec![
+      ("op", string(n.inner.op.as_str())),
+      ("left", child(n.left.as_node())),
+      ("right", child(n.right.as_node())),
+    ],
+    Node::AssignExpr(n) => vec![
+      ("op", string(n.inner.op.as_str())),
+      ("left", child(n.left.as_node())),
+      ("right", child(n.right.as_node())),
+    ],
+    Node::UnaryExpr(n) => vec![
+      ("op", string(n.inner.op.as_str())),
+      ("arg", child(n.arg.as_node())),
+    ],
+    Node::UpdateExpr(n) => vec![
+      ("op", string(n.inner.op.as_str())),
+      ("prefix", AstField::Bool(n.inner.prefix)),
+      ("arg", child(n.arg.as_node())),
+    ],
+    Node::CondExpr(n) => vec![
+      ("test", child(n.test.as_node())),
+      ("cons", child(n.cons.as_node())),
+      ("alt", child(n.alt.as_node())),
+    ],
+    Node::CallExpr(n) => vec![
+      ("callee", child(n.callee.as_node())),
+      (
+        "args",
+        child_list(n.args.iter().map(|a| a.as_node()).collect()),
+      ),
+    ],
+    Node::NewExpr(n) => vec![
+      ("callee", child(n.callee.as_node())),
+      (
+        "args",
+        child_list(n.args.iter().flatten().map(|a| a.as_node()).collect()),
+      ),
+    ],
+    Node::MemberExpr(n) => vec![
+      ("obj", child(n.obj.as_node())),
+      ("prop", child(n.prop.as_node())),
+    ],
+    Node::VarDecl(n) => vec![
+      ("kind", string(n.inner.kind.as_str())),
+      ("declare", AstField::Bool(n.inner.declare)),
+      (
+        "decls",
+        child_list(n.decls.iter().map(|d| d.as_node()).collect()),
+      ),
+    ],
+    Node::VarDeclarator(n) => vec![
+      ("name", child(n.name.as_node())),
+      ("init", opt_child(n.init.as_ref().map(|i| i.as_node()))),
+    ],
+    Node::FnDecl(n) => vec![
+      ("ident", child(n.ident.as_node())),
+      ("declare", AstField::Bool(n.inner.declare)),
+      ("function", child(n.function.as_node())),
+    ],
+    Node::Function(n) => vec![
+      ("isAsync", AstField::Bool(n.inner.is_async)),
+      ("isGenerator", AstField::Bool(n.inner.is_generator)),
+      (
+        "params",
+        child_list(n.params.iter().map(|p| p.as_node()).collect()),
+      ),
+      ("body", opt_child(n.body.as_ref().map(|b| b.as_node()))),
+    ],
+    Node::ArrowExpr(n) => vec![
+      ("isAsync", AstField::Bool(n.inner.is_async)),
+      ("isGenerator", AstField::Bool(n.inner.is_generator)),
+      (
+        "params",
+        child_list(n.params.iter().map(|p| p.as_node()).collect()),
+      ),
+      ("body", child(n.body.as_node())),
+    ],
+    Node::ClassDecl(n) => vec![
+      ("ident", child(n.ident.as_node())),
+      ("declare", AstField::Bool(n.inner.declare)),
+      ("class", child(n.class.as_node())),
+    ],
+    Node::Class(n) => vec![
+      (
+        "superClass",
+        opt_child(n.super_class.as_ref().map(|s| s.as_node())),
+      ),
+      (
+        "body",
+        child_list(n.body.iter().map(|m| m.as_node()).collect()),
+      ),
+    ],
+    Node::ClassMethod(n) => vec![
+      ("key", child(n.key.as_node())),
+      ("kind", string(method_kind_str(n.inner.kind))),
+      ("isStatic", AstField::Bool(n.inner.is_static)),
+      ("function", child(n.function.as_node())),
+    ],
+    Node::ClassProp(n) => vec![
+      ("key", child(n.key.as_node())),
+      ("value", opt_child(n.value.as_ref().map(|v| v.as_node()))),
+      ("isStatic", AstField::Bool(n.inner.is_static)),
+    ],
+    Node::KeyValueProp(n) => vec![
+      ("key", child(n.key.as_node())),
+      ("value", child(n.value.as_node())),
+    ],
+    Node::IfStmt(n) => vec![
+      ("test", child(n.test.as_node())),
+      ("cons", child(n.cons.as_node())),
+      ("alt", opt_child(n.alt.as_ref().map(|a| a.as_node()))),
+    ],
+    Node::ReturnStmt(n) => {
+      vec![("arg", opt_child(n.arg.as_ref().map(|a| a.as_node())))]
+    }
+    Node::ExprStmt(n) => vec![("expr", child(n.expr.as_node()))],
+    Node::ImportDecl(n) => vec![
+      (
+        "specifiers",
+        child_list(n.specifiers.iter().map(|s| s.as_node()).collect()),
+      ),
+      ("src", child(n.src.as_node())),
+      ("typeOnly", AstField::Bool(n.inner.type_only)),
+    ],
+    _ => Vec::new(),
+  };
+  fields.into_iter().collect()
+}
+
+fn method_kind_str(kind: MethodKind) -> &'static str {
+  match kind {
+    MethodKind::Method => "method",
+    MethodKind::Getter => "getter",
+    MethodKind::Setter => "setter",
+  }
+}
+
+/// Maps between byte and UTF-16 offsets into a text.
+struct Utf16Map {
+  /// The byte index of every char, along with its UTF-16 offset.
+  chars: Vec<(usize, usize)>,
+  byte_len: usize,
+  utf16_len: usize,
+}
+
+impl Utf16Map {
+  fn new(text: &str) -> Self {
+    let mut chars = Vec::with_capacity(text.len());
+    let mut utf16_index = 0;
+    for (byte_index, c) in text.char_indices() {
+      chars.push((byte_index, utf16_index));
+      utf16_index += c.len_utf16();
+    }
+    Self {
+      chars,
+      byte_len: text.len(),
+      utf16_len: utf16_index,
+    }
+  }
+
+  fn to_utf16_index(&self, byte_index: usize) -> usize {
+    let i = self.chars.partition_point(|(b, _)| *b < byte_index);
+    self.chars.get(i).map(|(_, u)| *u).unwrap_or(self.utf16_len)
+  }
+
+  fn to_byte_index(&self, utf16_index: usize) -> usize {
+    let i = self.chars.partition_point(|(_, u)| *u < utf16_index);
+    self.chars.get(i).map(|(b, _)| *b).unwrap_or(self.byte_len)
+  }
+}
+
+/// Loads the plugins, returning the codes of their rules and the function
+/// that runs them.
+async fn load_plugins(
+  js_runtime: &mut JsRuntime,
+  plugins: &[ModuleSpecifier],
+  exclude: &[String],
+) -> Result<(Vec<String>, v8::Global<v8::Function>), AnyError> {
+  js_runtime.execute_script_static(located_script_name!(), PLUGINS_HOST_SRC)?;
+
+  // A module that imports all plugins and passes them to the host.
+  let mut main_source = String::new();
+  for (i, plugin) in plugins.iter().enumerate() {
+    main_source.push_str(&format!(
+      "import plugin{i} from {};\n",
+      serde_json::to_string(plugin.as_str())?
+    ));
+  }
+  main_source.push_str(&format!(
+    "globalThis.registerLintPlugins([{}], {});\n",
+    (0..plugins.len())
+      .map(|i| format!("plugin{i}"))
+      .collect::<Vec<_>>()
+      .join(", "),
+    serde_json::to_string(exclude)?
+  ));
+  let main_specifier = ModuleSpecifier::parse("ext:lint_plugins/main.js")?;
+  let module_id = js_runtime
+    .load_main_module(&main_specifier, Some(main_source.into()))
+    .await?;
+  let evaluation = js_runtime.mod_evaluate(module_id);
+  js_runtime.run_event_loop(false).await?;
+  evaluation.await??;
+
+  let rule_codes = js_runtime.execute_script_static(
+    located_script_name!(),
+    "globalThis.lintRuleCodes()",
+  )?;
+  let run_fn = js_runtime.execute_script_static(
+    located_script_name!(),
+    "globalThis.runLintPlugins",
+  )?;
+  let scope = &mut js_runtime.handle_scope();
+  let rule_codes = v8::Local::new(scope, rule_codes);
+  let rule_codes = serde_v8::from_v8(scope, rule_codes)?;
+  let run_fn = v8::Local::new(scope, run_fn);
+  let run_fn = v8::Local::<v8::Function>::try_from(run_fn)?;
+  Ok((rule_codes, v8::Global::new(scope, run_fn)))
+}
+
+fn run_plugins(
+  js_runtime: &mut JsRuntime,
+  run_fn: &v8::Global<v8::Function>,
+  watchdog: &Watchdog,
+  request: &LintRequest,
+) -> Result<Vec<PluginDiagnostic>, AnyError> {
+  let scope = &mut js_runtime.handle_scope();
+  let run_fn = v8::Local::new(scope, run_fn);
+  let request = serde_v8::to_v8(scope, request)?;
+  let undefined = v8::undefined(scope).into();
+  let tc_scope = &mut v8::TryCatch::new(scope);
+  watchdog.arm();
+  let result = run_fn.call(tc_scope, undefined, &[request]);
+  if watchdog.disarm() {
+    tc_scope.cancel_terminate_execution();
+    return Err(generic_error(format!(
+      "The lint plugins were terminated after running for more than {} seconds.",
+      PLUGIN_RUN_TIMEOUT.as_secs()
+    )));
+  }
+  match result {
+    Some(diagnostics) => Ok(serde_v8::from_v8(tc_scope, diagnostics)?),
+    None => {
+      let exception = tc_scope
+        .exception()
+        .unwrap_or_else(|| v8::undefined(tc_scope).into());
+      Err(JsError::from_v8_exception(tc_scope, exception).into())
+    }
+  }
+}
+
+/// Terminates the execution of the plugins if a run takes longer than
+/// [PLUGIN_RUN_TIMEOUT], e.g. because a rule loops forever. A single thread
+/// watches all runs of a [LintPluginHost] and waits while no run is armed.
+struct Watchdog {
+  shared: Arc<(Mutex<WatchdogState>, Condvar)>,
+}
+
+#[derive(Default)]
+struct WatchdogState {
+  /// When the current run times out, if a run is in progress.
+  deadline: Option<Instant>,
+  timed_out: bool,
+  dropped: bool,
+}
+
+impl Watchdog {
+  fn new(isolate_handle: v8::IsolateHandle) -> Self {
+    let shared =
+      Arc::new((Mutex::new(WatchdogState::default()), Condvar::new()));
+    let thread_shared = shared.clone();
+    thread::spawn(move || {
+      let (state, condvar) = &*thread_shared;
+      let mut state = state.lock();
+      while !state.dropped {
+        match state.deadline {
+          None => condvar.wait(&mut state),
+          Some(deadline) => {
+            condvar.wait_until(&mut state, deadline);
+            // the run may have been disarmed, or another one armed meanwhile
+            if state.deadline == Some(deadline) && Instant::now() >= deadline {
+              isolate_handle.terminate_execution();
+              state.deadline = None;
+              state.timed_out = true;
+            }
+          }
+        }
+      }
+    });
+    Self { shared }
+  }
+
+  fn arm(&self) {
+    let (state, condvar) = &*self.shared;
+    let mut state = state.lock();
+    state.deadline = Some(Instant::now() + PLUGIN_RUN_TIMEOUT);
+    state.timed_out = false;
+    condvar.notify_one();
+  }
+
+  /// Returns whether the execution was terminated.
+  fn disarm(&self) -> bool {
+    let (state, condvar) = &*self.shared;
+    let mut state = state.lock();
+    state.deadline = None;
+    condvar.notify_one();
+    std::mem::take(&mut state.timed_out)
+  }
+}
+
+impl Drop for Watchdog {
+  fn drop(&mut self) {
+    let (state, condvar) = &*self.shared;
+    state.lock().dropped = true;
+    condvar.notify_one();
+  }
+}
+
+/// Loads local modules, transpiling TypeScript and JSX.
+#[derive(Default)]
+struct PluginModuleLoader {
+  source_hashes: RefCell<Vec<String>>,
+  modules: RefCell<Vec<(PathBuf, Option<SystemTime>)>>,
+}
+
+impl PluginModuleLoader {
+  fn sources_hash(&self) -> String {
+    let source_hashes = self.source_hashes.borrow();
+    checksum::gen(
+      &source_hashes
+        .iter()
+        .map(|hash| hash.as_bytes())
+        .collect::<Vec<_>>(),
+    )
+  }
+
+  fn load_source(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<ModuleSource, AnyError> {
+    let path = specifier.to_file_path().map_err(|_| {
+      anyhow!(
+        "Lint plugins can only import local modules, found \"{specifier}\"."
+      )
+    })?;
+    let media_type = MediaType::from_path(&path);
+    let (module_type, should_transpile) = match media_type {
+      MediaType::JavaScript | MediaType::Mjs => (ModuleType::JavaScript, false),
+      MediaType::Jsx
+      | MediaType::TypeScript
+      | MediaType::Mts
+      | MediaType::Tsx => (ModuleType::JavaScript, true),
+      MediaType::Json => (ModuleType::Json, false),
+      _ => {
+        return Err(anyhow!(
+          "Unsupported lint plugin module type \"{specifier}\"."
+        ))
+      }
+    };
+    let modified = modified_time(&path);
+    let code = std::fs::read_to_string(&path).map_err(|err| {
+      anyhow!("Failed to load lint plugin \"{specifier}\": {err}")
+    })?;
+    self.modules.borrow_mut().push((path, modified));
+    self.source_hashes.borrow_mut().push(checksum::gen(&[
+      specifier.as_str().as_bytes(),
+      code.as_bytes(),
+    ]));
+    let code = if should_transpile {
+      let parsed = deno_ast::parse_module(ParseParams {
+        specifier: specifier.to_string(),
+        text_info: SourceTextInfo::from_string(code),
+        media_type,
+        capture_tokens: false,
+        scope_analysis: false,
+        maybe_syntax: None,
+      })?;
+      parsed.transpile(&Default::default())?.text
+    } else {
+      code
+    };
+    Ok(ModuleSource::new(module_type, code.into(), specifier))
+  }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+  std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl ModuleLoader for PluginModuleLoader {
+  fn resolve(
+    &self,
+    specifier: &str,
+    referrer: &str,
+    _kind: ResolutionKind,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    Ok(resolve_import(specifier, referrer)?)
+  }
+
+  fn load(
+    &self,
+    module_specifier: &ModuleSpecifier,
+    _maybe_referrer: Option<&ModuleSpecifier>,
+    _is_dyn_import: bool,
+  ) -> Pin<Box<ModuleSourceFuture>> {
+    deno_core::futures::future::ready(self.load_source(module_specifier))
+      .boxed_local()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn utf16_map() {
+    let map = Utf16Map::new("a€😀b");
+    assert_eq!(map.to_utf16_index(0), 0);
+    assert_eq!(map.to_utf16_index(1), 1);
+    assert_eq!(map.to_utf16_index(4), 2);
+    assert_eq!(map.to_utf16_index(8), 4);
+    assert_eq!(map.to_utf16_index(9), 5);
+    assert_eq!(map.to_byte_index(2), 4);
+    assert_eq!(map.to_byte_index(4), 8);
+    assert_eq!(map.to_byte_index(5), 9);
+  }
+}
@@ -9,6 +9,7 @@ pub mod fmt;
 pub mod info;
 pub mod init;
 pub mod installer;
+pub mod kv;
 pub mod lint;
 pub mod repl;
 pub mod run;
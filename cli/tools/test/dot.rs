@@ -0,0 +1,147 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::time::Duration;
+
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::url::Url;
+use indexmap::IndexMap;
+
+use super::report_sigint;
+use super::report_summary;
+use super::TestDescription;
+use super::TestPlan;
+use super::TestReporter;
+use super::TestResult;
+use super::TestStepDescription;
+use super::TestStepResult;
+use super::TestSummary;
+use crate::colors;
+use crate::util::console::console_size;
+
+/// Prints a single character for every finished test and test step, which
+/// keeps the output of very large test suites short. Failures are listed in
+/// full in the summary, just like with the pretty reporter.
+///
+/// Output written by the tests themselves is not shown.
+pub struct DotTestReporter {
+  cwd: Url,
+  width: usize,
+  column: usize,
+}
+
+impl DotTestReporter {
+  pub fn new() -> Self {
+    let width = console_size()
+      .map(|size| size.cols as usize)
+      .filter(|cols| *cols > 0)
+      .unwrap_or(80);
+    Self {
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      width,
+      column: 0,
+    }
+  }
+
+  fn print_status(&mut self, status: String) {
+    if self.column == self.width {
+      println!();
+      self.column = 0;
+    }
+    print!("{status}");
+    self.column += 1;
+    // flush for faster feedback when line buffered, a failure to flush (e.g.
+    // a closed pipe) is reported by `flush_report`
+    let _ = std::io::stdout().flush();
+  }
+
+  fn end_line(&mut self) {
+    if self.column > 0 {
+      println!();
+      self.column = 0;
+    }
+  }
+}
+
+fn fmt_ok() -> String {
+  colors::gray(".").to_string()
+}
+
+fn fmt_ignored() -> String {
+  colors::yellow(",").to_string()
+}
+
+fn fmt_failed() -> String {
+  colors::red_bold("!").to_string()
+}
+
+impl TestReporter for DotTestReporter {
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, _plan: &TestPlan) {}
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_output(&mut self, _output: &[u8]) {}
+
+  fn report_result(
+    &mut self,
+    _description: &TestDescription,
+    result: &TestResult,
+    _elapsed: u64,
+  ) {
+    let status = match result {
+      TestResult::Ok => fmt_ok(),
+      TestResult::Ignored => fmt_ignored(),
+      TestResult::Failed(_) => fmt_failed(),
+      TestResult::Cancelled => colors::gray("!").to_string(),
+    };
+    self.print_status(status);
+  }
+
+  fn report_uncaught_error(&mut self, _origin: &str, _error: &JsError) {
+    self.print_status(fmt_failed());
+  }
+
+  fn report_step_register(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    _desc: &TestStepDescription,
+    result: &TestStepResult,
+    _elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    let status = match result {
+      TestStepResult::Ok => fmt_ok(),
+      TestStepResult::Ignored => fmt_ignored(),
+      TestStepResult::Failed(_) => fmt_failed(),
+    };
+    self.print_status(status);
+  }
+
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
+    self.end_line();
+    report_summary(&self.cwd, summary, elapsed);
+  }
+
+  fn report_sigint(
+    &mut self,
+    tests_pending: &HashSet<usize>,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.end_line();
+    report_sigint(&self.cwd, tests_pending, tests, test_steps);
+  }
+
+  fn flush_report(&mut self, _elapsed: &Duration) -> Result<(), AnyError> {
+    std::io::stdout().flush()?;
+    Ok(())
+  }
+}
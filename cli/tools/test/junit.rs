@@ -0,0 +1,466 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::url::Url;
+use indexmap::IndexMap;
+
+use super::format_test_error;
+use super::to_relative_path_or_remote_url;
+use super::TestDescription;
+use super::TestFailure;
+use super::TestPlan;
+use super::TestReporter;
+use super::TestResult;
+use super::TestStepDescription;
+use super::TestStepResult;
+use super::TestSummary;
+use crate::util::xml::escape_xml;
+
+/// Writes a JUnit XML report once all tests have finished.
+///
+/// Every test module is reported as a `<testsuite>` and every test and test
+/// step as a `<testcase>` in it. Steps are named after all of their
+/// ancestors, e.g. `test > step > nested step`, and directly follow the test
+/// they belong to.
+pub struct JunitTestReporter {
+  /// Where to write the report, or `None` to write it to stdout.
+  path: Option<PathBuf>,
+  cwd: Url,
+  suites: IndexMap<String, JunitTestSuite>,
+  /// Finished steps by the id of their root test, waiting for the test to
+  /// finish as well.
+  pending_steps: HashMap<usize, Vec<(usize, JunitTestCase)>>,
+  /// Tests that have started but not finished yet.
+  running: HashSet<usize>,
+  /// Output captured for each test. Output is only attributed to a test if
+  /// it was the only one running at the time, which is always the case
+  /// unless tests are run in parallel.
+  outputs: HashMap<usize, Vec<u8>>,
+}
+
+#[derive(Default)]
+struct JunitTestSuite {
+  cases: Vec<JunitTestCase>,
+}
+
+struct JunitTestCase {
+  name: String,
+  is_step: bool,
+  elapsed: u64,
+  status: JunitTestCaseStatus,
+  output: Vec<u8>,
+}
+
+enum JunitTestCaseStatus {
+  Passed,
+  Skipped,
+  Failed { message: String, details: String },
+  Errored { message: String, details: String },
+}
+
+impl JunitTestCaseStatus {
+  fn from_failure(failure: &TestFailure) -> Self {
+    match failure {
+      TestFailure::JsError(js_error) => Self::Failed {
+        message: js_error
+          .exception_message
+          .trim_start_matches("Uncaught ")
+          .to_string(),
+        details: format_test_error(js_error),
+      },
+      failure => {
+        let details = failure.to_string();
+        Self::Failed {
+          message: details.lines().next().unwrap_or_default().to_string(),
+          details,
+        }
+      }
+    }
+  }
+}
+
+impl JunitTestReporter {
+  pub fn new(path: Option<PathBuf>) -> Self {
+    Self {
+      path,
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      suites: Default::default(),
+      pending_steps: Default::default(),
+      running: Default::default(),
+      outputs: Default::default(),
+    }
+  }
+
+  fn suite(&mut self, origin: &str) -> &mut JunitTestSuite {
+    self.suites.entry(origin.to_string()).or_default()
+  }
+
+  fn serialize(&self, elapsed: &Duration) -> String {
+    let mut xml = String::new();
+    let cases = || self.suites.values().flat_map(|suite| &suite.cases);
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+      xml,
+      r#"<testsuites name="deno test" tests="{}" failures="{}" errors="{}" time="{}">"#,
+      cases().count(),
+      cases().filter(|case| case.is_failure()).count(),
+      cases().filter(|case| case.is_error()).count(),
+      format_seconds(elapsed.as_millis() as u64),
+    )
+    .unwrap();
+
+    for (origin, suite) in &self.suites {
+      let name = escape_xml(&to_relative_path_or_remote_url(&self.cwd, origin));
+      writeln!(
+        xml,
+        r#"  <testsuite name="{}" tests="{}" skipped="{}" failures="{}" errors="{}" time="{}">"#,
+        name,
+        suite.cases.len(),
+        suite.cases.iter().filter(|case| case.is_skipped()).count(),
+        suite.cases.iter().filter(|case| case.is_failure()).count(),
+        suite.cases.iter().filter(|case| case.is_error()).count(),
+        format_seconds(
+          suite
+            .cases
+            .iter()
+            .filter(|case| !case.is_step)
+            .map(|case| case.elapsed)
+            .sum()
+        ),
+      )
+      .unwrap();
+      for case in &suite.cases {
+        case.serialize(&mut xml, &name);
+      }
+      writeln!(xml, "  </testsuite>").unwrap();
+    }
+
+    writeln!(xml, "</testsuites>").unwrap();
+    xml
+  }
+}
+
+impl JunitTestCase {
+  fn is_skipped(&self) -> bool {
+    matches!(self.status, JunitTestCaseStatus::Skipped)
+  }
+
+  fn is_failure(&self) -> bool {
+    matches!(self.status, JunitTestCaseStatus::Failed { .. })
+  }
+
+  fn is_error(&self) -> bool {
+    matches!(self.status, JunitTestCaseStatus::Errored { .. })
+  }
+
+  fn serialize(&self, xml: &mut String, classname: &str) {
+    write!(
+      xml,
+      r#"    <testcase name="{}" classname="{}" time="{}""#,
+      escape_xml(&self.name),
+      classname,
+      format_seconds(self.elapsed),
+    )
+    .unwrap();
+    if matches!(self.status, JunitTestCaseStatus::Passed)
+      && self.output.is_empty()
+    {
+      writeln!(xml, "/>").unwrap();
+      return;
+    }
+    writeln!(xml, ">").unwrap();
+    match &self.status {
+      JunitTestCaseStatus::Passed => {}
+      JunitTestCaseStatus::Skipped => {
+        writeln!(xml, "      <skipped/>").unwrap();
+      }
+      JunitTestCaseStatus::Failed { message, details } => {
+        writeln!(
+          xml,
+          r#"      <failure message="{}">{}</failure>"#,
+          escape_xml(message),
+          escape_xml(details)
+        )
+        .unwrap();
+      }
+      JunitTestCaseStatus::Errored { message, details } => {
+        writeln!(
+          xml,
+          r#"      <error message="{}">{}</error>"#,
+          escape_xml(message),
+          escape_xml(details)
+        )
+        .unwrap();
+      }
+    }
+    if !self.output.is_empty() {
+      writeln!(
+        xml,
+        "      <system-out>{}</system-out>",
+        escape_xml(&String::from_utf8_lossy(&self.output))
+      )
+      .unwrap();
+    }
+    writeln!(xml, "    </testcase>").unwrap();
+  }
+}
+
+impl TestReporter for JunitTestReporter {
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    // Make sure that modules without any tests still show up as (empty)
+    // suites, in the order they were run.
+    self.suite(&plan.origin);
+  }
+
+  fn report_wait(&mut self, description: &TestDescription) {
+    self.running.insert(description.id);
+  }
+
+  fn report_output(&mut self, output: &[u8]) {
+    if self.running.len() == 1 {
+      let id = *self.running.iter().next().unwrap();
+      self
+        .outputs
+        .entry(id)
+        .or_default()
+        .extend_from_slice(output);
+    }
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    self.running.remove(&description.id);
+    let status = match result {
+      TestResult::Ok => JunitTestCaseStatus::Passed,
+      TestResult::Ignored => JunitTestCaseStatus::Skipped,
+      TestResult::Failed(failure) => JunitTestCaseStatus::from_failure(failure),
+      TestResult::Cancelled => JunitTestCaseStatus::Failed {
+        message: "Test was cancelled".to_string(),
+        details: String::new(),
+      },
+    };
+    let case = JunitTestCase {
+      name: description.name.clone(),
+      is_step: false,
+      elapsed,
+      status,
+      output: self.outputs.remove(&description.id).unwrap_or_default(),
+    };
+    let mut steps = self
+      .pending_steps
+      .remove(&description.id)
+      .unwrap_or_default();
+    // Steps finish before their parents, but are listed in the order they
+    // were started in, which is the order of their ids.
+    steps.sort_by_key(|(id, _)| *id);
+
+    let suite = self.suite(&description.origin);
+    suite.cases.push(case);
+    suite.cases.extend(steps.into_iter().map(|(_, step)| step));
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    let case = JunitTestCase {
+      name: "(uncaught error)".to_string(),
+      is_step: false,
+      elapsed: 0,
+      status: JunitTestCaseStatus::Errored {
+        message: error
+          .exception_message
+          .trim_start_matches("Uncaught ")
+          .to_string(),
+        details: format_test_error(error),
+      },
+      output: vec![],
+    };
+    self.suite(origin).cases.push(case);
+  }
+
+  fn report_step_register(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    let status = match result {
+      TestStepResult::Ok => JunitTestCaseStatus::Passed,
+      TestStepResult::Ignored => JunitTestCaseStatus::Skipped,
+      TestStepResult::Failed(failure) => {
+        JunitTestCaseStatus::from_failure(failure)
+      }
+    };
+    let mut names = vec![desc.name.as_str()];
+    let mut parent_id = desc.parent_id;
+    while let Some(parent) = test_steps.get(&parent_id) {
+      names.push(&parent.name);
+      parent_id = parent.parent_id;
+    }
+    names.push(&desc.root_name);
+    names.reverse();
+
+    self.pending_steps.entry(desc.root_id).or_default().push((
+      desc.id,
+      JunitTestCase {
+        name: names.join(" > "),
+        is_step: true,
+        elapsed,
+        status,
+        output: vec![],
+      },
+    ));
+  }
+
+  fn report_summary(&mut self, _summary: &TestSummary, _elapsed: &Duration) {}
+
+  fn report_sigint(
+    &mut self,
+    _tests_pending: &HashSet<usize>,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+  }
+
+  fn flush_report(&mut self, elapsed: &Duration) -> Result<(), AnyError> {
+    let xml = self.serialize(elapsed);
+    match &self.path {
+      Some(path) => std::fs::write(path, xml).with_context(|| {
+        format!("Failed to write JUnit report to {}", path.display())
+      })?,
+      None => print!("{xml}"),
+    }
+    Ok(())
+  }
+}
+
+fn format_seconds(ms: u64) -> String {
+  format!("{:.3}", ms as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::TestLocation;
+  use super::*;
+
+  fn test_description(id: usize, name: &str) -> TestDescription {
+    TestDescription {
+      id,
+      name: name.to_string(),
+      ignore: false,
+      only: false,
+      origin: "https://example.com/a_test.ts".to_string(),
+      location: TestLocation {
+        file_name: "https://example.com/a_test.ts".to_string(),
+        line_number: 1,
+        column_number: 1,
+      },
+    }
+  }
+
+  fn step_description(
+    id: usize,
+    name: &str,
+    parent_id: usize,
+    level: usize,
+  ) -> TestStepDescription {
+    TestStepDescription {
+      id,
+      name: name.to_string(),
+      origin: "https://example.com/a_test.ts".to_string(),
+      location: test_description(0, "").location,
+      level,
+      parent_id,
+      root_id: 1,
+      root_name: "parent".to_string(),
+    }
+  }
+
+  #[test]
+  fn serialize_report() {
+    let mut reporter = JunitTestReporter::new(None);
+    let tests = IndexMap::new();
+    let mut test_steps = IndexMap::new();
+
+    let pass = test_description(0, "pass & <co>");
+    reporter.report_wait(&pass);
+    reporter.report_output(b"hello\x1b[0m\n");
+    reporter.report_result(&pass, &TestResult::Ok, 12);
+
+    let parent = test_description(1, "parent");
+    reporter.report_wait(&parent);
+    let step = step_description(2, "step", 1, 1);
+    let nested = step_description(3, "nested", 2, 2);
+    test_steps.insert(2, step.clone());
+    test_steps.insert(3, nested.clone());
+    reporter.report_step_result(
+      &nested,
+      &TestStepResult::Failed(TestFailure::LeakedResources(vec![
+        "a file".to_string()
+      ])),
+      1,
+      &tests,
+      &test_steps,
+    );
+    reporter.report_step_result(
+      &step,
+      &TestStepResult::Failed(TestFailure::FailedSteps(1)),
+      2,
+      &tests,
+      &test_steps,
+    );
+    reporter.report_result(
+      &parent,
+      &TestResult::Failed(TestFailure::FailedSteps(1)),
+      3,
+    );
+
+    let ignored = test_description(4, "ignored");
+    reporter.report_result(&ignored, &TestResult::Ignored, 0);
+
+    assert_eq!(
+      reporter.serialize(&Duration::from_millis(1500)),
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="deno test" tests="5" failures="3" errors="0" time="1.500">
+  <testsuite name="https://example.com/a_test.ts" tests="5" skipped="1" failures="3" errors="0" time="0.015">
+    <testcase name="pass &amp; &lt;co&gt;" classname="https://example.com/a_test.ts" time="0.012">
+      <system-out>hello
+</system-out>
+    </testcase>
+    <testcase name="parent" classname="https://example.com/a_test.ts" time="0.003">
+      <failure message="1 test step failed.">1 test step failed.</failure>
+    </testcase>
+    <testcase name="parent &gt; step" classname="https://example.com/a_test.ts" time="0.002">
+      <failure message="1 test step failed.">1 test step failed.</failure>
+    </testcase>
+    <testcase name="parent &gt; step &gt; nested" classname="https://example.com/a_test.ts" time="0.001">
+      <failure message="Leaking resources:">Leaking resources:
+  - a file</failure>
+    </testcase>
+    <testcase name="ignored" classname="https://example.com/a_test.ts" time="0.000">
+      <skipped/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+    );
+  }
+}
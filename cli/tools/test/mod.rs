@@ -3,6 +3,8 @@
 use crate::args::CliOptions;
 use crate::args::FilesConfig;
 use crate::args::TestOptions;
+use crate::args::TestReporterConfig;
+use crate::args::TestShard;
 use crate::args::TypeCheckMode;
 use crate::colors;
 use crate::display;
@@ -73,6 +75,14 @@ use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::WeakUnboundedSender;
 
+mod dot;
+mod junit;
+mod tap;
+
+use dot::DotTestReporter;
+use junit::JunitTestReporter;
+use tap::TapTestReporter;
+
 /// The test mode is used to determine how a specifier is to be tested.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TestMode {
@@ -342,6 +352,8 @@ struct TestSpecifiersOptions {
   fail_fast: Option<NonZeroUsize>,
   log_level: Option<log::Level>,
   specifier: TestSpecifierOptions,
+  reporter: TestReporterConfig,
+  junit_path: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -373,6 +385,174 @@ impl TestSummary {
   }
 }
 
+pub trait TestReporter {
+  fn report_register(&mut self, description: &TestDescription);
+  fn report_plan(&mut self, plan: &TestPlan);
+  fn report_wait(&mut self, description: &TestDescription);
+  fn report_output(&mut self, output: &[u8]);
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  );
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError);
+  fn report_step_register(&mut self, description: &TestStepDescription);
+  fn report_step_wait(&mut self, description: &TestStepDescription);
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  );
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration);
+  fn report_sigint(
+    &mut self,
+    tests_pending: &HashSet<usize>,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  );
+  /// Called once after the last event, or after a SIGINT was reported, to
+  /// write out anything the reporter has buffered.
+  fn flush_report(&mut self, elapsed: &Duration) -> Result<(), AnyError>;
+}
+
+/// Forwards every event to all of the wrapped reporters, in order.
+struct CompoundTestReporter {
+  reporters: Vec<Box<dyn TestReporter + Send>>,
+}
+
+impl TestReporter for CompoundTestReporter {
+  fn report_register(&mut self, description: &TestDescription) {
+    for reporter in &mut self.reporters {
+      reporter.report_register(description);
+    }
+  }
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    for reporter in &mut self.reporters {
+      reporter.report_plan(plan);
+    }
+  }
+
+  fn report_wait(&mut self, description: &TestDescription) {
+    for reporter in &mut self.reporters {
+      reporter.report_wait(description);
+    }
+  }
+
+  fn report_output(&mut self, output: &[u8]) {
+    for reporter in &mut self.reporters {
+      reporter.report_output(output);
+    }
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    for reporter in &mut self.reporters {
+      reporter.report_result(description, result, elapsed);
+    }
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    for reporter in &mut self.reporters {
+      reporter.report_uncaught_error(origin, error);
+    }
+  }
+
+  fn report_step_register(&mut self, description: &TestStepDescription) {
+    for reporter in &mut self.reporters {
+      reporter.report_step_register(description);
+    }
+  }
+
+  fn report_step_wait(&mut self, description: &TestStepDescription) {
+    for reporter in &mut self.reporters {
+      reporter.report_step_wait(description);
+    }
+  }
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    for reporter in &mut self.reporters {
+      reporter.report_step_result(desc, result, elapsed, tests, test_steps);
+    }
+  }
+
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
+    for reporter in &mut self.reporters {
+      reporter.report_summary(summary, elapsed);
+    }
+  }
+
+  fn report_sigint(
+    &mut self,
+    tests_pending: &HashSet<usize>,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    for reporter in &mut self.reporters {
+      reporter.report_sigint(tests_pending, tests, test_steps);
+    }
+  }
+
+  fn flush_report(&mut self, elapsed: &Duration) -> Result<(), AnyError> {
+    let mut errors = vec![];
+    for reporter in &mut self.reporters {
+      if let Err(err) = reporter.flush_report(elapsed) {
+        errors.push(err);
+      }
+    }
+    match errors.into_iter().next() {
+      Some(err) => Err(err),
+      None => Ok(()),
+    }
+  }
+}
+
+fn create_reporter(
+  parallel: bool,
+  echo_output: bool,
+  reporter_config: TestReporterConfig,
+  junit_path: Option<String>,
+) -> Box<dyn TestReporter + Send> {
+  let mut reporters: Vec<Box<dyn TestReporter + Send>> = vec![];
+  match reporter_config {
+    TestReporterConfig::Pretty => {
+      reporters.push(Box::new(PrettyTestReporter::new(parallel, echo_output)))
+    }
+    TestReporterConfig::Junit => {
+      reporters.push(Box::new(JunitTestReporter::new(None)))
+    }
+    TestReporterConfig::Tap => {
+      reporters.push(Box::new(TapTestReporter::new(parallel)))
+    }
+    TestReporterConfig::Dot => reporters.push(Box::new(DotTestReporter::new())),
+  }
+  if let Some(junit_path) = junit_path {
+    reporters.push(Box::new(JunitTestReporter::new(Some(PathBuf::from(
+      junit_path,
+    )))));
+  }
+  if reporters.len() == 1 {
+    reporters.pop().unwrap()
+  } else {
+    Box::new(CompoundTestReporter { reporters })
+  }
+}
+
 struct PrettyTestReporter {
   parallel: bool,
   echo_output: bool,
@@ -420,16 +600,7 @@ impl PrettyTestReporter {
   }
 
   fn to_relative_path_or_remote_url(&self, path_or_url: &str) -> String {
-    let url = Url::parse(path_or_url).unwrap();
-    if url.scheme() == "file" {
-      if let Some(mut r) = self.cwd.make_relative(&url) {
-        if !r.starts_with("../") {
-          r = format!("./{r}");
-        }
-        return r;
-      }
-    }
-    path_or_url.to_string()
+    to_relative_path_or_remote_url(&self.cwd, path_or_url)
   }
 
   fn force_report_step_wait(&mut self, description: &TestStepDescription) {
@@ -506,7 +677,9 @@ impl PrettyTestReporter {
       self.did_have_user_output = false;
     }
   }
+}
 
+impl TestReporter for PrettyTestReporter {
   fn report_register(&mut self, _description: &TestDescription) {}
 
   fn report_plan(&mut self, plan: &TestPlan) {
@@ -624,7 +797,7 @@ impl PrettyTestReporter {
           "{} =>",
           self.to_relative_path_or_remote_url(&desc.origin)
         )),
-        self.format_test_step_ancestry(desc, tests, test_steps)
+        format_test_step_ancestry(desc, tests, test_steps)
       );
       self.in_new_line = false;
       self.scope_test_id = Some(desc.id);
@@ -649,210 +822,243 @@ impl PrettyTestReporter {
   }
 
   fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
-    if !summary.failures.is_empty() || !summary.uncaught_errors.is_empty() {
-      #[allow(clippy::type_complexity)] // Type alias doesn't look better here
-      let mut failures_by_origin: BTreeMap<
-        String,
-        (Vec<(&TestDescription, &TestFailure)>, Option<&JsError>),
-      > = BTreeMap::default();
-      let mut failure_titles = vec![];
-      for (description, failure) in &summary.failures {
-        let (failures, _) = failures_by_origin
-          .entry(description.origin.clone())
-          .or_default();
-        failures.push((description, failure));
-      }
-      for (origin, js_error) in &summary.uncaught_errors {
-        let (_, uncaught_error) =
-          failures_by_origin.entry(origin.clone()).or_default();
-        let _ = uncaught_error.insert(js_error.as_ref());
-      }
-      // note: the trailing whitespace is intentional to get a red background
-      println!("\n{}\n", colors::white_bold_on_red(" ERRORS "));
-      for (origin, (failures, uncaught_error)) in failures_by_origin {
-        for (description, failure) in failures {
-          if !failure.hide_in_summary() {
-            let failure_title = self.format_test_for_summary(description);
-            println!("{}", &failure_title);
-            println!("{}: {}", colors::red_bold("error"), failure.to_string());
-            println!();
-            failure_titles.push(failure_title);
-          }
-        }
-        if let Some(js_error) = uncaught_error {
-          let failure_title = format!(
-            "{} (uncaught error)",
-            self.to_relative_path_or_remote_url(&origin)
-          );
+    report_summary(&self.cwd, summary, elapsed);
+    self.in_new_line = true;
+  }
+
+  fn report_sigint(
+    &mut self,
+    tests_pending: &HashSet<usize>,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    report_sigint(&self.cwd, tests_pending, tests, test_steps);
+    self.in_new_line = true;
+  }
+
+  fn flush_report(&mut self, _elapsed: &Duration) -> Result<(), AnyError> {
+    std::io::stdout().flush()?;
+    Ok(())
+  }
+}
+
+/// Prints the list of failures and the final pass/fail counts.
+fn report_summary(cwd: &Url, summary: &TestSummary, elapsed: &Duration) {
+  if !summary.failures.is_empty() || !summary.uncaught_errors.is_empty() {
+    #[allow(clippy::type_complexity)] // Type alias doesn't look better here
+    let mut failures_by_origin: BTreeMap<
+      String,
+      (Vec<(&TestDescription, &TestFailure)>, Option<&JsError>),
+    > = BTreeMap::default();
+    let mut failure_titles = vec![];
+    for (description, failure) in &summary.failures {
+      let (failures, _) = failures_by_origin
+        .entry(description.origin.clone())
+        .or_default();
+      failures.push((description, failure));
+    }
+    for (origin, js_error) in &summary.uncaught_errors {
+      let (_, uncaught_error) =
+        failures_by_origin.entry(origin.clone()).or_default();
+      let _ = uncaught_error.insert(js_error.as_ref());
+    }
+    // note: the trailing whitespace is intentional to get a red background
+    println!("\n{}\n", colors::white_bold_on_red(" ERRORS "));
+    for (origin, (failures, uncaught_error)) in failures_by_origin {
+      for (description, failure) in failures {
+        if !failure.hide_in_summary() {
+          let failure_title = format_test_for_summary(cwd, description);
           println!("{}", &failure_title);
-          println!(
-            "{}: {}",
-            colors::red_bold("error"),
-            format_test_error(js_error)
-          );
-          println!("This error was not caught from a test and caused the test runner to fail on the referenced module.");
-          println!("It most likely originated from a dangling promise, event/timeout handler or top-level code.");
+          println!("{}: {}", colors::red_bold("error"), failure.to_string());
           println!();
           failure_titles.push(failure_title);
         }
       }
-      // note: the trailing whitespace is intentional to get a red background
-      println!("{}\n", colors::white_bold_on_red(" FAILURES "));
-      for failure_title in failure_titles {
-        println!("{failure_title}");
+      if let Some(js_error) = uncaught_error {
+        let failure_title = format!(
+          "{} (uncaught error)",
+          to_relative_path_or_remote_url(cwd, &origin)
+        );
+        println!("{}", &failure_title);
+        println!(
+          "{}: {}",
+          colors::red_bold("error"),
+          format_test_error(js_error)
+        );
+        println!("This error was not caught from a test and caused the test runner to fail on the referenced module.");
+        println!("It most likely originated from a dangling promise, event/timeout handler or top-level code.");
+        println!();
+        failure_titles.push(failure_title);
       }
     }
+    // note: the trailing whitespace is intentional to get a red background
+    println!("{}\n", colors::white_bold_on_red(" FAILURES "));
+    for failure_title in failure_titles {
+      println!("{failure_title}");
+    }
+  }
 
-    let status = if summary.has_failed() {
-      colors::red("FAILED").to_string()
+  let status = if summary.has_failed() {
+    colors::red("FAILED").to_string()
+  } else {
+    colors::green("ok").to_string()
+  };
+
+  let get_steps_text = |count: usize| -> String {
+    if count == 0 {
+      String::new()
+    } else if count == 1 {
+      " (1 step)".to_string()
     } else {
-      colors::green("ok").to_string()
-    };
+      format!(" ({count} steps)")
+    }
+  };
 
-    let get_steps_text = |count: usize| -> String {
-      if count == 0 {
-        String::new()
-      } else if count == 1 {
-        " (1 step)".to_string()
-      } else {
-        format!(" ({count} steps)")
-      }
-    };
+  let mut summary_result = String::new();
 
-    let mut summary_result = String::new();
+  write!(
+    summary_result,
+    "{} passed{} | {} failed{}",
+    summary.passed,
+    get_steps_text(summary.passed_steps),
+    summary.failed,
+    get_steps_text(summary.failed_steps),
+  )
+  .unwrap();
 
+  let ignored_steps = get_steps_text(summary.ignored_steps);
+  if summary.ignored > 0 || !ignored_steps.is_empty() {
     write!(
       summary_result,
-      "{} passed{} | {} failed{}",
-      summary.passed,
-      get_steps_text(summary.passed_steps),
-      summary.failed,
-      get_steps_text(summary.failed_steps),
+      " | {} ignored{}",
+      summary.ignored, ignored_steps
     )
-    .unwrap();
-
-    let ignored_steps = get_steps_text(summary.ignored_steps);
-    if summary.ignored > 0 || !ignored_steps.is_empty() {
-      write!(
-        summary_result,
-        " | {} ignored{}",
-        summary.ignored, ignored_steps
-      )
-      .unwrap()
-    }
+    .unwrap()
+  }
 
-    if summary.measured > 0 {
-      write!(summary_result, " | {} measured", summary.measured,).unwrap();
-    }
+  if summary.measured > 0 {
+    write!(summary_result, " | {} measured", summary.measured,).unwrap();
+  }
 
-    if summary.filtered_out > 0 {
-      write!(summary_result, " | {} filtered out", summary.filtered_out)
-        .unwrap()
-    };
+  if summary.filtered_out > 0 {
+    write!(summary_result, " | {} filtered out", summary.filtered_out).unwrap()
+  };
 
-    println!(
-      "\n{} | {} {}\n",
-      status,
-      summary_result,
-      colors::gray(format!(
-        "({})",
-        display::human_elapsed(elapsed.as_millis())
-      )),
-    );
-    self.in_new_line = true;
-  }
+  println!(
+    "\n{} | {} {}\n",
+    status,
+    summary_result,
+    colors::gray(format!("({})", display::human_elapsed(elapsed.as_millis()))),
+  );
+}
 
-  fn report_sigint(
-    &mut self,
-    tests_pending: &HashSet<usize>,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) {
-    if tests_pending.is_empty() {
-      return;
-    }
-    let mut formatted_pending = BTreeSet::new();
-    for id in tests_pending {
-      if let Some(desc) = tests.get(id) {
-        formatted_pending.insert(self.format_test_for_summary(desc));
-      }
-      if let Some(desc) = test_steps.get(id) {
-        formatted_pending
-          .insert(self.format_test_step_for_summary(desc, tests, test_steps));
-      }
+/// Prints the tests that were still running when the process received a
+/// SIGINT.
+fn report_sigint(
+  cwd: &Url,
+  tests_pending: &HashSet<usize>,
+  tests: &IndexMap<usize, TestDescription>,
+  test_steps: &IndexMap<usize, TestStepDescription>,
+) {
+  if tests_pending.is_empty() {
+    return;
+  }
+  let mut formatted_pending = BTreeSet::new();
+  for id in tests_pending {
+    if let Some(desc) = tests.get(id) {
+      formatted_pending.insert(format_test_for_summary(cwd, desc));
     }
-    println!(
-      "\n{} The following tests were pending:\n",
-      colors::intense_blue("SIGINT")
-    );
-    for entry in formatted_pending {
-      println!("{}", entry);
+    if let Some(desc) = test_steps.get(id) {
+      formatted_pending
+        .insert(format_test_step_for_summary(cwd, desc, tests, test_steps));
     }
-    println!();
-    self.in_new_line = true;
   }
+  println!(
+    "\n{} The following tests were pending:\n",
+    colors::intense_blue("SIGINT")
+  );
+  for entry in formatted_pending {
+    println!("{}", entry);
+  }
+  println!();
+}
 
-  fn format_test_step_ancestry(
-    &self,
-    desc: &TestStepDescription,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) -> String {
-    let root;
-    let mut ancestor_names = vec![];
-    let mut current_desc = desc;
-    loop {
-      if let Some(step_desc) = test_steps.get(&current_desc.parent_id) {
-        ancestor_names.push(&step_desc.name);
-        current_desc = step_desc;
-      } else {
-        root = tests.get(&current_desc.parent_id).unwrap();
-        break;
-      }
-    }
-    ancestor_names.reverse();
-    let mut result = String::new();
-    result.push_str(&root.name);
-    result.push_str(" ... ");
-    for name in ancestor_names {
-      result.push_str(name);
-      result.push_str(" ... ");
+fn format_test_for_summary(cwd: &Url, desc: &TestDescription) -> String {
+  format!(
+    "{} {}",
+    &desc.name,
+    colors::gray(format!(
+      "=> {}:{}:{}",
+      to_relative_path_or_remote_url(cwd, &desc.location.file_name),
+      desc.location.line_number,
+      desc.location.column_number
+    ))
+  )
+}
+
+fn format_test_step_for_summary(
+  cwd: &Url,
+  desc: &TestStepDescription,
+  tests: &IndexMap<usize, TestDescription>,
+  test_steps: &IndexMap<usize, TestStepDescription>,
+) -> String {
+  let long_name = format_test_step_ancestry(desc, tests, test_steps);
+  format!(
+    "{} {}",
+    long_name,
+    colors::gray(format!(
+      "=> {}:{}:{}",
+      to_relative_path_or_remote_url(cwd, &desc.location.file_name),
+      desc.location.line_number,
+      desc.location.column_number
+    ))
+  )
+}
+
+/// Formats the name of a test step along with the names of all of its
+/// ancestors, separated by " ... ".
+fn format_test_step_ancestry(
+  desc: &TestStepDescription,
+  tests: &IndexMap<usize, TestDescription>,
+  test_steps: &IndexMap<usize, TestStepDescription>,
+) -> String {
+  let root;
+  let mut ancestor_names = vec![];
+  let mut current_desc = desc;
+  loop {
+    if let Some(step_desc) = test_steps.get(&current_desc.parent_id) {
+      ancestor_names.push(&step_desc.name);
+      current_desc = step_desc;
+    } else {
+      root = tests.get(&current_desc.parent_id).unwrap();
+      break;
     }
-    result.push_str(&desc.name);
-    result
   }
-
-  fn format_test_for_summary(&self, desc: &TestDescription) -> String {
-    format!(
-      "{} {}",
-      &desc.name,
-      colors::gray(format!(
-        "=> {}:{}:{}",
-        self.to_relative_path_or_remote_url(&desc.location.file_name),
-        desc.location.line_number,
-        desc.location.column_number
-      ))
-    )
+  ancestor_names.reverse();
+  let mut result = String::new();
+  result.push_str(&root.name);
+  result.push_str(" ... ");
+  for name in ancestor_names {
+    result.push_str(name);
+    result.push_str(" ... ");
   }
+  result.push_str(&desc.name);
+  result
+}
 
-  fn format_test_step_for_summary(
-    &self,
-    desc: &TestStepDescription,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) -> String {
-    let long_name = self.format_test_step_ancestry(desc, tests, test_steps);
-    format!(
-      "{} {}",
-      long_name,
-      colors::gray(format!(
-        "=> {}:{}:{}",
-        self.to_relative_path_or_remote_url(&desc.location.file_name),
-        desc.location.line_number,
-        desc.location.column_number
-      ))
-    )
+/// Formats a file URL relative to `cwd`, so that it reads like a path the
+/// user passed on the command line. Remote URLs are returned as is.
+fn to_relative_path_or_remote_url(cwd: &Url, path_or_url: &str) -> String {
+  let url = Url::parse(path_or_url).unwrap();
+  if url.scheme() == "file" {
+    if let Some(mut r) = cwd.make_relative(&url) {
+      if !r.starts_with("../") {
+        r = format!("./{r}");
+      }
+      return r;
+    }
   }
+  path_or_url.to_string()
 }
 
 fn abbreviate_test_error(js_error: &JsError) -> JsError {
@@ -1344,10 +1550,12 @@ async fn test_specifiers(
     .buffer_unordered(concurrent_jobs.get())
     .collect::<Vec<Result<Result<(), AnyError>, tokio::task::JoinError>>>();
 
-  let mut reporter = Box::new(PrettyTestReporter::new(
+  let mut reporter = create_reporter(
     concurrent_jobs.get() > 1,
     options.log_level != Some(Level::Error),
-  ));
+    options.reporter,
+    options.junit_path,
+  );
 
   let handler = {
     tokio::task::spawn(async move {
@@ -1443,7 +1651,7 @@ async fn test_specifiers(
                   summary.failures.push((
                     TestDescription {
                       id: description.id,
-                      name: reporter.format_test_step_ancestry(
+                      name: format_test_step_ancestry(
                         description,
                         &tests,
                         &test_steps,
@@ -1477,6 +1685,10 @@ async fn test_specifiers(
               &tests,
               &test_steps,
             );
+            let elapsed = Instant::now().duration_since(earlier);
+            if let Err(err) = reporter.flush_report(&elapsed) {
+              eprintln!("Test reporter failed to flush: {}", err)
+            }
             std::process::exit(130);
           }
         }
@@ -1487,6 +1699,7 @@ async fn test_specifiers(
 
       let elapsed = Instant::now().duration_since(earlier);
       reporter.report_summary(&summary, &elapsed);
+      reporter.flush_report(&elapsed)?;
 
       if used_only {
         return Err(generic_error(
@@ -1559,37 +1772,66 @@ fn is_supported_test_ext(path: &Path) -> bool {
 /// `TestMode::Documentation`.
 /// - Specifiers matching the `is_supported_test_path` are marked as `TestMode::Executable`.
 /// - Specifiers matching both predicates are marked as `TestMode::Both`
+///
+/// If a `shard` is given, only the specifiers that belong to it are returned.
 fn collect_specifiers_with_test_mode(
   files: &FilesConfig,
   include_inline: &bool,
+  shard: Option<&TestShard>,
 ) -> Result<Vec<(ModuleSpecifier, TestMode)>, AnyError> {
   let module_specifiers = collect_specifiers(files, is_supported_test_path)?;
 
-  if *include_inline {
-    return collect_specifiers(files, is_supported_test_ext).map(
-      |specifiers| {
-        specifiers
-          .into_iter()
-          .map(|specifier| {
-            let mode = if module_specifiers.contains(&specifier) {
-              TestMode::Both
-            } else {
-              TestMode::Documentation
-            };
-
-            (specifier, mode)
-          })
-          .collect()
-      },
-    );
-  }
+  let specifiers_with_mode = if *include_inline {
+    collect_specifiers(files, is_supported_test_ext)?
+      .into_iter()
+      .map(|specifier| {
+        let mode = if module_specifiers.contains(&specifier) {
+          TestMode::Both
+        } else {
+          TestMode::Documentation
+        };
 
-  let specifiers_with_mode = module_specifiers
-    .into_iter()
-    .map(|specifier| (specifier, TestMode::Executable))
-    .collect();
+        (specifier, mode)
+      })
+      .collect()
+  } else {
+    module_specifiers
+      .into_iter()
+      .map(|specifier| (specifier, TestMode::Executable))
+      .collect()
+  };
 
-  Ok(specifiers_with_mode)
+  Ok(match shard {
+    Some(shard) => filter_shard(specifiers_with_mode, shard),
+    None => specifiers_with_mode,
+  })
+}
+
+/// Keeps the specifiers that belong to `shard`, without changing their order.
+///
+/// Specifiers are dealt out to the shards round-robin in sorted order, which
+/// doesn't depend on the order they were collected in. As long as every
+/// shard sees the same set of files, each of them ends up in exactly one
+/// shard and the shards differ in size by at most one.
+fn filter_shard<T>(
+  specifiers: Vec<(ModuleSpecifier, T)>,
+  shard: &TestShard,
+) -> Vec<(ModuleSpecifier, T)> {
+  let mut sorted = specifiers
+    .iter()
+    .map(|(specifier, _)| specifier.as_str())
+    .collect::<Vec<_>>();
+  sorted.sort_unstable();
+  let in_shard = sorted
+    .into_iter()
+    .enumerate()
+    .filter(|(position, _)| position % shard.total == shard.index - 1)
+    .map(|(_, specifier)| specifier.to_string())
+    .collect::<HashSet<_>>();
+  specifiers
+    .into_iter()
+    .filter(|(specifier, _)| in_shard.contains(specifier.as_str()))
+    .collect()
 }
 
 /// Collects module and document specifiers with test modes via
@@ -1604,8 +1846,10 @@ async fn fetch_specifiers_with_test_mode(
   file_fetcher: &FileFetcher,
   files: &FilesConfig,
   doc: &bool,
+  shard: Option<&TestShard>,
 ) -> Result<Vec<(ModuleSpecifier, TestMode)>, AnyError> {
-  let mut specifiers_with_mode = collect_specifiers_with_test_mode(files, doc)?;
+  let mut specifiers_with_mode =
+    collect_specifiers_with_test_mode(files, doc, shard)?;
 
   for (specifier, mode) in &mut specifiers_with_mode {
     let file = file_fetcher
@@ -1641,6 +1885,7 @@ pub async fn run_tests(
     file_fetcher,
     &test_options.files,
     &test_options.doc,
+    test_options.shard.as_ref(),
   )
   .await?;
 
@@ -1682,6 +1927,8 @@ pub async fn run_tests(
         shuffle: test_options.shuffle,
         trace_ops: test_options.trace_ops,
       },
+      reporter: test_options.reporter,
+      junit_path: test_options.junit_path,
     },
   )
   .await?;
@@ -1838,6 +2085,7 @@ pub async fn run_tests_with_watch(
         &file_fetcher,
         &test_options.files,
         &test_options.doc,
+        test_options.shard.as_ref(),
       )
       .await?
       .into_iter()
@@ -1875,6 +2123,8 @@ pub async fn run_tests_with_watch(
             shuffle: test_options.shuffle,
             trace_ops: test_options.trace_ops,
           },
+          reporter: test_options.reporter,
+          junit_path: test_options.junit_path.clone(),
         },
       )
       .await?;
@@ -2160,4 +2410,29 @@ mod inner_test {
     assert!(!is_supported_test_path(Path::new("notatest.js")));
     assert!(!is_supported_test_path(Path::new("NotAtest.ts")));
   }
+
+  #[test]
+  fn test_filter_shard() {
+    let specifiers = ["d", "b", "e", "a", "c"]
+      .iter()
+      .map(|name| {
+        let specifier =
+          ModuleSpecifier::parse(&format!("file:///{name}_test.ts")).unwrap();
+        (specifier, *name)
+      })
+      .collect::<Vec<_>>();
+    let names = |index| {
+      filter_shard(specifiers.clone(), &TestShard { index, total: 2 })
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect::<Vec<_>>()
+    };
+    // The input order is kept, but the split is based on the sorted order.
+    assert_eq!(names(1), vec!["e", "a", "c"]);
+    assert_eq!(names(2), vec!["d", "b"]);
+
+    let all =
+      filter_shard(specifiers.clone(), &TestShard { index: 1, total: 1 });
+    assert_eq!(all, specifiers);
+  }
 }
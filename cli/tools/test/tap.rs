@@ -0,0 +1,303 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write;
+use std::time::Duration;
+
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use indexmap::IndexMap;
+
+use super::format_test_error;
+use super::format_test_step_ancestry;
+use super::to_relative_path_or_remote_url;
+use super::TestDescription;
+use super::TestLocation;
+use super::TestPlan;
+use super::TestReporter;
+use super::TestResult;
+use super::TestStepDescription;
+use super::TestStepResult;
+use super::TestSummary;
+
+/// Prints results in the Test Anything Protocol, version 14.
+///
+/// Every test is a test point and its steps are reported as a subtest of it.
+/// Steps are held back until the test they belong to has finished, so that
+/// the output stays well-formed when tests run in parallel.
+pub struct TapTestReporter {
+  parallel: bool,
+  cwd: Url,
+  did_print_header: bool,
+  /// Number of top level test points printed so far.
+  count: usize,
+  /// Finished steps by the id of their root test.
+  steps: HashMap<usize, Vec<(TestStepDescription, TestStepResult)>>,
+}
+
+/// How a test point finished, independently of whether it is a test or a
+/// test step.
+enum TapStatus {
+  Ok,
+  Skip,
+  NotOk(String),
+}
+
+impl From<&TestResult> for TapStatus {
+  fn from(result: &TestResult) -> Self {
+    match result {
+      TestResult::Ok => TapStatus::Ok,
+      TestResult::Ignored => TapStatus::Skip,
+      TestResult::Failed(failure) => TapStatus::NotOk(failure.to_string()),
+      TestResult::Cancelled => {
+        TapStatus::NotOk("Test was cancelled".to_string())
+      }
+    }
+  }
+}
+
+impl From<&TestStepResult> for TapStatus {
+  fn from(result: &TestStepResult) -> Self {
+    match result {
+      TestStepResult::Ok => TapStatus::Ok,
+      TestStepResult::Ignored => TapStatus::Skip,
+      TestStepResult::Failed(failure) => TapStatus::NotOk(failure.to_string()),
+    }
+  }
+}
+
+impl TapTestReporter {
+  pub fn new(parallel: bool) -> Self {
+    Self {
+      parallel,
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      did_print_header: false,
+      count: 0,
+      steps: Default::default(),
+    }
+  }
+
+  fn print_header(&mut self) {
+    if !self.did_print_header {
+      println!("TAP version 14");
+      self.did_print_header = true;
+    }
+  }
+
+  fn print_test_point(
+    &self,
+    level: usize,
+    number: usize,
+    name: &str,
+    location: Option<&TestLocation>,
+    status: &TapStatus,
+  ) {
+    let indent = "    ".repeat(level);
+    let (ok, directive) = match status {
+      TapStatus::Ok => ("ok", ""),
+      TapStatus::Skip => ("ok", " # SKIP"),
+      TapStatus::NotOk(_) => ("not ok", ""),
+    };
+    println!(
+      "{}{} {} - {}{}",
+      indent,
+      ok,
+      number,
+      escape_description(name),
+      directive
+    );
+    if let TapStatus::NotOk(message) = status {
+      let message = console_static_text::ansi::strip_ansi_codes(message);
+      println!("{indent}  ---");
+      println!("{indent}  message: {}", serde_json::json!(message));
+      println!("{indent}  severity: fail");
+      if let Some(location) = location {
+        let file =
+          to_relative_path_or_remote_url(&self.cwd, &location.file_name);
+        println!("{indent}  at:");
+        println!("{indent}    file: {}", serde_json::json!(file));
+        println!("{indent}    line: {}", location.line_number);
+        println!("{indent}    column: {}", location.column_number);
+      }
+      println!("{indent}  ...");
+    }
+  }
+
+  /// Prints the steps whose parent has the id `parent_id` as a subtest named
+  /// `name`, with the test points indented to `level`.
+  fn print_subtest(
+    &self,
+    level: usize,
+    name: &str,
+    parent_id: usize,
+    children: &HashMap<usize, Vec<&(TestStepDescription, TestStepResult)>>,
+  ) {
+    let steps = match children.get(&parent_id) {
+      Some(steps) => steps,
+      None => return,
+    };
+    let indent = "    ".repeat(level);
+    println!("{}# Subtest: {}", indent, name);
+    for (index, (desc, result)) in steps.iter().enumerate() {
+      self.print_subtest(level + 1, &desc.name, desc.id, children);
+      self.print_test_point(
+        level,
+        index + 1,
+        &desc.name,
+        Some(&desc.location),
+        &result.into(),
+      );
+    }
+    println!("{}1..{}", indent, steps.len());
+  }
+}
+
+impl TestReporter for TapTestReporter {
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    self.print_header();
+    // Plans for different modules interleave with their tests when running
+    // in parallel, so they would only be misleading.
+    if !self.parallel {
+      println!(
+        "# {}",
+        to_relative_path_or_remote_url(&self.cwd, &plan.origin)
+      );
+    }
+  }
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_output(&mut self, output: &[u8]) {
+    self.print_header();
+    // Anything that isn't a comment would be parsed as TAP.
+    for line in String::from_utf8_lossy(output).lines() {
+      println!("# {}", line);
+    }
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    _elapsed: u64,
+  ) {
+    self.print_header();
+    let mut steps = self.steps.remove(&description.id).unwrap_or_default();
+    steps.sort_by_key(|(desc, _)| desc.id);
+    let mut children: HashMap<usize, Vec<_>> = HashMap::new();
+    for step in &steps {
+      children.entry(step.0.parent_id).or_default().push(step);
+    }
+    self.print_subtest(1, &description.name, description.id, &children);
+
+    self.count += 1;
+    self.print_test_point(
+      0,
+      self.count,
+      &description.name,
+      Some(&description.location),
+      &result.into(),
+    );
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    self.print_header();
+    self.count += 1;
+    let name = format!(
+      "{} (uncaught error)",
+      to_relative_path_or_remote_url(&self.cwd, origin)
+    );
+    self.print_test_point(
+      0,
+      self.count,
+      &name,
+      None,
+      &TapStatus::NotOk(format_test_error(error)),
+    );
+  }
+
+  fn report_step_register(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    _elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self
+      .steps
+      .entry(desc.root_id)
+      .or_default()
+      .push((desc.clone(), result.clone()));
+  }
+
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
+    self.print_header();
+    println!("1..{}", self.count);
+    println!("# tests {}", summary.total);
+    println!("# pass {}", summary.passed);
+    println!("# fail {}", summary.failed);
+    println!("# skip {}", summary.ignored);
+    println!("# duration_ms {}", elapsed.as_millis());
+  }
+
+  fn report_sigint(
+    &mut self,
+    tests_pending: &HashSet<usize>,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.print_header();
+    let mut pending = tests_pending
+      .iter()
+      .filter_map(|id| {
+        tests.get(id).map(|desc| desc.name.clone()).or_else(|| {
+          test_steps
+            .get(id)
+            .map(|desc| format_test_step_ancestry(desc, tests, test_steps))
+        })
+      })
+      .collect::<Vec<_>>();
+    pending.sort();
+    for name in pending {
+      println!("# pending: {}", name);
+    }
+    println!("Bail out! SIGINT");
+  }
+
+  fn flush_report(&mut self, _elapsed: &Duration) -> Result<(), AnyError> {
+    std::io::stdout().flush()?;
+    Ok(())
+  }
+}
+
+/// Escapes the characters that have a meaning in the description of a test
+/// point.
+fn escape_description(name: &str) -> String {
+  name
+    .replace('\\', "\\\\")
+    .replace('#', "\\#")
+    .replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_escape_description() {
+    assert_eq!(escape_description("plain name"), "plain name");
+    assert_eq!(escape_description("issue #123"), "issue \\#123");
+    assert_eq!(escape_description("a\\b"), "a\\\\b");
+    assert_eq!(escape_description("multi\nline"), "multi line");
+  }
+}
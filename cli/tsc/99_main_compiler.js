@@ -892,6 +892,82 @@ delete Object.prototype.__proto__;
     ops.op_respond({ id, data });
   }
 
+  const JSX_TAG_WORD_PATTERN = "[a-zA-Z0-9:\\-\\._$]*";
+
+  /**
+   * The language service of TypeScript 5.0 does not provide linked editing
+   * ranges, so this mirrors the implementation which landed in TypeScript
+   * 5.1.
+   * @param {string} specifier
+   * @param {number} position
+   */
+  function getLinkedEditingRangeAtPosition(specifier, position) {
+    const sourceFile = languageService.getProgram()?.getSourceFile(specifier);
+    if (!sourceFile) {
+      return null;
+    }
+    const token = ts.findPrecedingToken(position, sourceFile);
+    if (!token || token.parent.kind === ts.SyntaxKind.SourceFile) {
+      return null;
+    }
+
+    if (ts.isJsxFragment(token.parent.parent)) {
+      const { openingFragment, closingFragment } = token.parent.parent;
+      if (
+        ts.containsParseError(openingFragment) ||
+        ts.containsParseError(closingFragment)
+      ) {
+        return null;
+      }
+      // only link the cursors right after the brackets, `<| ></| >`
+      const openPos = openingFragment.getStart(sourceFile) + "<".length;
+      const closePos = closingFragment.getStart(sourceFile) + "</".length;
+      if (position !== openPos && position !== closePos) {
+        return null;
+      }
+      return {
+        ranges: [
+          { start: openPos, length: 0 },
+          { start: closePos, length: 0 },
+        ],
+        wordPattern: JSX_TAG_WORD_PATTERN,
+      };
+    }
+
+    const tag = ts.findAncestor(
+      token.parent,
+      (node) => ts.isJsxOpeningElement(node) || ts.isJsxClosingElement(node),
+    );
+    if (!tag) {
+      return null;
+    }
+    const { openingElement, closingElement } = tag.parent;
+    const openStart = openingElement.tagName.getStart(sourceFile);
+    const openEnd = openingElement.tagName.end;
+    const closeStart = closingElement.tagName.getStart(sourceFile);
+    const closeEnd = closingElement.tagName.end;
+    // only link the cursors when within a tag name and both names are equal
+    if (
+      !(openStart <= position && position <= openEnd) &&
+      !(closeStart <= position && position <= closeEnd)
+    ) {
+      return null;
+    }
+    if (
+      openingElement.tagName.getText(sourceFile) !==
+        closingElement.tagName.getText(sourceFile)
+    ) {
+      return null;
+    }
+    return {
+      ranges: [
+        { start: openStart, length: openEnd - openStart },
+        { start: closeStart, length: closeEnd - closeStart },
+      ],
+      wordPattern: JSX_TAG_WORD_PATTERN,
+    };
+  }
+
   /**
    * @param {LanguageServerRequest} request
    */
@@ -1105,6 +1181,12 @@ delete Object.prototype.__proto__;
           ),
         );
       }
+      case "getLinkedEditingRange": {
+        return respond(
+          id,
+          getLinkedEditingRangeAtPosition(request.specifier, request.position),
+        );
+      }
       case "getNavigateToItems": {
         return respond(
           id,
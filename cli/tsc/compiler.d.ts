@@ -73,6 +73,7 @@ declare global {
     | GetDocumentHighlightsRequest
     | GetEncodedSemanticClassifications
     | GetImplementationRequest
+    | GetLinkedEditingRangeRequest
     | GetNavigateToItems
     | GetNavigationTree
     | GetOutliningSpans
@@ -191,6 +192,12 @@ declare global {
     position: number;
   }
 
+  interface GetLinkedEditingRangeRequest extends BaseLanguageServerRequest {
+    method: "getLinkedEditingRange";
+    specifier: string;
+    position: number;
+  }
+
   interface GetNavigateToItems extends BaseLanguageServerRequest {
     method: "getNavigateToItems";
     search: string;
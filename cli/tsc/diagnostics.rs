@@ -1,5 +1,13 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::util::sarif::SarifColumnKind;
+use crate::util::sarif::SarifLevel;
+use crate::util::sarif::SarifLocation;
+use crate::util::sarif::SarifLog;
+use crate::util::sarif::SarifRegion;
+use crate::util::sarif::SarifResult;
+use crate::util::sarif::SarifRule;
+use crate::util::sarif::SarifRun;
 use deno_runtime::colors;
 
 use deno_core::serde::Deserialize;
@@ -305,6 +313,52 @@ impl Diagnostic {
   fn is_error(&self) -> bool {
     self.category == DiagnosticCategory::Error
   }
+
+  fn message(&self) -> String {
+    match &self.message_chain {
+      Some(message_chain) => message_chain.format_message(0),
+      None => format_message(
+        self.message_text.as_deref().unwrap_or_default(),
+        &self.code,
+      ),
+    }
+  }
+
+  fn sarif_location(&self) -> Option<SarifLocation> {
+    let file_name = self.file_name.clone()?;
+    let region = match (&self.start, &self.end) {
+      (Some(start), Some(end)) => Some(SarifRegion {
+        start_line: start.line as usize + 1,
+        start_column: start.character as usize + 1,
+        end_line: end.line as usize + 1,
+        end_column: end.character as usize + 1,
+        ..Default::default()
+      }),
+      _ => None,
+    };
+    Some(SarifLocation::new(file_name, region))
+  }
+
+  fn to_sarif_result(&self) -> SarifResult {
+    let level = match self.category {
+      DiagnosticCategory::Error => SarifLevel::Error,
+      DiagnosticCategory::Warning => SarifLevel::Warning,
+      DiagnosticCategory::Suggestion | DiagnosticCategory::Message => {
+        SarifLevel::Note
+      }
+    };
+    let mut result = SarifResult::new(
+      level,
+      self.message(),
+      self.sarif_location().into_iter().collect(),
+    );
+    for info in self.related_information.iter().flatten() {
+      if let Some(location) = info.sarif_location() {
+        result = result.with_related_location(info.message(), location);
+      }
+    }
+    result
+  }
 }
 
 impl fmt::Display for Diagnostic {
@@ -336,6 +390,23 @@ impl Diagnostics {
   pub fn is_empty(&self) -> bool {
     self.0.is_empty()
   }
+
+  /// Converts the diagnostics to the SARIF format used by code scanning
+  /// tools. The rules are identified by their TypeScript error code.
+  pub fn to_sarif(&self) -> SarifLog {
+    // positions from tsc are in UTF-16 code units
+    let mut run = SarifRun::new("deno check", SarifColumnKind::Utf16CodeUnits);
+    for diagnostic in &self.0 {
+      run.add_result(
+        SarifRule {
+          id: format!("TS{}", diagnostic.code),
+          ..Default::default()
+        },
+        diagnostic.to_sarif_result(),
+      );
+    }
+    SarifLog::new(run)
+  }
 }
 
 impl<'de> Deserialize<'de> for Diagnostics {
@@ -581,4 +652,81 @@ mod tests {
     let actual = diagnostics.to_string();
     assert_eq!(strip_ansi_codes(&actual), "TS2552 [ERROR]: Cannot find name \'foo_Bar\'. Did you mean \'foo_bar\'?\nfoo_Bar();\n~~~~~~~\n    at test.ts:8:1\n\n    \'foo_bar\' is declared here.\n    function foo_bar() {\n             ~~~~~~~\n        at test.ts:4:10");
   }
+
+  #[test]
+  fn test_diagnostics_to_sarif() {
+    let value = json!([
+      {
+        "start": {
+          "line": 7,
+          "character": 0
+        },
+        "end": {
+          "line": 7,
+          "character": 7
+        },
+        "fileName": "file:///test.ts",
+        "messageText": "Cannot find name 'foo_Bar'. Did you mean 'foo_bar'?",
+        "sourceLine": "foo_Bar();",
+        "relatedInformation": [
+          {
+            "start": {
+              "line": 3,
+              "character": 9
+            },
+            "end": {
+              "line": 3,
+              "character": 16
+            },
+            "fileName": "file:///test.ts",
+            "messageText": "'foo_bar' is declared here.",
+            "sourceLine": "function foo_bar() {",
+            "category": 3,
+            "code": 2728
+          }
+        ],
+        "category": 1,
+        "code": 2552
+      }
+    ]);
+    let diagnostics: Diagnostics = serde_json::from_value(value).unwrap();
+    let sarif = serde_json::to_value(diagnostics.to_sarif()).unwrap();
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"], json!([{ "id": "TS2552" }]));
+    assert_eq!(
+      run["results"][0],
+      json!({
+        "ruleId": "TS2552",
+        "ruleIndex": 0,
+        "level": "error",
+        "message": {
+          "text": "Cannot find name 'foo_Bar'. Did you mean 'foo_bar'?"
+        },
+        "locations": [{
+          "physicalLocation": {
+            "artifactLocation": { "uri": "file:///test.ts" },
+            "region": {
+              "startLine": 8,
+              "startColumn": 1,
+              "endLine": 8,
+              "endColumn": 8
+            }
+          }
+        }],
+        "relatedLocations": [{
+          "id": 0,
+          "message": { "text": "'foo_bar' is declared here." },
+          "physicalLocation": {
+            "artifactLocation": { "uri": "file:///test.ts" },
+            "region": {
+              "startLine": 4,
+              "startColumn": 10,
+              "endLine": 4,
+              "endColumn": 17
+            }
+          }
+        }]
+      })
+    );
+  }
 }
@@ -1011,6 +1011,26 @@ declare namespace Deno {
      * @default {"inherit"}
      */
     permissions?: PermissionOptions;
+    /** Number of iterations to run before measuring, so that the code is
+     * optimized by the time it is measured. At least one iteration is always
+     * run.
+     *
+     * Defaults to the value of `--warmup`, or to at least 20 iterations and
+     * 10ms. */
+    warmup?: number;
+    /** Minimum number of measured iterations.
+     *
+     * Defaults to the value of `--min-iterations`, or to 10. */
+    minIterations?: number;
+    /** Minimum time in milliseconds to spend measuring the benchmark.
+     *
+     * Defaults to the value of `--min-time`, or to 500. */
+    minTime?: number;
+    /** Maximum time in milliseconds to spend running the benchmark, including
+     * the warmup. Takes precedence over `minIterations` and `minTime`.
+     *
+     * Defaults to the value of `--max-time`, or to no limit. */
+    maxTime?: number;
   }
 
   /**
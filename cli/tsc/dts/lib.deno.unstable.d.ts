@@ -1529,10 +1529,26 @@ declare namespace Deno {
    * `localStorage` persistence). More information about the origin storage key
    * can be found in the Deno Manual.
    *
-   * @tags allow-read, allow-write
+   * When the path is an `http://` or `https://` URL, the database is opened as
+   * a remote database served at that URL. The access token used to
+   * authenticate with the server is read from the `DENO_KV_ACCESS_TOKEN`
+   * environment variable. Network access to the URL and read access to the
+   * environment variable are required.
+   *
+   * The `historyRetention` option enables history mode for the database,
+   * which allows reading at past versionstamps. It is the retention window in
+   * milliseconds for which old versions of keys are kept. Setting it to `0`
+   * disables history mode and discards the recorded history. If it is not
+   * set, the mode stored in the database is left as is. Remote databases do
+   * not support this option.
+   *
+   * @tags allow-read, allow-write, allow-net, allow-env
    * @category KV
    */
-  export function openKv(path?: string): Promise<Deno.Kv>;
+  export function openKv(
+    path?: string,
+    options?: { historyRetention?: number },
+  ): Promise<Deno.Kv>;
 
   /** **UNSTABLE**: New API, yet to be vetted.
    *
@@ -1625,7 +1641,9 @@ declare namespace Deno {
    * mutation is applied to the key.
    *
    * - `set` - Sets the value of the key to the given value, overwriting any
-   *   existing value.
+   *   existing value. Optionally an `expireIn` option can be specified to
+   *   set a time-to-live (TTL) for the key, in milliseconds. Once the TTL has
+   *   passed, the key is treated as if it does not exist.
    * - `delete` - Deletes the key from the database. The mutation is a no-op if
    *   the key does not exist.
    * - `sum` - Adds the given value to the existing value of the key. Both the
@@ -1647,7 +1665,7 @@ declare namespace Deno {
   export type KvMutation =
     & { key: KvKey }
     & (
-      | { type: "set"; value: unknown }
+      | { type: "set"; value: unknown; expireIn?: number }
       | { type: "delete" }
       | { type: "sum"; value: KvU64 }
       | { type: "max"; value: KvU64 }
@@ -1744,6 +1762,14 @@ declare namespace Deno {
      * by the list operation.
      */
     consistency?: KvConsistencyLevel;
+    /**
+     * If set, the list operation reads the database as it was right after the
+     * write with this versionstamp was committed. Unlike reads at the current
+     * state, all batches are then consistent with each other. This requires
+     * history mode to be enabled for the database, and the versionstamp to be
+     * within its retention window.
+     */
+    atVersionstamp?: string;
     /**
      * The size of the batches in which the list operation is performed. Larger
      * or smaller batch sizes may positively or negatively affect the
@@ -1859,13 +1885,43 @@ declare namespace Deno {
     /**
      * Add to the operation a mutation that sets the value of the specified key
      * to the specified value if all checks pass during the commit.
+     *
+     * Optionally an `expireIn` option can be specified to set a time-to-live
+     * (TTL) for the key, in milliseconds. Once the TTL has passed, the key is
+     * treated as if it does not exist.
      */
-    set(key: KvKey, value: unknown): this;
+    set(key: KvKey, value: unknown, options?: { expireIn?: number }): this;
     /**
      * Add to the operation a mutation that deletes the specified key if all
      * checks pass during the commit.
      */
     delete(key: KvKey): this;
+    /**
+     * Add to the operation a mutation that deletes all keys matching the
+     * specified selector if all checks pass during the commit. The selector
+     * is interpreted in the same way as the selector passed to
+     * {@linkcode Deno.Kv.list}.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * await db.atomic()
+     *   .deleteRange({ prefix: ["sessions"] })
+     *   .commit();
+     * ```
+     */
+    deleteRange(selector: KvListSelector): this;
+    /**
+     * Add to the operation a mutation that enqueues a value into the queue
+     * if all checks pass during the commit.
+     */
+    enqueue(
+      value: unknown,
+      options?: {
+        delay?: number;
+        keysIfUndelivered?: Deno.KvKey[];
+        backoffSchedule?: number[];
+      },
+    ): this;
     /**
      * Commit the operation to the KV store. Returns a value indicating whether
      * checks passed and mutations were performed. If the operation failed
@@ -1939,10 +1995,16 @@ declare namespace Deno {
      * use cases can benefit from using a weaker consistency level. For more
      * information on consistency levels, see the documentation for
      * {@linkcode Deno.KvConsistencyLevel}.
+     *
+     * The `atVersionstamp` option can be used to read the value as it was right
+     * after the write with the given versionstamp was committed. This requires
+     * history mode to be enabled for the database, by passing the
+     * `historyRetention` option to {@linkcode Deno.openKv}. Reading at a
+     * versionstamp outside of the retention window throws.
      */
     get<T = unknown>(
       key: KvKey,
-      options?: { consistency?: KvConsistencyLevel },
+      options?: { consistency?: KvConsistencyLevel; atVersionstamp?: string },
     ): Promise<KvEntryMaybe<T>>;
 
     /**
@@ -1968,10 +2030,14 @@ declare namespace Deno {
      * use cases can benefit from using a weaker consistency level. For more
      * information on consistency levels, see the documentation for
      * {@linkcode Deno.KvConsistencyLevel}.
+     *
+     * The `atVersionstamp` option can be used to read the values as they were
+     * right after the write with the given versionstamp was committed. See
+     * {@linkcode Deno.Kv.get} for details.
      */
     getMany<T extends readonly unknown[]>(
       keys: readonly [...{ [K in keyof T]: KvKey }],
-      options?: { consistency?: KvConsistencyLevel },
+      options?: { consistency?: KvConsistencyLevel; atVersionstamp?: string },
     ): Promise<{ [K in keyof T]: KvEntryMaybe<T[K]> }>;
     /**
      * Set the value for the given key in the database. If a value already
@@ -1981,8 +2047,23 @@ declare namespace Deno {
      * const db = await Deno.openKv();
      * await db.set(["foo"], "bar");
      * ```
+     *
+     * Optionally an `expireIn` option can be specified to set a time-to-live
+     * (TTL) for the key, in milliseconds. Once the TTL has passed, the key is
+     * treated as if it does not exist: reads do not return it, and checks
+     * behave as if it were absent. Expired keys are deleted from storage in
+     * the background.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * await db.set(["foo"], "bar", { expireIn: 1000 });
+     * ```
      */
-    set(key: KvKey, value: unknown): Promise<KvCommitResult>;
+    set(
+      key: KvKey,
+      value: unknown,
+      options?: { expireIn?: number },
+    ): Promise<KvCommitResult>;
 
     /**
      * Delete the value for the given key from the database. If no value exists
@@ -1995,6 +2076,74 @@ declare namespace Deno {
      */
     delete(key: KvKey): Promise<void>;
 
+    /**
+     * Add a value into the database queue to be delivered to the queue
+     * listener via {@linkcode Deno.Kv.listenQueue}.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * await db.enqueue("bar");
+     * ```
+     *
+     * The `delay` option can be used to specify the delay (in milliseconds)
+     * of the value delivery. The default delay is 0, which means immediate
+     * delivery.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * await db.enqueue("bar", { delay: 60000 });
+     * ```
+     *
+     * The `keysIfUndelivered` option can be used to specify the keys to
+     * be set if the value is not successfully delivered to the queue
+     * listener after several attempts. The values are set to the value of
+     * the queued message.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * await db.enqueue("bar", { keysIfUndelivered: [["foo", "bar"]] });
+     * ```
+     *
+     * The `backoffSchedule` option can be used to specify the retry policy
+     * for failed deliveries. Each interval is the delay (in milliseconds)
+     * before the next delivery attempt. An empty schedule means the value is
+     * not retried at all.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * await db.enqueue("bar", { backoffSchedule: [1000, 5000, 10000] });
+     * ```
+     */
+    enqueue(
+      value: unknown,
+      options?: {
+        delay?: number;
+        keysIfUndelivered?: Deno.KvKey[];
+        backoffSchedule?: number[];
+      },
+    ): Promise<KvCommitResult>;
+
+    /**
+     * Listen for queue values to be delivered from the database queue, which
+     * were enqueued with {@linkcode Deno.Kv.enqueue}. The provided handler
+     * callback is invoked on every dequeued value. A failed callback
+     * invocation is automatically retried multiple times until it succeeds
+     * or until the maximum number of retries is reached.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * db.listenQueue(async (msg: unknown) => {
+     *   await db.set(["foo"], msg);
+     * });
+     * ```
+     *
+     * The returned promise resolves when the database is closed. Only one
+     * listener may be active for a given {@linkcode Deno.Kv} at a time.
+     */
+    listenQueue(
+      handler: (value: unknown) => Promise<void> | void,
+    ): Promise<void>;
+
     /**
      * Retrieve a list of keys in the database. The returned list is an
      * {@linkcode Deno.KvListIterator} which can be used to iterate over the
@@ -2039,6 +2188,34 @@ declare namespace Deno {
       options?: KvListOptions,
     ): KvListIterator<T>;
 
+    /**
+     * Watch a set of keys in the database for changes. The returned stream
+     * yields an array of {@linkcode Deno.KvEntryMaybe} objects, one for each
+     * of the `keys`, in the same order. The first array reports the current
+     * state of the keys, and a new array is yielded every time the
+     * versionstamp of at least one of the keys changes.
+     *
+     * Changes that happen in quick succession may be coalesced, so not every
+     * intermediate value of a key is necessarily observed. At most 10 keys
+     * can be watched at a time.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * const stream = db.watch([["foo"], ["bar"]]);
+     * for await (const entries of stream) {
+     *   entries[0].key; // ["foo"]
+     *   entries[0].value; // "bar"
+     *   entries[1].key; // ["bar"]
+     *   entries[1].value; // null
+     * }
+     * ```
+     *
+     * The stream ends when the database is closed, or when it is cancelled.
+     */
+    watch<T extends readonly unknown[]>(
+      keys: readonly [...{ [K in keyof T]: KvKey }],
+    ): ReadableStream<{ [K in keyof T]: KvEntryMaybe<T[K]> }>;
+
     /**
      * Create a new {@linkcode Deno.AtomicOperation} object which can be used to
      * perform an atomic transaction on the database. This does not perform any
@@ -11,9 +11,11 @@ pub mod fs;
 pub mod logger;
 pub mod path;
 pub mod progress_bar;
+pub mod sarif;
 pub mod sync;
 pub mod text_encoding;
 pub mod time;
 pub mod unix;
 pub mod v8;
 pub mod windows;
+pub mod xml;
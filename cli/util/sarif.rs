@@ -0,0 +1,314 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! A subset of the SARIF 2.1.0 format, which is used by code scanning tools
+//! to import the results of static analysis.
+//! See https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use serde::Serialize;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+#[derive(Debug, Serialize)]
+pub struct SarifLog {
+  #[serde(rename = "$schema")]
+  schema: &'static str,
+  version: &'static str,
+  runs: Vec<SarifRun>,
+}
+
+impl SarifLog {
+  pub fn new(run: SarifRun) -> Self {
+    Self {
+      schema: SARIF_SCHEMA,
+      version: SARIF_VERSION,
+      runs: vec![run],
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRun {
+  tool: SarifTool,
+  column_kind: SarifColumnKind,
+  results: Vec<SarifResult>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  invocations: Vec<SarifInvocation>,
+}
+
+impl SarifRun {
+  pub fn new(driver_name: &str, column_kind: SarifColumnKind) -> Self {
+    Self {
+      tool: SarifTool {
+        driver: SarifDriver {
+          name: driver_name.to_string(),
+          information_uri: "https://deno.land",
+          version: crate::version::deno(),
+          rules: Vec::new(),
+        },
+      },
+      column_kind,
+      results: Vec::new(),
+      invocations: Vec::new(),
+    }
+  }
+
+  /// Adds a result for the rule, describing the rule the first time it is
+  /// used.
+  pub fn add_result(&mut self, rule: SarifRule, mut result: SarifResult) {
+    let rules = &mut self.tool.driver.rules;
+    let rule_index = match rules.iter().position(|r| r.id == rule.id) {
+      Some(index) => index,
+      None => {
+        rules.push(rule);
+        rules.len() - 1
+      }
+    };
+    result.rule_id = rules[rule_index].id.clone();
+    result.rule_index = rule_index;
+    self.results.push(result);
+  }
+
+  /// Records a problem that prevented the tool from analyzing a file.
+  pub fn add_notification(&mut self, message: String, uri: Option<String>) {
+    if self.invocations.is_empty() {
+      self.invocations.push(SarifInvocation {
+        execution_successful: false,
+        tool_execution_notifications: Vec::new(),
+      });
+    }
+    self.invocations[0]
+      .tool_execution_notifications
+      .push(SarifNotification {
+        level: SarifLevel::Error,
+        message: SarifMessage { text: message },
+        locations: uri
+          .map(|uri| vec![SarifLocation::new(uri, None)])
+          .unwrap_or_default(),
+      });
+  }
+}
+
+/// The unit of the column numbers of the regions.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SarifColumnKind {
+  Utf16CodeUnits,
+  UnicodeCodePoints,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifTool {
+  driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+  name: String,
+  information_uri: &'static str,
+  version: &'static str,
+  rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+  pub id: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub help_uri: Option<String>,
+  #[serde(skip_serializing_if = "SarifRuleProperties::is_empty")]
+  pub properties: SarifRuleProperties,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SarifRuleProperties {
+  pub tags: Vec<String>,
+}
+
+impl SarifRuleProperties {
+  fn is_empty(&self) -> bool {
+    self.tags.is_empty()
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+  rule_id: String,
+  rule_index: usize,
+  level: SarifLevel,
+  message: SarifMessage,
+  locations: Vec<SarifLocation>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  related_locations: Vec<SarifRelatedLocation>,
+}
+
+impl SarifResult {
+  pub fn new(
+    level: SarifLevel,
+    message: String,
+    locations: Vec<SarifLocation>,
+  ) -> Self {
+    Self {
+      rule_id: String::new(),
+      rule_index: 0,
+      level,
+      message: SarifMessage { text: message },
+      locations,
+      related_locations: Vec::new(),
+    }
+  }
+
+  pub fn with_related_location(
+    mut self,
+    message: String,
+    location: SarifLocation,
+  ) -> Self {
+    self.related_locations.push(SarifRelatedLocation {
+      id: self.related_locations.len(),
+      message: SarifMessage { text: message },
+      physical_location: location.physical_location,
+    });
+    self
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SarifLevel {
+  Error,
+  Warning,
+  Note,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifMessage {
+  text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+  physical_location: SarifPhysicalLocation,
+}
+
+impl SarifLocation {
+  pub fn new(uri: String, region: Option<SarifRegion>) -> Self {
+    Self {
+      physical_location: SarifPhysicalLocation {
+        artifact_location: SarifArtifactLocation { uri },
+        region,
+      },
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRelatedLocation {
+  id: usize,
+  message: SarifMessage,
+  physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+  artifact_location: SarifArtifactLocation,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifArtifactLocation {
+  uri: String,
+}
+
+/// Lines and columns are 1-based. The end column is exclusive.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+  pub start_line: usize,
+  pub start_column: usize,
+  pub end_line: usize,
+  pub end_column: usize,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub byte_offset: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub byte_length: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifInvocation {
+  execution_successful: bool,
+  tool_execution_notifications: Vec<SarifNotification>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifNotification {
+  level: SarifLevel,
+  message: SarifMessage,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  locations: Vec<SarifLocation>,
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use deno_core::serde_json;
+  use deno_core::serde_json::json;
+
+  #[test]
+  fn sarif_log() {
+    let mut run = SarifRun::new("deno lint", SarifColumnKind::Utf16CodeUnits);
+    let rule = || SarifRule {
+      id: "no-var".to_string(),
+      help_uri: Some("https://lint.deno.land/#no-var".to_string()),
+      properties: SarifRuleProperties {
+        tags: vec!["recommended".to_string()],
+      },
+    };
+    let location = || {
+      SarifLocation::new(
+        "file:///a.ts".to_string(),
+        Some(SarifRegion {
+          start_line: 1,
+          start_column: 1,
+          end_line: 1,
+          end_column: 4,
+          ..Default::default()
+        }),
+      )
+    };
+    run.add_result(
+      rule(),
+      SarifResult::new(SarifLevel::Error, "a".to_string(), vec![location()]),
+    );
+    run.add_result(
+      rule(),
+      SarifResult::new(SarifLevel::Error, "b".to_string(), vec![location()]),
+    );
+    run.add_notification("failed".to_string(), None);
+    let value = serde_json::to_value(SarifLog::new(run)).unwrap();
+    let runs = &value["runs"];
+    assert_eq!(value["version"], json!("2.1.0"));
+    assert_eq!(
+      runs[0]["tool"]["driver"]["rules"].as_array().unwrap().len(),
+      1
+    );
+    assert_eq!(runs[0]["results"][1]["ruleId"], json!("no-var"));
+    assert_eq!(runs[0]["results"][1]["ruleIndex"], json!(0));
+    assert_eq!(
+      runs[0]["results"][1]["locations"][0]["physicalLocation"]["region"],
+      json!({ "startLine": 1, "startColumn": 1, "endLine": 1, "endColumn": 4 })
+    );
+    assert_eq!(runs[0]["columnKind"], json!("utf16CodeUnits"));
+    assert_eq!(
+      runs[0]["invocations"][0]["executionSuccessful"],
+      json!(false)
+    );
+  }
+}
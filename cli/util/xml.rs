@@ -0,0 +1,37 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+/// Escapes text for use in XML or HTML content and attribute values. ANSI
+/// escape codes and other characters that are not allowed in XML 1.0 are
+/// removed.
+pub fn escape_xml(text: &str) -> String {
+  let text = console_static_text::ansi::strip_ansi_codes(text);
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      '\t' | '\n' | '\r' => escaped.push(c),
+      c if c < ' ' || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_escape_xml() {
+    assert_eq!(
+      escape_xml(r#"<a href="x">'&'</a>"#),
+      "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;"
+    );
+    assert_eq!(escape_xml("\u{1b}[31mred\u{1b}[0m"), "red");
+    assert_eq!(escape_xml("a\u{0}b\tc\r\nd\u{FFFF}"), "ab\tc\r\nd");
+  }
+}
@@ -1,17 +1,21 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use deno_ast::ModuleSpecifier;
 use deno_core::error::AnyError;
+use deno_core::futures::future::poll_fn;
 use deno_core::futures::task::LocalFutureObj;
+use deno_core::futures::Future;
 use deno_core::futures::FutureExt;
 use deno_core::located_script_name;
 use deno_core::url::Url;
 use deno_core::CompiledWasmModuleStore;
 use deno_core::Extension;
+use deno_core::JsRuntime;
 use deno_core::ModuleId;
 use deno_core::ModuleLoader;
 use deno_core::SharedArrayBufferStore;
@@ -28,13 +32,16 @@ use deno_runtime::fmt_errors::format_js_error;
 use deno_runtime::inspector_server::InspectorServer;
 use deno_runtime::ops::worker_host::CreateWebWorkerCb;
 use deno_runtime::ops::worker_host::WorkerEventCb;
+use deno_runtime::ops::worker_host::WorkersTable;
 use deno_runtime::permissions::PermissionsContainer;
 use deno_runtime::web_worker::WebWorker;
 use deno_runtime::web_worker::WebWorkerOptions;
+use deno_runtime::worker::DeferredExit;
 use deno_runtime::worker::MainWorker;
 use deno_runtime::worker::WorkerOptions;
 use deno_runtime::BootstrapOptions;
 use deno_semver::npm::NpmPackageReqReference;
+use tokio::sync::watch;
 
 use crate::args::StorageKeyResolver;
 use crate::errors;
@@ -100,6 +107,7 @@ struct SharedWorkerState {
   root_cert_store_provider: Arc<dyn RootCertStoreProvider>,
   fs: Arc<dyn deno_fs::FileSystem>,
   maybe_inspector_server: Option<Arc<InspectorServer>>,
+  pending_worker_coverage: PendingWorkerCoverage,
 }
 
 impl SharedWorkerState {
@@ -110,6 +118,37 @@ impl SharedWorkerState {
   }
 }
 
+/// Counts the web workers that collect coverage and haven't written it yet, so
+/// that the main worker can wait for them before the process exits.
+#[derive(Default)]
+struct PendingWorkerCoverage(Arc<watch::Sender<usize>>);
+
+impl PendingWorkerCoverage {
+  fn start(&self) -> PendingWorkerCoverageGuard {
+    self.0.send_modify(|count| *count += 1);
+    PendingWorkerCoverageGuard(self.0.clone())
+  }
+
+  async fn wait(&self) {
+    let mut receiver = self.0.subscribe();
+    while *receiver.borrow_and_update() > 0 {
+      if receiver.changed().await.is_err() {
+        break;
+      }
+    }
+  }
+}
+
+/// Put into the `OpState` of a web worker along with its coverage collector.
+/// It is dropped once the coverage is written, or the worker is gone.
+struct PendingWorkerCoverageGuard(Arc<watch::Sender<usize>>);
+
+impl Drop for PendingWorkerCoverageGuard {
+  fn drop(&mut self) {
+    self.0.send_modify(|count| *count -= 1);
+  }
+}
+
 pub struct CliMainWorker {
   main_module: ModuleSpecifier,
   is_main_cjs: bool,
@@ -130,8 +169,68 @@ impl CliMainWorker {
   pub async fn run(&mut self) -> Result<i32, AnyError> {
     let mut maybe_coverage_collector =
       self.maybe_setup_coverage_collector().await?;
+    // With coverage enabled `Deno.exit()` only terminates execution, so that
+    // the coverage can still be written before the process exits.
+    let deferred_exit = DeferredExit::default();
+    if maybe_coverage_collector.is_some() {
+      self
+        .worker
+        .js_runtime
+        .op_state()
+        .borrow_mut()
+        .put(deferred_exit.clone());
+    }
     log::debug!("main_module {}", self.main_module);
 
+    let result = self
+      .run_main_module(maybe_coverage_collector.is_none())
+      .await;
+
+    match maybe_coverage_collector.as_mut() {
+      Some(coverage_collector) if deferred_exit.is_requested() => {
+        self
+          .worker
+          .js_runtime
+          .v8_isolate()
+          .cancel_terminate_execution();
+        // The event loop isn't run anymore, so that no JavaScript runs after
+        // `Deno.exit()`.
+        with_inspector_sessions(
+          &mut self.worker.js_runtime,
+          coverage_collector.stop_collecting().boxed_local(),
+        )
+        .await?;
+      }
+      Some(coverage_collector) => {
+        result?;
+        self
+          .worker
+          .with_event_loop(coverage_collector.stop_collecting().boxed_local())
+          .await?;
+      }
+      None => result?,
+    }
+
+    if maybe_coverage_collector.is_some() {
+      // Terminate the web workers that are still running and wait for all of
+      // them to write their coverage, which they do while shutting down.
+      self
+        .worker
+        .js_runtime
+        .op_state()
+        .borrow_mut()
+        .borrow_mut::<WorkersTable>()
+        .clear();
+      self.shared.pending_worker_coverage.wait().await;
+    }
+
+    Ok(self.worker.exit_code())
+  }
+
+  async fn run_main_module(
+    &mut self,
+    wait_for_inspector: bool,
+  ) -> Result<(), AnyError> {
     if self.is_main_cjs {
       self.initialize_main_module_for_node()?;
       deno_node::load_cjs_module(
@@ -147,10 +246,7 @@ impl CliMainWorker {
     self.worker.dispatch_load_event(located_script_name!())?;
 
     loop {
-      self
-        .worker
-        .run_event_loop(maybe_coverage_collector.is_none())
-        .await?;
+      self.worker.run_event_loop(wait_for_inspector).await?;
       if !self
         .worker
         .dispatch_beforeunload_event(located_script_name!())?
@@ -159,16 +255,7 @@ impl CliMainWorker {
       }
     }
 
-    self.worker.dispatch_unload_event(located_script_name!())?;
-
-    if let Some(coverage_collector) = maybe_coverage_collector.as_mut() {
-      self
-        .worker
-        .with_event_loop(coverage_collector.stop_collecting().boxed_local())
-        .await?;
-    }
-
-    Ok(self.worker.exit_code())
+    self.worker.dispatch_unload_event(located_script_name!())
   }
 
   pub async fn run_for_watcher(self) -> Result<(), AnyError> {
@@ -328,6 +415,7 @@ impl CliMainWorkerFactory {
         root_cert_store_provider,
         fs,
         maybe_inspector_server,
+        pending_worker_coverage: Default::default(),
       }),
     }
   }
@@ -388,6 +476,7 @@ impl CliMainWorkerFactory {
       create_web_worker_preload_module_callback(shared);
     let web_worker_pre_execute_module_cb =
       create_web_worker_pre_execute_module_callback(shared.clone());
+    let web_worker_shutdown_cb = create_web_worker_shutdown_callback();
 
     let maybe_storage_key = shared
       .storage_key_resolver
@@ -442,6 +531,7 @@ impl CliMainWorkerFactory {
       create_web_worker_cb,
       web_worker_preload_module_cb,
       web_worker_pre_execute_module_cb,
+      web_worker_shutdown_cb,
       maybe_inspector_server,
       should_break_on_first_statement: shared.options.inspect_brk,
       should_wait_for_inspector_session: shared.options.inspect_wait,
@@ -475,13 +565,40 @@ impl CliMainWorkerFactory {
   }
 }
 
-// TODO(bartlomieju): this callback could have default value
-// and not be required
 fn create_web_worker_preload_module_callback(
-  _shared: &Arc<SharedWorkerState>,
+  shared: &Arc<SharedWorkerState>,
 ) -> Arc<WorkerEventCb> {
-  Arc::new(move |worker| {
-    let fut = async move { Ok(worker) };
+  let shared = shared.clone();
+  Arc::new(move |mut worker| {
+    let shared = shared.clone();
+    let fut = async move {
+      // Web workers write their own profiles to the same directory, so that
+      // `deno coverage` merges them with the ones of the main worker.
+      if let Some(coverage_dir) = &shared.options.coverage_dir {
+        let pending_coverage_guard = shared.pending_worker_coverage.start();
+        worker.js_runtime.maybe_init_inspector();
+        let session = worker
+          .js_runtime
+          .inspector()
+          .borrow()
+          .create_local_session();
+        let mut coverage_collector =
+          CoverageCollector::new(PathBuf::from(coverage_dir), session);
+        with_inspector_sessions(
+          &mut worker.js_runtime,
+          coverage_collector.start_collecting().boxed_local(),
+        )
+        .await?;
+        let op_state = worker.js_runtime.op_state();
+        let mut op_state = op_state.borrow_mut();
+        op_state.put(coverage_collector);
+        op_state.put(pending_coverage_guard);
+        // The collector's session must not keep the worker alive.
+        worker.wait_for_inspector = false;
+      }
+
+      Ok(worker)
+    };
     LocalFutureObj::new(Box::new(fut))
   })
 }
@@ -507,6 +624,62 @@ fn create_web_worker_pre_execute_module_callback(
   })
 }
 
+fn create_web_worker_shutdown_callback() -> Arc<WorkerEventCb> {
+  Arc::new(move |mut worker| {
+    let fut = async move {
+      let maybe_coverage_collector = worker
+        .js_runtime
+        .op_state()
+        .borrow_mut()
+        .try_take::<CoverageCollector>();
+      if let Some(mut coverage_collector) = maybe_coverage_collector {
+        // Nested workers write their coverage while shutting down as well,
+        // which the main worker waits for.
+        worker
+          .js_runtime
+          .op_state()
+          .borrow_mut()
+          .borrow_mut::<WorkersTable>()
+          .clear();
+        // The worker might have been terminated by its parent or by
+        // `self.close()`, which also terminates execution.
+        worker.js_runtime.v8_isolate().cancel_terminate_execution();
+        with_inspector_sessions(
+          &mut worker.js_runtime,
+          coverage_collector.stop_collecting().boxed_local(),
+        )
+        .await?;
+        // Let the main worker know that the coverage is written.
+        drop(
+          worker
+            .js_runtime
+            .op_state()
+            .borrow_mut()
+            .try_take::<PendingWorkerCoverageGuard>(),
+        );
+      }
+
+      Ok(worker)
+    };
+    LocalFutureObj::new(Box::new(fut))
+  })
+}
+
+/// Runs `fut` while only polling the inspector sessions of `js_runtime`,
+/// unlike `MainWorker::with_event_loop()`, so that no JavaScript runs in the
+/// meantime.
+async fn with_inspector_sessions<'a, T>(
+  js_runtime: &mut JsRuntime,
+  mut fut: Pin<Box<dyn Future<Output = T> + 'a>>,
+) -> T {
+  let inspector = js_runtime.inspector();
+  poll_fn(|cx| {
+    let _ = inspector.borrow().poll_sessions(Some(cx));
+    fut.poll_unpin(cx)
+  })
+  .await
+}
+
 fn create_web_worker_callback(
   shared: Arc<SharedWorkerState>,
   stdio: deno_runtime::deno_io::Stdio,
@@ -525,6 +698,7 @@ fn create_web_worker_callback(
     let preload_module_cb = create_web_worker_preload_module_callback(&shared);
     let pre_execute_module_cb =
       create_web_worker_pre_execute_module_callback(shared.clone());
+    let shutdown_cb = create_web_worker_shutdown_callback();
 
     let extensions = ops::cli_exts(shared.npm_resolver.clone());
 
@@ -568,6 +742,7 @@ fn create_web_worker_callback(
       create_web_worker_cb,
       preload_module_cb,
       pre_execute_module_cb,
+      shutdown_cb,
       format_js_error_fn: Some(Arc::new(format_js_error)),
       source_map_getter: maybe_source_map_getter,
       module_loader,
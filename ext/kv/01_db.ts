@@ -1,12 +1,21 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+// @ts-ignore internal api
+import { ReadableStream } from "ext:deno_web/06_streams.js";
+
 // @ts-ignore internal api
 const {
+  ArrayFrom,
+  ArrayIsArray,
+  ArrayPrototypeEvery,
   AsyncGeneratorPrototype,
   BigIntPrototypeToString,
+  NumberIsInteger,
+  NumberIsNaN,
   ObjectFreeze,
   ObjectGetPrototypeOf,
   ObjectPrototypeIsPrototypeOf,
+  PromisePrototypeThen,
   StringPrototypeReplace,
   SymbolFor,
   SymbolToStringTag,
@@ -21,8 +30,16 @@ const encodeCursor: (
 ) => string = (selector, boundaryKey) =>
   ops.op_kv_encode_cursor(selector, boundaryKey);
 
-async function openKv(path: string) {
-  const rid = await core.opAsync("op_kv_database_open", path);
+async function openKv(
+  path: string,
+  options: { historyRetention?: number } = {},
+) {
+  if (options.historyRetention !== undefined) {
+    validateHistoryRetention(options.historyRetention);
+  }
+  const rid = await core.opAsync("op_kv_database_open", path, {
+    historyRetention: options.historyRetention,
+  });
   return new Kv(rid, kvSymbol);
 }
 
@@ -47,6 +64,8 @@ const kvSymbol = Symbol("KvRid");
 
 class Kv {
   #rid: number;
+  #closed: boolean;
+  #listening: boolean;
 
   constructor(rid: number = undefined, symbol: symbol = undefined) {
     if (kvSymbol !== symbol) {
@@ -55,13 +74,21 @@ class Kv {
       );
     }
     this.#rid = rid;
+    this.#closed = false;
+    this.#listening = false;
   }
 
   atomic() {
     return new AtomicOperation(this.#rid);
   }
 
-  async get(key: Deno.KvKey, opts?: { consistency?: Deno.KvConsistencyLevel }) {
+  async get(
+    key: Deno.KvKey,
+    opts?: {
+      consistency?: Deno.KvConsistencyLevel;
+      atVersionstamp?: string;
+    },
+  ) {
     const [entries]: [RawKvEntry[]] = await core.opAsync(
       "op_kv_snapshot_read",
       this.#rid,
@@ -74,6 +101,7 @@ class Kv {
         null,
       ]],
       opts?.consistency ?? "strong",
+      opts?.atVersionstamp ?? null,
     );
     if (!entries.length) {
       return {
@@ -87,7 +115,10 @@ class Kv {
 
   async getMany(
     keys: Deno.KvKey[],
-    opts?: { consistency?: Deno.KvConsistencyLevel },
+    opts?: {
+      consistency?: Deno.KvConsistencyLevel;
+      atVersionstamp?: string;
+    },
   ): Promise<Deno.KvEntry<unknown>[]> {
     const ranges: RawKvEntry[][] = await core.opAsync(
       "op_kv_snapshot_read",
@@ -101,6 +132,7 @@ class Kv {
         null,
       ]),
       opts?.consistency ?? "strong",
+      opts?.atVersionstamp ?? null,
     );
     return ranges.map((entries, i) => {
       if (!entries.length) {
@@ -114,12 +146,17 @@ class Kv {
     });
   }
 
-  async set(key: Deno.KvKey, value: unknown) {
+  async set(
+    key: Deno.KvKey,
+    value: unknown,
+    options?: { expireIn?: number },
+  ) {
     value = serializeValue(value);
+    if (options?.expireIn !== undefined) validateExpireIn(options.expireIn);
 
     const checks: Deno.AtomicCheck[] = [];
     const mutations = [
-      [key, "set", value],
+      [key, "set", value, options?.expireIn, undefined],
     ];
 
     const versionstamp = await core.opAsync(
@@ -136,7 +173,7 @@ class Kv {
   async delete(key: Deno.KvKey) {
     const checks: Deno.AtomicCheck[] = [];
     const mutations = [
-      [key, "delete", null],
+      [key, "delete", null, undefined, undefined],
     ];
 
     const result = await core.opAsync(
@@ -149,6 +186,103 @@ class Kv {
     if (!result) throw new TypeError("Failed to set value");
   }
 
+  async enqueue(
+    message: unknown,
+    opts?: {
+      delay?: number;
+      keysIfUndelivered?: Deno.KvKey[];
+      backoffSchedule?: number[];
+    },
+  ) {
+    if (opts?.delay !== undefined) validateQueueDelay(opts.delay);
+    if (opts?.backoffSchedule !== undefined) {
+      validateBackoffSchedule(opts.backoffSchedule);
+    }
+
+    const enqueues = [
+      [
+        core.serialize(message, { forStorage: true }),
+        opts?.delay ?? 0,
+        opts?.keysIfUndelivered ?? [],
+        opts?.backoffSchedule ?? null,
+      ],
+    ];
+
+    const versionstamp = await core.opAsync(
+      "op_kv_atomic_write",
+      this.#rid,
+      [],
+      [],
+      enqueues,
+    );
+    if (versionstamp === null) throw new TypeError("Failed to enqueue value");
+    return { ok: true, versionstamp };
+  }
+
+  async listenQueue(
+    handler: (message: unknown) => Promise<void> | void,
+  ): Promise<void> {
+    if (this.#listening) {
+      throw new TypeError("Already listening to the queue");
+    }
+    this.#listening = true;
+
+    while (!this.#closed) {
+      // Wait for the next message.
+      let next: [Uint8Array, number];
+      try {
+        next = await core.opAsync("op_kv_dequeue_next_message", this.#rid);
+      } catch (error) {
+        if (this.#closed) {
+          break;
+        } else {
+          throw error;
+        }
+      }
+
+      const { 0: payload, 1: handleId } = next;
+      const finish = async (success: boolean) => {
+        try {
+          await core.opAsync(
+            "op_kv_finish_dequeued_message",
+            handleId,
+            success,
+          );
+        } catch (error) {
+          // The message is redelivered once its deadline passes, so a failure
+          // here is only worth reporting while the database is still open.
+          if (!this.#closed) {
+            console.error("Failed to finish queue message", error);
+          }
+        }
+      };
+
+      let message: unknown;
+      try {
+        message = core.deserialize(payload, { forStorage: true });
+      } catch (error) {
+        console.error("Failed to deserialize queue message", error);
+        await finish(false);
+        continue;
+      }
+
+      // Dispatch the message without waiting for the handler, so that
+      // messages can be processed concurrently.
+      PromisePrototypeThen(
+        (async () => {
+          try {
+            await handler(message);
+            return true;
+          } catch (error) {
+            console.error("Exception in queue handler", error);
+            return false;
+          }
+        })(),
+        finish,
+      );
+    }
+  }
+
   list(
     selector: Deno.KvListSelector,
     options: {
@@ -157,6 +291,7 @@ class Kv {
       cursor?: string;
       reverse?: boolean;
       consistency?: Deno.KvConsistencyLevel;
+      atVersionstamp?: string;
     } = {},
   ): KvListIterator {
     if (options.limit !== undefined && options.limit <= 0) {
@@ -174,11 +309,11 @@ class Kv {
       reverse: options.reverse ?? false,
       consistency: options.consistency ?? "strong",
       batchSize,
-      pullBatch: this.#pullBatch(batchSize),
+      pullBatch: this.#pullBatch(batchSize, options.atVersionstamp ?? null),
     });
   }
 
-  #pullBatch(batchSize: number): (
+  #pullBatch(batchSize: number, atVersionstamp: string | null): (
     selector: Deno.KvListSelector,
     cursor: string | undefined,
     reverse: boolean,
@@ -197,13 +332,57 @@ class Kv {
           cursor,
         ]],
         consistency,
+        atVersionstamp,
       );
 
       return entries.map(deserializeValue);
     };
   }
 
+  watch(keys: Deno.KvKey[]) {
+    const rid = ops.op_kv_watch(this.#rid, keys);
+    const lastEntries: (Deno.KvEntryMaybe<unknown> | undefined)[] = ArrayFrom(
+      { length: keys.length },
+      () => undefined,
+    );
+    return new ReadableStream({
+      async pull(controller) {
+        try {
+          const updates = await core.opAsync("op_kv_watch_next", rid);
+          if (updates === null) {
+            core.tryClose(rid);
+            controller.close();
+            return;
+          }
+
+          for (let i = 0; i < keys.length; i++) {
+            const update = updates[i];
+            if (update.kind === "unchanged") continue;
+            if (update.entry === null) {
+              lastEntries[i] = {
+                key: keys[i],
+                value: null,
+                versionstamp: null,
+              };
+            } else {
+              lastEntries[i] = deserializeValue(update.entry);
+            }
+          }
+
+          controller.enqueue([...lastEntries]);
+        } catch (err) {
+          core.tryClose(rid);
+          controller.error(err);
+        }
+      },
+      cancel() {
+        core.tryClose(rid);
+      },
+    });
+  }
+
   close() {
+    this.#closed = true;
     core.close(this.#rid);
   }
 }
@@ -212,7 +391,14 @@ class AtomicOperation {
   #rid: number;
 
   #checks: [Deno.KvKey, string | null][] = [];
-  #mutations: [Deno.KvKey, string, RawValue | null][] = [];
+  #mutations: [
+    Deno.KvKey,
+    string,
+    RawValue | null,
+    number | undefined,
+    [Deno.KvKey | null, Deno.KvKey | null, Deno.KvKey | null] | undefined,
+  ][] = [];
+  #enqueues: [Uint8Array, number, Deno.KvKey[], number[] | null][] = [];
 
   constructor(rid: number) {
     this.#rid = rid;
@@ -230,6 +416,7 @@ class AtomicOperation {
       const key = mutation.key;
       let type: string;
       let value: RawValue | null;
+      let expireIn: number | undefined = undefined;
       switch (mutation.type) {
         case "delete":
           type = "delete";
@@ -246,37 +433,106 @@ class AtomicOperation {
             throw new TypeError(`invalid mutation '${type}' without value`);
           }
           value = serializeValue(mutation.value);
+          if (mutation.type === "set" && mutation.expireIn !== undefined) {
+            validateExpireIn(mutation.expireIn);
+            expireIn = mutation.expireIn;
+          }
           break;
         default:
           throw new TypeError("Invalid mutation type");
       }
-      this.#mutations.push([key, type, value]);
+      this.#mutations.push([key, type, value, expireIn, undefined]);
     }
     return this;
   }
 
   sum(key: Deno.KvKey, n: bigint): this {
-    this.#mutations.push([key, "sum", serializeValue(new KvU64(n))]);
+    this.#mutations.push([
+      key,
+      "sum",
+      serializeValue(new KvU64(n)),
+      undefined,
+      undefined,
+    ]);
     return this;
   }
 
   min(key: Deno.KvKey, n: bigint): this {
-    this.#mutations.push([key, "min", serializeValue(new KvU64(n))]);
+    this.#mutations.push([
+      key,
+      "min",
+      serializeValue(new KvU64(n)),
+      undefined,
+      undefined,
+    ]);
     return this;
   }
 
   max(key: Deno.KvKey, n: bigint): this {
-    this.#mutations.push([key, "max", serializeValue(new KvU64(n))]);
+    this.#mutations.push([
+      key,
+      "max",
+      serializeValue(new KvU64(n)),
+      undefined,
+      undefined,
+    ]);
     return this;
   }
 
-  set(key: Deno.KvKey, value: unknown): this {
-    this.#mutations.push([key, "set", serializeValue(value)]);
+  set(
+    key: Deno.KvKey,
+    value: unknown,
+    options?: { expireIn?: number },
+  ): this {
+    if (options?.expireIn !== undefined) validateExpireIn(options.expireIn);
+    this.#mutations.push([
+      key,
+      "set",
+      serializeValue(value),
+      options?.expireIn,
+      undefined,
+    ]);
     return this;
   }
 
   delete(key: Deno.KvKey): this {
-    this.#mutations.push([key, "delete", null]);
+    this.#mutations.push([key, "delete", null, undefined, undefined]);
+    return this;
+  }
+
+  deleteRange(selector: Deno.KvListSelector): this {
+    this.#mutations.push([
+      [],
+      "delete_range",
+      null,
+      undefined,
+      [
+        "prefix" in selector ? selector.prefix : null,
+        "start" in selector ? selector.start : null,
+        "end" in selector ? selector.end : null,
+      ],
+    ]);
+    return this;
+  }
+
+  enqueue(
+    message: unknown,
+    opts?: {
+      delay?: number;
+      keysIfUndelivered?: Deno.KvKey[];
+      backoffSchedule?: number[];
+    },
+  ): this {
+    if (opts?.delay !== undefined) validateQueueDelay(opts.delay);
+    if (opts?.backoffSchedule !== undefined) {
+      validateBackoffSchedule(opts.backoffSchedule);
+    }
+    this.#enqueues.push([
+      core.serialize(message, { forStorage: true }),
+      opts?.delay ?? 0,
+      opts?.keysIfUndelivered ?? [],
+      opts?.backoffSchedule ?? null,
+    ]);
     return this;
   }
 
@@ -286,7 +542,7 @@ class AtomicOperation {
       this.#rid,
       this.#checks,
       this.#mutations,
-      [], // TODO(@losfair): enqueue
+      this.#enqueues,
     );
     if (versionstamp === null) return { ok: false };
     return { ok: true, versionstamp };
@@ -299,6 +555,46 @@ class AtomicOperation {
   }
 }
 
+function validateQueueDelay(delay: number) {
+  if (NumberIsNaN(delay)) {
+    throw new TypeError("delay cannot be NaN");
+  }
+  if (delay < 0) {
+    throw new TypeError("delay cannot be negative");
+  }
+}
+
+function validateBackoffSchedule(backoffSchedule: number[]) {
+  if (!ArrayIsArray(backoffSchedule)) {
+    throw new TypeError("backoffSchedule must be an array");
+  }
+  if (
+    !ArrayPrototypeEvery(
+      backoffSchedule,
+      (interval) => NumberIsInteger(interval) && interval >= 0,
+    )
+  ) {
+    throw new TypeError(
+      "backoffSchedule intervals must be non-negative integers",
+    );
+  }
+}
+
+function validateHistoryRetention(historyRetention: number) {
+  if (!NumberIsInteger(historyRetention) || historyRetention < 0) {
+    throw new TypeError("historyRetention must be a non-negative integer");
+  }
+}
+
+function validateExpireIn(expireIn: number) {
+  if (NumberIsNaN(expireIn)) {
+    throw new TypeError("expireIn cannot be NaN");
+  }
+  if (expireIn < 0) {
+    throw new TypeError("expireIn cannot be negative");
+  }
+}
+
 const MIN_U64 = BigInt("0");
 const MAX_U64 = BigInt("0xffffffffffffffff");
 
@@ -0,0 +1,154 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use async_trait::async_trait;
+use deno_core::error::AnyError;
+use deno_core::futures::Stream;
+use deno_core::OpState;
+
+use crate::remote::RemoteDb;
+use crate::remote::RemoteDbHandler;
+use crate::remote::RemoteDbHandlerPermissions;
+use crate::remote::RemoteQueueMessageHandle;
+use crate::sqlite::SqliteDb;
+use crate::sqlite::SqliteDbHandler;
+use crate::sqlite::SqliteDbHandlerPermissions;
+use crate::sqlite::SqliteQueueMessageHandle;
+use crate::AtomicWrite;
+use crate::CommitResult;
+use crate::Database;
+use crate::DatabaseHandler;
+use crate::OpenOptions;
+use crate::QueueMessageHandle;
+use crate::ReadRange;
+use crate::ReadRangeOutput;
+use crate::SnapshotReadOptions;
+use crate::WatchKeyOutput;
+
+/// A database handler that opens `http://` and `https://` paths as remote
+/// databases, and everything else as a local SQLite database.
+pub struct MultiBackendDbHandler<
+  P: SqliteDbHandlerPermissions + RemoteDbHandlerPermissions + 'static,
+> {
+  sqlite: SqliteDbHandler<P>,
+  remote: RemoteDbHandler<P>,
+}
+
+impl<P: SqliteDbHandlerPermissions + RemoteDbHandlerPermissions>
+  MultiBackendDbHandler<P>
+{
+  pub fn remote_or_sqlite(default_storage_dir: Option<PathBuf>) -> Self {
+    Self {
+      sqlite: SqliteDbHandler::new(default_storage_dir),
+      remote: RemoteDbHandler::new(),
+    }
+  }
+}
+
+#[async_trait(?Send)]
+impl<P: SqliteDbHandlerPermissions + RemoteDbHandlerPermissions> DatabaseHandler
+  for MultiBackendDbHandler<P>
+{
+  type DB = MultiBackendDb;
+
+  async fn open(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    options: OpenOptions,
+  ) -> Result<Self::DB, AnyError> {
+    match path.as_deref() {
+      Some(path)
+        if path.starts_with("http://") || path.starts_with("https://") =>
+      {
+        let db = self
+          .remote
+          .open(state, Some(path.to_string()), options)
+          .await?;
+        Ok(MultiBackendDb::Remote(db))
+      }
+      _ => {
+        let db = self.sqlite.open(state, path, options).await?;
+        Ok(MultiBackendDb::Sqlite(db))
+      }
+    }
+  }
+}
+
+pub enum MultiBackendDb {
+  Sqlite(SqliteDb),
+  Remote(RemoteDb),
+}
+
+#[async_trait(?Send)]
+impl Database for MultiBackendDb {
+  type QMH = MultiBackendQueueMessageHandle;
+
+  async fn snapshot_read(
+    &self,
+    requests: Vec<ReadRange>,
+    options: SnapshotReadOptions,
+  ) -> Result<Vec<ReadRangeOutput>, AnyError> {
+    match self {
+      Self::Sqlite(db) => db.snapshot_read(requests, options).await,
+      Self::Remote(db) => db.snapshot_read(requests, options).await,
+    }
+  }
+
+  async fn atomic_write(
+    &self,
+    write: AtomicWrite,
+  ) -> Result<Option<CommitResult>, AnyError> {
+    match self {
+      Self::Sqlite(db) => db.atomic_write(write).await,
+      Self::Remote(db) => db.atomic_write(write).await,
+    }
+  }
+
+  async fn dequeue_next_message(&self) -> Result<Self::QMH, AnyError> {
+    match self {
+      Self::Sqlite(db) => Ok(MultiBackendQueueMessageHandle::Sqlite(
+        db.dequeue_next_message().await?,
+      )),
+      Self::Remote(db) => Ok(MultiBackendQueueMessageHandle::Remote(
+        db.dequeue_next_message().await?,
+      )),
+    }
+  }
+
+  fn watch(
+    &self,
+    keys: Vec<Vec<u8>>,
+  ) -> Pin<Box<dyn Stream<Item = Result<Vec<WatchKeyOutput>, AnyError>>>> {
+    match self {
+      Self::Sqlite(db) => db.watch(keys),
+      Self::Remote(db) => db.watch(keys),
+    }
+  }
+}
+
+pub enum MultiBackendQueueMessageHandle {
+  Sqlite(SqliteQueueMessageHandle),
+  Remote(RemoteQueueMessageHandle),
+}
+
+#[async_trait(?Send)]
+impl QueueMessageHandle for MultiBackendQueueMessageHandle {
+  async fn take_payload(&mut self) -> Result<Vec<u8>, AnyError> {
+    match self {
+      Self::Sqlite(handle) => handle.take_payload().await,
+      Self::Remote(handle) => handle.take_payload().await,
+    }
+  }
+
+  async fn finish(&self, success: bool) -> Result<(), AnyError> {
+    match self {
+      Self::Sqlite(handle) => handle.finish(success).await,
+      Self::Remote(handle) => handle.finish(success).await,
+    }
+  }
+}
@@ -0,0 +1,37 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+//! This example serves a local SQLite KV database over HTTP, so that several
+//! processes can share it with `Deno.openKv("http://127.0.0.1:4512/")`.
+//!
+//! Usage:
+//!
+//! ```sh
+//! DENO_KV_ACCESS_TOKEN=secret cargo run -p deno_kv --features remote_server --example kv_server -- ./kv.sqlite3 127.0.0.1:4512
+//! ```
+//!
+//! Clients must set `DENO_KV_ACCESS_TOKEN` to the same token.
+
+use deno_core::error::AnyError;
+use deno_kv::remote::ACCESS_TOKEN_ENV_VAR;
+use deno_kv::remote_server::serve;
+use deno_kv::sqlite::SqliteDb;
+use tokio::net::TcpListener;
+use tokio::task::LocalSet;
+
+fn main() -> Result<(), AnyError> {
+  let mut args = std::env::args().skip(1);
+  let path = args.next().unwrap_or_else(|| "kv.sqlite3".to_string());
+  let addr = args.next().unwrap_or_else(|| "127.0.0.1:4512".to_string());
+  let access_token = std::env::var(ACCESS_TOKEN_ENV_VAR)
+    .map_err(|_| anyhow::anyhow!("{ACCESS_TOKEN_ENV_VAR} must be set"))?;
+
+  let runtime = tokio::runtime::Builder::new_current_thread()
+    .enable_all()
+    .build()?;
+  LocalSet::new().block_on(&runtime, async move {
+    let conn = rusqlite::Connection::open(&path)?;
+    let db = SqliteDb::new(conn)?;
+    let listener = TcpListener::bind(&addr).await?;
+    println!("Serving {path} on http://{addr}/");
+    serve(listener, db, access_token).await
+  })
+}
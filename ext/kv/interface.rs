@@ -3,12 +3,15 @@
 use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::num::NonZeroU32;
+use std::pin::Pin;
 use std::rc::Rc;
 
 use async_trait::async_trait;
 use deno_core::error::AnyError;
+use deno_core::futures::Stream;
 use deno_core::OpState;
 use num_bigint::BigInt;
+use serde::Deserialize;
 
 use crate::codec::canonicalize_f64;
 
@@ -20,11 +23,25 @@ pub trait DatabaseHandler {
     &self,
     state: Rc<RefCell<OpState>>,
     path: Option<String>,
+    options: OpenOptions,
   ) -> Result<Self::DB, AnyError>;
 }
 
+/// Options passed to `Deno.openKv`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenOptions {
+  /// The history retention window in milliseconds. `Some(0)` disables history
+  /// mode and discards the recorded history. If it is `None`, the mode stored
+  /// in the database is left as is.
+  #[serde(default)]
+  pub history_retention: Option<u64>,
+}
+
 #[async_trait(?Send)]
 pub trait Database {
+  type QMH: QueueMessageHandle + 'static;
+
   async fn snapshot_read(
     &self,
     requests: Vec<ReadRange>,
@@ -35,11 +52,41 @@ pub trait Database {
     &self,
     write: AtomicWrite,
   ) -> Result<Option<CommitResult>, AnyError>;
+
+  async fn dequeue_next_message(&self) -> Result<Self::QMH, AnyError>;
+
+  /// Watches a set of keys for changes. The returned stream yields one item
+  /// per key, in the same order as `keys`, every time the versionstamp of at
+  /// least one of the keys changes. The first item reports the current state
+  /// of all keys.
+  ///
+  /// A database may coalesce multiple changes into a single item, so not
+  /// every intermediate version of a key is necessarily observed.
+  fn watch(
+    &self,
+    keys: Vec<Vec<u8>>,
+  ) -> Pin<Box<dyn Stream<Item = Result<Vec<WatchKeyOutput>, AnyError>>>>;
+}
+
+/// A handle to a message that was dequeued from the queue of a database.
+///
+/// The message is considered to be in-flight until `finish` is called. If the
+/// message was not successfully processed, it is redelivered according to its
+/// backoff schedule.
+#[async_trait(?Send)]
+pub trait QueueMessageHandle {
+  async fn take_payload(&mut self) -> Result<Vec<u8>, AnyError>;
+  async fn finish(&self, success: bool) -> Result<(), AnyError>;
 }
 
 /// Options for a snapshot read.
 pub struct SnapshotReadOptions {
   pub consistency: Consistency,
+  /// If set, the read observes the database as it was right after the write
+  /// with this versionstamp was committed, rather than its current state.
+  /// Backends that do not keep enough history to serve the read fail with an
+  /// error.
+  pub at_versionstamp: Option<[u8; 10]>,
 }
 
 /// The consistency of a read.
@@ -222,13 +269,21 @@ pub struct KvCheck {
 ///
 /// The type of mutation is specified by the `kind` field. The action performed
 /// by each mutation kind is specified in the docs for [MutationKind].
+///
+/// A set mutation may specify an expiration time in `expire_at`, as a Unix
+/// timestamp in milliseconds. Once this time has passed, the key is treated as
+/// if it does not exist: it is not returned by reads, and checks against it
+/// behave as if it were absent. The database may garbage-collect expired keys
+/// at any later time.
 pub struct KvMutation {
   pub key: Vec<u8>,
   pub kind: MutationKind,
+  pub expire_at: Option<u64>,
 }
 
 /// A request to enqueue a message to the database. This message is delivered
-/// to a listener of the queue at least once.
+/// to a listener of the queue at least once, no earlier than `delay_ms`
+/// milliseconds after the atomic write that enqueued it was committed.
 ///
 /// ## Retry
 ///
@@ -242,7 +297,7 @@ pub struct KvMutation {
 /// keys specified in `keys_if_undelivered`.
 pub struct Enqueue {
   pub payload: Vec<u8>,
-  pub deadline_ms: u64,
+  pub delay_ms: u64,
   pub keys_if_undelivered: Vec<Vec<u8>>,
   pub backoff_schedule: Option<Vec<u32>>,
 }
@@ -252,7 +307,8 @@ pub struct Enqueue {
 /// ## Set
 ///
 /// The set mutation sets the value of the key to the specified value. It
-/// discards the previous value of the key, if any.
+/// discards the previous value of the key, if any, including its expiration
+/// time.
 ///
 /// This operand supports all [Value] types.
 ///
@@ -260,6 +316,11 @@ pub struct Enqueue {
 ///
 /// The delete mutation deletes the value of the key.
 ///
+/// ## DeleteRange
+///
+/// The delete range mutation deletes all keys that are greater than or equal
+/// to the key of the mutation, and less than the specified end key.
+///
 /// ## Sum
 ///
 /// The sum mutation adds the specified value to the existing value of the key.
@@ -291,6 +352,7 @@ pub struct Enqueue {
 pub enum MutationKind {
   Set(Value),
   Delete,
+  DeleteRange { end: Vec<u8> },
   Sum(Value),
   Min(Value),
   Max(Value),
@@ -303,11 +365,20 @@ impl MutationKind {
       MutationKind::Sum(value) => Some(value),
       MutationKind::Min(value) => Some(value),
       MutationKind::Max(value) => Some(value),
-      MutationKind::Delete => None,
+      MutationKind::Delete | MutationKind::DeleteRange { .. } => None,
     }
   }
 }
 
+/// The state of a single watched key, as reported by [Database::watch].
+pub enum WatchKeyOutput {
+  /// The versionstamp of the key changed since the last item of the stream,
+  /// or this is the first item. `entry` is `None` if the key does not exist.
+  Changed { entry: Option<KvEntry> },
+  /// The key did not change since the last item of the stream.
+  Unchanged,
+}
+
 /// The result of a successful commit of an atomic write operation.
 pub struct CommitResult {
   /// The new versionstamp of the data that was committed.
@@ -1,24 +1,36 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 pub mod codec;
+pub mod dynamic;
 mod interface;
+pub mod remote;
+#[cfg(any(test, feature = "remote_server"))]
+pub mod remote_server;
 pub mod sqlite;
 
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::num::NonZeroU32;
+use std::pin::Pin;
 use std::rc::Rc;
+use std::time::SystemTime;
 
 use codec::decode_key;
 use codec::encode_key;
 use deno_core::anyhow::Context;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
+use deno_core::futures::Stream;
+use deno_core::futures::StreamExt;
 use deno_core::op;
 use deno_core::serde_v8::AnyValue;
 use deno_core::serde_v8::BigInt;
+use deno_core::AsyncRefCell;
 use deno_core::ByteString;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
 use deno_core::OpState;
+use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_core::ZeroCopyBuf;
@@ -35,6 +47,11 @@ const MAX_READ_RANGES: usize = 10;
 const MAX_READ_ENTRIES: usize = 1000;
 const MAX_CHECKS: usize = 10;
 const MAX_MUTATIONS: usize = 10;
+const MAX_QUEUE_DELAY_MS: u64 = 30 * 24 * 60 * 60 * 1000; // 30 days
+const MAX_QUEUE_BACKOFF_INTERVALS: usize = 5;
+const MAX_QUEUE_BACKOFF_MS: u32 = 3600000; // 1 hour
+const MAX_EXPIRE_IN_MS: u64 = 365 * 24 * 60 * 60 * 1000; // 1 year
+const MAX_WATCHED_KEYS: usize = 10;
 
 struct UnstableChecker {
   pub unstable: bool,
@@ -53,13 +70,17 @@ impl UnstableChecker {
 }
 
 deno_core::extension!(deno_kv,
-  deps = [ deno_console ],
+  deps = [ deno_console, deno_web ],
   parameters = [ DBH: DatabaseHandler ],
   ops = [
     op_kv_database_open<DBH>,
     op_kv_snapshot_read<DBH>,
     op_kv_atomic_write<DBH>,
     op_kv_encode_cursor,
+    op_kv_dequeue_next_message<DBH>,
+    op_kv_finish_dequeued_message<DBH>,
+    op_kv_watch<DBH>,
+    op_kv_watch_next,
   ],
   esm = [ "01_db.ts" ],
   options = {
@@ -74,18 +95,53 @@ deno_core::extension!(deno_kv,
 
 struct DatabaseResource<DB: Database + 'static> {
   db: Rc<DB>,
+  cancel_handle: Rc<CancelHandle>,
 }
 
 impl<DB: Database + 'static> Resource for DatabaseResource<DB> {
   fn name(&self) -> Cow<str> {
     "database".into()
   }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_handle.cancel();
+  }
+}
+
+struct QueueMessageResource<QMH: QueueMessageHandle + 'static> {
+  handle: QMH,
+}
+
+impl<QMH: QueueMessageHandle + 'static> Resource for QueueMessageResource<QMH> {
+  fn name(&self) -> Cow<str> {
+    "queueMessage".into()
+  }
+}
+
+type WatchStream =
+  Pin<Box<dyn Stream<Item = Result<Vec<WatchKeyOutput>, AnyError>>>>;
+
+struct DatabaseWatcherResource {
+  stream: AsyncRefCell<WatchStream>,
+  db_cancel_handle: Rc<CancelHandle>,
+  cancel_handle: Rc<CancelHandle>,
+}
+
+impl Resource for DatabaseWatcherResource {
+  fn name(&self) -> Cow<str> {
+    "databaseWatcher".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_handle.cancel();
+  }
 }
 
 #[op]
 async fn op_kv_database_open<DBH>(
   state: Rc<RefCell<OpState>>,
   path: Option<String>,
+  options: OpenOptions,
 ) -> Result<ResourceId, AnyError>
 where
   DBH: DatabaseHandler + 'static,
@@ -97,11 +153,11 @@ where
       .check_unstable("Deno.openKv");
     state.borrow::<Rc<DBH>>().clone()
   };
-  let db = handler.open(state.clone(), path).await?;
-  let rid = state
-    .borrow_mut()
-    .resource_table
-    .add(DatabaseResource { db: Rc::new(db) });
+  let db = handler.open(state.clone(), path, options).await?;
+  let rid = state.borrow_mut().resource_table.add(DatabaseResource {
+    db: Rc::new(db),
+    cancel_handle: CancelHandle::new_rc(),
+  });
   Ok(rid)
 }
 
@@ -184,6 +240,25 @@ impl TryFrom<KvEntry> for V8KvEntry {
   }
 }
 
+#[derive(Serialize)]
+#[serde(tag = "kind", content = "entry", rename_all = "snake_case")]
+enum V8WatchKeyOutput {
+  Changed(Option<V8KvEntry>),
+  Unchanged,
+}
+
+impl TryFrom<WatchKeyOutput> for V8WatchKeyOutput {
+  type Error = AnyError;
+  fn try_from(output: WatchKeyOutput) -> Result<Self, AnyError> {
+    Ok(match output {
+      WatchKeyOutput::Changed { entry } => {
+        V8WatchKeyOutput::Changed(entry.map(TryInto::try_into).transpose()?)
+      }
+      WatchKeyOutput::Unchanged => V8WatchKeyOutput::Unchanged,
+    })
+  }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 enum V8Consistency {
@@ -216,6 +291,7 @@ async fn op_kv_snapshot_read<DBH>(
   rid: ResourceId,
   ranges: Vec<SnapshotReadRange>,
   consistency: V8Consistency,
+  at_versionstamp: Option<ByteString>,
 ) -> Result<Vec<Vec<V8KvEntry>>, AnyError>
 where
   DBH: DatabaseHandler + 'static,
@@ -227,15 +303,6 @@ where
     resource.db.clone()
   };
 
-  if ranges.len() > MAX_READ_RANGES {
-    return Err(type_error(format!(
-      "too many ranges (max {})",
-      MAX_READ_RANGES
-    )));
-  }
-
-  let mut total_entries = 0usize;
-
   let read_ranges = ranges
     .into_iter()
     .map(|(prefix, start, end, limit, reverse, cursor)| {
@@ -243,10 +310,6 @@ where
 
       let (start, end) =
         decode_selector_and_cursor(&selector, reverse, cursor.as_ref())?;
-      check_read_key_size(&start)?;
-      check_read_key_size(&end)?;
-
-      total_entries += limit as usize;
       Ok(ReadRange {
         start,
         end,
@@ -257,15 +320,21 @@ where
     })
     .collect::<Result<Vec<_>, AnyError>>()?;
 
-  if total_entries > MAX_READ_ENTRIES {
-    return Err(type_error(format!(
-      "too many entries (max {})",
-      MAX_READ_ENTRIES
-    )));
-  }
+  check_snapshot_read(&read_ranges)?;
+
+  let at_versionstamp = match at_versionstamp {
+    Some(data) => {
+      let mut out = [0u8; 10];
+      hex::decode_to_slice(data, &mut out)
+        .map_err(|_| type_error("invalid versionstamp"))?;
+      Some(out)
+    }
+    None => None,
+  };
 
   let opts = SnapshotReadOptions {
     consistency: consistency.into(),
+    at_versionstamp,
   };
   let output_ranges = db.snapshot_read(read_ranges, opts).await?;
   let output_ranges = output_ranges
@@ -301,12 +370,57 @@ impl TryFrom<V8KvCheck> for KvCheck {
   }
 }
 
-type V8KvMutation = (KvKey, String, Option<V8Value>);
+// (key, kind, value, expire_in, range)
+type V8KvMutation = (
+  KvKey,
+  String,
+  Option<V8Value>,
+  Option<u64>,
+  Option<V8RangeSelector>,
+);
 
-impl TryFrom<V8KvMutation> for KvMutation {
+impl TryFrom<(V8KvMutation, u64)> for KvMutation {
   type Error = AnyError;
-  fn try_from(value: V8KvMutation) -> Result<Self, AnyError> {
+  fn try_from(
+    (value, current_timestamp): (V8KvMutation, u64),
+  ) -> Result<Self, AnyError> {
+    if value.1 == "delete_range" {
+      let Some((prefix, start, end)) = value.4 else {
+        return Err(type_error("invalid mutation 'delete_range' without range"));
+      };
+      if value.2.is_some() || value.3.is_some() {
+        return Err(type_error(
+          "invalid mutation 'delete_range' with value or expireIn",
+        ));
+      }
+      let selector = RawSelector::from_tuple(prefix, start, end)?;
+      return Ok(KvMutation {
+        key: selector.range_start_key(),
+        kind: MutationKind::DeleteRange {
+          end: selector.range_end_key(),
+        },
+        expire_at: None,
+      });
+    }
+    if value.4.is_some() {
+      return Err(type_error(format!(
+        "invalid mutation '{}' with range",
+        value.1
+      )));
+    }
+
     let key = encode_v8_key(value.0)?;
+    let expire_at = match (value.1.as_str(), value.3) {
+      (_, None) => None,
+      ("set", Some(expire_in)) => {
+        Some(current_timestamp.saturating_add(expire_in))
+      }
+      (op, Some(_)) => {
+        return Err(type_error(format!(
+          "invalid mutation '{op}' with expireIn"
+        )))
+      }
+    };
     let kind = match (value.1.as_str(), value.2) {
       ("set", Some(value)) => MutationKind::Set(value.try_into()?),
       ("delete", None) => MutationKind::Delete,
@@ -322,10 +436,131 @@ impl TryFrom<V8KvMutation> for KvMutation {
         )))
       }
     };
-    Ok(KvMutation { key, kind })
+    Ok(KvMutation {
+      key,
+      kind,
+      expire_at,
+    })
   }
 }
 
+#[op]
+async fn op_kv_dequeue_next_message<DBH>(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<(ZeroCopyBuf, ResourceId), AnyError>
+where
+  DBH: DatabaseHandler + 'static,
+{
+  let (db, cancel_handle) = {
+    let state = state.borrow();
+    let resource =
+      state.resource_table.get::<DatabaseResource<DBH::DB>>(rid)?;
+    (resource.db.clone(), resource.cancel_handle.clone())
+  };
+
+  let mut handle = db.dequeue_next_message().or_cancel(cancel_handle).await??;
+  let payload = handle.take_payload().await?.into();
+  let handle_rid = {
+    let mut state = state.borrow_mut();
+    state.resource_table.add(QueueMessageResource { handle })
+  };
+  Ok((payload, handle_rid))
+}
+
+#[op]
+async fn op_kv_finish_dequeued_message<DBH>(
+  state: Rc<RefCell<OpState>>,
+  handle_rid: ResourceId,
+  success: bool,
+) -> Result<(), AnyError>
+where
+  DBH: DatabaseHandler + 'static,
+{
+  let handle = {
+    let mut state = state.borrow_mut();
+    let handle = state
+      .resource_table
+      .take::<QueueMessageResource<<DBH::DB as Database>::QMH>>(handle_rid)
+      .map_err(|_| type_error("Queue message not found"))?;
+    Rc::try_unwrap(handle)
+      .map_err(|_| type_error("Queue message not found"))?
+      .handle
+  };
+  handle.finish(success).await
+}
+
+#[op]
+fn op_kv_watch<DBH>(
+  state: &mut OpState,
+  rid: ResourceId,
+  keys: Vec<KvKey>,
+) -> Result<ResourceId, AnyError>
+where
+  DBH: DatabaseHandler + 'static,
+{
+  let resource = state.resource_table.get::<DatabaseResource<DBH::DB>>(rid)?;
+
+  if keys.len() > MAX_WATCHED_KEYS {
+    return Err(type_error(format!(
+      "too many keys (max {})",
+      MAX_WATCHED_KEYS
+    )));
+  }
+
+  let keys = keys
+    .into_iter()
+    .map(encode_v8_key)
+    .collect::<std::io::Result<Vec<_>>>()?;
+
+  for key in &keys {
+    check_read_key_size(key)?;
+  }
+
+  let stream = resource.db.watch(keys);
+  let rid = state.resource_table.add(DatabaseWatcherResource {
+    stream: AsyncRefCell::new(stream),
+    db_cancel_handle: resource.cancel_handle.clone(),
+    cancel_handle: CancelHandle::new_rc(),
+  });
+  Ok(rid)
+}
+
+#[op]
+async fn op_kv_watch_next(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<Option<Vec<V8WatchKeyOutput>>, AnyError> {
+  let resource = {
+    let state = state.borrow();
+    state.resource_table.get::<DatabaseWatcherResource>(rid)?
+  };
+
+  let db_cancel_handle = resource.db_cancel_handle.clone();
+  let cancel_handle = resource.cancel_handle.clone();
+  let mut stream = RcRef::map(&resource, |r| &r.stream).borrow_mut().await;
+
+  // Closing either the database or the watcher ends the stream.
+  let next = match stream
+    .next()
+    .or_cancel(db_cancel_handle)
+    .or_cancel(cancel_handle)
+    .await
+  {
+    Ok(Ok(next)) => next,
+    _ => return Ok(None),
+  };
+  let Some(outputs) = next else {
+    return Ok(None);
+  };
+
+  let outputs = outputs?
+    .into_iter()
+    .map(TryInto::try_into)
+    .collect::<Result<Vec<_>, AnyError>>()?;
+  Ok(Some(outputs))
+}
+
 type V8Enqueue = (ZeroCopyBuf, u64, Vec<KvKey>, Option<Vec<u32>>);
 
 impl TryFrom<V8Enqueue> for Enqueue {
@@ -333,7 +568,7 @@ impl TryFrom<V8Enqueue> for Enqueue {
   fn try_from(value: V8Enqueue) -> Result<Self, AnyError> {
     Ok(Enqueue {
       payload: value.0.to_vec(),
-      deadline_ms: value.1,
+      delay_ms: value.1,
       keys_if_undelivered: value
         .2
         .into_iter()
@@ -348,6 +583,9 @@ fn encode_v8_key(key: KvKey) -> Result<Vec<u8>, std::io::Error> {
   encode_key(&Key(key.into_iter().map(From::from).collect()))
 }
 
+// (prefix, start, end)
+type V8RangeSelector = (Option<KvKey>, Option<KvKey>, Option<KvKey>);
+
 enum RawSelector {
   Prefixed {
     prefix: Vec<u8>,
@@ -529,25 +767,15 @@ where
     resource.db.clone()
   };
 
-  if checks.len() > MAX_CHECKS {
-    return Err(type_error(format!("too many checks (max {})", MAX_CHECKS)));
-  }
-
-  if mutations.len() + enqueues.len() > MAX_MUTATIONS {
-    return Err(type_error(format!(
-      "too many mutations (max {})",
-      MAX_MUTATIONS
-    )));
-  }
-
   let checks = checks
     .into_iter()
     .map(TryInto::try_into)
     .collect::<Result<Vec<KvCheck>, AnyError>>()
     .with_context(|| "invalid check")?;
+  let current_timestamp = current_timestamp_ms();
   let mutations = mutations
     .into_iter()
-    .map(TryInto::try_into)
+    .map(|mutation| (mutation, current_timestamp).try_into())
     .collect::<Result<Vec<KvMutation>, AnyError>>()
     .with_context(|| "invalid mutation")?;
   let enqueues = enqueues
@@ -556,43 +784,22 @@ where
     .collect::<Result<Vec<Enqueue>, AnyError>>()
     .with_context(|| "invalid enqueue")?;
 
-  for key in checks
-    .iter()
-    .map(|c| &c.key)
-    .chain(mutations.iter().map(|m| &m.key))
-  {
-    if key.is_empty() {
-      return Err(type_error("key cannot be empty"));
-    }
-
-    check_write_key_size(key)?;
-  }
-
-  for value in mutations.iter().flat_map(|m| m.kind.value()) {
-    check_value_size(value)?;
-  }
-
-  for enqueue in &enqueues {
-    check_enqueue_payload_size(&enqueue.payload)?;
-  }
-
   let atomic_write = AtomicWrite {
     checks,
     mutations,
     enqueues,
   };
 
+  check_atomic_write(&atomic_write, current_timestamp)?;
+
   let result = db.atomic_write(atomic_write).await?;
 
   Ok(result.map(|res| hex::encode(res.versionstamp)))
 }
 
-// (prefix, start, end)
-type EncodeCursorRangeSelector = (Option<KvKey>, Option<KvKey>, Option<KvKey>);
-
 #[op]
 fn op_kv_encode_cursor(
-  (prefix, start, end): EncodeCursorRangeSelector,
+  (prefix, start, end): V8RangeSelector,
   boundary_key: KvKey,
 ) -> Result<String, AnyError> {
   let selector = RawSelector::from_tuple(prefix, start, end)?;
@@ -601,6 +808,111 @@ fn op_kv_encode_cursor(
   Ok(cursor)
 }
 
+pub(crate) fn current_timestamp_ms() -> u64 {
+  SystemTime::now()
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .unwrap()
+    .as_millis() as u64
+}
+
+/// Checks that a snapshot read stays within the limits of the KV API. This is
+/// shared by `op_kv_snapshot_read` and the remote server, which receives the
+/// ranges from clients that didn't necessarily check them.
+pub(crate) fn check_snapshot_read(
+  ranges: &[ReadRange],
+) -> Result<(), AnyError> {
+  if ranges.len() > MAX_READ_RANGES {
+    return Err(type_error(format!(
+      "too many ranges (max {})",
+      MAX_READ_RANGES
+    )));
+  }
+
+  let mut total_entries = 0usize;
+  for range in ranges {
+    check_read_key_size(&range.start)?;
+    check_read_key_size(&range.end)?;
+    total_entries += range.limit.get() as usize;
+  }
+
+  if total_entries > MAX_READ_ENTRIES {
+    return Err(type_error(format!(
+      "too many entries (max {})",
+      MAX_READ_ENTRIES
+    )));
+  }
+
+  Ok(())
+}
+
+/// Checks that an atomic write stays within the limits of the KV API. Like
+/// [check_snapshot_read], this is shared by `op_kv_atomic_write` and the
+/// remote server.
+pub(crate) fn check_atomic_write(
+  write: &AtomicWrite,
+  current_timestamp: u64,
+) -> Result<(), AnyError> {
+  if write.checks.len() > MAX_CHECKS {
+    return Err(type_error(format!("too many checks (max {})", MAX_CHECKS)));
+  }
+
+  if write.mutations.len() + write.enqueues.len() > MAX_MUTATIONS {
+    return Err(type_error(format!(
+      "too many mutations (max {})",
+      MAX_MUTATIONS
+    )));
+  }
+
+  for key in write.checks.iter().map(|c| &c.key).chain(
+    write
+      .mutations
+      .iter()
+      .filter(|m| !matches!(m.kind, MutationKind::DeleteRange { .. }))
+      .map(|m| &m.key),
+  ) {
+    if key.is_empty() {
+      return Err(type_error("key cannot be empty"));
+    }
+
+    check_write_key_size(key)?;
+  }
+
+  // The bounds of a deleted range are not keys themselves, so they are held
+  // to the same limits as the bounds of a read range.
+  for mutation in &write.mutations {
+    if let MutationKind::DeleteRange { end } = &mutation.kind {
+      check_read_key_size(&mutation.key)?;
+      check_read_key_size(end)?;
+    }
+  }
+
+  for value in write.mutations.iter().flat_map(|m| m.kind.value()) {
+    check_value_size(value)?;
+  }
+
+  for expire_at in write.mutations.iter().flat_map(|m| m.expire_at) {
+    check_expire_at(expire_at, current_timestamp)?;
+  }
+
+  for enqueue in &write.enqueues {
+    check_enqueue_payload_size(&enqueue.payload)?;
+    check_enqueue_delay(enqueue.delay_ms)?;
+    if let Some(schedule) = enqueue.backoff_schedule.as_deref() {
+      check_enqueue_backoff_schedule(schedule)?;
+    }
+  }
+
+  for key in write.enqueues.iter().flat_map(|e| &e.keys_if_undelivered) {
+    if key.is_empty() {
+      return Err(type_error("key cannot be empty"));
+    }
+
+    check_write_key_size(key)?;
+  }
+
+  Ok(())
+}
+
 fn check_read_key_size(key: &[u8]) -> Result<(), AnyError> {
   if key.len() > MAX_READ_KEY_SIZE_BYTES {
     Err(type_error(format!(
@@ -650,3 +962,47 @@ fn check_enqueue_payload_size(payload: &[u8]) -> Result<(), AnyError> {
     Ok(())
   }
 }
+
+fn check_enqueue_delay(delay_ms: u64) -> Result<(), AnyError> {
+  if delay_ms > MAX_QUEUE_DELAY_MS {
+    Err(type_error(format!(
+      "delay cannot be greater than {} milliseconds",
+      MAX_QUEUE_DELAY_MS
+    )))
+  } else {
+    Ok(())
+  }
+}
+
+fn check_enqueue_backoff_schedule(schedule: &[u32]) -> Result<(), AnyError> {
+  if schedule.len() > MAX_QUEUE_BACKOFF_INTERVALS {
+    return Err(type_error(format!(
+      "backoff schedule cannot have more than {} intervals",
+      MAX_QUEUE_BACKOFF_INTERVALS
+    )));
+  }
+  if schedule
+    .iter()
+    .any(|interval| *interval > MAX_QUEUE_BACKOFF_MS)
+  {
+    return Err(type_error(format!(
+      "backoff interval cannot be greater than {} milliseconds",
+      MAX_QUEUE_BACKOFF_MS
+    )));
+  }
+  Ok(())
+}
+
+fn check_expire_at(
+  expire_at: u64,
+  current_timestamp: u64,
+) -> Result<(), AnyError> {
+  if expire_at.saturating_sub(current_timestamp) > MAX_EXPIRE_IN_MS {
+    Err(type_error(format!(
+      "expireIn cannot be greater than {} milliseconds",
+      MAX_EXPIRE_IN_MS
+    )))
+  } else {
+    Ok(())
+  }
+}
@@ -0,0 +1,807 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! A database backend that talks to a remote KV server over HTTP.
+//!
+//! ## Wire protocol
+//!
+//! A remote database is identified by an `http://` or `https://` base URL.
+//! Every operation is a `POST` request to an endpoint relative to that URL,
+//! with a JSON request body and a JSON response body. All requests carry an
+//! `Authorization: Bearer <token>` header; the server responds with status
+//! `401` if the token is missing or invalid. Any other error is reported with
+//! a non-`2xx` status and a plain text body describing the error.
+//!
+//! Binary data (keys, values and payloads) is encoded as URL-safe base64
+//! strings. Keys are encoded with the key codec in `codec.rs`. Versionstamps
+//! are encoded as 20 character hex strings, the same as in the JS API.
+//!
+//! ### `snapshot_read`
+//!
+//! ```json
+//! {
+//!   "ranges": [{ "start": "<key>", "end": "<key>", "limit": 10, "reverse": false }],
+//!   "consistency": "strong",
+//!   "at_versionstamp": null
+//! }
+//! ```
+//!
+//! `at_versionstamp` is optional. If set, the ranges are read as of the
+//! write with that versionstamp, which requires the server's database to be
+//! in history mode.
+//!
+//! Responds with one list of entries per requested range, in order:
+//!
+//! ```json
+//! {
+//!   "ranges": [{
+//!     "entries": [{
+//!       "key": "<key>",
+//!       "value": { "kind": "v8", "value": "<bytes>" },
+//!       "versionstamp": "00000000000000010000"
+//!     }]
+//!   }]
+//! }
+//! ```
+//!
+//! Values are tagged with their `kind`, one of `v8` and `bytes` (base64 in
+//! `value`), or `u64` (a decimal string in `value`, to avoid losing precision
+//! in JSON parsers that only support doubles).
+//!
+//! ### `atomic_write`
+//!
+//! ```json
+//! {
+//!   "checks": [{ "key": "<key>", "versionstamp": null }],
+//!   "mutations": [{
+//!     "key": "<key>",
+//!     "kind": { "type": "set", "value": { "kind": "bytes", "value": "<bytes>" } },
+//!     "expire_at": null
+//!   }],
+//!   "enqueues": [{
+//!     "payload": "<bytes>",
+//!     "delay_ms": 0,
+//!     "keys_if_undelivered": ["<key>"],
+//!     "backoff_schedule": null
+//!   }]
+//! }
+//! ```
+//!
+//! The mutation `type` is one of `set`, `delete`, `delete_range`, `sum`,
+//! `min` and `max`. All but `delete` and `delete_range` carry a `value`.
+//! `delete_range` carries an exclusive `end` key, and deletes all keys from
+//! the mutation `key` up to it. `expire_at` is an optional Unix timestamp in
+//! milliseconds. The server responds with
+//! `{ "status": "success", "versionstamp": "..." }` if the write was
+//! committed, or `{ "status": "check_failure" }` if a check failed.
+//!
+//! ### `dequeue`
+//!
+//! The request body is `{}`. Waits for the next queue message to become
+//! ready and hands it out to the client:
+//!
+//! ```json
+//! { "message": { "id": "<id>", "payload": "<bytes>" } }
+//! ```
+//!
+//! To stay below the idle timeouts of proxies, the server responds with
+//! `{ "message": null }` if no message became ready within 30 seconds, in
+//! which case the client sends the request again.
+//!
+//! ### `finish`
+//!
+//! ```json
+//! { "id": "<id>", "success": true }
+//! ```
+//!
+//! Reports whether a dequeued message was processed successfully, and
+//! responds with `{}`. Failed messages are redelivered according to their
+//! backoff schedule. Messages that are not finished within 5 minutes are
+//! considered abandoned and are redelivered as well.
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::num::NonZeroU32;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use deno_core::error::generic_error;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::futures;
+use deno_core::futures::Stream;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_core::OpState;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::AtomicWrite;
+use crate::CommitResult;
+use crate::Consistency;
+use crate::Database;
+use crate::DatabaseHandler;
+use crate::Enqueue;
+use crate::KvCheck;
+use crate::KvEntry;
+use crate::KvMutation;
+use crate::MutationKind;
+use crate::OpenOptions;
+use crate::QueueMessageHandle;
+use crate::ReadRange;
+use crate::ReadRangeOutput;
+use crate::SnapshotReadOptions;
+use crate::Value;
+use crate::WatchKeyOutput;
+
+/// The environment variable that holds the access token used to
+/// authenticate with remote KV servers.
+pub const ACCESS_TOKEN_ENV_VAR: &str = "DENO_KV_ACCESS_TOKEN";
+
+/// How often a watcher on a remote database reads its keys to look for
+/// changes. Remote servers have no way to push changes to clients.
+const WATCH_POLL_INTERVAL_MS: u64 = 1000;
+
+pub trait RemoteDbHandlerPermissions {
+  fn check_env(&mut self, var: &str) -> Result<(), AnyError>;
+  fn check_net_url(
+    &mut self,
+    url: &Url,
+    api_name: &str,
+  ) -> Result<(), AnyError>;
+}
+
+pub struct RemoteDbHandler<P: RemoteDbHandlerPermissions + 'static> {
+  _p: PhantomData<P>,
+}
+
+impl<P: RemoteDbHandlerPermissions> RemoteDbHandler<P> {
+  pub fn new() -> Self {
+    Self { _p: PhantomData }
+  }
+}
+
+impl<P: RemoteDbHandlerPermissions> Default for RemoteDbHandler<P> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[async_trait(?Send)]
+impl<P: RemoteDbHandlerPermissions> DatabaseHandler for RemoteDbHandler<P> {
+  type DB = RemoteDb;
+
+  async fn open(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    options: OpenOptions,
+  ) -> Result<Self::DB, AnyError> {
+    let Some(url) = path else {
+      return Err(type_error("Missing database url"));
+    };
+    if options.history_retention.is_some() {
+      return Err(type_error(
+        "The historyRetention option is not supported by remote databases",
+      ));
+    }
+
+    let url = Url::parse(&url)?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+      return Err(type_error("Invalid database url scheme"));
+    }
+
+    let client = {
+      let mut state = state.borrow_mut();
+      let permissions = state.borrow_mut::<P>();
+      permissions.check_env(ACCESS_TOKEN_ENV_VAR)?;
+      permissions.check_net_url(&url, "Deno.openKv")?;
+      // Share the client of `fetch`, so that the root certificates, proxy
+      // and `--unsafely-ignore-certificate-errors` settings apply.
+      deno_fetch::get_or_create_client_from_state(&mut state)?
+    };
+
+    let access_token = std::env::var(ACCESS_TOKEN_ENV_VAR)
+      .ok()
+      .filter(|token| !token.is_empty())
+      .ok_or_else(|| {
+        type_error(format!(
+          "Missing {ACCESS_TOKEN_ENV_VAR} environment variable. Please set it to your access token for the remote database."
+        ))
+      })?;
+
+    Ok(RemoteDb::new(client, url, access_token))
+  }
+}
+
+/// A client for a remote KV server, speaking the wire protocol described in
+/// the module documentation.
+#[derive(Clone)]
+pub struct RemoteDb {
+  client: reqwest::Client,
+  url: Url,
+  access_token: String,
+}
+
+impl RemoteDb {
+  pub fn new(
+    client: reqwest::Client,
+    mut url: Url,
+    access_token: String,
+  ) -> Self {
+    // Endpoints are resolved relative to the base URL, so it must be treated
+    // as a directory.
+    if !url.path().ends_with('/') {
+      url.set_path(&format!("{}/", url.path()));
+    }
+    Self {
+      client,
+      url,
+      access_token,
+    }
+  }
+
+  async fn call<Req: Serialize, Res: DeserializeOwned>(
+    &self,
+    endpoint: &str,
+    request: &Req,
+  ) -> Result<Res, AnyError> {
+    let url = self.url.join(endpoint)?;
+    let res = self
+      .client
+      .post(url.as_str())
+      .bearer_auth(&self.access_token)
+      .header(reqwest::header::CONTENT_TYPE, "application/json")
+      .body(serde_json::to_vec(request)?)
+      .send()
+      .await?;
+
+    let status = res.status();
+    let body = res.bytes().await?;
+    if !status.is_success() {
+      return Err(generic_error(format!(
+        "Remote database returned an error ({}): {}",
+        status,
+        String::from_utf8_lossy(&body)
+      )));
+    }
+
+    Ok(serde_json::from_slice(&body)?)
+  }
+}
+
+#[async_trait(?Send)]
+impl Database for RemoteDb {
+  type QMH = RemoteQueueMessageHandle;
+
+  async fn snapshot_read(
+    &self,
+    requests: Vec<ReadRange>,
+    options: SnapshotReadOptions,
+  ) -> Result<Vec<ReadRangeOutput>, AnyError> {
+    let request = SnapshotReadRequest {
+      ranges: requests.into_iter().map(Into::into).collect(),
+      consistency: options.consistency.into(),
+      at_versionstamp: options.at_versionstamp.map(hex::encode),
+    };
+    let response: SnapshotReadResponse =
+      self.call("snapshot_read", &request).await?;
+    response.ranges.into_iter().map(TryInto::try_into).collect()
+  }
+
+  async fn atomic_write(
+    &self,
+    write: AtomicWrite,
+  ) -> Result<Option<CommitResult>, AnyError> {
+    let request: AtomicWriteRequest = write.into();
+    let response: AtomicWriteResponse =
+      self.call("atomic_write", &request).await?;
+    match response {
+      AtomicWriteResponse::Success { versionstamp } => Ok(Some(CommitResult {
+        versionstamp: decode_versionstamp(&versionstamp)?,
+      })),
+      AtomicWriteResponse::CheckFailure => Ok(None),
+    }
+  }
+
+  async fn dequeue_next_message(&self) -> Result<Self::QMH, AnyError> {
+    loop {
+      let response: DequeueResponse =
+        self.call("dequeue", &DequeueRequest {}).await?;
+      if let Some(message) = response.message {
+        return Ok(RemoteQueueMessageHandle {
+          db: self.clone(),
+          id: message.id,
+          payload: Some(message.payload.0),
+        });
+      }
+    }
+  }
+
+  fn watch(
+    &self,
+    keys: Vec<Vec<u8>>,
+  ) -> Pin<Box<dyn Stream<Item = Result<Vec<WatchKeyOutput>, AnyError>>>> {
+    let db = self.clone();
+    let state = (db, keys, None::<Vec<Option<[u8; 10]>>>);
+    Box::pin(futures::stream::unfold(
+      state,
+      |(db, keys, mut last_versionstamps)| async move {
+        loop {
+          if last_versionstamps.is_some() {
+            tokio::time::sleep(Duration::from_millis(WATCH_POLL_INTERVAL_MS))
+              .await;
+          }
+
+          let entries = match db.read_keys(&keys).await {
+            Ok(entries) => entries,
+            Err(err) => {
+              return Some((Err(err), (db, keys, last_versionstamps)))
+            }
+          };
+          let versionstamps = entries
+            .iter()
+            .map(|entry| entry.as_ref().map(|e| e.versionstamp))
+            .collect::<Vec<_>>();
+          if last_versionstamps.as_ref() == Some(&versionstamps) {
+            continue;
+          }
+
+          let outputs = entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| match &last_versionstamps {
+              Some(last) if last[i] == versionstamps[i] => {
+                WatchKeyOutput::Unchanged
+              }
+              _ => WatchKeyOutput::Changed { entry },
+            })
+            .collect();
+          last_versionstamps = Some(versionstamps);
+          return Some((Ok(outputs), (db, keys, last_versionstamps)));
+        }
+      },
+    ))
+  }
+}
+
+impl RemoteDb {
+  /// Reads the current entries of the given keys in a single snapshot.
+  async fn read_keys(
+    &self,
+    keys: &[Vec<u8>],
+  ) -> Result<Vec<Option<KvEntry>>, AnyError> {
+    let ranges = keys
+      .iter()
+      .map(|key| ReadRange {
+        start: key.clone(),
+        end: key.iter().copied().chain(Some(0)).collect(),
+        limit: NonZeroU32::new(1).unwrap(),
+        reverse: false,
+      })
+      .collect();
+    let outputs = self
+      .snapshot_read(
+        ranges,
+        SnapshotReadOptions {
+          consistency: Consistency::Strong,
+          at_versionstamp: None,
+        },
+      )
+      .await?;
+    Ok(
+      outputs
+        .into_iter()
+        .map(|output| output.entries.into_iter().next())
+        .collect(),
+    )
+  }
+}
+
+/// A message handed out by the `dequeue` endpoint, which is reported back
+/// to the `finish` endpoint once it has been processed.
+pub struct RemoteQueueMessageHandle {
+  db: RemoteDb,
+  id: String,
+  payload: Option<Vec<u8>>,
+}
+
+#[async_trait(?Send)]
+impl QueueMessageHandle for RemoteQueueMessageHandle {
+  async fn take_payload(&mut self) -> Result<Vec<u8>, AnyError> {
+    self
+      .payload
+      .take()
+      .ok_or_else(|| type_error("Payload already consumed"))
+  }
+
+  async fn finish(&self, success: bool) -> Result<(), AnyError> {
+    let request = FinishRequest {
+      id: self.id.clone(),
+      success,
+    };
+    let _: FinishResponse = self.db.call("finish", &request).await?;
+    Ok(())
+  }
+}
+
+/// Binary data, encoded as a URL-safe base64 string on the wire.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WireBytes(pub Vec<u8>);
+
+impl Serialize for WireBytes {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&base64::encode_config(&self.0, base64::URL_SAFE))
+  }
+}
+
+impl<'de> Deserialize<'de> for WireBytes {
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    base64::decode_config(s, base64::URL_SAFE)
+      .map(WireBytes)
+      .map_err(serde::de::Error::custom)
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SnapshotReadRequest {
+  pub ranges: Vec<WireReadRange>,
+  pub consistency: WireConsistency,
+  #[serde(default)]
+  pub at_versionstamp: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WireReadRange {
+  pub start: WireBytes,
+  pub end: WireBytes,
+  pub limit: u32,
+  pub reverse: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireConsistency {
+  Strong,
+  Eventual,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SnapshotReadResponse {
+  pub ranges: Vec<WireReadRangeOutput>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WireReadRangeOutput {
+  pub entries: Vec<WireKvEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WireKvEntry {
+  pub key: WireBytes,
+  pub value: WireValue,
+  pub versionstamp: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum WireValue {
+  V8(WireBytes),
+  Bytes(WireBytes),
+  U64(String),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AtomicWriteRequest {
+  pub checks: Vec<WireCheck>,
+  pub mutations: Vec<WireMutation>,
+  pub enqueues: Vec<WireEnqueue>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WireCheck {
+  pub key: WireBytes,
+  pub versionstamp: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WireMutation {
+  pub key: WireBytes,
+  pub kind: WireMutationKind,
+  pub expire_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WireMutationKind {
+  Set { value: WireValue },
+  Delete,
+  DeleteRange { end: WireBytes },
+  Sum { value: WireValue },
+  Min { value: WireValue },
+  Max { value: WireValue },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WireEnqueue {
+  pub payload: WireBytes,
+  pub delay_ms: u64,
+  pub keys_if_undelivered: Vec<WireBytes>,
+  pub backoff_schedule: Option<Vec<u32>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AtomicWriteResponse {
+  Success { versionstamp: String },
+  CheckFailure,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DequeueRequest {}
+
+#[derive(Serialize, Deserialize)]
+pub struct DequeueResponse {
+  pub message: Option<WireQueueMessage>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WireQueueMessage {
+  pub id: String,
+  pub payload: WireBytes,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FinishRequest {
+  pub id: String,
+  pub success: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FinishResponse {}
+
+pub(crate) fn decode_versionstamp(
+  versionstamp: &str,
+) -> Result<[u8; 10], AnyError> {
+  let mut out = [0u8; 10];
+  hex::decode_to_slice(versionstamp, &mut out)
+    .map_err(|_| type_error("invalid versionstamp"))?;
+  Ok(out)
+}
+
+impl From<Consistency> for WireConsistency {
+  fn from(value: Consistency) -> Self {
+    match value {
+      Consistency::Strong => WireConsistency::Strong,
+      Consistency::Eventual => WireConsistency::Eventual,
+    }
+  }
+}
+
+impl From<WireConsistency> for Consistency {
+  fn from(value: WireConsistency) -> Self {
+    match value {
+      WireConsistency::Strong => Consistency::Strong,
+      WireConsistency::Eventual => Consistency::Eventual,
+    }
+  }
+}
+
+impl From<ReadRange> for WireReadRange {
+  fn from(value: ReadRange) -> Self {
+    WireReadRange {
+      start: WireBytes(value.start),
+      end: WireBytes(value.end),
+      limit: value.limit.get(),
+      reverse: value.reverse,
+    }
+  }
+}
+
+impl TryFrom<WireReadRange> for ReadRange {
+  type Error = AnyError;
+  fn try_from(value: WireReadRange) -> Result<Self, AnyError> {
+    Ok(ReadRange {
+      start: value.start.0,
+      end: value.end.0,
+      limit: value
+        .limit
+        .try_into()
+        .map_err(|_| type_error("limit must be greater than 0"))?,
+      reverse: value.reverse,
+    })
+  }
+}
+
+impl From<Value> for WireValue {
+  fn from(value: Value) -> Self {
+    match value {
+      Value::V8(buf) => WireValue::V8(WireBytes(buf)),
+      Value::Bytes(buf) => WireValue::Bytes(WireBytes(buf)),
+      Value::U64(n) => WireValue::U64(n.to_string()),
+    }
+  }
+}
+
+impl TryFrom<WireValue> for Value {
+  type Error = AnyError;
+  fn try_from(value: WireValue) -> Result<Self, AnyError> {
+    Ok(match value {
+      WireValue::V8(buf) => Value::V8(buf.0),
+      WireValue::Bytes(buf) => Value::Bytes(buf.0),
+      WireValue::U64(n) => {
+        Value::U64(n.parse().map_err(|_| type_error("invalid u64 value"))?)
+      }
+    })
+  }
+}
+
+impl From<KvEntry> for WireKvEntry {
+  fn from(value: KvEntry) -> Self {
+    WireKvEntry {
+      key: WireBytes(value.key),
+      value: value.value.into(),
+      versionstamp: hex::encode(value.versionstamp),
+    }
+  }
+}
+
+impl TryFrom<WireKvEntry> for KvEntry {
+  type Error = AnyError;
+  fn try_from(value: WireKvEntry) -> Result<Self, AnyError> {
+    Ok(KvEntry {
+      key: value.key.0,
+      value: value.value.try_into()?,
+      versionstamp: decode_versionstamp(&value.versionstamp)?,
+    })
+  }
+}
+
+impl From<ReadRangeOutput> for WireReadRangeOutput {
+  fn from(value: ReadRangeOutput) -> Self {
+    WireReadRangeOutput {
+      entries: value.entries.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+impl TryFrom<WireReadRangeOutput> for ReadRangeOutput {
+  type Error = AnyError;
+  fn try_from(value: WireReadRangeOutput) -> Result<Self, AnyError> {
+    Ok(ReadRangeOutput {
+      entries: value
+        .entries
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, AnyError>>()?,
+    })
+  }
+}
+
+impl From<AtomicWrite> for AtomicWriteRequest {
+  fn from(value: AtomicWrite) -> Self {
+    AtomicWriteRequest {
+      checks: value
+        .checks
+        .into_iter()
+        .map(|check| WireCheck {
+          key: WireBytes(check.key),
+          versionstamp: check.versionstamp.map(hex::encode),
+        })
+        .collect(),
+      mutations: value
+        .mutations
+        .into_iter()
+        .map(|mutation| WireMutation {
+          key: WireBytes(mutation.key),
+          kind: match mutation.kind {
+            MutationKind::Set(value) => WireMutationKind::Set {
+              value: value.into(),
+            },
+            MutationKind::Delete => WireMutationKind::Delete,
+            MutationKind::DeleteRange { end } => {
+              WireMutationKind::DeleteRange {
+                end: WireBytes(end),
+              }
+            }
+            MutationKind::Sum(value) => WireMutationKind::Sum {
+              value: value.into(),
+            },
+            MutationKind::Min(value) => WireMutationKind::Min {
+              value: value.into(),
+            },
+            MutationKind::Max(value) => WireMutationKind::Max {
+              value: value.into(),
+            },
+          },
+          expire_at: mutation.expire_at,
+        })
+        .collect(),
+      enqueues: value
+        .enqueues
+        .into_iter()
+        .map(|enqueue| WireEnqueue {
+          payload: WireBytes(enqueue.payload),
+          delay_ms: enqueue.delay_ms,
+          keys_if_undelivered: enqueue
+            .keys_if_undelivered
+            .into_iter()
+            .map(WireBytes)
+            .collect(),
+          backoff_schedule: enqueue.backoff_schedule,
+        })
+        .collect(),
+    }
+  }
+}
+
+impl TryFrom<AtomicWriteRequest> for AtomicWrite {
+  type Error = AnyError;
+  fn try_from(value: AtomicWriteRequest) -> Result<Self, AnyError> {
+    Ok(AtomicWrite {
+      checks: value
+        .checks
+        .into_iter()
+        .map(|check| {
+          Ok(KvCheck {
+            key: check.key.0,
+            versionstamp: check
+              .versionstamp
+              .as_deref()
+              .map(decode_versionstamp)
+              .transpose()?,
+          })
+        })
+        .collect::<Result<_, AnyError>>()?,
+      mutations: value
+        .mutations
+        .into_iter()
+        .map(|mutation| {
+          Ok(KvMutation {
+            key: mutation.key.0,
+            kind: match mutation.kind {
+              WireMutationKind::Set { value } => {
+                MutationKind::Set(value.try_into()?)
+              }
+              WireMutationKind::Delete => MutationKind::Delete,
+              WireMutationKind::DeleteRange { end } => {
+                MutationKind::DeleteRange { end: end.0 }
+              }
+              WireMutationKind::Sum { value } => {
+                MutationKind::Sum(value.try_into()?)
+              }
+              WireMutationKind::Min { value } => {
+                MutationKind::Min(value.try_into()?)
+              }
+              WireMutationKind::Max { value } => {
+                MutationKind::Max(value.try_into()?)
+              }
+            },
+            expire_at: mutation.expire_at,
+          })
+        })
+        .collect::<Result<_, AnyError>>()?,
+      enqueues: value
+        .enqueues
+        .into_iter()
+        .map(|enqueue| Enqueue {
+          payload: enqueue.payload.0,
+          delay_ms: enqueue.delay_ms,
+          keys_if_undelivered: enqueue
+            .keys_if_undelivered
+            .into_iter()
+            .map(|key| key.0)
+            .collect(),
+          backoff_schedule: enqueue.backoff_schedule,
+        })
+        .collect(),
+    })
+  }
+}
@@ -0,0 +1,568 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! A reference server for the remote KV wire protocol (see the `remote`
+//! module), backed by a [SqliteDb]. This lets several processes share one
+//! database by opening it with `Deno.openKv("http://...")`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use hyper::body::HttpBody;
+use hyper::header::AUTHORIZATION;
+use hyper::header::CONTENT_TYPE;
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::Body;
+use hyper::Method;
+use hyper::Request;
+use hyper::Response;
+use hyper::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::net::TcpListener;
+use tokio::task::spawn_local;
+use uuid::Uuid;
+
+use crate::check_atomic_write;
+use crate::check_snapshot_read;
+use crate::current_timestamp_ms;
+use crate::remote::decode_versionstamp;
+use crate::remote::AtomicWriteRequest;
+use crate::remote::AtomicWriteResponse;
+use crate::remote::DequeueRequest;
+use crate::remote::DequeueResponse;
+use crate::remote::FinishRequest;
+use crate::remote::FinishResponse;
+use crate::remote::SnapshotReadRequest;
+use crate::remote::SnapshotReadResponse;
+use crate::remote::WireBytes;
+use crate::remote::WireQueueMessage;
+use crate::sqlite::SqliteDb;
+use crate::sqlite::SqliteQueueMessageHandle;
+use crate::sqlite::QUEUE_MESSAGE_DEADLINE_MS;
+use crate::AtomicWrite;
+use crate::Database;
+use crate::QueueMessageHandle;
+use crate::ReadRange;
+use crate::SnapshotReadOptions;
+
+/// The maximum size of a request body accepted by the server.
+const MAX_REQUEST_BODY_SIZE: u64 = 1024 * 1024;
+
+/// How long a `dequeue` request waits for a message to become ready before
+/// the server responds without one.
+const DEQUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+struct ServerState {
+  db: SqliteDb,
+  access_token: String,
+  /// Messages which were handed out to clients, by the id they were handed
+  /// out with, along with the time they were dequeued.
+  messages: RefCell<HashMap<String, (Instant, SqliteQueueMessageHandle)>>,
+}
+
+/// Serves the database on the given listener until an error occurs while
+/// accepting connections. Requests must carry `access_token` as a bearer
+/// token.
+///
+/// Like [SqliteDb] itself, this must be run within a `LocalSet`.
+pub async fn serve(
+  listener: TcpListener,
+  db: SqliteDb,
+  access_token: String,
+) -> Result<(), AnyError> {
+  let state = Rc::new(ServerState {
+    db,
+    access_token,
+    messages: Default::default(),
+  });
+
+  loop {
+    let (stream, _) = listener.accept().await?;
+    let state = state.clone();
+    let service = service_fn(move |req| handle_request(state.clone(), req));
+    let conn = Http::new()
+      .http1_only(true)
+      .with_executor(LocalExecutor)
+      .serve_connection(stream, service);
+    spawn_local(async move {
+      if let Err(err) = conn.await {
+        log::error!("KV server connection error: {err}");
+      }
+    });
+  }
+}
+
+async fn handle_request(
+  state: Rc<ServerState>,
+  req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+  let authorized = req
+    .headers()
+    .get(AUTHORIZATION)
+    .and_then(|value| value.as_bytes().strip_prefix(b"Bearer "))
+    .map(|token| constant_time_eq(token, state.access_token.as_bytes()))
+    .unwrap_or(false);
+  if !authorized {
+    return Ok(error_response(StatusCode::UNAUTHORIZED, "Unauthorized"));
+  }
+
+  if req.method() != Method::POST {
+    return Ok(error_response(
+      StatusCode::METHOD_NOT_ALLOWED,
+      "Method not allowed",
+    ));
+  }
+
+  // Endpoints are relative to the base URL of the database, so only the last
+  // path segment is significant.
+  let endpoint = req.uri().path().rsplit('/').next().unwrap_or_default();
+  let result = match endpoint {
+    "snapshot_read" => handle_json(req, |r| snapshot_read(&state.db, r)).await,
+    "atomic_write" => handle_json(req, |r| atomic_write(&state.db, r)).await,
+    "dequeue" => handle_json(req, |r| dequeue(&state, r)).await,
+    "finish" => handle_json(req, |r| finish(&state, r)).await,
+    _ => return Ok(error_response(StatusCode::NOT_FOUND, "Not found")),
+  };
+
+  Ok(match result {
+    Ok(response) => response,
+    Err(err) => {
+      error_response(StatusCode::INTERNAL_SERVER_ERROR, &err.to_string())
+    }
+  })
+}
+
+async fn handle_json<Req, Res, Fut>(
+  req: Request<Body>,
+  handler: impl FnOnce(Req) -> Fut,
+) -> Result<Response<Body>, AnyError>
+where
+  Req: DeserializeOwned,
+  Res: Serialize,
+  Fut: Future<Output = Result<Res, AnyError>>,
+{
+  let body = req.into_body();
+  if body.size_hint().lower() > MAX_REQUEST_BODY_SIZE {
+    return Ok(error_response(
+      StatusCode::PAYLOAD_TOO_LARGE,
+      "Request body too large",
+    ));
+  }
+  let body = hyper::body::to_bytes(body).await?;
+  if body.len() as u64 > MAX_REQUEST_BODY_SIZE {
+    return Ok(error_response(
+      StatusCode::PAYLOAD_TOO_LARGE,
+      "Request body too large",
+    ));
+  }
+  let request = match serde_json::from_slice(&body) {
+    Ok(request) => request,
+    Err(err) => {
+      return Ok(error_response(StatusCode::BAD_REQUEST, &err.to_string()))
+    }
+  };
+  let response = handler(request).await?;
+  Ok(
+    Response::builder()
+      .header(CONTENT_TYPE, "application/json")
+      .body(serde_json::to_vec(&response)?.into())?,
+  )
+}
+
+async fn snapshot_read(
+  db: &SqliteDb,
+  request: SnapshotReadRequest,
+) -> Result<SnapshotReadResponse, AnyError> {
+  let ranges = request
+    .ranges
+    .into_iter()
+    .map(TryInto::try_into)
+    .collect::<Result<Vec<ReadRange>, AnyError>>()?;
+  for range in &ranges {
+    if range.start > range.end {
+      return Err(type_error("invalid range"));
+    }
+  }
+  check_snapshot_read(&ranges)?;
+  let options = SnapshotReadOptions {
+    consistency: request.consistency.into(),
+    at_versionstamp: request
+      .at_versionstamp
+      .as_deref()
+      .map(decode_versionstamp)
+      .transpose()?,
+  };
+  let outputs = db.snapshot_read(ranges, options).await?;
+  Ok(SnapshotReadResponse {
+    ranges: outputs.into_iter().map(Into::into).collect(),
+  })
+}
+
+async fn atomic_write(
+  db: &SqliteDb,
+  request: AtomicWriteRequest,
+) -> Result<AtomicWriteResponse, AnyError> {
+  let write: AtomicWrite = request.try_into()?;
+  check_atomic_write(&write, current_timestamp_ms())?;
+  Ok(match db.atomic_write(write).await? {
+    Some(result) => AtomicWriteResponse::Success {
+      versionstamp: hex::encode(result.versionstamp),
+    },
+    None => AtomicWriteResponse::CheckFailure,
+  })
+}
+
+async fn dequeue(
+  state: &ServerState,
+  _request: DequeueRequest,
+) -> Result<DequeueResponse, AnyError> {
+  let mut handle = match tokio::time::timeout(
+    DEQUEUE_TIMEOUT,
+    state.db.dequeue_next_message(),
+  )
+  .await
+  {
+    Ok(handle) => handle?,
+    Err(_) => return Ok(DequeueResponse { message: None }),
+  };
+  let payload = handle.take_payload().await?;
+  let id = Uuid::new_v4().to_string();
+
+  let mut messages = state.messages.borrow_mut();
+  // Messages that were never finished have been made available for delivery
+  // again by the database by now, so their handles can be dropped.
+  let deadline = Duration::from_millis(QUEUE_MESSAGE_DEADLINE_MS);
+  messages.retain(|_, (dequeued_at, _)| dequeued_at.elapsed() < deadline);
+  messages.insert(id.clone(), (Instant::now(), handle));
+
+  Ok(DequeueResponse {
+    message: Some(WireQueueMessage {
+      id,
+      payload: WireBytes(payload),
+    }),
+  })
+}
+
+async fn finish(
+  state: &ServerState,
+  request: FinishRequest,
+) -> Result<FinishResponse, AnyError> {
+  let handle = state
+    .messages
+    .borrow_mut()
+    .remove(&request.id)
+    .map(|(_, handle)| handle)
+    .ok_or_else(|| type_error("Queue message not found"))?;
+  handle.finish(request.success).await?;
+  Ok(FinishResponse {})
+}
+
+/// Compares two byte strings in a time that only depends on their lengths, so
+/// that response times don't reveal how much of a token was guessed right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+  if a.len() != b.len() {
+    return false;
+  }
+  a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+  Response::builder()
+    .status(status)
+    .header(CONTENT_TYPE, "text/plain")
+    .body(message.to_string().into())
+    .unwrap()
+}
+
+#[derive(Clone)]
+struct LocalExecutor;
+
+impl<Fut> hyper::rt::Executor<Fut> for LocalExecutor
+where
+  Fut: Future + 'static,
+  Fut::Output: 'static,
+{
+  fn execute(&self, fut: Fut) {
+    spawn_local(fut);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::num::NonZeroU32;
+
+  use deno_core::url::Url;
+  use tokio::task::LocalSet;
+
+  use super::*;
+  use crate::remote::RemoteDb;
+  use crate::Consistency;
+  use crate::Enqueue;
+  use crate::KvCheck;
+  use crate::KvMutation;
+  use crate::MutationKind;
+  use crate::Value;
+
+  async fn start_server(access_token: &str) -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    let db = SqliteDb::new(conn).unwrap();
+    spawn_local(serve(listener, db, access_token.to_string()));
+    Url::parse(&format!("http://{addr}/kv")).unwrap()
+  }
+
+  fn set(key: &[u8], value: Value) -> AtomicWrite {
+    AtomicWrite {
+      checks: vec![],
+      mutations: vec![KvMutation {
+        key: key.to_vec(),
+        kind: MutationKind::Set(value),
+        expire_at: None,
+      }],
+      enqueues: vec![],
+    }
+  }
+
+  async fn get(db: &RemoteDb, key: &[u8]) -> Vec<crate::KvEntry> {
+    let mut outputs = db
+      .snapshot_read(
+        vec![ReadRange {
+          start: key.to_vec(),
+          end: key.iter().copied().chain(Some(0)).collect(),
+          limit: NonZeroU32::new(1).unwrap(),
+          reverse: false,
+        }],
+        SnapshotReadOptions {
+          consistency: Consistency::Strong,
+          at_versionstamp: None,
+        },
+      )
+      .await
+      .unwrap();
+    outputs.remove(0).entries
+  }
+
+  #[tokio::test]
+  async fn remote_read_write() {
+    LocalSet::new()
+      .run_until(async {
+        let url = start_server("secret").await;
+        let db =
+          RemoteDb::new(reqwest::Client::new(), url, "secret".to_string());
+
+        assert!(get(&db, b"a").await.is_empty());
+
+        let result = db
+          .atomic_write(set(b"a", Value::Bytes(b"hello".to_vec())))
+          .await
+          .unwrap()
+          .unwrap();
+
+        let entries = get(&db, b"a").await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, b"a");
+        assert_eq!(entries[0].versionstamp, result.versionstamp);
+        assert!(matches!(&entries[0].value, Value::Bytes(v) if v == b"hello"));
+
+        db.atomic_write(set(b"b", Value::U64(u64::MAX)))
+          .await
+          .unwrap()
+          .unwrap();
+        let entries = get(&db, b"b").await;
+        assert!(matches!(entries[0].value, Value::U64(u64::MAX)));
+      })
+      .await;
+  }
+
+  #[tokio::test]
+  async fn remote_check_failure() {
+    LocalSet::new()
+      .run_until(async {
+        let url = start_server("secret").await;
+        let db =
+          RemoteDb::new(reqwest::Client::new(), url, "secret".to_string());
+
+        db.atomic_write(set(b"a", Value::U64(1)))
+          .await
+          .unwrap()
+          .unwrap();
+
+        let mut write = set(b"a", Value::U64(2));
+        write.checks.push(KvCheck {
+          key: b"a".to_vec(),
+          versionstamp: None,
+        });
+        assert!(db.atomic_write(write).await.unwrap().is_none());
+
+        let entries = get(&db, b"a").await;
+        assert!(matches!(entries[0].value, Value::U64(1)));
+      })
+      .await;
+  }
+
+  #[tokio::test]
+  async fn remote_shared_between_clients() {
+    LocalSet::new()
+      .run_until(async {
+        let url = start_server("secret").await;
+        let db1 = RemoteDb::new(
+          reqwest::Client::new(),
+          url.clone(),
+          "secret".to_string(),
+        );
+        let db2 =
+          RemoteDb::new(reqwest::Client::new(), url, "secret".to_string());
+
+        db1
+          .atomic_write(set(b"a", Value::V8(vec![1, 2, 3])))
+          .await
+          .unwrap()
+          .unwrap();
+        let entries = get(&db2, b"a").await;
+        assert!(matches!(&entries[0].value, Value::V8(v) if v == &[1, 2, 3]));
+      })
+      .await;
+  }
+
+  #[tokio::test]
+  async fn remote_delete_range() {
+    LocalSet::new()
+      .run_until(async {
+        let url = start_server("secret").await;
+        let db =
+          RemoteDb::new(reqwest::Client::new(), url, "secret".to_string());
+
+        for key in [b"a", b"b", b"c"] {
+          db.atomic_write(set(key, Value::U64(1)))
+            .await
+            .unwrap()
+            .unwrap();
+        }
+
+        db.atomic_write(AtomicWrite {
+          checks: vec![],
+          mutations: vec![KvMutation {
+            key: b"a".to_vec(),
+            kind: MutationKind::DeleteRange { end: b"c".to_vec() },
+            expire_at: None,
+          }],
+          enqueues: vec![],
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert!(get(&db, b"a").await.is_empty());
+        assert!(get(&db, b"b").await.is_empty());
+        assert_eq!(get(&db, b"c").await.len(), 1);
+      })
+      .await;
+  }
+
+  #[tokio::test]
+  async fn remote_queue() {
+    LocalSet::new()
+      .run_until(async {
+        let url = start_server("secret").await;
+        let db =
+          RemoteDb::new(reqwest::Client::new(), url, "secret".to_string());
+
+        db.atomic_write(AtomicWrite {
+          checks: vec![],
+          mutations: vec![],
+          enqueues: vec![Enqueue {
+            payload: b"hello".to_vec(),
+            delay_ms: 0,
+            keys_if_undelivered: vec![b"undelivered".to_vec()],
+            backoff_schedule: Some(vec![]),
+          }],
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        let mut handle = db.dequeue_next_message().await.unwrap();
+        assert_eq!(handle.take_payload().await.unwrap(), b"hello");
+        handle.finish(false).await.unwrap();
+        // The handle can only be finished once.
+        assert!(handle.finish(false).await.is_err());
+
+        // The backoff schedule is empty, so the message was not retried and
+        // written to `keys_if_undelivered` instead.
+        let entries = get(&db, b"undelivered").await;
+        assert!(matches!(&entries[0].value, Value::V8(v) if v == b"hello"));
+      })
+      .await;
+  }
+
+  #[tokio::test]
+  async fn remote_limits() {
+    LocalSet::new()
+      .run_until(async {
+        let url = start_server("secret").await;
+        let db =
+          RemoteDb::new(reqwest::Client::new(), url, "secret".to_string());
+
+        let err = db
+          .atomic_write(set(b"a", Value::Bytes(vec![0; 65537])))
+          .await
+          .err()
+          .unwrap();
+        assert!(err.to_string().contains("value too large"), "{err}");
+
+        let err = db
+          .atomic_write(set(b"", Value::U64(1)))
+          .await
+          .err()
+          .unwrap();
+        assert!(err.to_string().contains("key cannot be empty"), "{err}");
+
+        let err = db
+          .snapshot_read(
+            vec![ReadRange {
+              start: vec![],
+              end: vec![0xff],
+              limit: NonZeroU32::new(1001).unwrap(),
+              reverse: false,
+            }],
+            SnapshotReadOptions {
+              consistency: Consistency::Strong,
+              at_versionstamp: None,
+            },
+          )
+          .await
+          .err()
+          .unwrap();
+        assert!(err.to_string().contains("too many entries"), "{err}");
+      })
+      .await;
+  }
+
+  #[tokio::test]
+  async fn remote_invalid_token() {
+    LocalSet::new()
+      .run_until(async {
+        let url = start_server("secret").await;
+        let db =
+          RemoteDb::new(reqwest::Client::new(), url, "wrong".to_string());
+
+        let err = db
+          .atomic_write(set(b"a", Value::U64(1)))
+          .await
+          .err()
+          .unwrap();
+        assert!(err.to_string().contains("401"), "{err}");
+      })
+      .await;
+  }
+}
@@ -5,16 +5,27 @@ use std::cell::RefCell;
 use std::marker::PhantomData;
 use std::path::Path;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::rc::Rc;
+use std::rc::Weak;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use async_trait::async_trait;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
+use deno_core::futures;
+use deno_core::futures::Stream;
+use deno_core::serde_json;
 use deno_core::OpState;
 use rusqlite::params;
 use rusqlite::OpenFlags;
 use rusqlite::OptionalExtension;
 use rusqlite::Transaction;
+use tokio::sync::watch;
+use tokio::sync::Notify;
+use tokio::task::spawn_local;
+use uuid::Uuid;
 
 use crate::AtomicWrite;
 use crate::CommitResult;
@@ -22,24 +33,121 @@ use crate::Database;
 use crate::DatabaseHandler;
 use crate::KvEntry;
 use crate::MutationKind;
+use crate::OpenOptions;
+use crate::QueueMessageHandle;
 use crate::ReadRange;
 use crate::ReadRangeOutput;
 use crate::SnapshotReadOptions;
 use crate::Value;
+use crate::WatchKeyOutput;
 
 const STATEMENT_INC_AND_GET_DATA_VERSION: &str =
   "update data_version set version = version + 1 where k = 0 returning version";
+const STATEMENT_ADVANCE_DATA_VERSION: &str =
+  "update data_version set version = max(version, ?) where k = 0";
+const STATEMENT_GET_DATA_VERSION: &str =
+  "select version from data_version where k = 0";
 const STATEMENT_KV_RANGE_SCAN: &str =
-  "select k, v, v_encoding, version from kv where k >= ? and k < ? order by k asc limit ?";
+  "select k, v, v_encoding, version from kv where k >= ? and k < ? and (expiration_ms < 0 or expiration_ms > ?) order by k asc limit ?";
 const STATEMENT_KV_RANGE_SCAN_REVERSE: &str =
-  "select k, v, v_encoding, version from kv where k >= ? and k < ? order by k desc limit ?";
+  "select k, v, v_encoding, version from kv where k >= ? and k < ? and (expiration_ms < 0 or expiration_ms > ?) order by k desc limit ?";
 const STATEMENT_KV_POINT_GET_VALUE_ONLY: &str =
-  "select v, v_encoding from kv where k = ?";
+  "select v, v_encoding from kv where k = ? and (expiration_ms < 0 or expiration_ms > ?)";
+const STATEMENT_KV_POINT_GET: &str =
+  "select k, v, v_encoding, version from kv where k = ? and (expiration_ms < 0 or expiration_ms > ?)";
 const STATEMENT_KV_POINT_GET_VERSION_ONLY: &str =
-  "select version from kv where k = ?";
+  "select version from kv where k = ? and (expiration_ms < 0 or expiration_ms > ?)";
 const STATEMENT_KV_POINT_SET: &str =
-  "insert into kv (k, v, v_encoding, version) values (:k, :v, :v_encoding, :version) on conflict(k) do update set v = :v, v_encoding = :v_encoding, version = :version";
+  "insert into kv (k, v, v_encoding, version, expiration_ms) values (:k, :v, :v_encoding, :version, :expiration_ms) on conflict(k) do update set v = :v, v_encoding = :v_encoding, version = :version, expiration_ms = :expiration_ms";
 const STATEMENT_KV_POINT_DELETE: &str = "delete from kv where k = ?";
+const STATEMENT_KV_RANGE_DELETE: &str = "delete from kv where k >= ? and k < ?";
+const STATEMENT_KV_DUMP: &str =
+  "select k, v, v_encoding, version, expiration_ms from kv where expiration_ms < 0 or expiration_ms > ? order by k asc";
+const STATEMENT_KV_DELETE_EXPIRED: &str =
+  "delete from kv where expiration_ms >= 0 and expiration_ms <= ?";
+
+const STATEMENT_HISTORY_GET_CONFIG: &str =
+  "select retention_ms, min_version from kv_history_config where k = 0";
+const STATEMENT_HISTORY_SET_RETENTION: &str = "insert into kv_history_config (k, retention_ms, min_version) values (0, ?, ?) on conflict(k) do update set retention_ms = excluded.retention_ms";
+const STATEMENT_HISTORY_ADVANCE_MIN_VERSION: &str =
+  "update kv_history_config set min_version = max(min_version, ?) where k = 0";
+const STATEMENT_HISTORY_ADD_VERSION: &str =
+  "insert or ignore into kv_history_version (version, ts) values (?, ?)";
+const STATEMENT_HISTORY_GET_VERSION_TS: &str =
+  "select ts from kv_history_version where version <= ? order by version desc limit 1";
+const STATEMENT_HISTORY_GET_FIRST_VERSION_SINCE: &str =
+  "select min(version) from kv_history_version where ts >= ?";
+const STATEMENT_HISTORY_DELETE_SUPERSEDED: &str =
+  "delete from kv_history where superseded_version <= ?";
+const STATEMENT_HISTORY_DELETE_VERSIONS: &str =
+  "delete from kv_history_version where version < ?";
+const STATEMENT_HISTORY_RANGE_SCAN: &str = "
+select k, v, v_encoding, version from (
+  select k, v, v_encoding, version, expiration_ms from kv
+    where k >= ?1 and k < ?2 and version <= ?3
+  union all
+  select k, v, v_encoding, version, expiration_ms from kv_history
+    where k >= ?1 and k < ?2 and version <= ?3 and superseded_version > ?3
+) where expiration_ms < 0 or expiration_ms > ?4 order by k asc limit ?5";
+const STATEMENT_HISTORY_RANGE_SCAN_REVERSE: &str = "
+select k, v, v_encoding, version from (
+  select k, v, v_encoding, version, expiration_ms from kv
+    where k >= ?1 and k < ?2 and version <= ?3
+  union all
+  select k, v, v_encoding, version, expiration_ms from kv_history
+    where k >= ?1 and k < ?2 and version <= ?3 and superseded_version > ?3
+) where expiration_ms < 0 or expiration_ms > ?4 order by k desc limit ?5";
+
+/// Triggers that record the previous state of every key written while
+/// history mode is enabled, along with the time of every version.
+///
+/// A superseded row is visible to reads at versions `v` with
+/// `version <= v < superseded_version`. Rows deleted by the garbage
+/// collection of expired keys are not superseded by any write, so they stay
+/// visible at the current version, where they are filtered out by their
+/// expiration time like any other expired row.
+const STATEMENT_HISTORY_CREATE_TRIGGERS: &str = "
+create trigger if not exists kv_history_update after update on kv
+when old.version < new.version
+begin
+  insert or ignore into kv_history (k, v, v_encoding, version, expiration_ms, superseded_version)
+    values (old.k, old.v, old.v_encoding, old.version, old.expiration_ms, new.version);
+end;
+create trigger if not exists kv_history_delete after delete on kv
+begin
+  insert or ignore into kv_history (k, v, v_encoding, version, expiration_ms, superseded_version)
+    select old.k, old.v, old.v_encoding, old.version, old.expiration_ms, superseded_version
+    from (
+      select version + (old.expiration_ms >= 0 and old.expiration_ms <= (julianday('now') - 2440587.5) * 86400000) as superseded_version
+      from data_version where k = 0
+    )
+    where old.version < superseded_version;
+end;
+create trigger if not exists kv_history_version after update on data_version
+begin
+  insert or ignore into kv_history_version (version, ts)
+    values (new.version, cast((julianday('now') - 2440587.5) * 86400000 as integer));
+end;
+";
+const STATEMENT_HISTORY_DISABLE: &str = "
+drop trigger if exists kv_history_update;
+drop trigger if exists kv_history_delete;
+drop trigger if exists kv_history_version;
+delete from kv_history;
+delete from kv_history_version;
+delete from kv_history_config;
+";
+
+const STATEMENT_QUEUE_ADD_READY: &str = "insert into queue (ts, id, data, backoff_schedule, keys_if_undelivered) values(?, ?, ?, ?, ?)";
+const STATEMENT_QUEUE_GET_NEXT_READY: &str = "select ts, id, data, backoff_schedule, keys_if_undelivered from queue where ts <= ? order by ts limit 1";
+const STATEMENT_QUEUE_GET_EARLIEST_READY: &str =
+  "select ts from queue order by ts limit 1";
+const STATEMENT_QUEUE_REMOVE_READY: &str = "delete from queue where id = ?";
+const STATEMENT_QUEUE_ADD_RUNNING: &str = "insert into queue_running (deadline, id, data, backoff_schedule, keys_if_undelivered) values(?, ?, ?, ?, ?)";
+const STATEMENT_QUEUE_REMOVE_RUNNING: &str =
+  "delete from queue_running where id = ?";
+const STATEMENT_QUEUE_GET_RUNNING_BY_ID: &str = "select deadline, id, data, backoff_schedule, keys_if_undelivered from queue_running where id = ?";
+const STATEMENT_QUEUE_GET_EXPIRED_RUNNING: &str = "select deadline, id, data, backoff_schedule, keys_if_undelivered from queue_running where deadline <= ? order by deadline";
 
 const STATEMENT_CREATE_MIGRATION_TABLE: &str = "
 create table if not exists migration_state(
@@ -48,7 +156,7 @@ create table if not exists migration_state(
 )
 ";
 
-const MIGRATIONS: [&str; 2] = [
+const MIGRATIONS: [&str; 4] = [
   "
 create table data_version (
   k integer primary key,
@@ -81,9 +189,59 @@ create table queue_running(
 
   primary key (deadline, id)
 );
+",
+  "
+alter table kv add column expiration_ms integer not null default -1;
+create index kv_expiration_ms_idx on kv (expiration_ms);
+",
+  "
+create table kv_history (
+  k blob not null,
+  v blob not null,
+  v_encoding integer not null,
+  version integer not null,
+  expiration_ms integer not null,
+  superseded_version integer not null,
+
+  primary key (k, superseded_version)
+) without rowid;
+create index kv_history_superseded_version_idx on kv_history (superseded_version);
+create table kv_history_version (
+  version integer primary key,
+  ts integer not null
+);
+create table kv_history_config (
+  k integer primary key,
+  retention_ms integer not null,
+  min_version integer not null
+);
 ",
 ];
 
+/// The backoff schedule used for a queue message when none was specified on
+/// enqueue, in milliseconds.
+const DEFAULT_BACKOFF_SCHEDULE: [u32; 5] = [100, 1000, 5000, 30000, 60000];
+
+/// How long a dequeued message may be in-flight before it is considered
+/// abandoned (for example because the process handling it crashed) and is
+/// made available for delivery again.
+pub(crate) const QUEUE_MESSAGE_DEADLINE_MS: u64 = 5 * 60 * 1000;
+
+/// The maximum time a listener waits before looking at the queue again. This
+/// bounds the latency of picking up messages enqueued by other processes,
+/// which can not wake up listeners in this process.
+const QUEUE_POLL_INTERVAL_MS: u64 = 1000;
+
+/// How often expired keys are garbage-collected from the database. Expired
+/// keys are filtered out of reads regardless, so this only affects how long
+/// they keep taking up space.
+const EXPIRATION_GC_INTERVAL_MS: u64 = 60 * 1000;
+
+/// How often watchers look at the versions of their keys when not woken up
+/// by a write. This bounds the latency of observing writes made by other
+/// processes, and keys that expired.
+const WATCH_POLL_INTERVAL_MS: u64 = 1000;
+
 pub struct SqliteDbHandler<P: SqliteDbHandlerPermissions + 'static> {
   pub default_storage_dir: Option<PathBuf>,
   _permissions: PhantomData<P>,
@@ -111,6 +269,7 @@ impl<P: SqliteDbHandlerPermissions> DatabaseHandler for SqliteDbHandler<P> {
     &self,
     state: Rc<RefCell<OpState>>,
     path: Option<String>,
+    options: OpenOptions,
   ) -> Result<Self::DB, AnyError> {
     let conn = match (path.as_deref(), &self.default_storage_dir) {
       (Some(":memory:"), _) | (None, None) => {
@@ -142,6 +301,31 @@ impl<P: SqliteDbHandlerPermissions> DatabaseHandler for SqliteDbHandler<P> {
       }
     };
 
+    let db = SqliteDb::new(conn)?;
+    if let Some(retention_ms) = options.history_retention {
+      db.set_history_retention(
+        (retention_ms > 0).then(|| Duration::from_millis(retention_ms)),
+      )?;
+    }
+    Ok(db)
+  }
+}
+
+pub struct SqliteDb {
+  conn: Rc<RefCell<rusqlite::Connection>>,
+  queue_notify: Rc<Notify>,
+  /// Signalled after every committed write to the `kv` table, to wake up
+  /// watchers in this process.
+  write_notify: Rc<watch::Sender<()>>,
+}
+
+impl SqliteDb {
+  /// Creates a database from an open SQLite connection, migrating the schema
+  /// to the latest version if necessary.
+  ///
+  /// This spawns a background task on the current `LocalSet` that
+  /// garbage-collects expired keys, so it must be called from within one.
+  pub fn new(conn: rusqlite::Connection) -> Result<Self, AnyError> {
     conn.pragma_update(None, "journal_mode", "wal")?;
     conn.execute(STATEMENT_CREATE_MIGRATION_TABLE, [])?;
 
@@ -165,51 +349,202 @@ impl<P: SqliteDbHandlerPermissions> DatabaseHandler for SqliteDbHandler<P> {
       }
     }
 
-    Ok(SqliteDb(RefCell::new(conn)))
+    let conn = Rc::new(RefCell::new(conn));
+    spawn_local(collect_expired_keys(Rc::downgrade(&conn)));
+
+    Ok(SqliteDb {
+      conn,
+      queue_notify: Rc::new(Notify::new()),
+      write_notify: Rc::new(watch::channel(()).0),
+    })
+  }
+
+  /// Creates a database from an open SQLite connection for reading only. The
+  /// schema is not migrated and expired keys are not garbage-collected, so
+  /// the database file is left untouched. Fails if the schema is not at the
+  /// latest version.
+  pub fn new_read_only(conn: rusqlite::Connection) -> Result<Self, AnyError> {
+    // The table does not exist until the database has been migrated once.
+    let current_version: usize = conn
+      .query_row(
+        "select version from migration_state where k = 0",
+        [],
+        |row| row.get(0),
+      )
+      .unwrap_or(0);
+    if current_version < MIGRATIONS.len() {
+      return Err(type_error(
+        "The database schema is out of date. It is migrated the next time it is written to.",
+      ));
+    }
+
+    Ok(SqliteDb {
+      conn: Rc::new(RefCell::new(conn)),
+      queue_notify: Rc::new(Notify::new()),
+      write_notify: Rc::new(watch::channel(()).0),
+    })
+  }
+
+  /// Enables history mode with the given retention window, or disables it
+  /// and discards the recorded history if `retention` is `None`.
+  ///
+  /// While history mode is enabled, superseded values are kept so that the
+  /// database can be read as of any versionstamp committed within the
+  /// retention window (see [SnapshotReadOptions::at_versionstamp]). The mode
+  /// is stored in the database, so it stays in effect for all connections
+  /// until it is disabled again.
+  pub fn set_history_retention(
+    &self,
+    retention: Option<Duration>,
+  ) -> Result<(), AnyError> {
+    let mut db = self.conn.borrow_mut();
+    let tx = db.transaction()?;
+    match retention {
+      Some(retention) => {
+        let version: i64 = tx
+          .prepare_cached(STATEMENT_GET_DATA_VERSION)?
+          .query_row([], |row| row.get(0))?;
+        tx.execute_batch(STATEMENT_HISTORY_CREATE_TRIGGERS)?;
+        tx.prepare_cached(STATEMENT_HISTORY_SET_RETENTION)?
+          .execute(params![retention.as_millis() as i64, version])?;
+        tx.prepare_cached(STATEMENT_HISTORY_ADD_VERSION)?
+          .execute(params![version, now_ms()])?;
+      }
+      None => tx.execute_batch(STATEMENT_HISTORY_DISABLE)?,
+    }
+    tx.commit()?;
+    Ok(())
+  }
+
+  /// Calls `f` with every entry in the database that has not expired, in key
+  /// order, read from a single consistent snapshot.
+  pub fn dump(
+    &self,
+    mut f: impl FnMut(DumpedKvEntry) -> Result<(), AnyError>,
+  ) -> Result<(), AnyError> {
+    let mut db = self.conn.borrow_mut();
+    let tx = db.transaction()?;
+    let mut stmt = tx.prepare_cached(STATEMENT_KV_DUMP)?;
+    let mut rows = stmt.query([now_ms()])?;
+    while let Some(row) = rows.next()? {
+      let key: Vec<u8> = row.get(0)?;
+      let value: Vec<u8> = row.get(1)?;
+      let encoding: i64 = row.get(2)?;
+      let version: i64 = row.get(3)?;
+      let expiration_ms: i64 = row.get(4)?;
+      f(DumpedKvEntry {
+        key,
+        value: decode_value(value, encoding),
+        versionstamp: version_to_versionstamp(version),
+        expire_at: (expiration_ms >= 0).then_some(expiration_ms as u64),
+      })?;
+    }
+    Ok(())
+  }
+
+  /// Writes the given entries to the database in a single transaction,
+  /// keeping their versionstamps. The version of the database is advanced
+  /// past the largest restored versionstamp, so that later writes still get
+  /// greater versionstamps than any existing entry.
+  ///
+  /// If any item of `entries` is an error, nothing is written and the error
+  /// is returned.
+  pub fn restore(
+    &self,
+    entries: impl IntoIterator<Item = Result<DumpedKvEntry, AnyError>>,
+  ) -> Result<(), AnyError> {
+    let mut db = self.conn.borrow_mut();
+    let tx = db.transaction()?;
+    let mut max_version = 0;
+
+    for entry in entries {
+      let entry = entry?;
+      let version = versionstamp_to_version(&entry.versionstamp)
+        .ok_or_else(|| type_error("invalid versionstamp"))?;
+      max_version = max_version.max(version);
+
+      let (value, encoding) = encode_value(&entry.value);
+      let expiration_ms = expire_at_to_expiration_ms(entry.expire_at);
+      let changed =
+        tx.prepare_cached(STATEMENT_KV_POINT_SET)?.execute(params![
+          entry.key,
+          &value,
+          &encoding,
+          &version,
+          &expiration_ms
+        ])?;
+      assert_eq!(changed, 1)
+    }
+
+    tx.prepare_cached(STATEMENT_ADVANCE_DATA_VERSION)?
+      .execute([max_version])?;
+    tx.commit()?;
+
+    self.write_notify.send_replace(());
+    Ok(())
   }
 }
 
-pub struct SqliteDb(RefCell<rusqlite::Connection>);
+/// An entry along with the metadata needed to recreate it exactly, as
+/// produced by [SqliteDb::dump] and consumed by [SqliteDb::restore].
+pub struct DumpedKvEntry {
+  pub key: Vec<u8>,
+  pub value: Value,
+  pub versionstamp: [u8; 10],
+  pub expire_at: Option<u64>,
+}
 
 #[async_trait(?Send)]
 impl Database for SqliteDb {
+  type QMH = SqliteQueueMessageHandle;
+
   async fn snapshot_read(
     &self,
     requests: Vec<ReadRange>,
-    _options: SnapshotReadOptions,
+    options: SnapshotReadOptions,
   ) -> Result<Vec<ReadRangeOutput>, AnyError> {
     let mut responses = Vec::with_capacity(requests.len());
-    let mut db = self.0.borrow_mut();
+    let mut db = self.conn.borrow_mut();
     let tx = db.transaction()?;
+    let now = now_ms();
+
+    let at_version = match &options.at_versionstamp {
+      Some(versionstamp) => Some(get_history_read_version(&tx, versionstamp)?),
+      None => None,
+    };
 
     for request in requests {
-      let mut stmt = tx.prepare_cached(if request.reverse {
-        STATEMENT_KV_RANGE_SCAN_REVERSE
-      } else {
-        STATEMENT_KV_RANGE_SCAN
-      })?;
-      let entries = stmt
-        .query_map(
-          (
-            request.start.as_slice(),
-            request.end.as_slice(),
-            request.limit.get(),
-          ),
-          |row| {
-            let key: Vec<u8> = row.get(0)?;
-            let value: Vec<u8> = row.get(1)?;
-            let encoding: i64 = row.get(2)?;
-
-            let value = decode_value(value, encoding);
-
-            let version: i64 = row.get(3)?;
-            Ok(KvEntry {
-              key,
-              value,
-              versionstamp: version_to_versionstamp(version),
-            })
-          },
-        )?
+      let mut stmt =
+        tx.prepare_cached(match (at_version, request.reverse) {
+          (None, false) => STATEMENT_KV_RANGE_SCAN,
+          (None, true) => STATEMENT_KV_RANGE_SCAN_REVERSE,
+          (Some(_), false) => STATEMENT_HISTORY_RANGE_SCAN,
+          (Some(_), true) => STATEMENT_HISTORY_RANGE_SCAN_REVERSE,
+        })?;
+      let start = request.start.as_slice();
+      let end = request.end.as_slice();
+      let limit = request.limit.get();
+      let rows = match at_version {
+        None => stmt.query(params![start, end, now, limit])?,
+        Some((version, ts)) => {
+          stmt.query(params![start, end, version, ts, limit])?
+        }
+      };
+      let entries = rows
+        .mapped(|row| {
+          let key: Vec<u8> = row.get(0)?;
+          let value: Vec<u8> = row.get(1)?;
+          let encoding: i64 = row.get(2)?;
+
+          let value = decode_value(value, encoding);
+
+          let version: i64 = row.get(3)?;
+          Ok(KvEntry {
+            key,
+            value,
+            versionstamp: version_to_versionstamp(version),
+          })
+        })
         .collect::<Result<Vec<_>, rusqlite::Error>>()?;
       responses.push(ReadRangeOutput { entries });
     }
@@ -221,14 +556,15 @@ impl Database for SqliteDb {
     &self,
     write: AtomicWrite,
   ) -> Result<Option<CommitResult>, AnyError> {
-    let mut db = self.0.borrow_mut();
+    let mut db = self.conn.borrow_mut();
 
     let tx = db.transaction()?;
+    let now = now_ms();
 
     for check in write.checks {
       let real_versionstamp = tx
         .prepare_cached(STATEMENT_KV_POINT_GET_VERSION_ONLY)?
-        .query_row([check.key.as_slice()], |row| row.get(0))
+        .query_row(params![check.key.as_slice(), now], |row| row.get(0))
         .optional()?
         .map(version_to_versionstamp);
       if real_versionstamp != check.versionstamp {
@@ -244,9 +580,15 @@ impl Database for SqliteDb {
       match mutation.kind {
         MutationKind::Set(value) => {
           let (value, encoding) = encode_value(&value);
-          let changed = tx
-            .prepare_cached(STATEMENT_KV_POINT_SET)?
-            .execute(params![mutation.key, &value, &encoding, &version])?;
+          let expiration_ms = expire_at_to_expiration_ms(mutation.expire_at);
+          let changed =
+            tx.prepare_cached(STATEMENT_KV_POINT_SET)?.execute(params![
+              mutation.key,
+              &value,
+              &encoding,
+              &version,
+              &expiration_ms
+            ])?;
           assert_eq!(changed, 1)
         }
         MutationKind::Delete => {
@@ -255,34 +597,518 @@ impl Database for SqliteDb {
             .execute(params![mutation.key])?;
           assert!(changed == 0 || changed == 1)
         }
+        MutationKind::DeleteRange { end } => {
+          tx.prepare_cached(STATEMENT_KV_RANGE_DELETE)?
+            .execute(params![mutation.key, end])?;
+        }
         MutationKind::Sum(operand) => {
-          mutate_le64(&tx, &mutation.key, "sum", &operand, version, |a, b| {
-            a.wrapping_add(b)
-          })?;
+          mutate_le64(
+            &tx,
+            &mutation.key,
+            "sum",
+            &operand,
+            version,
+            now,
+            |a, b| a.wrapping_add(b),
+          )?;
         }
         MutationKind::Min(operand) => {
-          mutate_le64(&tx, &mutation.key, "min", &operand, version, |a, b| {
-            a.min(b)
-          })?;
+          mutate_le64(
+            &tx,
+            &mutation.key,
+            "min",
+            &operand,
+            version,
+            now,
+            |a, b| a.min(b),
+          )?;
         }
         MutationKind::Max(operand) => {
-          mutate_le64(&tx, &mutation.key, "max", &operand, version, |a, b| {
-            a.max(b)
-          })?;
+          mutate_le64(
+            &tx,
+            &mutation.key,
+            "max",
+            &operand,
+            version,
+            now,
+            |a, b| a.max(b),
+          )?;
         }
       }
     }
 
-    // TODO(@losfair): enqueues
+    let has_enqueues = !write.enqueues.is_empty();
+    for enqueue in write.enqueues {
+      let id = Uuid::new_v4().to_string();
+      let backoff_schedule = serde_json::to_string(
+        &enqueue
+          .backoff_schedule
+          .unwrap_or_else(|| DEFAULT_BACKOFF_SCHEDULE.to_vec()),
+      )?;
+      let keys_if_undelivered =
+        serde_json::to_vec(&enqueue.keys_if_undelivered)?;
+
+      let changed =
+        tx.prepare_cached(STATEMENT_QUEUE_ADD_READY)?
+          .execute(params![
+            now + enqueue.delay_ms,
+            id,
+            &enqueue.payload,
+            &backoff_schedule,
+            &keys_if_undelivered
+          ])?;
+      assert_eq!(changed, 1)
+    }
 
     tx.commit()?;
 
+    self.write_notify.send_replace(());
+    if has_enqueues {
+      self.queue_notify.notify_one();
+    }
+
     let new_vesionstamp = version_to_versionstamp(version);
 
     Ok(Some(CommitResult {
       versionstamp: new_vesionstamp,
     }))
   }
+
+  async fn dequeue_next_message(
+    &self,
+  ) -> Result<SqliteQueueMessageHandle, AnyError> {
+    loop {
+      let now = now_ms();
+      let next_ready_ts = {
+        let mut db = self.conn.borrow_mut();
+        let tx = db.transaction()?;
+
+        requeue_expired_running_messages(&tx, now)?;
+
+        let message = tx
+          .prepare_cached(STATEMENT_QUEUE_GET_NEXT_READY)?
+          .query_row([now], QueueMessage::from_row)
+          .optional()?;
+
+        if let Some(message) = message {
+          let changed = tx
+            .prepare_cached(STATEMENT_QUEUE_REMOVE_READY)?
+            .execute([&message.id])?;
+          assert_eq!(changed, 1);
+
+          let changed = tx
+            .prepare_cached(STATEMENT_QUEUE_ADD_RUNNING)?
+            .execute(params![
+              now + QUEUE_MESSAGE_DEADLINE_MS,
+              &message.id,
+              &message.payload,
+              &message.backoff_schedule,
+              &message.keys_if_undelivered
+            ])?;
+          assert_eq!(changed, 1);
+          tx.commit()?;
+
+          return Ok(SqliteQueueMessageHandle {
+            id: message.id,
+            payload: Some(message.payload),
+            conn: self.conn.clone(),
+            queue_notify: self.queue_notify.clone(),
+            write_notify: self.write_notify.clone(),
+          });
+        }
+
+        let next_ready_ts: Option<u64> = tx
+          .prepare_cached(STATEMENT_QUEUE_GET_EARLIEST_READY)?
+          .query_row([], |row| row.get(0))
+          .optional()?;
+        tx.commit()?;
+        next_ready_ts
+      };
+
+      let wait_ms = next_ready_ts
+        .map(|ts| ts.saturating_sub(now))
+        .unwrap_or(QUEUE_POLL_INTERVAL_MS)
+        .min(QUEUE_POLL_INTERVAL_MS);
+      tokio::select! {
+        _ = self.queue_notify.notified() => {}
+        _ = tokio::time::sleep(Duration::from_millis(wait_ms)) => {}
+      }
+    }
+  }
+
+  fn watch(
+    &self,
+    keys: Vec<Vec<u8>>,
+  ) -> Pin<Box<dyn Stream<Item = Result<Vec<WatchKeyOutput>, AnyError>>>> {
+    let watcher = SqliteWatcher {
+      conn: self.conn.clone(),
+      write_rx: self.write_notify.subscribe(),
+      keys,
+      last_versionstamps: None,
+    };
+    Box::pin(futures::stream::unfold(watcher, |mut watcher| async move {
+      let output = watcher.next().await?;
+      Some((output, watcher))
+    }))
+  }
+}
+
+struct SqliteWatcher {
+  conn: Rc<RefCell<rusqlite::Connection>>,
+  write_rx: watch::Receiver<()>,
+  keys: Vec<Vec<u8>>,
+  last_versionstamps: Option<Vec<Option<[u8; 10]>>>,
+}
+
+impl SqliteWatcher {
+  /// Waits until the versionstamp of at least one of the watched keys
+  /// changes, and returns the new state of the keys. Returns `None` once the
+  /// database is closed.
+  async fn next(&mut self) -> Option<Result<Vec<WatchKeyOutput>, AnyError>> {
+    let poll_interval = Duration::from_millis(WATCH_POLL_INTERVAL_MS);
+    loop {
+      match self.read_changes() {
+        Ok(Some(outputs)) => return Some(Ok(outputs)),
+        Ok(None) => {}
+        Err(err) => return Some(Err(err)),
+      }
+
+      tokio::select! {
+        res = self.write_rx.changed() => {
+          if res.is_err() {
+            return None;
+          }
+        }
+        _ = tokio::time::sleep(poll_interval) => {}
+      }
+    }
+  }
+
+  /// Reads the versionstamps of the watched keys, and if any of them changed
+  /// since the last call, the entries of the changed keys.
+  fn read_changes(&mut self) -> Result<Option<Vec<WatchKeyOutput>>, AnyError> {
+    let mut db = self.conn.borrow_mut();
+    let tx = db.transaction()?;
+    let now = now_ms();
+
+    let versionstamps = self
+      .keys
+      .iter()
+      .map(|key| {
+        tx.prepare_cached(STATEMENT_KV_POINT_GET_VERSION_ONLY)?
+          .query_row(params![key, now], |row| row.get(0))
+          .optional()
+          .map(|version| version.map(version_to_versionstamp))
+      })
+      .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    if self.last_versionstamps.as_ref() == Some(&versionstamps) {
+      return Ok(None);
+    }
+
+    let mut outputs = Vec::with_capacity(self.keys.len());
+    for (i, key) in self.keys.iter().enumerate() {
+      let unchanged = self
+        .last_versionstamps
+        .as_ref()
+        .map(|last| last[i] == versionstamps[i])
+        .unwrap_or(false);
+      if unchanged {
+        outputs.push(WatchKeyOutput::Unchanged);
+        continue;
+      }
+      let entry = tx
+        .prepare_cached(STATEMENT_KV_POINT_GET)?
+        .query_row(params![key, now], |row| {
+          let key: Vec<u8> = row.get(0)?;
+          let value: Vec<u8> = row.get(1)?;
+          let encoding: i64 = row.get(2)?;
+          let version: i64 = row.get(3)?;
+          Ok(KvEntry {
+            key,
+            value: decode_value(value, encoding),
+            versionstamp: version_to_versionstamp(version),
+          })
+        })
+        .optional()?;
+      outputs.push(WatchKeyOutput::Changed { entry });
+    }
+
+    self.last_versionstamps = Some(versionstamps);
+    Ok(Some(outputs))
+  }
+}
+
+pub struct SqliteQueueMessageHandle {
+  id: String,
+  payload: Option<Vec<u8>>,
+  conn: Rc<RefCell<rusqlite::Connection>>,
+  queue_notify: Rc<Notify>,
+  write_notify: Rc<watch::Sender<()>>,
+}
+
+#[async_trait(?Send)]
+impl QueueMessageHandle for SqliteQueueMessageHandle {
+  async fn take_payload(&mut self) -> Result<Vec<u8>, AnyError> {
+    self
+      .payload
+      .take()
+      .ok_or_else(|| type_error("Payload already consumed"))
+  }
+
+  async fn finish(&self, success: bool) -> Result<(), AnyError> {
+    let requeued = {
+      let mut db = self.conn.borrow_mut();
+      let tx = db.transaction()?;
+
+      let message = tx
+        .prepare_cached(STATEMENT_QUEUE_GET_RUNNING_BY_ID)?
+        .query_row([&self.id], QueueMessage::from_row)
+        .optional()?;
+
+      // The message may have exceeded its deadline and been made available
+      // for redelivery already, in which case there is nothing left to do.
+      let Some(message) = message else {
+        return Ok(());
+      };
+
+      let changed = tx
+        .prepare_cached(STATEMENT_QUEUE_REMOVE_RUNNING)?
+        .execute([&message.id])?;
+      assert_eq!(changed, 1);
+
+      let requeued = if success {
+        false
+      } else {
+        retry_or_dead_letter_message(&tx, message, now_ms())?
+      };
+
+      tx.commit()?;
+      requeued
+    };
+
+    if requeued {
+      self.queue_notify.notify_one();
+    } else if !success {
+      // The payload may have been written to `keys_if_undelivered`.
+      self.write_notify.send_replace(());
+    }
+
+    Ok(())
+  }
+}
+
+/// A queue message as stored in either the `queue` or `queue_running` table.
+/// `ts` is the delivery time for ready messages, and the deadline for running
+/// messages.
+struct QueueMessage {
+  ts: u64,
+  id: String,
+  payload: Vec<u8>,
+  backoff_schedule: String,
+  keys_if_undelivered: Vec<u8>,
+}
+
+impl QueueMessage {
+  fn from_row(row: &rusqlite::Row) -> Result<Self, rusqlite::Error> {
+    Ok(QueueMessage {
+      ts: row.get(0)?,
+      id: row.get(1)?,
+      payload: row.get(2)?,
+      backoff_schedule: row.get(3)?,
+      keys_if_undelivered: row.get(4)?,
+    })
+  }
+}
+
+/// Makes all running messages whose deadline has passed available for
+/// delivery again. These messages are treated as failed deliveries.
+fn requeue_expired_running_messages(
+  tx: &Transaction,
+  now: u64,
+) -> Result<(), AnyError> {
+  let expired = tx
+    .prepare_cached(STATEMENT_QUEUE_GET_EXPIRED_RUNNING)?
+    .query_map([now], QueueMessage::from_row)?
+    .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+  for message in expired {
+    debug_assert!(message.ts <= now);
+    let changed = tx
+      .prepare_cached(STATEMENT_QUEUE_REMOVE_RUNNING)?
+      .execute([&message.id])?;
+    assert_eq!(changed, 1);
+    retry_or_dead_letter_message(tx, message, now)?;
+  }
+
+  Ok(())
+}
+
+/// Handles a failed delivery of a message that has already been removed from
+/// the `queue_running` table. If the backoff schedule is not yet exhausted,
+/// the message is put back into the queue to be retried after the next
+/// backoff interval, and `true` is returned. Otherwise the payload is written
+/// to the `keys_if_undelivered` keys, and `false` is returned.
+fn retry_or_dead_letter_message(
+  tx: &Transaction,
+  message: QueueMessage,
+  now: u64,
+) -> Result<bool, AnyError> {
+  let backoff_schedule: Vec<u32> =
+    serde_json::from_str(&message.backoff_schedule)?;
+
+  if let Some((next_backoff, remaining)) = backoff_schedule.split_first() {
+    let changed =
+      tx.prepare_cached(STATEMENT_QUEUE_ADD_READY)?
+        .execute(params![
+          now + *next_backoff as u64,
+          &message.id,
+          &message.payload,
+          serde_json::to_string(remaining)?,
+          &message.keys_if_undelivered
+        ])?;
+    assert_eq!(changed, 1);
+    return Ok(true);
+  }
+
+  let keys_if_undelivered: Vec<Vec<u8>> =
+    serde_json::from_slice(&message.keys_if_undelivered)?;
+  if !keys_if_undelivered.is_empty() {
+    let version: i64 = tx
+      .prepare_cached(STATEMENT_INC_AND_GET_DATA_VERSION)?
+      .query_row([], |row| row.get(0))?;
+    for key in keys_if_undelivered {
+      let changed =
+        tx.prepare_cached(STATEMENT_KV_POINT_SET)?.execute(params![
+          key,
+          &message.payload,
+          &VALUE_ENCODING_V8,
+          &version,
+          -1i64
+        ])?;
+      assert_eq!(changed, 1);
+    }
+  }
+
+  Ok(false)
+}
+
+/// Periodically deletes expired keys and history that has left the retention
+/// window from the database, until the database is dropped.
+async fn collect_expired_keys(conn: Weak<RefCell<rusqlite::Connection>>) {
+  loop {
+    tokio::time::sleep(Duration::from_millis(EXPIRATION_GC_INTERVAL_MS)).await;
+
+    let Some(conn) = conn.upgrade() else {
+      break;
+    };
+    // Skip this round if the connection is in use, rather than blocking.
+    let Ok(mut db) = conn.try_borrow_mut() else {
+      continue;
+    };
+    if let Err(err) = db
+      .prepare_cached(STATEMENT_KV_DELETE_EXPIRED)
+      .and_then(|mut stmt| stmt.execute([now_ms()]))
+    {
+      log::warn!("Failed to delete expired keys from KV database: {err}");
+    }
+    if let Err(err) = db.transaction().and_then(|tx| {
+      prune_history(&tx, now_ms())?;
+      tx.commit()
+    }) {
+      log::warn!("Failed to prune history of KV database: {err}");
+    }
+  }
+}
+
+/// Checks that the database can be read as of the given versionstamp, and
+/// returns the corresponding version along with the time it was committed,
+/// which is used to determine which keys had expired at that point.
+fn get_history_read_version(
+  tx: &Transaction,
+  versionstamp: &[u8; 10],
+) -> Result<(i64, u64), AnyError> {
+  let version = versionstamp_to_version(versionstamp)
+    .ok_or_else(|| type_error("invalid versionstamp"))?;
+
+  let min_version: Option<i64> = tx
+    .prepare_cached(STATEMENT_HISTORY_GET_CONFIG)?
+    .query_row([], |row| row.get(1))
+    .optional()?;
+  let Some(min_version) = min_version else {
+    return Err(type_error(
+      "Reading at a versionstamp requires history mode to be enabled for the database",
+    ));
+  };
+
+  let current_version: i64 = tx
+    .prepare_cached(STATEMENT_GET_DATA_VERSION)?
+    .query_row([], |row| row.get(0))?;
+  if version > current_version {
+    return Err(type_error(format!(
+      "Versionstamp {} has not been committed yet",
+      hex::encode(versionstamp)
+    )));
+  }
+  if version < min_version {
+    return Err(type_error(format!(
+      "Versionstamp {} is outside the history retention window, the oldest readable versionstamp is {}",
+      hex::encode(versionstamp),
+      hex::encode(version_to_versionstamp(min_version))
+    )));
+  }
+
+  let ts: Option<u64> = tx
+    .prepare_cached(STATEMENT_HISTORY_GET_VERSION_TS)?
+    .query_row([version], |row| row.get(0))
+    .optional()?;
+  Ok((version, ts.unwrap_or_else(now_ms)))
+}
+
+/// Discards the history that is no longer needed to read the database as of
+/// the versionstamps committed within the retention window. Does nothing if
+/// history mode is not enabled.
+fn prune_history(tx: &Transaction, now: u64) -> Result<(), rusqlite::Error> {
+  let retention_ms: Option<i64> = tx
+    .prepare_cached(STATEMENT_HISTORY_GET_CONFIG)?
+    .query_row([], |row| row.get(0))
+    .optional()?;
+  let Some(retention_ms) = retention_ms else {
+    return Ok(());
+  };
+
+  // The oldest version committed within the window. If there is none, the
+  // current state is the only one that must remain readable.
+  let first_version: Option<i64> = tx
+    .prepare_cached(STATEMENT_HISTORY_GET_FIRST_VERSION_SINCE)?
+    .query_row([now as i64 - retention_ms], |row| row.get(0))?;
+  let min_version = match first_version {
+    Some(version) => version,
+    None => tx
+      .prepare_cached(STATEMENT_GET_DATA_VERSION)?
+      .query_row([], |row| row.get(0))?,
+  };
+
+  tx.prepare_cached(STATEMENT_HISTORY_ADVANCE_MIN_VERSION)?
+    .execute([min_version])?;
+  tx.prepare_cached(STATEMENT_HISTORY_DELETE_SUPERSEDED)?
+    .execute([min_version])?;
+  tx.prepare_cached(STATEMENT_HISTORY_DELETE_VERSIONS)?
+    .execute([min_version])?;
+  Ok(())
+}
+
+/// Converts an optional expiration timestamp into the representation used by
+/// the `expiration_ms` column, where a negative value means "never expires".
+fn expire_at_to_expiration_ms(expire_at: Option<u64>) -> i64 {
+  expire_at.map(|x| x as i64).unwrap_or(-1)
+}
+
+fn now_ms() -> u64 {
+  SystemTime::now()
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .unwrap()
+    .as_millis() as u64
 }
 
 /// Mutates a LE64 value in the database, defaulting to setting it to the
@@ -293,6 +1119,7 @@ fn mutate_le64(
   op_name: &str,
   operand: &Value,
   new_version: i64,
+  now: u64,
   mutate: impl FnOnce(u64, u64) -> u64,
 ) -> Result<(), AnyError> {
   let Value::U64(operand) = *operand else {
@@ -301,7 +1128,7 @@ fn mutate_le64(
 
   let old_value = tx
     .prepare_cached(STATEMENT_KV_POINT_GET_VALUE_ONLY)?
-    .query_row([key], |row| {
+    .query_row(params![key, now], |row| {
       let value: Vec<u8> = row.get(0)?;
       let encoding: i64 = row.get(1)?;
 
@@ -323,7 +1150,8 @@ fn mutate_le64(
     key,
     &new_value[..],
     encoding,
-    new_version
+    new_version,
+    -1i64
   ])?;
   assert_eq!(changed, 1);
 
@@ -336,6 +1164,14 @@ fn version_to_versionstamp(version: i64) -> [u8; 10] {
   versionstamp
 }
 
+fn versionstamp_to_version(versionstamp: &[u8; 10]) -> Option<i64> {
+  if versionstamp[8..] != [0, 0] {
+    return None;
+  }
+  let version = i64::from_be_bytes(versionstamp[..8].try_into().unwrap());
+  (version > 0).then_some(version)
+}
+
 const VALUE_ENCODING_V8: i64 = 1;
 const VALUE_ENCODING_LE64: i64 = 2;
 const VALUE_ENCODING_BYTES: i64 = 3;
@@ -364,3 +1200,153 @@ fn encode_value(value: &crate::Value) -> (Cow<'_, [u8]>, i64) {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::num::NonZeroU32;
+
+  use tokio::task::LocalSet;
+
+  use super::*;
+  use crate::Consistency;
+  use crate::KvMutation;
+
+  fn open_db() -> SqliteDb {
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    SqliteDb::new(conn).unwrap()
+  }
+
+  async fn write(db: &SqliteDb, key: &[u8], kind: MutationKind) -> [u8; 10] {
+    let write = AtomicWrite {
+      checks: vec![],
+      mutations: vec![KvMutation {
+        key: key.to_vec(),
+        kind,
+        expire_at: None,
+      }],
+      enqueues: vec![],
+    };
+    db.atomic_write(write).await.unwrap().unwrap().versionstamp
+  }
+
+  async fn read_at(
+    db: &SqliteDb,
+    at_versionstamp: Option<[u8; 10]>,
+  ) -> Result<Vec<(Vec<u8>, u64)>, AnyError> {
+    let mut outputs = db
+      .snapshot_read(
+        vec![ReadRange {
+          start: vec![],
+          end: vec![0xff],
+          limit: NonZeroU32::new(100).unwrap(),
+          reverse: false,
+        }],
+        SnapshotReadOptions {
+          consistency: Consistency::Strong,
+          at_versionstamp,
+        },
+      )
+      .await?;
+    Ok(
+      outputs
+        .remove(0)
+        .entries
+        .into_iter()
+        .map(|entry| match entry.value {
+          Value::U64(value) => (entry.key, value),
+          _ => unreachable!(),
+        })
+        .collect(),
+    )
+  }
+
+  #[tokio::test]
+  async fn history_read_at_versionstamp() {
+    LocalSet::new()
+      .run_until(async {
+        let db = open_db();
+        db.set_history_retention(Some(Duration::from_secs(3600)))
+          .unwrap();
+
+        let v1 = write(&db, b"a", MutationKind::Set(Value::U64(1))).await;
+        let v2 = write(&db, b"a", MutationKind::Set(Value::U64(2))).await;
+        let v3 = write(&db, b"b", MutationKind::Set(Value::U64(3))).await;
+        let v4 = write(&db, b"a", MutationKind::Delete).await;
+        let v5 = write(&db, b"b", MutationKind::Sum(Value::U64(1))).await;
+
+        assert_eq!(read_at(&db, Some(v1)).await.unwrap(), [(b"a".to_vec(), 1)]);
+        assert_eq!(read_at(&db, Some(v2)).await.unwrap(), [(b"a".to_vec(), 2)]);
+        assert_eq!(
+          read_at(&db, Some(v3)).await.unwrap(),
+          [(b"a".to_vec(), 2), (b"b".to_vec(), 3)]
+        );
+        assert_eq!(read_at(&db, Some(v4)).await.unwrap(), [(b"b".to_vec(), 3)]);
+        assert_eq!(
+          read_at(&db, Some(v5)).await.unwrap(),
+          read_at(&db, None).await.unwrap()
+        );
+
+        let future = version_to_versionstamp(100);
+        let err = read_at(&db, Some(future)).await.unwrap_err();
+        assert!(err.to_string().contains("has not been committed yet"));
+      })
+      .await;
+  }
+
+  #[tokio::test]
+  async fn history_outside_retention_window() {
+    LocalSet::new()
+      .run_until(async {
+        let db = open_db();
+        db.set_history_retention(Some(Duration::from_millis(1)))
+          .unwrap();
+
+        let v1 = write(&db, b"a", MutationKind::Set(Value::U64(1))).await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let v2 = write(&db, b"a", MutationKind::Set(Value::U64(2))).await;
+
+        {
+          let mut conn = db.conn.borrow_mut();
+          let tx = conn.transaction().unwrap();
+          prune_history(&tx, now_ms() + 5).unwrap();
+          tx.commit().unwrap();
+        }
+
+        let err = read_at(&db, Some(v1)).await.unwrap_err();
+        assert!(
+          err
+            .to_string()
+            .contains("is outside the history retention window"),
+          "{err}"
+        );
+        assert_eq!(read_at(&db, Some(v2)).await.unwrap(), [(b"a".to_vec(), 2)]);
+      })
+      .await;
+  }
+
+  #[tokio::test]
+  async fn history_disabled() {
+    LocalSet::new()
+      .run_until(async {
+        let db = open_db();
+        let v1 = write(&db, b"a", MutationKind::Set(Value::U64(1))).await;
+        let v2 = write(&db, b"a", MutationKind::Set(Value::U64(2))).await;
+        let err = read_at(&db, Some(v2)).await.unwrap_err();
+        assert!(err.to_string().contains("requires history mode"), "{err}");
+
+        db.set_history_retention(Some(Duration::from_secs(3600)))
+          .unwrap();
+        write(&db, b"a", MutationKind::Set(Value::U64(3))).await;
+        // The state at the time history mode was enabled is readable, but
+        // versions superseded before that are not.
+        assert_eq!(read_at(&db, Some(v2)).await.unwrap(), [(b"a".to_vec(), 2)]);
+        let err = read_at(&db, Some(v1)).await.unwrap_err();
+        assert!(err.to_string().contains("outside the history retention"));
+
+        db.set_history_retention(None).unwrap();
+        let err = read_at(&db, Some(v2)).await.unwrap_err();
+        assert!(err.to_string().contains("requires history mode"), "{err}");
+      })
+      .await;
+  }
+}
@@ -215,6 +215,20 @@ mod startup_snapshot {
     }
   }
 
+  impl deno_kv::remote::RemoteDbHandlerPermissions for Permissions {
+    fn check_env(&mut self, _var: &str) -> Result<(), AnyError> {
+      unreachable!("snapshotting!")
+    }
+
+    fn check_net_url(
+      &mut self,
+      _url: &deno_core::url::Url,
+      _api_name: &str,
+    ) -> Result<(), AnyError> {
+      unreachable!("snapshotting!")
+    }
+  }
+
   deno_core::extension!(runtime,
     deps = [
       deno_webidl,
@@ -316,7 +330,9 @@ mod startup_snapshot {
       ),
       deno_tls::deno_tls::init_ops_and_esm(),
       deno_kv::deno_kv::init_ops_and_esm(
-        deno_kv::sqlite::SqliteDbHandler::<Permissions>::new(None),
+        deno_kv::dynamic::MultiBackendDbHandler::<Permissions>::remote_or_sqlite(
+          None,
+        ),
         false, // No --unstable
       ),
       deno_napi::deno_napi::init_ops_and_esm::<Permissions>(),
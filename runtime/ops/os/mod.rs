@@ -2,6 +2,7 @@
 
 use super::utils::into_string;
 use crate::permissions::PermissionsContainer;
+use crate::worker::DeferredExit;
 use crate::worker::ExitCode;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
@@ -155,8 +156,13 @@ fn op_set_exit_code(state: &mut OpState, code: i32) {
   state.borrow_mut::<ExitCode>().set(code);
 }
 
-#[op]
-fn op_exit(state: &mut OpState) {
+#[op(v8)]
+fn op_exit(scope: &mut v8::HandleScope, state: &mut OpState) {
+  if let Some(deferred_exit) = state.try_borrow::<DeferredExit>() {
+    deferred_exit.request();
+    scope.terminate_execution();
+    return;
+  }
   let code = state.borrow::<ExitCode>().get();
   std::process::exit(code)
 }
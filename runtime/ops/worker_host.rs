@@ -60,6 +60,9 @@ struct PreloadModuleCbHolder(Arc<WorkerEventCb>);
 #[derive(Clone)]
 struct PreExecuteModuleCbHolder(Arc<WorkerEventCb>);
 
+#[derive(Clone)]
+struct ShutdownCbHolder(Arc<WorkerEventCb>);
+
 pub struct WorkerThread {
   worker_handle: WebWorkerHandle,
   cancel_handle: Rc<CancelHandle>,
@@ -100,6 +103,7 @@ deno_core::extension!(
     create_web_worker_cb: Arc<CreateWebWorkerCb>,
     preload_module_cb: Arc<WorkerEventCb>,
     pre_execute_module_cb: Arc<WorkerEventCb>,
+    shutdown_cb: Arc<WorkerEventCb>,
     format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
   },
   state = |state, options| {
@@ -115,6 +119,8 @@ deno_core::extension!(
     let pre_execute_module_cb_holder =
       PreExecuteModuleCbHolder(options.pre_execute_module_cb);
     state.put::<PreExecuteModuleCbHolder>(pre_execute_module_cb_holder);
+    let shutdown_cb_holder = ShutdownCbHolder(options.shutdown_cb);
+    state.put::<ShutdownCbHolder>(shutdown_cb_holder);
     let format_js_error_fn_holder =
       FormatJsErrorFnHolder(options.format_js_error_fn);
     state.put::<FormatJsErrorFnHolder>(format_js_error_fn_holder);
@@ -181,6 +187,8 @@ fn op_create_worker(
   state.put::<PreloadModuleCbHolder>(preload_module_cb.clone());
   let pre_execute_module_cb = state.take::<PreExecuteModuleCbHolder>();
   state.put::<PreExecuteModuleCbHolder>(pre_execute_module_cb.clone());
+  let shutdown_cb = state.take::<ShutdownCbHolder>();
+  state.put::<ShutdownCbHolder>(shutdown_cb.clone());
   let format_js_error_fn = state.take::<FormatJsErrorFnHolder>();
   state.put::<FormatJsErrorFnHolder>(format_js_error_fn.clone());
   state.put::<WorkerId>(worker_id.next().unwrap());
@@ -226,6 +234,7 @@ fn op_create_worker(
       maybe_source_code,
       preload_module_cb.0,
       pre_execute_module_cb.0,
+      shutdown_cb.0,
       format_js_error_fn.0,
     )
   })?;
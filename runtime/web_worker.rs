@@ -37,7 +37,7 @@ use deno_core::SourceMapGetter;
 use deno_fs::FileSystem;
 use deno_http::DefaultHttpPropertyExtractor;
 use deno_io::Stdio;
-use deno_kv::sqlite::SqliteDbHandler;
+use deno_kv::dynamic::MultiBackendDbHandler;
 use deno_tls::RootCertStoreProvider;
 use deno_web::create_entangled_message_port;
 use deno_web::BlobStore;
@@ -321,6 +321,10 @@ pub struct WebWorker {
   internal_handle: WebWorkerInternalHandle,
   pub worker_type: WebWorkerType,
   pub main_module: ModuleSpecifier,
+  /// If false, the worker finishes once its event loop is done even if
+  /// inspector sessions are still connected, e.g. local sessions that are
+  /// used to collect coverage.
+  pub wait_for_inspector: bool,
   poll_for_messages_fn: Option<v8::Global<v8::Value>>,
   bootstrap_fn_global: Option<v8::Global<v8::Function>>,
 }
@@ -338,6 +342,8 @@ pub struct WebWorkerOptions {
   pub create_web_worker_cb: Arc<ops::worker_host::CreateWebWorkerCb>,
   pub preload_module_cb: Arc<ops::worker_host::WorkerEventCb>,
   pub pre_execute_module_cb: Arc<ops::worker_host::WorkerEventCb>,
+  /// Called once the worker has finished running, right before it is dropped.
+  pub shutdown_cb: Arc<ops::worker_host::WorkerEventCb>,
   pub format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
   pub source_map_getter: Option<Box<dyn SourceMapGetter>>,
   pub worker_type: WebWorkerType,
@@ -436,7 +442,7 @@ impl WebWorker {
       ),
       deno_tls::deno_tls::init_ops(),
       deno_kv::deno_kv::init_ops(
-        SqliteDbHandler::<PermissionsContainer>::new(None),
+        MultiBackendDbHandler::<PermissionsContainer>::remote_or_sqlite(None),
         unstable,
       ),
       deno_napi::deno_napi::init_ops::<PermissionsContainer>(),
@@ -457,6 +463,7 @@ impl WebWorker {
         options.create_web_worker_cb.clone(),
         options.preload_module_cb.clone(),
         options.pre_execute_module_cb.clone(),
+        options.shutdown_cb.clone(),
         options.format_js_error_fn.clone(),
       ),
       ops::fs_events::deno_fs_events::init_ops(),
@@ -550,6 +557,7 @@ impl WebWorker {
         internal_handle,
         worker_type: options.worker_type,
         main_module,
+        wait_for_inspector: true,
         poll_for_messages_fn: None,
         bootstrap_fn_global: Some(bootstrap_fn_global),
       },
@@ -760,6 +768,7 @@ pub fn run_web_worker(
   mut maybe_source_code: Option<String>,
   preload_module_cb: Arc<ops::worker_host::WorkerEventCb>,
   pre_execute_module_cb: Arc<ops::worker_host::WorkerEventCb>,
+  shutdown_cb: Arc<ops::worker_host::WorkerEventCb>,
   format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
 ) -> Result<(), AnyError> {
   let name = worker.name.to_string();
@@ -815,16 +824,19 @@ pub fn run_web_worker(
 
     // If sender is closed it means that worker has already been closed from
     // within using "globalThis.close()"
-    if internal_handle.is_terminated() {
-      return Ok(());
-    }
-
-    let result = if result.is_ok() {
-      worker.run_event_loop(true).await
+    let result = if internal_handle.is_terminated() {
+      Ok(())
+    } else if result.is_ok() {
+      let wait_for_inspector = worker.wait_for_inspector;
+      worker.run_event_loop(wait_for_inspector).await
     } else {
       result
     };
 
+    if let Err(e) = (shutdown_cb)(worker).await {
+      print_worker_error(&e, &name, format_js_error_fn.as_deref());
+    }
+
     if let Err(e) = result {
       print_worker_error(&e, &name, format_js_error_fn.as_deref());
       internal_handle
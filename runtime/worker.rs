@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use std::cell::Cell;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::atomic::AtomicI32;
@@ -33,7 +34,7 @@ use deno_core::SourceMapGetter;
 use deno_fs::FileSystem;
 use deno_http::DefaultHttpPropertyExtractor;
 use deno_io::Stdio;
-use deno_kv::sqlite::SqliteDbHandler;
+use deno_kv::dynamic::MultiBackendDbHandler;
 use deno_tls::RootCertStoreProvider;
 use deno_web::BlobStore;
 use log::debug;
@@ -57,6 +58,24 @@ impl ExitCode {
     self.0.store(code, Relaxed);
   }
 }
+
+/// When put into the `OpState` of a worker, `Deno.exit()` terminates
+/// JavaScript execution and records the request here instead of ending the
+/// process right away. The embedder is then expected to exit with
+/// `MainWorker::exit_code()` once it is done.
+#[derive(Clone, Default)]
+pub struct DeferredExit(Rc<Cell<bool>>);
+
+impl DeferredExit {
+  pub fn is_requested(&self) -> bool {
+    self.0.get()
+  }
+
+  pub fn request(&self) {
+    self.0.set(true);
+  }
+}
+
 /// This worker is created and used by almost all
 /// subcommands in Deno executable.
 ///
@@ -100,6 +119,7 @@ pub struct WorkerOptions {
   pub create_web_worker_cb: Arc<ops::worker_host::CreateWebWorkerCb>,
   pub web_worker_preload_module_cb: Arc<ops::worker_host::WorkerEventCb>,
   pub web_worker_pre_execute_module_cb: Arc<ops::worker_host::WorkerEventCb>,
+  pub web_worker_shutdown_cb: Arc<ops::worker_host::WorkerEventCb>,
   pub format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
 
   /// Source map reference for errors.
@@ -147,6 +167,9 @@ impl Default for WorkerOptions {
       web_worker_pre_execute_module_cb: Arc::new(|_| {
         unimplemented!("web workers are not supported")
       }),
+      web_worker_shutdown_cb: Arc::new(|_| {
+        unimplemented!("web workers are not supported")
+      }),
       create_web_worker_cb: Arc::new(|_| {
         unimplemented!("web workers are not supported")
       }),
@@ -259,7 +282,7 @@ impl MainWorker {
       ),
       deno_tls::deno_tls::init_ops(),
       deno_kv::deno_kv::init_ops(
-        SqliteDbHandler::<PermissionsContainer>::new(
+        MultiBackendDbHandler::<PermissionsContainer>::remote_or_sqlite(
           options.origin_storage_dir.clone(),
         ),
         unstable,
@@ -281,6 +304,7 @@ impl MainWorker {
         options.create_web_worker_cb.clone(),
         options.web_worker_preload_module_cb.clone(),
         options.web_worker_pre_execute_module_cb.clone(),
+        options.web_worker_shutdown_cb.clone(),
         options.format_js_error_fn.clone(),
       ),
       ops::fs_events::deno_fs_events::init_ops(),
@@ -178,6 +178,9 @@ impl LspStdoutReader {
 
 pub struct InitializeParamsBuilder {
   params: InitializeParams,
+  /// `lsp_types` doesn't know the pull diagnostics capabilities yet, so
+  /// they're added to the serialized params.
+  pull_diagnostics: bool,
 }
 
 impl InitializeParamsBuilder {
@@ -272,6 +275,7 @@ impl InitializeParamsBuilder {
         },
         ..Default::default()
       },
+      pull_diagnostics: false,
     }
   }
 
@@ -335,6 +339,11 @@ impl InitializeParamsBuilder {
     self
   }
 
+  pub fn enable_pull_diagnostics(&mut self) -> &mut Self {
+    self.pull_diagnostics = true;
+    self
+  }
+
   pub fn set_cache(&mut self, value: impl AsRef<str>) -> &mut Self {
     let options = self.initialization_options_mut();
     options.insert("cache".to_string(), value.as_ref().to_string().into());
@@ -430,8 +439,19 @@ impl InitializeParamsBuilder {
     options.as_object_mut().unwrap()
   }
 
-  pub fn build(&self) -> InitializeParams {
-    self.params.clone()
+  pub fn build(&self) -> Value {
+    let mut params = to_value(&self.params).unwrap();
+    if self.pull_diagnostics {
+      let capabilities = &mut params["capabilities"];
+      capabilities["textDocument"]["diagnostic"] = json!({
+        "dynamicRegistration": false,
+        "relatedDocumentSupport": false,
+      });
+      capabilities["workspace"]["diagnostics"] = json!({
+        "refreshSupport": true,
+      });
+    }
+    params
   }
 }
 